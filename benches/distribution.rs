@@ -0,0 +1,62 @@
+//! Times drawing pieces uniformly at random against the 7-bag, building the
+//! drought histograms `examples/analyze_distribution.rs` reports on.
+
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use rand::prelude::*;
+use tetris_game::{Bag, BlockType};
+
+const GAMES: usize = 100_000;
+const PIECES_PER_GAME: usize = 10;
+
+/// Droughts seen per piece kind: `histogram[kind][n]` counts the times `n`
+/// other pieces came between two of that kind
+type Histogram = [Vec<u32>; 7];
+
+fn index(kind: BlockType) -> usize {
+    BlockType::all().iter().position(|&k| k == kind).unwrap()
+}
+
+fn record_game(pieces: impl Iterator<Item = BlockType>, histogram: &mut Histogram) {
+    let mut last_seen = [None; 7];
+    for (i, kind) in pieces.enumerate() {
+        let k = index(kind);
+        if let Some(last) = last_seen[k] {
+            let drought = i - last - 1;
+            if histogram[k].len() <= drought {
+                histogram[k].resize(drought + 1, 0);
+            }
+            histogram[k][drought] += 1;
+        }
+        last_seen[k] = Some(i);
+    }
+}
+
+fn random_games() -> Histogram {
+    let mut rng = thread_rng();
+    let mut histogram = Histogram::default();
+    for _ in 0..GAMES {
+        let pieces = (0..PIECES_PER_GAME).map(|_| *BlockType::all().choose(&mut rng).unwrap());
+        record_game(pieces, &mut histogram);
+    }
+    histogram
+}
+
+fn bag_games() -> Histogram {
+    let mut histogram = Histogram::default();
+    for _ in 0..GAMES {
+        record_game(Bag::new(thread_rng()).take(PIECES_PER_GAME), &mut histogram);
+    }
+    histogram
+}
+
+fn bench_bag_analysis(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bag_analysis");
+    // each run is a million pieces, so a few samples are plenty
+    group.sample_size(10);
+    group.bench_function("random", |b| b.iter(|| black_box(random_games())));
+    group.bench_function("bag", |b| b.iter(|| black_box(bag_games())));
+    group.finish();
+}
+
+criterion_group!(benches, bench_bag_analysis);
+criterion_main!(benches);
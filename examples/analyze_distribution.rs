@@ -0,0 +1,132 @@
+//! How evenly pieces come out when drawn uniformly at random versus from the
+//! 7-bag: droughts per piece kind and a Kolmogorov–Smirnov test of the piece
+//! counts against a uniform spread.
+//!
+//! cargo run --example analyze_distribution -- --games 10000 --pieces 100
+
+use rand::prelude::*;
+use rand_chacha::ChaCha12Rng;
+use std::{env, process};
+use tetris_game::{Bag, BlockType};
+
+/// Both randomizers start from this, so runs can be compared
+const SEED: u64 = 0x7e7215;
+
+const USAGE: &str = "usage: analyze_distribution [--games N] [--pieces N]";
+
+/// What one randomizer produced over every game
+#[derive(Default)]
+struct Stats {
+    counts: [u64; 7],
+    /// Pieces between two of the same kind, per kind
+    droughts: [Vec<usize>; 7],
+}
+
+impl Stats {
+    fn record_game(&mut self, pieces: impl Iterator<Item = BlockType>) {
+        let mut last_seen = [None; 7];
+        for (i, kind) in pieces.enumerate() {
+            let k = BlockType::all().iter().position(|&b| b == kind).unwrap();
+            self.counts[k] += 1;
+            if let Some(last) = last_seen[k] {
+                self.droughts[k].push(i - last - 1);
+            }
+            last_seen[k] = Some(i);
+        }
+    }
+
+    /// p-value of the piece counts coming from a uniform spread over the
+    /// seven kinds; the asymptotic distribution makes it approximate
+    fn ks_p_value(&self) -> f64 {
+        let n: u64 = self.counts.iter().sum();
+        let mut seen = 0;
+        let mut d: f64 = 0.0;
+        for (k, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            let expected = (k + 1) as f64 / 7.0;
+            d = d.max((seen as f64 / n as f64 - expected).abs());
+        }
+        let root_n = (n as f64).sqrt();
+        kolmogorov_q((root_n + 0.12 + 0.11 / root_n) * d)
+    }
+
+    fn print(&self, name: &str) {
+        println!("{}", name);
+        println!("  piece    count   mean drought   max drought");
+        for (k, kind) in BlockType::all().iter().enumerate() {
+            let droughts = &self.droughts[k];
+            let mean = if droughts.is_empty() {
+                0.0
+            } else {
+                droughts.iter().sum::<usize>() as f64 / droughts.len() as f64
+            };
+            let max = droughts.iter().max().copied().unwrap_or(0);
+            println!(
+                "  {:?}     {:>8}   {:>12.2}   {:>11}",
+                kind, self.counts[k], mean, max
+            );
+        }
+        println!("  KS test for uniformity: p = {:.4}", self.ks_p_value());
+        println!();
+    }
+}
+
+/// Probability that the Kolmogorov statistic exceeds `lambda`
+fn kolmogorov_q(lambda: f64) -> f64 {
+    if lambda < 1e-3 {
+        return 1.0;
+    }
+    let mut sum = 0.0;
+    for j in 1..=100 {
+        let j = j as f64;
+        let term = 2.0 * (-2.0 * j * j * lambda * lambda).exp();
+        sum += if j as u32 % 2 == 1 { term } else { -term };
+        if term < 1e-12 {
+            break;
+        }
+    }
+    sum.clamp(0.0, 1.0)
+}
+
+fn parse_args() -> Result<(usize, usize), String> {
+    let (mut games, mut pieces) = (10_000, 100);
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let target = match arg.as_str() {
+            "--games" => &mut games,
+            "--pieces" => &mut pieces,
+            _ => return Err(format!("unknown option '{}'\n{}", arg, USAGE)),
+        };
+        let value = args
+            .next()
+            .ok_or_else(|| format!("option '{}' needs a value\n{}", arg, USAGE))?;
+        *target = value
+            .parse()
+            .map_err(|_| format!("option '{}' expects a number, got '{}'", arg, value))?;
+    }
+    Ok((games, pieces))
+}
+
+fn main() {
+    let (games, pieces) = parse_args().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(2);
+    });
+
+    let mut rng = ChaCha12Rng::seed_from_u64(SEED);
+    let mut random = Stats::default();
+    for _ in 0..games {
+        random.record_game((0..pieces).map(|_| *BlockType::all().choose(&mut rng).unwrap()));
+    }
+
+    let mut rng = ChaCha12Rng::seed_from_u64(SEED);
+    let mut bag = Stats::default();
+    for _ in 0..games {
+        let seed = rng.r#gen();
+        bag.record_game(Bag::new(ChaCha12Rng::seed_from_u64(seed)).take(pieces));
+    }
+
+    println!("{} games of {} pieces, seed {:#x}\n", games, pieces, SEED);
+    random.print("Uniform random");
+    bag.print("7-bag");
+}
@@ -0,0 +1,79 @@
+use std::time::{Duration, Instant};
+
+use tetris_game::{Game, GameEvent, milestones::thousands};
+
+use crate::format_duration;
+
+/// How often the periodic state summary is printed
+const SUMMARY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Writes plain-text game announcements to stderr so screen readers can follow
+/// along; the TUI owns stdout, so the two never mix.
+pub struct Announcer {
+    last_summary: Instant,
+}
+
+impl Announcer {
+    pub fn new() -> Self {
+        Announcer {
+            last_summary: Instant::now(),
+        }
+    }
+
+    pub fn achievement(&self, name: &str) {
+        eprintln!("[TETRIS] Achievement unlocked: {}", name);
+    }
+
+    pub fn record(&self, name: &str) {
+        eprintln!("[TETRIS] New record: {}", name);
+    }
+
+    /// Print the periodic summary when it's due
+    pub fn tick(&mut self, game: &Game) {
+        if game.paused || game.game_over || self.last_summary.elapsed() < SUMMARY_INTERVAL {
+            return;
+        }
+        self.last_summary = Instant::now();
+        eprintln!(
+            "[TETRIS] Score: {}, Level: {}, Lines: {}, Time: {}",
+            game.score,
+            game.level,
+            game.lines_cleared,
+            format_duration(game.elapsed())
+        );
+    }
+
+    pub fn on_event(&mut self, ev: &GameEvent, game: &Game) {
+        match *ev {
+            GameEvent::PieceLocked { .. } | GameEvent::HardDropped { .. } => {}
+            GameEvent::LinesCleared { count, points, .. } => eprintln!(
+                "[TETRIS] Cleared {} {}! Score: +{}, Total: {}",
+                count,
+                if count == 1 { "line" } else { "lines" },
+                points,
+                game.score
+            ),
+            GameEvent::LevelUp { level } => eprintln!(
+                "[TETRIS] Level up! Now level {}, gravity {}ms",
+                level,
+                game.gravity_interval.as_millis()
+            ),
+            GameEvent::GoalReached { time } => eprintln!(
+                "[TETRIS] Goal reached in {}! Score: {}",
+                format_duration(time),
+                game.score
+            ),
+            GameEvent::Milestone { threshold } => {
+                eprintln!("[TETRIS] Milestone: {} points!", thousands(threshold))
+            }
+            GameEvent::DangerEntered => eprintln!("[TETRIS] Danger: the stack is near the top!"),
+            GameEvent::GameOver => eprintln!(
+                "[TETRIS] Game Over. Final score: {}, Level {}, {} lines in {}",
+                game.score,
+                game.level,
+                game.lines_cleared,
+                format_duration(game.elapsed())
+            ),
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io, path::PathBuf, time::Duration};
+
+use tetris_game::{Game, GameEvent, GameMode, TICK, paths};
+
+use crate::Term;
+
+/// Level from which time counts towards the high-level survival goal
+const HIGH_LEVEL: usize = 15;
+
+/// What a predicate can look at when an event fires
+pub struct Context<'a> {
+    pub event: &'a GameEvent,
+    pub game: &'a Game,
+    /// Lines cleared across every game, this one included
+    pub lifetime_lines: usize,
+    /// Game time spent at `HIGH_LEVEL` or above in this game
+    pub high_level_time: Duration,
+}
+
+/// A goal that unlocks once, the first time `unlocked_by` holds for an event
+pub struct Achievement {
+    /// Stable key in the saved file; never rename
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    unlocked_by: fn(&Context) -> bool,
+}
+
+/// Every achievement, in the order the achievements screen lists them
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "first_tetris",
+        name: "First Tetris",
+        description: "Clear four lines at once",
+        unlocked_by: |ctx| matches!(ctx.event, GameEvent::LinesCleared { count: 4, .. }),
+    },
+    Achievement {
+        id: "t_spin_double",
+        name: "Twist and Shout",
+        description: "Perform a T-spin double",
+        unlocked_by: |ctx| {
+            matches!(
+                ctx.event,
+                GameEvent::LinesCleared {
+                    count: 2,
+                    t_spin: true,
+                    ..
+                }
+            )
+        },
+    },
+    Achievement {
+        id: "lifetime_lines_1000",
+        name: "Line Worker",
+        description: "Clear 1,000 lines across all games",
+        unlocked_by: |ctx| ctx.lifetime_lines >= 1000,
+    },
+    Achievement {
+        id: "survive_level_15",
+        name: "Terminal Velocity",
+        description: "Survive 5 minutes at level 15 or higher",
+        unlocked_by: |ctx| ctx.high_level_time >= Duration::from_secs(5 * 60),
+    },
+    Achievement {
+        id: "fast_sprint",
+        name: "Sprinter",
+        description: "Finish a sprint in under 2 minutes",
+        unlocked_by: |ctx| {
+            ctx.game.setup.mode == GameMode::Sprint
+                && matches!(ctx.event, GameEvent::GoalReached { time } if *time < Duration::from_secs(120))
+        },
+    },
+];
+
+/// What's persisted per profile: unlock times plus the lifetime counters goals need
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Record {
+    /// Achievement id -> local time it unlocked
+    unlocked: BTreeMap<String, String>,
+    lifetime_lines: usize,
+}
+
+/// Watches game events and unlocks achievements as their predicates pass
+pub struct Tracker {
+    record: Record,
+    /// Game tick the current game reached `HIGH_LEVEL`
+    high_level_since: Option<u64>,
+}
+
+fn path() -> Option<PathBuf> {
+    paths::data_dir().map(|d| d.join("achievements.json"))
+}
+
+impl Tracker {
+    /// Read the saved progress; a missing or unreadable file starts from scratch
+    pub fn load() -> Self {
+        let record = path()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Tracker {
+            record,
+            high_level_since: None,
+        }
+    }
+
+    /// Forget per-game progress, e.g. after a restart
+    pub fn new_game(&mut self) {
+        self.high_level_since = None;
+    }
+
+    pub fn unlocked_at(&self, id: &str) -> Option<&str> {
+        self.record.unlocked.get(id).map(String::as_str)
+    }
+
+    /// Feed one batch of events from `game`, returning achievements that just unlocked
+    pub fn observe(&mut self, game: &Game, events: &[GameEvent]) -> Vec<&'static Achievement> {
+        if game.level >= HIGH_LEVEL && self.high_level_since.is_none() {
+            self.high_level_since = Some(game.ticks);
+        }
+        let mut changed = false;
+        let mut unlocked = Vec::new();
+        for event in events {
+            if let GameEvent::LinesCleared { count, .. } = event {
+                self.record.lifetime_lines += count;
+                changed = true;
+            }
+            let ctx = Context {
+                event,
+                game,
+                lifetime_lines: self.record.lifetime_lines,
+                high_level_time: self
+                    .high_level_since
+                    .map_or(Duration::ZERO, |since| TICK * (game.ticks - since) as u32),
+            };
+            for achievement in ACHIEVEMENTS {
+                if !self.record.unlocked.contains_key(achievement.id)
+                    && (achievement.unlocked_by)(&ctx)
+                {
+                    let now = chrono::Local::now().format("%Y-%m-%d %H:%M").to_string();
+                    self.record.unlocked.insert(achievement.id.to_string(), now);
+                    unlocked.push(achievement);
+                    changed = true;
+                }
+            }
+        }
+        if changed && let Err(e) = self.save() {
+            eprintln!("failed to save achievements: {}", e);
+        }
+        unlocked
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let path =
+            path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(&self.record).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+}
+
+/// Full-screen list of unlocked and locked achievements; any of Q, Esc or Enter closes it
+pub fn show(terminal: &mut Term) -> io::Result<()> {
+    let tracker = Tracker::load();
+    loop {
+        terminal.draw(|f| ui(f, &tracker))?;
+        if let Event::Key(key) = event::read()?
+            && key.kind != KeyEventKind::Release
+            && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter)
+        {
+            return Ok(());
+        }
+    }
+}
+
+fn ui<B: ratatui::backend::Backend>(f: &mut ratatui::Frame<B>, tracker: &Tracker) {
+    let unlocked = ACHIEVEMENTS
+        .iter()
+        .filter(|a| tracker.unlocked_at(a.id).is_some())
+        .count();
+    let mut lines = vec![
+        Line::from(format!(
+            "{} of {} unlocked   Lifetime lines: {}",
+            unlocked,
+            ACHIEVEMENTS.len(),
+            tracker.record.lifetime_lines
+        )),
+        Line::from(""),
+    ];
+    for achievement in ACHIEVEMENTS {
+        let line = match tracker.unlocked_at(achievement.id) {
+            Some(when) => Line::from(vec![
+                Span::styled(
+                    format!("✔ {}", achievement.name),
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!(" — {} ", achievement.description)),
+                Span::styled(format!("({})", when), Style::default().fg(Color::DarkGray)),
+            ]),
+            None => Line::from(Span::styled(
+                format!("  {} — {}", achievement.name, achievement.description),
+                Style::default().fg(Color::DarkGray),
+            )),
+        };
+        lines.push(line);
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("Q : Close"));
+
+    let title = match paths::profile() {
+        Some(name) => format!(" Achievements — {} ", name),
+        None => " Achievements ".to_string(),
+    };
+    let paragraph = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, f.size());
+}
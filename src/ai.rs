@@ -0,0 +1,208 @@
+//! Placement search for computer play and move analysis: tries every rotation
+//! and column of the current piece, hard dropped, and scores the board it leaves
+
+use crate::{ActivePiece, BOARD_HEIGHT, BOARD_WIDTH, BlockType, Board, Game};
+
+/// A hard-drop placement: the piece's rotation and the column of its 4x4 grid
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AiMove {
+    pub rotation: usize,
+    pub column: i32,
+}
+
+/// How much each board feature counts; heights, holes and bumpiness are penalties
+#[derive(Copy, Clone, Debug)]
+pub struct Weights {
+    pub height: f64,
+    pub lines: f64,
+    pub holes: f64,
+    pub bumpiness: f64,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Weights {
+            height: -0.51,
+            lines: 0.76,
+            holes: -0.36,
+            bumpiness: -0.18,
+        }
+    }
+}
+
+/// The board after a piece locks, with full rows already removed
+pub struct Placement {
+    pub board: Board,
+    pub lines: usize,
+}
+
+/// Lock `piece` into a copy of `game`'s board and clear any full rows
+pub fn place(game: &Game, piece: &ActivePiece) -> Placement {
+    let mut board = game.board;
+    for (x, y) in piece.cells() {
+        if (0..BOARD_WIDTH as i32).contains(&x) && (0..BOARD_HEIGHT as i32).contains(&y) {
+            board[y as usize][x as usize] = Some(piece.tetro.kind);
+        }
+    }
+    let kept: Vec<_> = board
+        .iter()
+        .filter(|row| row.iter().any(Option::is_none))
+        .copied()
+        .collect();
+    let lines = BOARD_HEIGHT - kept.len();
+    let mut cleared = [[None; BOARD_WIDTH]; BOARD_HEIGHT];
+    cleared[lines..].copy_from_slice(&kept);
+    Placement {
+        board: cleared,
+        lines,
+    }
+}
+
+/// Every spot the current piece can be hard dropped to from above the stack
+pub fn placements(game: &Game) -> Vec<(AiMove, Placement)> {
+    let mut out = Vec::new();
+    for rotation in 0..game.current.tetro.rotations.len() {
+        for column in -2..BOARD_WIDTH as i32 {
+            let mut piece = game.current.clone();
+            piece.rotation = rotation;
+            piece.x = column;
+            if !game.fits(&piece) {
+                continue;
+            }
+            let landed = game.landing(&piece);
+            out.push((AiMove { rotation, column }, place(game, &landed)));
+        }
+    }
+    out
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Ai {
+    pub weights: Weights,
+}
+
+impl Ai {
+    /// Score a board that just had `lines` rows cleared; higher is better
+    pub fn evaluate(&self, board: &Board, lines: usize) -> f64 {
+        let mut heights = [0usize; BOARD_WIDTH];
+        let mut holes = 0;
+        for (x, height) in heights.iter_mut().enumerate() {
+            if let Some(top) = (0..BOARD_HEIGHT).find(|&y| board[y][x].is_some()) {
+                *height = BOARD_HEIGHT - top;
+                holes += (top..BOARD_HEIGHT)
+                    .filter(|&y| board[y][x].is_none())
+                    .count();
+            }
+        }
+        let height: usize = heights.iter().sum();
+        let bumpiness: usize = heights.windows(2).map(|w| w[0].abs_diff(w[1])).sum();
+        let w = &self.weights;
+        w.height * height as f64
+            + w.lines * lines as f64
+            + w.holes * holes as f64
+            + w.bumpiness * bumpiness as f64
+    }
+
+    /// The best placement for the current piece and its score
+    pub fn best_move(&self, game: &Game) -> Option<(AiMove, f64)> {
+        placements(game)
+            .into_iter()
+            .map(|(mv, p)| (mv, self.evaluate(&p.board, p.lines)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    pub fn choose_move(&self, game: &Game) -> Option<AiMove> {
+        self.best_move(game).map(|(mv, _)| mv)
+    }
+}
+
+/// The AI's pick for one piece, as the cells it would lock into
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    pub kind: BlockType,
+    pub mv: AiMove,
+    /// (x, y) on the board, sorted
+    pub cells: Vec<(i32, i32)>,
+}
+
+impl Suggestion {
+    /// e.g. "T piece at column 4, rotated CW", counting columns from 1 at the
+    /// piece's leftmost cell
+    pub fn describe(&self) -> String {
+        let column = self.cells.iter().map(|&(x, _)| x).min().unwrap_or(0) + 1;
+        let rotation = match self.mv.rotation {
+            0 => "",
+            1 => ", rotated CW",
+            2 => ", rotated twice",
+            _ => ", rotated CCW",
+        };
+        format!("{:?} piece at column {}{}", self.kind, column, rotation)
+    }
+}
+
+fn sorted_cells(piece: &ActivePiece) -> Vec<(i32, i32)> {
+    let mut cells = piece.cells();
+    cells.sort_unstable();
+    cells
+}
+
+/// Coaching for practice: what the AI would do with each piece, checked
+/// against where the player put it. Placements are compared by the cells
+/// they fill, so rotations that look the same (an O, a flat I) count as one.
+#[derive(Clone, Debug, Default)]
+pub struct PracticeAiAdvisor {
+    pub ai: Ai,
+    pub last_suggestion: Option<AiMove>,
+    /// The suggestion for the piece in play, until it locks
+    pending: Option<Suggestion>,
+    pub agreed: usize,
+    pub compared: usize,
+}
+
+impl PracticeAiAdvisor {
+    /// Work out a suggestion for the piece in play, if it doesn't have one
+    /// yet; call again after a hold brings out a different piece
+    pub fn consider(&mut self, game: &Game) {
+        if !game.piece_active()
+            || game.game_over
+            || self
+                .pending
+                .as_ref()
+                .is_some_and(|s| s.kind == game.current.tetro.kind)
+        {
+            return;
+        }
+        self.pending = self.ai.choose_move(game).map(|mv| {
+            let mut piece = game.current.clone();
+            piece.rotation = mv.rotation;
+            piece.x = mv.column;
+            Suggestion {
+                kind: piece.tetro.kind,
+                mv,
+                cells: sorted_cells(&game.landing(&piece)),
+            }
+        });
+        self.last_suggestion = self.pending.as_ref().map(|s| s.mv);
+    }
+
+    /// Score the piece that just locked as `piece` against its suggestion;
+    /// the suggestion when they differ. A piece with no suggestion isn't counted.
+    pub fn judge(&mut self, piece: &ActivePiece) -> Option<Suggestion> {
+        let suggestion = self.pending.take()?;
+        if suggestion.kind != piece.tetro.kind {
+            return None;
+        }
+        self.compared += 1;
+        if suggestion.cells == sorted_cells(piece) {
+            self.agreed += 1;
+            None
+        } else {
+            Some(suggestion)
+        }
+    }
+
+    /// Share of judged pieces placed where the AI would have, 0.0..=100.0
+    pub fn agreement_rate(&self) -> Option<f64> {
+        (self.compared > 0).then(|| self.agreed as f64 / self.compared as f64 * 100.0)
+    }
+}
@@ -0,0 +1,477 @@
+//! Post-game review: each placement in a replay compared with the AI's
+//! choice, and the shape of the stack a game was played on
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::{
+    ActivePiece, BOARD_HEIGHT, BOARD_WIDTH, BlockType, Board, Game, GameEvent, Tetromino,
+    ai::{self, Ai, AiMove},
+    replay::{Replay, ReplayPlayer},
+};
+
+/// Placements listed as the worst in the text report
+const WORST_SHOWN: usize = 5;
+
+/// One locked piece next to what the AI would have done with it
+#[derive(Clone, Debug)]
+pub struct PlacementAnalysis {
+    /// 1-based count of pieces locked so far
+    pub piece: usize,
+    pub kind: BlockType,
+    pub actual: AiMove,
+    pub suggested: AiMove,
+    /// AI evaluation of the actual placement minus that of its own choice;
+    /// negative means the actual one was worse
+    pub score_delta: f64,
+    /// A T-spin clear was available and the piece didn't make one
+    pub missed_t_spin: bool,
+    /// Some placement would have emptied the board and this one didn't
+    pub missed_perfect_clear: bool,
+}
+
+/// 1-based board column of the leftmost cell of `kind` placed as `mv`
+fn leftmost_column(kind: BlockType, mv: AiMove) -> i32 {
+    let piece = ActivePiece {
+        tetro: Tetromino::new(kind),
+        rotation: mv.rotation,
+        x: mv.column,
+        y: 0,
+    };
+    piece
+        .cells()
+        .iter()
+        .map(|&(x, _)| x)
+        .min()
+        .unwrap_or(mv.column)
+        + 1
+}
+
+impl PlacementAnalysis {
+    pub fn describe(&self) -> String {
+        format!(
+            "#{} {:?}: rotation {} at column {}, AI suggests rotation {} at column {} ({:+.2})",
+            self.piece,
+            self.kind,
+            self.actual.rotation,
+            leftmost_column(self.kind, self.actual),
+            self.suggested.rotation,
+            leftmost_column(self.kind, self.suggested),
+            self.score_delta
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AnalysisReport {
+    pub placements: Vec<PlacementAnalysis>,
+}
+
+impl AnalysisReport {
+    pub fn average_delta(&self) -> f64 {
+        if self.placements.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = self.placements.iter().map(|p| p.score_delta).sum();
+        total / self.placements.len() as f64
+    }
+
+    /// The `n` placements furthest below the AI's choice, worst first
+    pub fn worst(&self, n: usize) -> Vec<&PlacementAnalysis> {
+        let mut sorted: Vec<&PlacementAnalysis> = self
+            .placements
+            .iter()
+            .filter(|p| p.score_delta < 0.0)
+            .collect();
+        sorted.sort_by(|a, b| a.score_delta.total_cmp(&b.score_delta));
+        sorted.truncate(n);
+        sorted
+    }
+
+    pub fn missed_t_spins(&self) -> usize {
+        self.placements.iter().filter(|p| p.missed_t_spin).count()
+    }
+
+    pub fn missed_perfect_clears(&self) -> usize {
+        self.placements
+            .iter()
+            .filter(|p| p.missed_perfect_clear)
+            .count()
+    }
+}
+
+impl fmt::Display for AnalysisReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Replay analysis: {} pieces", self.placements.len())?;
+        writeln!(
+            f,
+            "Average score delta per piece: {:+.2}",
+            self.average_delta()
+        )?;
+        writeln!(f, "Missed T-spin opportunities: {}", self.missed_t_spins())?;
+        writeln!(f, "Missed perfect clears: {}", self.missed_perfect_clears())?;
+        let worst = self.worst(WORST_SHOWN);
+        if !worst.is_empty() {
+            writeln!(f, "Worst placements:")?;
+            for placement in worst {
+                writeln!(f, "  {}", placement.describe())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_empty(board: &Board) -> bool {
+    board.iter().flatten().all(Option::is_none)
+}
+
+/// The current piece is a T that could rest in a T-slot and clear a line there.
+/// Whether the slot can actually be reached isn't checked.
+fn t_spin_available(game: &Game) -> bool {
+    if game.current.tetro.kind != BlockType::T {
+        return false;
+    }
+    let mut piece = game.current.clone();
+    for rotation in 0..piece.tetro.rotations.len() {
+        for x in -2..BOARD_WIDTH as i32 {
+            for y in -2..BOARD_HEIGHT as i32 {
+                piece.rotation = rotation;
+                piece.x = x;
+                piece.y = y;
+                if !game.fits(&piece) || !game.in_t_slot(&piece) {
+                    continue;
+                }
+                let resting = game.landing(&piece).y == y;
+                if resting && ai::place(game, &piece).lines > 0 {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Re-simulates a replay and reviews every placement in it
+pub struct ReplayAnalyzer;
+
+impl ReplayAnalyzer {
+    pub fn analyze(replay: &Replay) -> AnalysisReport {
+        let ai = Ai::default();
+        let mut player = ReplayPlayer::new(replay.clone());
+        let mut game = player.new_game();
+        let mut report = AnalysisReport::default();
+        // the game as the piece now in play entered it (re-taken after a hold)
+        let mut before: Option<Game> = None;
+        let mut locked = 0;
+        loop {
+            if game.piece_active()
+                && before
+                    .as_ref()
+                    .is_none_or(|b| b.current.tetro.kind != game.current.tetro.kind)
+            {
+                before = Some(game.fork());
+            }
+            let more = player.step_once(&mut game);
+            let events = game.drain_events();
+            for (i, event) in events.iter().enumerate() {
+                let GameEvent::PieceLocked {
+                    kind,
+                    rotation,
+                    x,
+                    y,
+                } = *event
+                else {
+                    continue;
+                };
+                locked += 1;
+                let Some(fork) = before.take() else {
+                    continue;
+                };
+                if fork.current.tetro.kind != kind {
+                    continue;
+                }
+                let mut piece = fork.current.clone();
+                piece.rotation = rotation;
+                piece.x = x;
+                piece.y = y;
+                let t_spin = matches!(
+                    events.get(i + 1),
+                    Some(GameEvent::LinesCleared { t_spin: true, .. })
+                );
+                if let Some(analysis) = review(&ai, &fork, &piece, t_spin, locked) {
+                    report.placements.push(analysis);
+                }
+            }
+            if !more {
+                return report;
+            }
+        }
+    }
+}
+
+/// Compare the piece locked as `piece` with the AI's best move from `game`
+fn review(
+    ai: &Ai,
+    game: &Game,
+    piece: &ActivePiece,
+    t_spin: bool,
+    number: usize,
+) -> Option<PlacementAnalysis> {
+    let (suggested, best) = ai.best_move(game)?;
+    let actual = ai::place(game, piece);
+    let cleared_all = is_empty(&actual.board);
+    Some(PlacementAnalysis {
+        piece: number,
+        kind: piece.tetro.kind,
+        actual: AiMove {
+            rotation: piece.rotation,
+            column: piece.x,
+        },
+        suggested,
+        score_delta: ai.evaluate(&actual.board, actual.lines) - best,
+        missed_t_spin: !t_spin && t_spin_available(game),
+        missed_perfect_clear: !cleared_all
+            && ai::placements(game).iter().any(|(_, p)| is_empty(&p.board)),
+    })
+}
+
+/// The stack at one moment, reduced to the figures `BoardAnalyzer` needs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BoardSnapshot {
+    /// Empty cells with a block somewhere above them
+    pub holes: usize,
+    /// Sum of height differences between neighbouring columns
+    pub bumpiness: usize,
+    /// Sum of column heights
+    pub aggregate_height: usize,
+    pub fill: f64,
+    /// Pieces locked, lines cleared and keys pressed in the game so far
+    pub pieces: usize,
+    pub lines: usize,
+    pub inputs: usize,
+}
+
+impl BoardSnapshot {
+    pub fn of(game: &Game) -> Self {
+        let heights = game.column_heights();
+        BoardSnapshot {
+            holes: game.holes(),
+            bumpiness: heights.windows(2).map(|w| w[0].abs_diff(w[1])).sum(),
+            aggregate_height: heights.iter().sum(),
+            fill: game.fill_percentage(),
+            pieces: game.pieces_placed,
+            lines: game.lines_cleared,
+            inputs: game.inputs,
+        }
+    }
+}
+
+/// What one placement did, between the snapshots either side of it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlacementChange {
+    /// 1-based count of pieces locked so far
+    pub piece: usize,
+    pub holes_added: usize,
+    pub lines: usize,
+    pub inputs: usize,
+}
+
+impl PlacementChange {
+    /// Lines cleared per key pressed; a piece placed without a key counts one
+    pub fn lines_per_input(&self) -> f64 {
+        self.lines as f64 / self.inputs.max(1) as f64
+    }
+}
+
+/// Averages over a game's stack and its standout placements
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BoardAnalysis {
+    pub avg_holes: f64,
+    pub avg_bumpiness: f64,
+    pub avg_height: f64,
+    pub avg_fill: f64,
+    /// The placement that opened the most holes, if any did
+    pub worst: Option<PlacementChange>,
+    /// The clear with the most lines per key pressed, if there was one
+    pub best: Option<PlacementChange>,
+}
+
+impl fmt::Display for BoardAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Stack quality: avg holes: {:.1}, avg bumpiness: {:.1}",
+            self.avg_holes, self.avg_bumpiness
+        )
+    }
+}
+
+/// Summarizes `Game::board_history`
+pub struct BoardAnalyzer;
+
+impl BoardAnalyzer {
+    /// Ties for worst and best go to the earlier placement
+    pub fn compute(snapshots: &[BoardSnapshot]) -> BoardAnalysis {
+        if snapshots.is_empty() {
+            return BoardAnalysis::default();
+        }
+        let n = snapshots.len() as f64;
+        let average =
+            |figure: fn(&BoardSnapshot) -> f64| snapshots.iter().map(figure).sum::<f64>() / n;
+        let changes: Vec<PlacementChange> = snapshots
+            .windows(2)
+            .map(|pair| PlacementChange {
+                piece: pair[1].pieces,
+                holes_added: pair[1].holes.saturating_sub(pair[0].holes),
+                lines: pair[1].lines - pair[0].lines,
+                inputs: pair[1].inputs - pair[0].inputs,
+            })
+            .collect();
+        let worst = changes
+            .iter()
+            .filter(|c| c.holes_added > 0)
+            .reduce(|worst, c| {
+                if c.holes_added > worst.holes_added {
+                    c
+                } else {
+                    worst
+                }
+            })
+            .copied();
+        let best = changes
+            .iter()
+            .filter(|c| c.lines > 0)
+            .reduce(|best, c| {
+                if c.lines_per_input() > best.lines_per_input() {
+                    c
+                } else {
+                    best
+                }
+            })
+            .copied();
+        BoardAnalysis {
+            avg_holes: average(|s| s.holes as f64),
+            avg_bumpiness: average(|s| s.bumpiness as f64),
+            avg_height: average(|s| s.aggregate_height as f64),
+            avg_fill: average(|s| s.fill),
+            worst,
+            best,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Action, GameMode, layout, rules::Rules};
+
+    /// A replay of `actions`, ten ticks apart
+    fn replay_of(rules: Rules, actions: &[Action]) -> Replay {
+        let mut replay = Replay::new(1, GameMode::Marathon, rules);
+        for (i, &action) in actions.iter().enumerate() {
+            replay.record(i as u64 * 10 + 1, action);
+        }
+        replay.end_tick = actions.len() as u64 * 10 + 10;
+        replay
+    }
+
+    /// An O to play onto two rows open at the right, which it can clear entirely
+    fn perfect_clear_setup() -> Rules {
+        let rows = ["GGGGGGGG..".to_string(), "GGGGGGGG..".to_string()];
+        Rules {
+            pieces: Some(vec![BlockType::O, BlockType::O]),
+            start_board: Some(Box::new(layout::from_rows(&rows).unwrap())),
+            ..Rules::default()
+        }
+    }
+
+    #[test]
+    fn a_tower_in_the_middle_is_flagged() {
+        let report = ReplayAnalyzer::analyze(&replay_of(Rules::default(), &[Action::HardDrop; 8]));
+        assert_eq!(report.placements.len(), 8);
+        assert!(report.average_delta() < 0.0);
+        let worst = report.worst(1)[0];
+        assert!(worst.score_delta < 0.0);
+        assert!(worst.piece > 1);
+    }
+
+    #[test]
+    fn a_missed_perfect_clear_is_flagged() {
+        let report =
+            ReplayAnalyzer::analyze(&replay_of(perfect_clear_setup(), &[Action::HardDrop]));
+        assert_eq!(report.missed_perfect_clears(), 1);
+        assert!(report.placements[0].score_delta < 0.0);
+    }
+
+    #[test]
+    fn taking_the_perfect_clear_is_not_flagged() {
+        let mut actions = vec![Action::MoveRight; 4];
+        actions.push(Action::HardDrop);
+        let report = ReplayAnalyzer::analyze(&replay_of(perfect_clear_setup(), &actions));
+        assert_eq!(report.placements.len(), 1);
+        assert_eq!(report.missed_perfect_clears(), 0);
+        assert_eq!(report.placements[0].score_delta, 0.0);
+    }
+
+    /// A snapshot after `pieces` pieces, `lines` lines and `inputs` keys
+    fn snapshot(
+        holes: usize,
+        bumpiness: usize,
+        pieces: usize,
+        lines: usize,
+        inputs: usize,
+    ) -> BoardSnapshot {
+        BoardSnapshot {
+            holes,
+            bumpiness,
+            aggregate_height: bumpiness * 2,
+            fill: holes as f64,
+            pieces,
+            lines,
+            inputs,
+        }
+    }
+
+    #[test]
+    fn the_analysis_averages_the_snapshots_and_picks_out_placements() {
+        let analysis = BoardAnalyzer::compute(&[
+            snapshot(0, 0, 0, 0, 0),
+            snapshot(2, 4, 1, 0, 3),
+            snapshot(5, 6, 2, 0, 5),
+            snapshot(1, 2, 3, 2, 9),
+            snapshot(1, 2, 4, 3, 10),
+        ]);
+        assert_eq!(analysis.avg_holes, 1.8);
+        assert_eq!(analysis.avg_bumpiness, 2.8);
+        assert_eq!(analysis.avg_height, 5.6);
+        assert_eq!(analysis.avg_fill, 1.8);
+        let worst = analysis.worst.unwrap();
+        assert_eq!((worst.piece, worst.holes_added), (2, 3));
+        let best = analysis.best.unwrap();
+        assert_eq!((best.piece, best.lines, best.inputs), (4, 1, 1));
+        assert_eq!(
+            analysis.to_string(),
+            "Stack quality: avg holes: 1.8, avg bumpiness: 2.8"
+        );
+    }
+
+    #[test]
+    fn ties_go_to_the_earlier_placement() {
+        let analysis = BoardAnalyzer::compute(&[
+            snapshot(0, 0, 0, 0, 0),
+            snapshot(1, 0, 1, 1, 2),
+            snapshot(2, 0, 2, 2, 4),
+        ]);
+        assert_eq!(analysis.worst.unwrap().piece, 1);
+        assert_eq!(analysis.best.unwrap().piece, 1);
+    }
+
+    #[test]
+    fn a_clean_game_without_clears_has_no_standouts() {
+        assert_eq!(BoardAnalyzer::compute(&[]), BoardAnalysis::default());
+        let analysis = BoardAnalyzer::compute(&[snapshot(0, 0, 0, 0, 0), snapshot(0, 2, 1, 0, 1)]);
+        assert_eq!(analysis.worst, None);
+        assert_eq!(analysis.best, None);
+        assert_eq!(analysis.avg_bumpiness, 1.0);
+    }
+}
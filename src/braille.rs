@@ -0,0 +1,117 @@
+//! The board as a Braille raster on a `Canvas`, scaled to the space it gets.
+//! A Braille character is two dots by four and about twice as tall as wide,
+//! so cells `scale` dots a side come out square.
+
+use ratatui::{
+    layout::Rect,
+    style::Color,
+    widgets::canvas::{Painter, Shape},
+};
+use tetris_game::{BOARD_HEIGHT, BOARD_WIDTH};
+
+/// The smallest cell: two dots a side, half the half-block board's lines
+pub const MIN_SCALE: u16 = 2;
+
+/// Dots per cell side for the biggest board that fits in `area` with its
+/// border; a board cell is `scale` / 2 characters wide and `scale` / 4 tall
+pub fn scale_for(area: Rect) -> u16 {
+    let width = area.width.saturating_sub(2) * 2 / BOARD_WIDTH as u16;
+    let height = area.height.saturating_sub(2) * 4 / BOARD_HEIGHT as u16;
+    width.min(height).max(MIN_SCALE)
+}
+
+/// Characters wide and lines tall for `cols` by `rows` cells at `scale`
+pub fn size(cols: usize, rows: usize, scale: u16) -> (u16, u16) {
+    let scale = usize::from(scale);
+    (
+        (cols * scale).div_ceil(2) as u16,
+        (rows * scale).div_ceil(4) as u16,
+    )
+}
+
+/// What a cell of the raster shows
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BrailleCell {
+    Solid(Color),
+    /// Just the outline; solid below three dots a side, where there's no inside
+    Ghost(Color),
+    /// The piece in play, drawn over everything else since a character only
+    /// takes one color
+    Piece(Color),
+}
+
+impl BrailleCell {
+    fn color(self) -> Color {
+        match self {
+            BrailleCell::Solid(color) | BrailleCell::Ghost(color) | BrailleCell::Piece(color) => {
+                color
+            }
+        }
+    }
+}
+
+/// Rows of cells, top first, `None` for empty, drawn `top` dots down. From
+/// three dots a side each cell leaves its last row and column of dots blank
+/// where the next cell is another color, so touching pieces stay apart.
+pub struct BrailleBoard {
+    pub cells: Vec<Vec<Option<BrailleCell>>>,
+    pub scale: u16,
+    pub top: usize,
+    /// Dots across and down the canvas has; any past them are dropped
+    /// rather than wrapped onto the next line
+    pub dots: (usize, usize),
+}
+
+impl BrailleBoard {
+    fn at(&self, x: usize, y: usize) -> Option<Color> {
+        self.cells
+            .get(y)
+            .and_then(|row| row.get(x))
+            .copied()
+            .flatten()
+            .map(BrailleCell::color)
+    }
+
+    fn lit(&self, cell: BrailleCell, x: usize, y: usize, dx: usize, dy: usize) -> bool {
+        let scale = usize::from(self.scale);
+        if scale < 3 {
+            return true;
+        }
+        let last = scale - 1;
+        let color = Some(cell.color());
+        let right = dx < last || self.at(x + 1, y) == color;
+        let below = dy < last || self.at(x, y + 1) == color;
+        let corner = dx < last || dy < last || self.at(x + 1, y + 1) == color;
+        match cell {
+            BrailleCell::Ghost(_) => {
+                dx < last && dy < last && (dx == 0 || dy == 0 || dx + 2 == scale || dy + 2 == scale)
+            }
+            BrailleCell::Solid(_) | BrailleCell::Piece(_) => right && below && corner,
+        }
+    }
+}
+
+impl Shape for BrailleBoard {
+    fn draw(&self, painter: &mut Painter) {
+        let scale = usize::from(self.scale);
+        let (width, height) = self.dots;
+        for piece_pass in [false, true] {
+            for (y, row) in self.cells.iter().enumerate() {
+                for (x, cell) in row.iter().enumerate() {
+                    let Some(cell) = *cell else { continue };
+                    if matches!(cell, BrailleCell::Piece(_)) != piece_pass {
+                        continue;
+                    }
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            let (px, py) = (x * scale + dx, self.top + y * scale + dy);
+                            if px < width && py < height && self.lit(cell, x, y, dx, dy) {
+                                painter.paint(px, py, cell.color());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
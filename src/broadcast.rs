@@ -0,0 +1,153 @@
+use serde::Serialize;
+use std::{
+    io,
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+use tungstenite::{Message, WebSocket};
+
+use tetris_game::{BOARD_HEIGHT, BOARD_WIDTH, BlockType, Game};
+
+/// A spectator that can't take a frame within this long is dropped rather than stalling the game
+const WRITE_TIMEOUT: Duration = Duration::from_millis(50);
+
+fn name(kind: BlockType) -> &'static str {
+    match kind {
+        BlockType::I => "I",
+        BlockType::O => "O",
+        BlockType::T => "T",
+        BlockType::S => "S",
+        BlockType::Z => "Z",
+        BlockType::J => "J",
+        BlockType::L => "L",
+        BlockType::Garbage => "G",
+    }
+}
+
+#[derive(Serialize)]
+pub struct PieceSnapshot {
+    pub kind: &'static str,
+    /// Board (x, y) of each block; y can be negative above the board
+    pub cells: Vec<(i32, i32)>,
+}
+
+/// The game as spectators see it, sent as JSON after every frame
+#[derive(Serialize)]
+pub struct GameSnapshot {
+    /// Settled blocks by piece letter ("G" for garbage), top row first
+    pub board: [[Option<&'static str>; BOARD_WIDTH]; BOARD_HEIGHT],
+    pub score: usize,
+    pub level: usize,
+    pub lines: usize,
+    /// None between a lock and the next spawn
+    pub current_piece: Option<PieceSnapshot>,
+    pub next_piece: Option<&'static str>,
+    pub held_piece: Option<&'static str>,
+    pub game_over: bool,
+}
+
+impl GameSnapshot {
+    pub fn of(game: &Game) -> Self {
+        let mut board = [[None; BOARD_WIDTH]; BOARD_HEIGHT];
+        for (y, row) in game.board.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                board[y][x] = cell.map(name);
+            }
+        }
+        GameSnapshot {
+            board,
+            score: game.score,
+            level: game.level,
+            lines: game.lines_cleared,
+            current_piece: game.piece_active().then(|| PieceSnapshot {
+                kind: name(game.current.tetro.kind),
+                cells: game.current.cells(),
+            }),
+            next_piece: game.next.front().copied().map(name),
+            held_piece: game.hold.map(name),
+            game_over: game.game_over,
+        }
+    }
+}
+
+/// WebSocket server pushing the game state to every connected spectator
+pub struct Broadcaster {
+    clients: Arc<Mutex<Vec<WebSocket<TcpStream>>>>,
+}
+
+impl Broadcaster {
+    /// Listen on `port` on all interfaces, accepting spectators on a background thread
+    pub fn start(port: u16) -> io::Result<Self> {
+        Ok(Broadcaster::serve(TcpListener::bind(("0.0.0.0", port))?))
+    }
+
+    /// Accept spectators from `listener` on a background thread
+    fn serve(listener: TcpListener) -> Self {
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let accepted = Arc::clone(&clients);
+        thread::spawn(move || {
+            for stream in listener.incoming().filter_map(Result::ok) {
+                let _ = stream.set_nodelay(true);
+                if let Ok(socket) = tungstenite::accept(stream) {
+                    let _ = socket.get_ref().set_write_timeout(Some(WRITE_TIMEOUT));
+                    accepted.lock().unwrap().push(socket);
+                }
+            }
+        });
+        Broadcaster { clients }
+    }
+
+    /// Send `game` to every spectator, dropping any whose connection failed
+    pub fn send(&self, game: &Game) {
+        let mut clients = self.clients.lock().unwrap();
+        if clients.is_empty() {
+            return;
+        }
+        let Ok(json) = serde_json::to_string(&GameSnapshot::of(game)) else {
+            return;
+        };
+        clients.retain_mut(|socket| socket.send(Message::text(json.clone())).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tetris_game::{GameMode, GameSetup, rules::Rules};
+
+    #[test]
+    fn a_spectator_receives_the_board() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let broadcaster = Broadcaster::serve(listener);
+        let (mut client, _) = tungstenite::connect(format!("ws://127.0.0.1:{}", port)).unwrap();
+        // the server finishes its side of the handshake on its own thread
+        for _ in 0..100 {
+            if !broadcaster.clients.lock().unwrap().is_empty() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let mut game = Game::new(GameSetup {
+            mode: GameMode::Marathon,
+            rules: Rules::default(),
+            seed: Some(1),
+        });
+        game.set_cell(0, BOARD_HEIGHT - 1, Some(BlockType::Garbage));
+        game.set_cell(1, BOARD_HEIGHT - 1, Some(BlockType::T));
+        broadcaster.send(&game);
+
+        let frame = client.read().unwrap();
+        let sent: serde_json::Value = serde_json::from_str(frame.to_text().unwrap()).unwrap();
+        let expected = serde_json::to_value(GameSnapshot::of(&game)).unwrap();
+        assert_eq!(sent, expected);
+        let bottom = &sent["board"][BOARD_HEIGHT - 1];
+        assert_eq!(
+            (&bottom[0], &bottom[1], &bottom[2]),
+            (&"G".into(), &"T".into(), &serde_json::Value::Null)
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs, io,
+    path::PathBuf,
+    time::Duration,
+};
+
+use tetris_game::{Game, TICK, milestones::thousands, paths};
+
+use crate::{Term, format_duration, sparkline::Sparkline};
+
+/// Format of career.json; older files are upgraded when loaded
+const CAREER_VERSION: u32 = 2;
+
+/// Scores kept for the trend line on the career screen
+const RECENT_SCORES: usize = 30;
+
+/// Weeks of best scores the career screen lists
+const WEEKS_SHOWN: usize = 8;
+
+/// Totals over every game ever played, per profile
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CareerStats {
+    /// 0 for files written before the format was versioned
+    version: u32,
+    pub games: u64,
+    pub lines: u64,
+    pub pieces: u64,
+    pub total_score: u64,
+    pub best_score: usize,
+    pub best_level: usize,
+    /// Game time in milliseconds
+    play_ms: u64,
+    /// Four-line clears (each group of four in a zone counts)
+    pub tetrises: u64,
+    /// Best score per ISO week, e.g. "2026-W42"
+    pub weekly_best: BTreeMap<String, usize>,
+    /// Final scores of the last `RECENT_SCORES` games, oldest first
+    pub recent_scores: VecDeque<usize>,
+}
+
+fn path() -> Option<PathBuf> {
+    paths::data_dir().map(|d| d.join("career.json"))
+}
+
+impl CareerStats {
+    /// Read the saved totals; a missing file starts from zero, and so does an
+    /// unreadable one, with a warning. `None` if a newer version wrote the
+    /// file, so it isn't overwritten.
+    pub fn load() -> Option<Self> {
+        let Some(text) = path().and_then(|p| fs::read_to_string(p).ok()) else {
+            return Some(CareerStats::default().migrated());
+        };
+        match serde_json::from_str::<CareerStats>(&text) {
+            Ok(stats) if stats.version <= CAREER_VERSION => Some(stats.migrated()),
+            Ok(stats) => {
+                eprintln!(
+                    "career stats were written by a newer version (format {}), leaving them alone",
+                    stats.version
+                );
+                None
+            }
+            Err(e) => {
+                eprintln!("could not read career stats, starting over: {}", e);
+                Some(CareerStats::default().migrated())
+            }
+        }
+    }
+
+    /// Bring an older file up to `CAREER_VERSION`. Version 1 didn't track
+    /// tetrises or trends; they start from zero, as the field defaults do.
+    fn migrated(mut self) -> Self {
+        self.version = CAREER_VERSION;
+        self
+    }
+
+    pub fn record(&mut self, game: &Game) {
+        self.games += 1;
+        self.lines += game.lines_cleared as u64;
+        self.pieces += game.pieces_placed as u64;
+        self.total_score += game.score as u64;
+        self.best_score = self.best_score.max(game.score);
+        self.best_level = self.best_level.max(game.level);
+        self.play_ms += (TICK * game.ticks as u32).as_millis() as u64;
+        self.tetrises += game.tetrises as u64;
+        let week = chrono::Local::now().format("%G-W%V").to_string();
+        let best = self.weekly_best.entry(week).or_default();
+        *best = (*best).max(game.score);
+        self.recent_scores.push_back(game.score);
+        if self.recent_scores.len() > RECENT_SCORES {
+            self.recent_scores.pop_front();
+        }
+    }
+
+    pub fn play_time(&self) -> Duration {
+        Duration::from_millis(self.play_ms)
+    }
+
+    pub fn average_score(&self) -> u64 {
+        self.total_score.checked_div(self.games).unwrap_or(0)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path =
+            path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+}
+
+/// Full-screen career totals and trends; any of Q, Esc or Enter closes it.
+/// `career` is `None` when the saved stats couldn't be used.
+pub fn show(terminal: &mut Term, career: Option<&CareerStats>) -> io::Result<()> {
+    loop {
+        terminal.draw(|f| ui(f, career))?;
+        if let Event::Key(key) = event::read()?
+            && key.kind != KeyEventKind::Release
+            && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter)
+        {
+            return Ok(());
+        }
+    }
+}
+
+fn ui<B: ratatui::backend::Backend>(f: &mut ratatui::Frame<B>, career: Option<&CareerStats>) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let row = |name: &str, value: String| {
+        Line::from(vec![
+            Span::raw(format!("{:<20}", name)),
+            Span::styled(
+                format!("{:>12}", value),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ])
+    };
+    let mut lines = vec![Line::from("")];
+    match career {
+        None => lines.push(Line::styled("Career stats are unavailable", dim)),
+        Some(stats) if stats.games == 0 => lines.push(Line::styled("No games played yet", dim)),
+        Some(stats) => {
+            lines.extend([
+                row("Games played", thousands(stats.games as usize)),
+                row("Lines", thousands(stats.lines as usize)),
+                row("Tetrises", thousands(stats.tetrises as usize)),
+                row("Pieces", thousands(stats.pieces as usize)),
+                row("Time played", format_duration(stats.play_time())),
+                row("Average score", thousands(stats.average_score() as usize)),
+                row("Best score", thousands(stats.best_score)),
+                row("Best level", stats.best_level.to_string()),
+                Line::from(""),
+                Line::from("Best score by week"),
+            ]);
+            for (week, best) in stats.weekly_best.iter().rev().take(WEEKS_SHOWN) {
+                lines.push(row(&format!("  {}", week), thousands(*best)));
+            }
+            let scores: Vec<f64> = stats.recent_scores.iter().map(|&s| s as f64).collect();
+            let top = scores.iter().copied().fold(0.0, f64::max);
+            lines.extend([
+                Line::from(""),
+                Line::from(format!("Last {} scores", scores.len())),
+                Line::styled(
+                    Sparkline::render(&scores, 0.0, top),
+                    Style::default().fg(Color::Cyan),
+                ),
+            ]);
+        }
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("Q : Close"));
+
+    let title = match paths::profile() {
+        Some(name) => format!(" Career — {} ", name),
+        None => " Career ".to_string(),
+    };
+    let paragraph =
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, f.size());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tetris_game::{GameMode, GameSetup, rules::Rules};
+
+    /// A finished game with these totals, `seconds` long
+    fn played(score: usize, lines: usize, level: usize, seconds: u64) -> Game {
+        let mut game = Game::new(GameSetup {
+            mode: GameMode::Marathon,
+            rules: Rules::default(),
+            seed: Some(1),
+        });
+        game.score = score;
+        game.lines_cleared = lines;
+        game.pieces_placed = lines * 5 / 2;
+        game.level = level;
+        game.tetrises = lines / 8;
+        game.ticks = seconds * 1000 / TICK.as_millis() as u64;
+        game
+    }
+
+    #[test]
+    fn games_add_up() {
+        let mut stats = CareerStats::default();
+        stats.record(&played(4000, 20, 3, 90));
+        stats.record(&played(1000, 8, 5, 30));
+        assert_eq!(stats.games, 2);
+        assert_eq!(stats.lines, 28);
+        assert_eq!(stats.pieces, 70);
+        assert_eq!(stats.tetrises, 3);
+        assert_eq!(stats.total_score, 5000);
+        assert_eq!(stats.average_score(), 2500);
+        assert_eq!(stats.best_score, 4000);
+        assert_eq!(stats.best_level, 5);
+        assert_eq!(stats.play_time(), Duration::from_secs(120));
+        assert_eq!(stats.recent_scores, [4000, 1000]);
+        assert_eq!(stats.weekly_best.values().max(), Some(&4000));
+    }
+
+    #[test]
+    fn only_the_last_scores_are_kept() {
+        let mut stats = CareerStats::default();
+        for score in 0..RECENT_SCORES + 5 {
+            stats.record(&played(score, 0, 1, 1));
+        }
+        assert_eq!(stats.recent_scores.len(), RECENT_SCORES);
+        assert_eq!(stats.recent_scores.front(), Some(&5));
+    }
+
+    #[test]
+    fn stats_survive_a_round_trip() {
+        let mut stats = CareerStats::default().migrated();
+        stats.record(&played(4000, 20, 3, 90));
+        let json = serde_json::to_string(&stats).unwrap();
+        let read: CareerStats = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&read).unwrap(), json);
+        assert_eq!(read.version, CAREER_VERSION);
+        assert_eq!(read.play_time(), Duration::from_secs(90));
+    }
+
+    #[test]
+    fn older_files_are_upgraded() {
+        let v1 = r#"{"version":1,"games":3,"lines":40,"pieces":100,"total_score":9000,
+            "best_score":5000,"best_level":4,"play_ms":60000}"#;
+        let stats = serde_json::from_str::<CareerStats>(v1).unwrap().migrated();
+        assert_eq!(stats.version, CAREER_VERSION);
+        assert_eq!((stats.games, stats.lines, stats.best_score), (3, 40, 5000));
+        assert_eq!(stats.tetrises, 0);
+        assert!(stats.recent_scores.is_empty());
+        let unversioned: CareerStats = serde_json::from_str(r#"{"games":1}"#).unwrap();
+        assert_eq!(unversioned.version, 0);
+    }
+}
@@ -0,0 +1,328 @@
+use std::{env, path::PathBuf};
+
+use tetris_game::{GameMode, config::THEMES, rules::ClearGravity};
+
+use crate::profiles;
+
+/// Command line options
+#[derive(Debug)]
+pub struct Args {
+    pub help: bool,
+    pub mute: bool,
+    pub accessibility: bool,
+    pub replay: Option<PathBuf>,
+    /// `--convert-replay <FROM> <TO>`
+    pub convert_replay: Option<(PathBuf, PathBuf)>,
+    pub versus: bool,
+    /// `--host <PORT>`
+    pub host: Option<u16>,
+    /// `--connect <ADDR:PORT>`
+    pub connect: Option<String>,
+    /// `--match <ADDR:PORT>`, a matchmaking server
+    pub match_addr: Option<String>,
+    /// `--name <NAME>`, one word, for matchmaking
+    pub name: Option<String>,
+    /// `--serve-matches <PORT>`
+    pub serve_matches: Option<u16>,
+    pub export_svg: Option<PathBuf>,
+    /// `--log-events <FILE>`
+    pub log_events: Option<PathBuf>,
+    pub debug: bool,
+    /// `--mode`, already checked to be a known mode name
+    pub mode: Option<String>,
+    pub cheese_rows: Option<usize>,
+    pub seed: Option<u64>,
+    pub resume: bool,
+    pub grid: bool,
+    pub outline_blocks: bool,
+    pub halfblock: bool,
+    pub braille: bool,
+    pub ascii: bool,
+    pub high_contrast: bool,
+    /// `--theme`, already checked to be a built-in theme
+    pub theme: Option<String>,
+    pub config: Option<PathBuf>,
+    pub write_default_config: bool,
+    /// `--profile`, already checked to be a usable name
+    pub profile: Option<String>,
+    pub achievements: bool,
+    pub records: bool,
+    pub career: bool,
+    pub leaderboard: bool,
+    pub settings: bool,
+    /// `--preview <N>`, or 0 for `--no-preview`
+    pub preview: Option<usize>,
+    pub fair_start: bool,
+    pub practice: bool,
+    pub no_lock_on_soft_drop: bool,
+    pub clear_gravity: Option<ClearGravity>,
+    pub mirror: bool,
+    pub puzzle: Option<u32>,
+    pub puzzles: bool,
+    pub edit: Option<PathBuf>,
+    pub board: Option<PathBuf>,
+    /// `--position`, a code exported from a game
+    pub position: Option<String>,
+    pub no_summary: bool,
+    pub ws_broadcast: Option<u16>,
+}
+
+pub const USAGE: &str = "\
+Usage: tetris_game [OPTIONS]
+
+Options:
+  --mute           Disable sound effects
+  --accessibility  Announce game state on stderr for screen readers
+  --replay <FILE>  Watch a recorded replay (JSON or .tgr) instead of playing
+  --convert-replay <FROM> <TO>
+                   Convert a replay to .tgr (compact, for sharing) or back to
+                   JSON, by the extension of TO, then exit
+  --versus         Two players on one keyboard, sending garbage to each other
+  --host <PORT>    Wait for a player to --connect on PORT and play versus over
+                   the network (builds with the net feature)
+  --connect <ADDR:PORT>
+                   Join a network versus game hosted at ADDR:PORT
+  --match <ADDR:PORT>
+                   Wait at the matchmaking server ADDR:PORT to be paired with
+                   the next player, then race them on the same pieces
+  --name <NAME>    Name other players see with --match (default: login name)
+  --serve-matches <PORT>
+                   Run a matchmaking server on PORT, without a game
+  --ws-broadcast <PORT>
+                   Stream the game as JSON to WebSocket spectators on PORT
+                   (assets/spectate.html draws it in a browser)
+  --export-svg <FILE>
+                   Save the final board as an SVG image when the game ends
+  --log-events <FILE>
+                   Append a JSON line to FILE for each spawn, lock, clear,
+                   hold, level up and game over, stamped with the game tick
+  --debug          Show the game's state hash, and store one per piece in the
+                   replay so watching it later catches any desync
+  --mode <MODE>    marathon (default), sprint (40 lines), ultra (2 minutes), cheese,
+                   daily (today's shared seed, one attempt), competitive (no
+                   ghost, one preview, hidden hold, 300ms lock delay; clears
+                   score 1.25x) or tutorial (the basics step by step)
+  --cheese-rows <N>
+                   Garbage rows to dig through in cheese mode (default 10)
+  --seed <N>       Use a fixed seed so runs can be compared
+  --fair-start     Never start with an S or Z piece
+  --practice       Coaching: when a piece lands away from where the AI would put
+                   it, ghost the AI's spot in green; tracks how often you agree.
+                   After a restart, your best game's last board shows dimmed
+                   behind the stack
+  --no-lock-on-soft-drop
+                   Soft drop never locks; a piece on the floor waits for lock
+                   delay or gravity
+  --clear-gravity <naive|sticky>
+                   After a clear, move the rows above down whole (naive, the
+                   default) or let each group of touching blocks fall on its own
+  --mirror         Swap what the left and right keys do, and the two rotations,
+                   for the single-player keys
+  --board <FILE>   Start with the blocks of a board saved by the editor
+  --position <CODE>
+                   Practice from a position code exported in game: its board,
+                   current piece, hold and queue
+  --puzzle <N>     Play puzzle N: clear a preset board with a fixed set of pieces
+  --puzzles        List the puzzles (built in, and any .toml files in the
+                   config directory's puzzles/ folder) with progress, then exit
+  --edit <FILE>    Build a starting board in an editor (saved to FILE), then play it
+  --resume         Continue the game saved with S (the save is then deleted)
+  --grid           Shade empty cells in a checkerboard for readability
+  --outline-blocks Draw locked blocks as outlines around each run of one kind
+  --halfblock      Draw two board rows per line for squarer, smaller cells
+  --braille        Draw the board as Braille dots, scaled to fit the terminal
+  --ascii          Draw everything with plain ASCII, for terminals or fonts
+                   that garble block and box-drawing characters
+  --preview <N>    Show only the next N pieces, for less lookahead
+  --no-preview     Hide the next and held pieces (same as --preview 0)
+  --high-contrast  Bold bright colors, thick white borders, an outlined ghost
+                   and a letter on every block (the high-contrast theme; F2
+                   toggles it in game)
+  --theme <NAME>   Colors to play in: default, pastel, dark, deuteranopia or
+                   tritanopia (colorblind-safe), mono or high-contrast (the
+                   config's [colors] still apply)
+  --profile <NAME> Keep saves, replays and settings under profile NAME (created
+                   if needed); without it a selector appears once profiles exist
+  --no-summary     Exit straight to the shell instead of showing a session summary
+  --achievements   List unlocked and locked achievements, then exit
+  --records        List all-time records (best combo, longest game, ...), then exit
+  --career         Show lifetime totals and recent score trends, then exit
+  --leaderboard    Show the top scores and times per mode, then exit
+  --settings       Change common settings (ghost, grid, theme, preview, DAS/ARR)
+                   on a screen that saves them to the config file, then exit
+  --config <FILE>  Read settings from FILE instead of the default location
+  --write-default-config
+                   Write a commented config file with the defaults and exit
+  -h, --help       Show this help";
+
+impl Args {
+    /// Parse `std::env::args`, returning a message on bad input
+    pub fn parse() -> Result<Args, String> {
+        Args::parse_from(env::args().skip(1))
+    }
+
+    /// Local or network versus, neither of which touches saved data
+    pub fn two_player(&self) -> bool {
+        self.versus || self.host.is_some() || self.connect.is_some() || self.match_addr.is_some()
+    }
+
+    pub fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+        let mut out = Args {
+            help: false,
+            mute: false,
+            accessibility: false,
+            replay: None,
+            convert_replay: None,
+            versus: false,
+            host: None,
+            connect: None,
+            match_addr: None,
+            name: None,
+            serve_matches: None,
+            export_svg: None,
+            log_events: None,
+            debug: false,
+            mode: None,
+            cheese_rows: None,
+            seed: None,
+            resume: false,
+            grid: false,
+            outline_blocks: false,
+            halfblock: false,
+            braille: false,
+            ascii: false,
+            high_contrast: false,
+            theme: None,
+            config: None,
+            write_default_config: false,
+            profile: None,
+            achievements: false,
+            records: false,
+            career: false,
+            leaderboard: false,
+            settings: false,
+            preview: None,
+            fair_start: false,
+            practice: false,
+            no_lock_on_soft_drop: false,
+            clear_gravity: None,
+            mirror: false,
+            puzzle: None,
+            puzzles: false,
+            edit: None,
+            board: None,
+            position: None,
+            no_summary: false,
+            ws_broadcast: None,
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--mute" => out.mute = true,
+                "--accessibility" => out.accessibility = true,
+                "--versus" => out.versus = true,
+                "--resume" => out.resume = true,
+                "--grid" => out.grid = true,
+                "--outline-blocks" => out.outline_blocks = true,
+                "--halfblock" => out.halfblock = true,
+                "--braille" => out.braille = true,
+                "--ascii" => out.ascii = true,
+                "--high-contrast" => out.high_contrast = true,
+                "--achievements" => out.achievements = true,
+                "--records" => out.records = true,
+                "--career" => out.career = true,
+                "--leaderboard" => out.leaderboard = true,
+                "--settings" => out.settings = true,
+                "--fair-start" => out.fair_start = true,
+                "--practice" => out.practice = true,
+                "--debug" => out.debug = true,
+                "--no-lock-on-soft-drop" => out.no_lock_on_soft_drop = true,
+                "--clear-gravity" => {
+                    let name = value(&arg, args.next())?;
+                    match ClearGravity::from_name(&name) {
+                        Some(gravity) => out.clear_gravity = Some(gravity),
+                        None => {
+                            return Err(format!("unknown clear gravity '{}'\n\n{}", name, USAGE));
+                        }
+                    }
+                }
+                "--mirror" => out.mirror = true,
+                "--puzzles" => out.puzzles = true,
+                "--puzzle" => out.puzzle = Some(number(&arg, args.next())?),
+                "--preview" => out.preview = Some(number(&arg, args.next())?),
+                "--no-preview" => out.preview = Some(0),
+                "--no-summary" => out.no_summary = true,
+                "--write-default-config" => out.write_default_config = true,
+                "--mode" => out.mode = Some(value(&arg, args.next())?),
+                "--theme" => out.theme = Some(value(&arg, args.next())?),
+                "--cheese-rows" => out.cheese_rows = Some(number(&arg, args.next())?),
+                "--profile" => out.profile = Some(value(&arg, args.next())?),
+                "--config" => out.config = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--host" => out.host = Some(number(&arg, args.next())?),
+                "--connect" => out.connect = Some(value(&arg, args.next())?),
+                "--match" => out.match_addr = Some(value(&arg, args.next())?),
+                "--name" => out.name = Some(value(&arg, args.next())?),
+                "--serve-matches" => out.serve_matches = Some(number(&arg, args.next())?),
+                "--ws-broadcast" => out.ws_broadcast = Some(number(&arg, args.next())?),
+                "--seed" => out.seed = Some(number(&arg, args.next())?),
+                "--export-svg" => out.export_svg = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--log-events" => out.log_events = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--edit" => out.edit = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--board" => out.board = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--position" => out.position = Some(value(&arg, args.next())?),
+                "--replay" => out.replay = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--convert-replay" => {
+                    let from = PathBuf::from(value(&arg, args.next())?);
+                    let to = PathBuf::from(value(&arg, args.next())?);
+                    out.convert_replay = Some((from, to));
+                }
+                "-h" | "--help" => out.help = true,
+                other => return Err(format!("unknown option '{}'\n\n{}", other, USAGE)),
+            }
+        }
+        if let Some(mode) = &out.mode
+            && GameMode::from_name(mode, 0).is_none()
+        {
+            return Err(format!("unknown mode '{}'\n\n{}", mode, USAGE));
+        }
+        if let Some(theme) = &out.theme
+            && !THEMES.contains(&theme.as_str())
+        {
+            return Err(format!("unknown theme '{}'\n\n{}", theme, USAGE));
+        }
+        if let Some(name) = &out.profile {
+            profiles::validate_name(name)?;
+        }
+        let networks = [
+            out.host.is_some(),
+            out.connect.is_some(),
+            out.match_addr.is_some(),
+        ];
+        if networks.iter().filter(|&&n| n).count() > 1 {
+            return Err("use one of --host, --connect and --match".to_string());
+        }
+        if let Some(name) = &out.name
+            && (name.is_empty() || name.contains(char::is_whitespace))
+        {
+            return Err(format!("--name must be one word, got '{}'", name));
+        }
+        if cfg!(not(feature = "net")) && (networks.contains(&true) || out.serve_matches.is_some()) {
+            return Err(
+                "network play isn't in this build; rebuild with `--features net`".to_string(),
+            );
+        }
+        Ok(out)
+    }
+}
+
+/// A numeric option value
+fn number<T: std::str::FromStr>(flag: &str, next: Option<String>) -> Result<T, String> {
+    let text = value(flag, next)?;
+    text.parse()
+        .map_err(|_| format!("option '{}' expects a number, got '{}'", flag, text))
+}
+
+/// The value following an option that requires one
+fn value(flag: &str, next: Option<String>) -> Result<String, String> {
+    next.ok_or_else(|| format!("option '{}' needs a value\n\n{}", flag, USAGE))
+}
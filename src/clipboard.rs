@@ -0,0 +1,51 @@
+use tetris_game::{Game, layout, milestones::Notification};
+
+/// The system clipboard, opened on first use. On X11 copied text only lasts
+/// while its owner is alive, so one is kept for the whole game.
+#[derive(Default)]
+pub struct Clipboard {
+    inner: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    fn open(&mut self) -> Result<&mut arboard::Clipboard, arboard::Error> {
+        if self.inner.is_none() {
+            self.inner = Some(arboard::Clipboard::new()?);
+        }
+        Ok(self.inner.as_mut().unwrap())
+    }
+
+    /// Copy the board as text rows; without a clipboard they go to `problems`,
+    /// to print once the terminal is back. Returns the message for the Status box.
+    pub fn copy_board(&mut self, game: &Game, problems: &mut Vec<String>) -> Notification {
+        let text = layout::board_to_string(&game.board);
+        match self
+            .open()
+            .and_then(|clipboard| clipboard.set_text(text.clone()))
+        {
+            Ok(()) => Notification::clipboard("Copied!", false),
+            Err(e) => {
+                problems.push(format!(
+                    "clipboard unavailable ({}), the board is:\n{}",
+                    e, text
+                ));
+                Notification::clipboard("Clipboard unavailable", true)
+            }
+        }
+    }
+
+    /// Replace the board with the clipboard's text rows, if they make a board
+    /// the game can go on with. Returns the message for the Status box.
+    pub fn paste_board(&mut self, game: &mut Game) -> Notification {
+        let text = match self.open().and_then(|clipboard| clipboard.get_text()) {
+            Ok(text) => text,
+            Err(e) => {
+                return Notification::clipboard(format!("Clipboard unavailable: {}", e), true);
+            }
+        };
+        match layout::board_from_str(&text).and_then(|board| game.paste_board(board)) {
+            Ok(()) => Notification::clipboard("Pasted!", false),
+            Err(e) => Notification::clipboard(format!("Can't paste: {}", e), true),
+        }
+    }
+}
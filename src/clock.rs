@@ -0,0 +1,68 @@
+//! Where a game reads the wall clock. The simulation itself counts ticks
+//! (gravity, lock delay, ARE), so only wall-time figures go through here:
+//! elapsed play time, pauses, the rolling LPM, the lock flash and the
+//! frontend's key timing. A test can swap in a `ManualClock` and move time
+//! by hand.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// The real clock
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to
+pub struct ManualClock {
+    now: Mutex<Instant>,
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        ManualClock {
+            now: Mutex::new(Instant::now()),
+        }
+    }
+}
+
+impl ManualClock {
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// A game's clock; copies of a game share it. The default is the real one.
+#[derive(Clone)]
+pub struct GameClock(Arc<dyn Clock>);
+
+impl Default for GameClock {
+    fn default() -> Self {
+        GameClock(Arc::new(SystemClock))
+    }
+}
+
+impl GameClock {
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        GameClock(clock)
+    }
+
+    pub fn now(&self) -> Instant {
+        self.0.now()
+    }
+}
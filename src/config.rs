@@ -0,0 +1,897 @@
+use ratatui::widgets::BorderType;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    Action, ActivePiece, BOARD_HEIGHT, BOARD_WIDTH, BlockType, GameMode,
+    keymap::Command,
+    paths,
+    rules::{ClearGravity, InputBuffer, SpawnRule},
+    scoring::ScoreConfig,
+    theme::{ColorsConfig, Theme},
+};
+
+/// Longest next queue the sidebar has room for
+pub const MAX_NEXT_QUEUE: usize = 6;
+
+/// Highest level gravity still speeds up at
+pub const MAX_START_LEVEL: usize = 15;
+
+/// Recognised `ui.theme` values
+pub const THEMES: &[&str] = &[
+    "default",
+    "pastel",
+    "dark",
+    "deuteranopia",
+    "tritanopia",
+    "mono",
+    HIGH_CONTRAST,
+];
+
+/// Theme for low vision: bright bold colors, thick borders and a letter on every block
+pub const HIGH_CONTRAST: &str = "high-contrast";
+
+/// User settings read from `config.toml`; every field has a default so the file is optional
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub general: GeneralConfig,
+    pub gameplay: GameplayConfig,
+    pub ui: UiConfig,
+    /// Colors overriding the `ui.theme` preset's
+    pub colors: ColorsConfig,
+    pub scoring: ScoreConfig,
+    pub keys: KeysConfig,
+    /// Per-piece spawn placement, keyed by piece letter
+    pub spawn: BTreeMap<BlockType, SpawnRule>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneralConfig {
+    /// Mode used when `--mode` isn't given ("marathon", "sprint", "ultra",
+    /// "cheese", "competitive" or "tutorial")
+    pub mode: String,
+    /// Garbage rows in cheese mode when `--cheese-rows` isn't given
+    pub cheese_rows: usize,
+    pub start_level: usize,
+    /// Write a replay of every game to the data directory as it ends
+    pub save_replays: bool,
+}
+
+impl Default for GeneralConfig {
+    fn default() -> Self {
+        GeneralConfig {
+            mode: "marathon".to_string(),
+            cheese_rows: crate::DEFAULT_CHEESE_ROWS,
+            start_level: 1,
+            save_replays: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameplayConfig {
+    /// Delay between a piece locking and the next spawn (0 = none)
+    pub are_delay_ms: u64,
+    /// Holding a rotation key when a piece spawns pre-rotates it
+    pub irs_enabled: bool,
+    /// How long a landed piece can still be moved (0 = locks on the next gravity step)
+    pub lock_delay_ms: u64,
+    /// Delayed auto shift: how long a move key is held before it repeats
+    pub das_ms: u64,
+    /// Auto repeat rate once DAS has charged (0 = straight to the wall)
+    pub arr_ms: u64,
+    /// Show where the piece would land
+    pub ghost: bool,
+    /// Upcoming pieces shown in the sidebar
+    pub next_queue: usize,
+    /// How long the zone lasts once entered
+    pub zone_duration_ms: u64,
+    /// Pieces to lock after a zone before it can be entered again (0 = once per game)
+    pub zone_recharge_pieces: usize,
+    /// Keep the last move pressed during ARE for the next piece, or drop it
+    pub input_buffer: InputBuffer,
+    /// Soft dropping into the floor locks the piece straight away
+    pub soft_drop_locks: bool,
+    /// "naive" moves whole rows down after a clear; "sticky" drops each
+    /// group of touching blocks on its own
+    pub clear_gravity: ClearGravity,
+    /// Pause when the terminal loses focus and resume when it gets it back,
+    /// where the terminal reports focus
+    pub auto_pause: bool,
+}
+
+impl Default for GameplayConfig {
+    fn default() -> Self {
+        GameplayConfig {
+            are_delay_ms: 0,
+            irs_enabled: false,
+            lock_delay_ms: 0,
+            das_ms: 170,
+            arr_ms: 50,
+            ghost: true,
+            next_queue: 1,
+            zone_duration_ms: 10_000,
+            zone_recharge_pieces: 0,
+            input_buffer: InputBuffer::Discard,
+            soft_drop_locks: true,
+            clear_gravity: ClearGravity::Naive,
+            auto_pause: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiConfig {
+    /// Mirror game state to stderr for screen readers
+    pub accessibility_mode: bool,
+    /// Checkerboard the empty cells so columns are easier to follow
+    pub grid: bool,
+    /// Outline locked blocks along their outer edges instead of filling them,
+    /// so touching blocks of one kind read as one shape
+    pub outline_blocks: bool,
+    /// Color preset: "default", "pastel", "dark", "deuteranopia",
+    /// "tritanopia", "mono" or "high-contrast"
+    pub theme: String,
+    /// Draw with plain ASCII for terminals without block or box-drawing
+    /// characters (takes precedence over the `block_*` strings, `half_block`
+    /// and `braille`)
+    pub ascii: bool,
+    /// What a board cell is drawn with; each must be two columns wide
+    pub block_filled: String,
+    pub block_empty: String,
+    pub block_ghost: String,
+    /// Draw two board rows per terminal line with half-block characters, for
+    /// squarer cells on a board half as tall
+    pub half_block: bool,
+    /// Draw half blocks anyway while the terminal is too short for the
+    /// full-size board, and full-size cells again once it's tall enough
+    pub auto_half_block: bool,
+    /// Draw the board as a Braille raster, scaled to fit the terminal
+    /// (takes precedence over `half_block`)
+    pub braille: bool,
+    /// Show recent inputs in the sidebar, for streaming and teaching
+    pub input_display: bool,
+    /// Line style of the board and sidebar boxes (the high-contrast theme
+    /// always draws thick ones)
+    pub border_style: BorderStyle,
+    /// Shown in the board's top border; empty for none
+    pub board_title: String,
+    /// Mark the spots a T could spin into while a T is in play
+    pub hints_enabled: bool,
+    /// Show each column's height under the board, as a training aid
+    pub column_heights: bool,
+    /// Upcoming pieces to show when fewer than `gameplay.next_queue`, for
+    /// less lookahead; 0 hides the held piece too
+    pub preview: Option<usize>,
+    /// Skip animations: rows clear without flashing, locked pieces aren't
+    /// highlighted and hard drops leave no trail
+    pub reduced_motion: bool,
+}
+
+/// Box outlines `ui.border_style` can pick
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BorderStyle {
+    #[default]
+    Plain,
+    Rounded,
+    Double,
+}
+
+impl BorderStyle {
+    pub fn name(self) -> &'static str {
+        match self {
+            BorderStyle::Plain => "plain",
+            BorderStyle::Rounded => "rounded",
+            BorderStyle::Double => "double",
+        }
+    }
+
+    pub fn border_type(self) -> BorderType {
+        match self {
+            BorderStyle::Plain => BorderType::Plain,
+            BorderStyle::Rounded => BorderType::Rounded,
+            BorderStyle::Double => BorderType::Double,
+        }
+    }
+}
+
+impl UiConfig {
+    /// How many of `queued` upcoming pieces to draw; 0 hides the held piece too
+    pub fn previews_shown(&self, queued: usize) -> usize {
+        self.preview.map_or(queued, |n| n.min(queued))
+    }
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        UiConfig {
+            accessibility_mode: false,
+            grid: false,
+            outline_blocks: false,
+            theme: "default".to_string(),
+            ascii: false,
+            block_filled: "██".to_string(),
+            block_empty: "  ".to_string(),
+            block_ghost: "░░".to_string(),
+            half_block: false,
+            auto_half_block: true,
+            braille: false,
+            input_display: false,
+            border_style: BorderStyle::Plain,
+            board_title: "Tetris".to_string(),
+            hints_enabled: false,
+            column_heights: false,
+            preview: None,
+            reduced_motion: false,
+        }
+    }
+}
+
+/// Single-player key bindings: each action takes one key descriptor or a list
+/// of them, e.g. `rotate_cw = ["Up", "x"]` or `hold = "shift+c"`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeysConfig {
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub move_left: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub move_right: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub soft_drop: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub hard_drop: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub rotate_cw: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub rotate_ccw: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub hold: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub zone: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub pause: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub restart: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub quit: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub save: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub input_display: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub hint: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub export_position: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub help: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub leaderboard: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub column_heights: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub settings: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub hold_preview: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub copy_board: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub paste_board: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub history: Vec<String>,
+    #[serde(deserialize_with = "crate::serde_util::one_or_many")]
+    pub high_contrast: Vec<String>,
+}
+
+impl Default for KeysConfig {
+    fn default() -> Self {
+        let keys = |k: &[&str]| k.iter().map(|s| s.to_string()).collect();
+        KeysConfig {
+            move_left: keys(&["Left"]),
+            move_right: keys(&["Right"]),
+            soft_drop: keys(&["Down"]),
+            hard_drop: keys(&["space"]),
+            rotate_cw: keys(&["Up"]),
+            rotate_ccw: keys(&["z"]),
+            hold: keys(&["c"]),
+            zone: keys(&["f"]),
+            pause: keys(&["p"]),
+            restart: keys(&["r"]),
+            quit: keys(&["q"]),
+            save: keys(&["s"]),
+            input_display: keys(&["i"]),
+            hint: keys(&["h"]),
+            export_position: keys(&["x"]),
+            help: keys(&["?", "F1"]),
+            leaderboard: keys(&["l"]),
+            column_heights: keys(&["k"]),
+            settings: keys(&["o"]),
+            hold_preview: keys(&["v"]),
+            copy_board: keys(&["ctrl+c"]),
+            paste_board: keys(&["ctrl+v"]),
+            history: keys(&["Tab"]),
+            high_contrast: keys(&["F2"]),
+        }
+    }
+}
+
+impl KeysConfig {
+    /// Every bindable command with its config name and keys
+    pub fn entries(&self) -> [(&'static str, &Vec<String>, Command); 24] {
+        [
+            (
+                "move_left",
+                &self.move_left,
+                Command::Play(Action::MoveLeft),
+            ),
+            (
+                "move_right",
+                &self.move_right,
+                Command::Play(Action::MoveRight),
+            ),
+            (
+                "soft_drop",
+                &self.soft_drop,
+                Command::Play(Action::SoftDrop),
+            ),
+            (
+                "hard_drop",
+                &self.hard_drop,
+                Command::Play(Action::HardDrop),
+            ),
+            (
+                "rotate_cw",
+                &self.rotate_cw,
+                Command::Play(Action::RotateCw),
+            ),
+            (
+                "rotate_ccw",
+                &self.rotate_ccw,
+                Command::Play(Action::RotateCcw),
+            ),
+            ("hold", &self.hold, Command::Play(Action::Hold)),
+            ("zone", &self.zone, Command::Play(Action::Zone)),
+            ("pause", &self.pause, Command::Pause),
+            ("restart", &self.restart, Command::Restart),
+            ("quit", &self.quit, Command::Quit),
+            ("save", &self.save, Command::SaveQuit),
+            ("input_display", &self.input_display, Command::ToggleInputs),
+            ("hint", &self.hint, Command::Hint),
+            (
+                "export_position",
+                &self.export_position,
+                Command::ExportPosition,
+            ),
+            ("help", &self.help, Command::Help),
+            ("leaderboard", &self.leaderboard, Command::Leaderboard),
+            (
+                "column_heights",
+                &self.column_heights,
+                Command::ToggleHeights,
+            ),
+            ("settings", &self.settings, Command::Settings),
+            ("hold_preview", &self.hold_preview, Command::HoldPreview),
+            ("copy_board", &self.copy_board, Command::CopyBoard),
+            ("paste_board", &self.paste_board, Command::PasteBoard),
+            ("history", &self.history, Command::FocusHistory),
+            (
+                "high_contrast",
+                &self.high_contrast,
+                Command::ToggleContrast,
+            ),
+        ]
+    }
+
+    /// Config name of a command, for error messages
+    pub fn name_of(command: Command) -> &'static str {
+        KeysConfig::default()
+            .entries()
+            .iter()
+            .find(|(_, _, c)| *c == command)
+            .map_or("?", |(name, _, _)| name)
+    }
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        paths::config_dir().map(|d| d.join("config.toml"))
+    }
+
+    /// Load from `path` (or the default location) with `overrides` (a profile's
+    /// settings) layered on top, falling back to defaults for anything missing
+    /// or invalid; problems are reported on stderr
+    pub fn load(path: Option<&Path>, overrides: Option<&Path>) -> Config {
+        let base = path.map(Path::to_path_buf).or_else(Config::default_path);
+        let files: Vec<&Path> = base.as_deref().into_iter().chain(overrides).collect();
+        let mut table = toml::Table::new();
+        for path in &files {
+            if let Some(layer) = read_table(path) {
+                merge(&mut table, layer);
+            }
+        }
+        let mut warnings = Vec::new();
+        let config = Config::from_table(&table, &mut warnings);
+        let names: Vec<String> = files.iter().map(|p| p.display().to_string()).collect();
+        for warning in warnings {
+            eprintln!("config {}: {}", names.join(" + "), warning);
+        }
+        config
+    }
+
+    /// Read settings section by section, so one bad value only resets its own section
+    fn from_table(table: &toml::Table, warnings: &mut Vec<String>) -> Config {
+        // optional values are filled in so their keys count as known
+        let mut sample = Config::default();
+        sample.ui.preview = Some(0);
+        for (_, value) in sample.colors.entries_mut() {
+            *value = Some(String::new());
+        }
+        if let Ok(toml::Value::Table(known)) = toml::Value::try_from(sample) {
+            unknown_keys(table, &known, "", warnings);
+        }
+        let mut config = Config {
+            general: section(table, "general", warnings),
+            gameplay: section(table, "gameplay", warnings),
+            ui: section(table, "ui", warnings),
+            colors: section(table, "colors", warnings),
+            scoring: section(table, "scoring", warnings),
+            keys: section(table, "keys", warnings),
+            spawn: section(table, "spawn", warnings),
+        };
+        config.validate(warnings);
+        config
+    }
+
+    /// The `ui.theme` preset with the `[colors]` section applied
+    pub fn theme(&self) -> Theme {
+        Theme::builtin(&self.ui.theme)
+            .unwrap_or_default()
+            .with(&self.colors)
+    }
+
+    /// Reset out-of-range values to their defaults
+    fn validate(&mut self, warnings: &mut Vec<String>) {
+        let defaults = Config::default();
+        if GameMode::from_name(&self.general.mode, 0).is_none() {
+            warnings.push(format!(
+                "unknown general.mode '{}', using '{}'",
+                self.general.mode, defaults.general.mode
+            ));
+            self.general.mode = defaults.general.mode;
+        }
+        if !(1..=MAX_START_LEVEL).contains(&self.general.start_level) {
+            warnings.push(format!(
+                "general.start_level must be 1-{}, using {}",
+                MAX_START_LEVEL, defaults.general.start_level
+            ));
+            self.general.start_level = defaults.general.start_level;
+        }
+        if !(1..=MAX_NEXT_QUEUE).contains(&self.gameplay.next_queue) {
+            warnings.push(format!(
+                "gameplay.next_queue must be 1-{}, using {}",
+                MAX_NEXT_QUEUE, defaults.gameplay.next_queue
+            ));
+            self.gameplay.next_queue = defaults.gameplay.next_queue;
+        }
+        if !THEMES.contains(&self.ui.theme.as_str()) {
+            warnings.push(format!(
+                "unknown ui.theme '{}' (expected one of {}), using '{}'",
+                self.ui.theme,
+                THEMES.join(", "),
+                defaults.ui.theme
+            ));
+            self.ui.theme = defaults.ui.theme;
+        }
+        self.colors.validate(warnings);
+        for (name, value, default) in [
+            (
+                "block_filled",
+                &mut self.ui.block_filled,
+                defaults.ui.block_filled,
+            ),
+            (
+                "block_empty",
+                &mut self.ui.block_empty,
+                defaults.ui.block_empty,
+            ),
+            (
+                "block_ghost",
+                &mut self.ui.block_ghost,
+                defaults.ui.block_ghost,
+            ),
+        ] {
+            if let Err(e) = check_cell_glyph(value) {
+                warnings.push(format!("ui.{}: {}, using \"{}\"", name, e, default));
+                *value = default;
+            }
+        }
+        if self.spawn.remove(&BlockType::Garbage).is_some() {
+            warnings.push("spawn.Garbage is not a piece, ignoring it".to_string());
+        }
+        self.spawn.retain(|&kind, &mut rule| {
+            let fits = ActivePiece::new(kind, rule)
+                .cells()
+                .iter()
+                .all(|&(x, y)| (0..BOARD_WIDTH as i32).contains(&x) && y < BOARD_HEIGHT as i32);
+            if !fits {
+                warnings.push(format!(
+                    "spawn.{:?} would place the piece off the board, using the default",
+                    kind
+                ));
+            }
+            fits
+        });
+    }
+
+    /// The default settings as a commented config file, for `--write-default-config`
+    pub fn default_toml() -> String {
+        let c = Config::default();
+        let s = &c.scoring;
+        format!(
+            "\
+# tetris-game settings; every key is optional
+
+[general]
+# mode used when --mode isn't given: \"marathon\", \"sprint\", \"ultra\", \"cheese\",
+# \"competitive\" or \"tutorial\"
+mode = \"{mode}\"
+# garbage rows to dig through in cheese mode
+cheese_rows = {cheese_rows}
+# level to start on (1-{max_level})
+start_level = {start_level}
+# keep a replay of every game, named by date, mode and score, in the data
+# directory's replays/ folder
+save_replays = {save_replays}
+
+[gameplay]
+# pause between a piece locking and the next spawn, in milliseconds
+are_delay_ms = {are}
+# holding a rotation key while a piece spawns pre-rotates it
+irs_enabled = {irs}
+# how long a landed piece can still move before locking (0 = next gravity step)
+lock_delay_ms = {lock}
+# delayed auto shift and auto repeat rate for held move keys, in milliseconds
+# (needs a terminal that reports key releases; otherwise the OS repeat is used)
+das_ms = {das}
+arr_ms = {arr}
+# show where the piece will land
+ghost = {ghost}
+# upcoming pieces to preview (1-{max_queue})
+next_queue = {queue}
+# the zone slows gravity and banks clears to score them all at once when it ends
+zone_duration_ms = {zone}
+# pieces to lock before the zone recharges (0 = once per game)
+zone_recharge_pieces = {recharge}
+# moves and rotations pressed during ARE: \"discard\" them, or \"apply-last\" to
+# give the most recent one to the next piece as it spawns
+input_buffer = \"{buffer}\"
+# soft dropping into the floor locks at once; false leaves the piece resting
+# there for lock delay or gravity to lock
+soft_drop_locks = {soft_drop_locks}
+# after a clear: \"naive\" moves the rows above down whole; \"sticky\" lets each
+# group of touching blocks fall until it lands, clearing any rows that fills
+clear_gravity = \"{clear_gravity}\"
+# pause while the terminal is out of focus, for terminals that say so; a game
+# suspended with Ctrl-Z pauses either way
+auto_pause = {auto_pause}
+
+[ui]
+# print game state to stderr for screen readers
+accessibility_mode = {a11y}
+# checkerboard the empty cells
+grid = {grid}
+# outline locked blocks where they meet something else, instead of filling them
+outline_blocks = {outline_blocks}
+# colors: {themes} (bold, with thick white borders,
+# an outlined ghost, nothing dimmed and a letter on every block so pieces don't
+# rely on color alone; keys.high_contrast toggles it in game)
+theme = \"{theme}\"
+# draw with plain ASCII only, borders included (overrides block_*, half_block
+# and braille)
+ascii = {ascii}
+# what board cells are drawn with; each must be exactly two columns wide
+block_filled = \"{filled}\"
+block_empty = \"{empty}\"
+block_ghost = \"{ghost_glyph}\"
+# two board rows per terminal line with half-block characters (ignores the above)
+half_block = {half_block}
+# switch to half blocks only while the terminal is too short for the full board
+auto_half_block = {auto_half_block}
+# draw the board as Braille dots, as big as the terminal allows (overrides
+# half_block; the sidebar stays text)
+braille = {braille}
+# show the last few inputs in the sidebar (toggle in game with keys.input_display)
+input_display = {input_display}
+# box outlines: \"plain\", \"rounded\" or \"double\"
+border_style = \"{border_style}\"
+# text in the board's top border (empty for none)
+board_title = \"{board_title}\"
+# while a T is in play, hatch the slots it could spin into for a clear
+hints_enabled = {hints_enabled}
+# each column's height under the board (toggle in game with keys.column_heights)
+column_heights = {column_heights}
+# show only this many upcoming pieces, for a harder game (0 hides hold too)
+# preview = 1
+# skip animations: no flash before rows clear, no highlight on locked pieces
+# and no trail behind hard drops
+reduced_motion = {reduced_motion}
+
+[colors]
+# override any of the theme's colors: a name (\"red\", \"lightblue\", \"darkgray\"),
+# \"#rrggbb\" or a palette index 0-255
+# i, o, t, s, z, j, l and garbage color the pieces
+# i = \"#00c8ff\"
+# background = \"black\"
+# border = \"gray\"
+# text = \"white\"
+# ghost = \"darkgray\"
+# danger = \"red\"
+# danger_background = \"#400000\"
+
+[scoring]
+# points per clear, times level
+single = {single}
+double = {double}
+triple = {triple}
+tetris = {tetris}
+# points per cell dropped
+soft_drop = {soft}
+hard_drop = {hard}
+t_spin_multiplier = {tspin:?}
+# per consecutive clear after the first, times level
+combo_bonus = {combo}
+back_to_back_multiplier = {b2b:?}
+
+[keys]
+# one key or a list per action: letters, Left/Right/Up/Down, space, Enter, Tab,
+# Esc, F1-F12, ..., optionally with shift+/ctrl+/alt+ (e.g. \"shift+z\")
+{keys}
+[spawn]
+# per-piece spawn placement (I, O, T, S, Z, J, L): the piece's 4x4 grid is placed
+# with its top-left at column/row; unlisted pieces use column = 3, row = -1, rotation = 0
+# T = {{ column = 3, row = -1, rotation = 0 }}
+",
+            mode = c.general.mode,
+            cheese_rows = c.general.cheese_rows,
+            max_level = MAX_START_LEVEL,
+            start_level = c.general.start_level,
+            save_replays = c.general.save_replays,
+            are = c.gameplay.are_delay_ms,
+            irs = c.gameplay.irs_enabled,
+            lock = c.gameplay.lock_delay_ms,
+            das = c.gameplay.das_ms,
+            arr = c.gameplay.arr_ms,
+            ghost = c.gameplay.ghost,
+            max_queue = MAX_NEXT_QUEUE,
+            queue = c.gameplay.next_queue,
+            zone = c.gameplay.zone_duration_ms,
+            recharge = c.gameplay.zone_recharge_pieces,
+            buffer = c.gameplay.input_buffer.name(),
+            soft_drop_locks = c.gameplay.soft_drop_locks,
+            clear_gravity = c.gameplay.clear_gravity.name(),
+            auto_pause = c.gameplay.auto_pause,
+            a11y = c.ui.accessibility_mode,
+            grid = c.ui.grid,
+            outline_blocks = c.ui.outline_blocks,
+            themes = THEMES.join(", "),
+            theme = c.ui.theme,
+            ascii = c.ui.ascii,
+            filled = c.ui.block_filled,
+            empty = c.ui.block_empty,
+            half_block = c.ui.half_block,
+            auto_half_block = c.ui.auto_half_block,
+            braille = c.ui.braille,
+            input_display = c.ui.input_display,
+            border_style = c.ui.border_style.name(),
+            board_title = c.ui.board_title,
+            hints_enabled = c.ui.hints_enabled,
+            column_heights = c.ui.column_heights,
+            reduced_motion = c.ui.reduced_motion,
+            ghost_glyph = c.ui.block_ghost,
+            single = s.single,
+            double = s.double,
+            triple = s.triple,
+            tetris = s.tetris,
+            soft = s.soft_drop,
+            hard = s.hard_drop,
+            tspin = s.t_spin_multiplier,
+            combo = s.combo_bonus,
+            b2b = s.back_to_back_multiplier,
+            keys = c
+                .keys
+                .entries()
+                .iter()
+                .map(|(name, keys, _)| format!("{} = {:?}\n", name, keys))
+                .collect::<String>(),
+        )
+    }
+
+    /// Write the commented defaults to `path`, refusing to replace an existing file
+    pub fn write_default(path: &Path) -> io::Result<()> {
+        if path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", path.display()),
+            ));
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, Config::default_toml())
+    }
+}
+
+/// A board cell is two terminal columns, so its string must be exactly that
+/// wide: "██" and "[]" fit, as does a single full-width character
+pub fn check_cell_glyph(text: &str) -> Result<(), String> {
+    let width = text.width();
+    if width == 2 {
+        Ok(())
+    } else {
+        Err(format!(
+            "\"{}\" is {} column{} wide, expected exactly 2",
+            text,
+            width,
+            if width == 1 { "" } else { "s" }
+        ))
+    }
+}
+
+/// A config file as a TOML table; missing files are skipped silently and
+/// unreadable ones with a message
+fn read_table(path: &Path) -> Option<toml::Table> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            eprintln!("could not read config {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    match text.parse() {
+        Ok(table) => Some(table),
+        Err(e) => {
+            eprintln!(
+                "config {}: not valid TOML, ignoring it: {}",
+                path.display(),
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Overlay `layer` onto `base`, replacing values key by key inside tables
+fn merge(base: &mut toml::Table, layer: toml::Table) {
+    for (key, value) in layer {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(inner)), toml::Value::Table(over)) => merge(inner, over),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// One section of the file, or its defaults (with a warning) if it doesn't fit
+fn section<T: DeserializeOwned + Default>(
+    table: &toml::Table,
+    name: &str,
+    warnings: &mut Vec<String>,
+) -> T {
+    let Some(value) = table.get(name) else {
+        return T::default();
+    };
+    match value.clone().try_into() {
+        Ok(section) => section,
+        Err(e) => {
+            warnings.push(format!(
+                "invalid [{}] section, using defaults: {}",
+                name,
+                e.to_string().trim().replace('\n', " ")
+            ));
+            T::default()
+        }
+    }
+}
+
+/// Warn about keys in `table` that have no counterpart in `known`
+fn unknown_keys(
+    table: &toml::Table,
+    known: &toml::Table,
+    prefix: &str,
+    warnings: &mut Vec<String>,
+) {
+    for (key, value) in table {
+        let name = format!("{}{}", prefix, key);
+        match (value, known.get(key)) {
+            (_, None) => warnings.push(format!("unknown key '{}'", name)),
+            // tables that are empty by default (like [spawn]) are free-form
+            (toml::Value::Table(inner), Some(toml::Value::Table(known_inner)))
+                if !known_inner.is_empty() =>
+            {
+                unknown_keys(inner, known_inner, &format!("{}.", name), warnings)
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_column_glyphs_are_accepted() {
+        for glyph in ["[]", "##", "<>", "██", "  ", "░░", "国"] {
+            assert_eq!(check_cell_glyph(glyph), Ok(()), "{}", glyph);
+        }
+    }
+
+    #[test]
+    fn other_widths_are_rejected() {
+        assert_eq!(
+            check_cell_glyph("#"),
+            Err("\"#\" is 1 column wide, expected exactly 2".to_string())
+        );
+        assert_eq!(
+            check_cell_glyph("[#]"),
+            Err("\"[#]\" is 3 columns wide, expected exactly 2".to_string())
+        );
+        assert!(check_cell_glyph("").is_err());
+    }
+
+    #[test]
+    fn a_bad_glyph_falls_back_to_the_default() {
+        let mut config = Config::default();
+        config.ui.block_filled = "#".to_string();
+        config.ui.block_ghost = "[]".to_string();
+        let mut warnings = Vec::new();
+        config.validate(&mut warnings);
+        assert_eq!(config.ui.block_filled, Config::default().ui.block_filled);
+        assert_eq!(config.ui.block_ghost, "[]");
+        assert_eq!(
+            warnings,
+            ["ui.block_filled: \"#\" is 1 column wide, expected exactly 2, using \"██\""]
+        );
+    }
+
+    fn from_toml(text: &str) -> (Config, Vec<String>) {
+        let mut warnings = Vec::new();
+        let config = Config::from_table(&text.parse().unwrap(), &mut warnings);
+        (config, warnings)
+    }
+
+    #[test]
+    fn border_styles_parse_by_name() {
+        let (config, warnings) = from_toml("[ui]\nborder_style = \"rounded\"\n");
+        assert_eq!(warnings, Vec::<String>::new());
+        assert_eq!(config.ui.border_style, BorderStyle::Rounded);
+        assert_eq!(config.ui.border_style.border_type(), BorderType::Rounded);
+        for style in [
+            BorderStyle::Plain,
+            BorderStyle::Rounded,
+            BorderStyle::Double,
+        ] {
+            let (config, _) = from_toml(&format!("[ui]\nborder_style = \"{}\"\n", style.name()));
+            assert_eq!(config.ui.border_style, style);
+        }
+    }
+
+    #[test]
+    fn an_unknown_border_style_is_a_warning() {
+        let (config, warnings) = from_toml("[ui]\nborder_style = \"wavy\"\n");
+        assert_eq!(config.ui.border_style, BorderStyle::Plain);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("wavy"), "{}", warnings[0]);
+    }
+}
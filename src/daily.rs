@@ -0,0 +1,141 @@
+use chrono::{Days, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, fs, io, path::PathBuf};
+
+use crate::{Game, paths};
+
+/// The day of challenge #1
+fn epoch() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 1, 1).expect("valid date")
+}
+
+/// Today's challenge number (#1 was on 2024-01-01)
+pub fn today() -> u32 {
+    let days = Local::now()
+        .date_naive()
+        .signed_duration_since(epoch())
+        .num_days();
+    days.max(0) as u32 + 1
+}
+
+/// The date of challenge `number`, as used for its seed and in the scores file
+pub fn date_of(number: u32) -> String {
+    epoch()
+        .checked_add_days(Days::new(u64::from(number.saturating_sub(1))))
+        .unwrap_or_else(epoch)
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+/// Everyone playing on `date` ("YYYY-MM-DD") gets this seed, and so the same pieces
+pub fn seed(date: &str) -> u64 {
+    let hash = Sha256::digest(date.as_bytes());
+    u64::from_le_bytes(hash[..8].try_into().expect("sha256 is 32 bytes"))
+}
+
+/// How a day's challenge went
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DailyResult {
+    pub score: usize,
+    pub level: usize,
+    pub lines: usize,
+}
+
+impl DailyResult {
+    pub fn of(game: &Game) -> Self {
+        DailyResult {
+            score: game.score,
+            level: game.level,
+            lines: game.lines_cleared,
+        }
+    }
+
+    /// One line to paste to friends
+    pub fn share_text(&self, number: u32) -> String {
+        format!(
+            "Tetris Daily #{} – Score: {} (Level {}, {} lines)",
+            number, self.score, self.level, self.lines
+        )
+    }
+}
+
+fn scores_path() -> Option<PathBuf> {
+    paths::data_dir().map(|d| d.join("daily_scores.json"))
+}
+
+fn load_results() -> BTreeMap<String, DailyResult> {
+    scores_path()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// The result already recorded for challenge `number`, if it has been played
+pub fn result_for(number: u32) -> Option<DailyResult> {
+    load_results().remove(&date_of(number))
+}
+
+/// Store the result of challenge `number`; only the first attempt counts
+pub fn record(number: u32, result: &DailyResult) -> io::Result<()> {
+    let mut results = load_results();
+    if results.contains_key(&date_of(number)) {
+        return Ok(());
+    }
+    results.insert(date_of(number), result.clone());
+    let path = scores_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string_pretty(&results).map_err(io::Error::other)?;
+    fs::write(path, json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameMode, GameSetup, rules::Rules};
+
+    #[test]
+    fn a_date_always_gives_the_same_seed() {
+        assert_eq!(seed("2026-10-16"), seed("2026-10-16"));
+        assert_ne!(seed("2026-10-16"), seed("2026-10-17"));
+        let hash = Sha256::digest(b"2026-10-16");
+        assert_eq!(seed("2026-10-16").to_le_bytes(), hash[..8]);
+    }
+
+    #[test]
+    fn challenges_count_days_from_the_epoch() {
+        assert_eq!(date_of(1), "2024-01-01");
+        assert_eq!(date_of(366), "2024-12-31");
+        assert_eq!(date_of(0), "2024-01-01");
+    }
+
+    #[test]
+    fn everyone_gets_the_same_pieces_on_a_day() {
+        let game = || {
+            Game::new(GameSetup {
+                mode: GameMode::DailyChallenge { number: 100 },
+                rules: Rules::default(),
+                seed: Some(seed(&date_of(100))),
+            })
+        };
+        let (a, b) = (game(), game());
+        assert_eq!(a.current.tetro.kind, b.current.tetro.kind);
+        assert_eq!(a.next, b.next);
+    }
+
+    #[test]
+    fn the_share_text_has_the_number_and_result() {
+        let result = DailyResult {
+            score: 12450,
+            level: 8,
+            lines: 47,
+        };
+        assert_eq!(
+            result.share_text(12),
+            "Tetris Daily #12 – Score: 12450 (Level 8, 47 lines)"
+        );
+    }
+}
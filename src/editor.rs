@@ -0,0 +1,275 @@
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+use std::{io, path::Path};
+
+use tetris_game::{
+    BOARD_HEIGHT, BOARD_WIDTH, BlockType, Board, Game, GameMode, GameSetup, layout, rules::Rules,
+    theme::Theme,
+};
+
+use crate::Term;
+
+/// Board editor state: a scratch game whose cells are set by hand
+struct Editor {
+    game: Game,
+    /// (x, y) of the highlighted cell
+    cursor: (usize, usize),
+    /// Result of the last save, or why a key did nothing
+    status: Option<String>,
+}
+
+/// The cell Space turns `cell` into: empty, each piece color in turn, garbage, then empty again
+fn next_cell(cell: Option<BlockType>) -> Option<BlockType> {
+    let all = BlockType::all();
+    match cell {
+        None => Some(all[0]),
+        Some(BlockType::Garbage) => None,
+        Some(kind) => match all.iter().position(|&k| k == kind) {
+            Some(i) if i + 1 < all.len() => Some(all[i + 1]),
+            _ => Some(BlockType::Garbage),
+        },
+    }
+}
+
+impl Editor {
+    fn cell(&self) -> Option<BlockType> {
+        let (x, y) = self.cursor;
+        self.game.board[y][x]
+    }
+
+    fn set(&mut self, cell: Option<BlockType>) {
+        let (x, y) = self.cursor;
+        self.game.set_cell(x, y, cell);
+    }
+
+    fn save(&mut self, path: &Path) -> bool {
+        match layout::save(path, &self.game.board) {
+            Ok(()) => {
+                self.status = Some(format!("Saved to {}", path.display()));
+                true
+            }
+            Err(e) => {
+                self.status = Some(format!("Could not save: {}", e));
+                false
+            }
+        }
+    }
+
+    /// Handle one key press; `Some` ends the editor, with the board to play if any
+    fn key(&mut self, code: KeyCode, path: &Path) -> Option<Option<Board>> {
+        let (x, y) = self.cursor;
+        match code {
+            KeyCode::Left => self.cursor.0 = x.saturating_sub(1),
+            KeyCode::Right => self.cursor.0 = (x + 1).min(BOARD_WIDTH - 1),
+            KeyCode::Up => self.cursor.1 = y.saturating_sub(1),
+            KeyCode::Down => self.cursor.1 = (y + 1).min(BOARD_HEIGHT - 1),
+            KeyCode::Char(' ') => self.set(next_cell(self.cell())),
+            KeyCode::Backspace | KeyCode::Delete => self.set(None),
+            KeyCode::Char('c') => {
+                self.game.board = [[None; BOARD_WIDTH]; BOARD_HEIGHT];
+                self.status = Some("Board cleared".to_string());
+            }
+            KeyCode::Char('s') => {
+                self.save(path);
+            }
+            KeyCode::Enter if self.save(path) => return Some(Some(self.game.board)),
+            KeyCode::Char('q') | KeyCode::Esc => return Some(None),
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Full-screen editor for the board in `path` (created if missing). Enter
+/// saves and returns the board to start a game on; Q quits without playing.
+pub fn run(terminal: &mut Term, path: &Path, theme: &Theme) -> io::Result<Option<Board>> {
+    let mut game = Game::new(GameSetup {
+        mode: GameMode::Marathon,
+        rules: Rules::default(),
+        seed: Some(0),
+    });
+    let mut status = None;
+    if path.exists() {
+        match layout::load(path) {
+            Ok(board) => game.board = board,
+            // keep the broken file until the user saves over it
+            Err(e) => status = Some(format!("Could not load: {}", e)),
+        }
+    }
+    let mut editor = Editor {
+        game,
+        cursor: (BOARD_WIDTH / 2, BOARD_HEIGHT - 1),
+        status,
+    };
+    loop {
+        terminal.draw(|f| ui(f, &editor, theme))?;
+        if let Event::Key(key) = event::read()?
+            && key.kind != KeyEventKind::Release
+            && let Some(board) = editor.key(key.code, path)
+        {
+            return Ok(board);
+        }
+    }
+}
+
+fn cell_name(cell: Option<BlockType>) -> String {
+    match cell {
+        None => "empty".to_string(),
+        Some(BlockType::Garbage) => "garbage".to_string(),
+        Some(kind) => format!("{:?}", kind),
+    }
+}
+
+fn ui<B: ratatui::backend::Backend>(f: &mut ratatui::Frame<B>, editor: &Editor, theme: &Theme) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Length(BOARD_WIDTH as u16 * 2 + 2),
+                Constraint::Min(0),
+            ]
+            .as_ref(),
+        )
+        .split(f.size());
+    let board_area = Rect {
+        height: chunks[0].height.min(BOARD_HEIGHT as u16 + 2),
+        ..chunks[0]
+    };
+
+    let rows: Vec<Line> = (0..BOARD_HEIGHT)
+        .map(|y| {
+            let spans: Vec<Span> = (0..BOARD_WIDTH)
+                .map(|x| {
+                    let cell = editor.game.board[y][x];
+                    let color = cell.map(|kind| theme.piece(kind));
+                    if (x, y) == editor.cursor {
+                        Span::styled(
+                            "[]",
+                            Style::default()
+                                .fg(color.unwrap_or(Color::Black))
+                                .bg(Color::White),
+                        )
+                    } else if let Some(color) = color {
+                        Span::styled("██", Style::default().fg(color))
+                    } else {
+                        Span::raw("  ")
+                    }
+                })
+                .collect();
+            Line::from(spans)
+        })
+        .collect();
+    let board = Paragraph::new(rows).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Board Editor "),
+    );
+    f.render_widget(board, board_area);
+
+    let (x, y) = editor.cursor;
+    let mut lines = vec![
+        Line::from(format!(
+            "Cursor: column {}, row {}",
+            x + 1,
+            BOARD_HEIGHT - y
+        )),
+        Line::from(format!("Cell: {}", cell_name(editor.cell()))),
+        Line::from(""),
+        Line::from("← → ↑ ↓ : Move"),
+        Line::from("Space : Cycle color"),
+        Line::from("Del : Clear cell"),
+        Line::from("C : Clear board"),
+        Line::from("S : Save"),
+        Line::from("Enter : Save and play"),
+        Line::from("Q : Quit"),
+    ];
+    if let Some(status) = &editor.status {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            status.clone(),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )));
+    }
+    let help = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" Keys "));
+    f.render_widget(help, chunks[1]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(cursor: (usize, usize)) -> Editor {
+        Editor {
+            game: Game::new(GameSetup {
+                mode: GameMode::Marathon,
+                rules: Rules::default(),
+                seed: Some(0),
+            }),
+            cursor,
+            status: None,
+        }
+    }
+
+    #[test]
+    fn the_cursor_stays_on_the_board() {
+        let path = Path::new("unused.json");
+        let mut top_left = editor((0, 0));
+        assert_eq!(top_left.key(KeyCode::Left, path), None);
+        top_left.key(KeyCode::Up, path);
+        assert_eq!(top_left.cursor, (0, 0));
+        top_left.key(KeyCode::Right, path);
+        top_left.key(KeyCode::Down, path);
+        assert_eq!(top_left.cursor, (1, 1));
+
+        let bottom_right = (BOARD_WIDTH - 1, BOARD_HEIGHT - 1);
+        let mut editor = editor(bottom_right);
+        editor.key(KeyCode::Right, path);
+        editor.key(KeyCode::Down, path);
+        assert_eq!(editor.cursor, bottom_right);
+    }
+
+    #[test]
+    fn space_cycles_the_cell_through_every_block() {
+        let path = Path::new("unused.json");
+        let mut editor = editor((2, 5));
+        let mut seen = Vec::new();
+        loop {
+            editor.key(KeyCode::Char(' '), path);
+            match editor.game.board[5][2] {
+                Some(kind) => seen.push(kind),
+                None => break,
+            }
+        }
+        let mut expected = BlockType::all().to_vec();
+        expected.push(BlockType::Garbage);
+        assert_eq!(seen, expected);
+        assert!(editor.game.board.iter().flatten().all(Option::is_none));
+
+        editor.key(KeyCode::Char(' '), path);
+        editor.key(KeyCode::Delete, path);
+        assert_eq!(editor.cell(), None);
+    }
+
+    #[test]
+    fn enter_plays_the_saved_board_and_clear_empties_it() {
+        let path = std::env::temp_dir().join(format!("editor-test-{}.json", std::process::id()));
+        let mut editor = editor((0, BOARD_HEIGHT - 1));
+        editor.key(KeyCode::Char(' '), &path);
+        let board = editor.key(KeyCode::Enter, &path).unwrap().unwrap();
+        let saved = layout::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(board[BOARD_HEIGHT - 1][0], Some(BlockType::all()[0]));
+        assert_eq!(saved.unwrap(), board);
+
+        editor.key(KeyCode::Char('c'), &path);
+        assert!(editor.game.board.iter().flatten().all(Option::is_none));
+        assert_eq!(editor.key(KeyCode::Char('q'), &path), Some(None));
+    }
+}
@@ -0,0 +1,103 @@
+//! Opt-in record of what happened in a game, one JSON object per line, e.g.
+//! `{"tick":112,"event":"lock","kind":"T","cells":[[4,19],[5,19],[6,19],[5,18]]}`
+
+use serde::Serialize;
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{BlockType, GameOverReason};
+
+/// Something worth a line in the log
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LoggedEvent {
+    Spawn {
+        kind: BlockType,
+    },
+    /// `cells` are (x, y) on the board, row 0 at the top
+    Lock {
+        kind: BlockType,
+        cells: Vec<(i32, i32)>,
+    },
+    /// `rows` as they were numbered before the clear
+    Clear {
+        rows: Vec<usize>,
+        points: usize,
+    },
+    Hold {
+        kind: BlockType,
+        /// The piece that came out of hold, if any
+        swapped_in: Option<BlockType>,
+    },
+    LevelUp {
+        level: usize,
+    },
+    GameOver {
+        reason: GameOverReason,
+    },
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    tick: u64,
+    #[serde(flatten)]
+    event: &'a LoggedEvent,
+}
+
+/// Buffered writer for `LoggedEvent`s; the default one writes nowhere
+#[derive(Default)]
+pub struct EventLog {
+    writer: Option<BufWriter<File>>,
+    /// Ticks of the games before this one, so ticks keep rising across restarts
+    tick_base: u64,
+}
+
+/// A copied game (AI lookahead, analysis) doesn't write to the original's log
+impl Clone for EventLog {
+    fn clone(&self) -> Self {
+        EventLog::default()
+    }
+}
+
+impl EventLog {
+    /// Append to `path`, creating it if needed
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(EventLog {
+            writer: Some(BufWriter::new(file)),
+            tick_base: 0,
+        })
+    }
+
+    /// Log `event` at game tick `tick`; the first failed write closes the log
+    pub fn write(&mut self, tick: u64, event: &LoggedEvent) {
+        let Some(writer) = &mut self.writer else {
+            return;
+        };
+        let line = Line {
+            tick: self.tick_base + tick,
+            event,
+        };
+        let written = serde_json::to_writer(&mut *writer, &line)
+            .map_err(io::Error::other)
+            .and_then(|()| writer.write_all(b"\n"));
+        if written.is_err() {
+            self.writer = None;
+        }
+    }
+
+    /// Carry on into a new game that starts after `ticks` more
+    pub fn continue_after(&mut self, ticks: u64) {
+        self.tick_base += ticks;
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match &mut self.writer {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+}
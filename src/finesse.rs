@@ -0,0 +1,95 @@
+//! Finesse: the fewest key presses that take a piece from its spawn to a
+//! column and rotation. Holding a shift key until the piece reaches the wall
+//! counts as one press, as DAS makes it.
+
+use std::collections::{HashSet, VecDeque};
+
+use crate::{ActivePiece, BOARD_WIDTH, BlockType, KICKS, rules::SpawnRule};
+
+/// Clear of the walls; finesse is judged as if the board were empty
+fn fits(piece: &ActivePiece) -> bool {
+    piece
+        .cells()
+        .iter()
+        .all(|&(x, _)| x >= 0 && x < BOARD_WIDTH as i32)
+}
+
+fn rotated(piece: &ActivePiece, clockwise: bool) -> Option<ActivePiece> {
+    let mut turned = piece.clone();
+    if clockwise {
+        turned.rotate_cw();
+    } else {
+        turned.rotate_ccw();
+    }
+    KICKS.into_iter().find_map(|(dx, dy)| {
+        let mut kicked = turned.clone();
+        kicked.x += dx;
+        kicked.y += dy;
+        fits(&kicked).then_some(kicked)
+    })
+}
+
+fn shifted(piece: &ActivePiece, dx: i32) -> Option<ActivePiece> {
+    let mut moved = piece.clone();
+    moved.x += dx;
+    fits(&moved).then_some(moved)
+}
+
+/// Shifted as far as it goes, as a held key does
+fn to_wall(piece: &ActivePiece, dx: i32) -> ActivePiece {
+    let mut moved = piece.clone();
+    while let Some(next) = shifted(&moved, dx) {
+        moved = next;
+    }
+    moved
+}
+
+/// Presses needed to bring `kind` from `spawn` to `rotation` at column `x`;
+/// `None` if that's out of reach
+pub fn min_inputs(kind: BlockType, spawn: SpawnRule, rotation: usize, x: i32) -> Option<usize> {
+    let start = ActivePiece::new(kind, spawn);
+    let rotation = rotation % start.tetro.rotations.len();
+    let mut seen = HashSet::from([(start.rotation, start.x)]);
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((piece, presses)) = queue.pop_front() {
+        if piece.rotation == rotation && piece.x == x {
+            return Some(presses);
+        }
+        let moves = [
+            shifted(&piece, -1),
+            shifted(&piece, 1),
+            Some(to_wall(&piece, -1)),
+            Some(to_wall(&piece, 1)),
+            rotated(&piece, true),
+            rotated(&piece, false),
+        ];
+        for next in moves.into_iter().flatten() {
+            if seen.insert((next.rotation, next.x)) {
+                queue.push_back((next, presses + 1));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn min(kind: BlockType, rotation: usize, dx: i32) -> Option<usize> {
+        let spawn = SpawnRule::default();
+        min_inputs(kind, spawn, rotation, spawn.column + dx)
+    }
+
+    #[test]
+    fn presses_count_taps_turns_and_wall_shifts() {
+        assert_eq!(min(BlockType::T, 0, 0), Some(0));
+        assert_eq!(min(BlockType::T, 0, 1), Some(1));
+        assert_eq!(min(BlockType::T, 0, 2), Some(2));
+        assert_eq!(min(BlockType::T, 1, 0), Some(1));
+        assert_eq!(min(BlockType::T, 2, 0), Some(2));
+        // held against the left wall
+        assert_eq!(min(BlockType::T, 0, -3), Some(1));
+        assert_eq!(min(BlockType::O, 0, 20), None);
+    }
+}
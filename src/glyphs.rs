@@ -0,0 +1,101 @@
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+use tetris_game::config::{HIGH_CONTRAST, UiConfig};
+
+/// What board and preview cells are drawn with; each is two columns wide
+#[derive(Copy, Clone, Debug)]
+pub struct Glyphs<'a> {
+    pub filled: &'a str,
+    pub ghost: &'a str,
+    pub empty: &'a str,
+    /// Hatching over T-spin slots
+    pub slot: &'a str,
+    /// A hard drop's streak, just dropped and then fading
+    pub trail: [&'a str; 2],
+}
+
+impl Glyphs<'static> {
+    pub const BLOCKS: Glyphs<'static> = Glyphs {
+        filled: "██",
+        ghost: "░░",
+        empty: "  ",
+        slot: "╱╱",
+        trail: ["▒▒", "░░"],
+    };
+
+    /// For terminals and fonts that get block characters wrong
+    pub const ASCII: Glyphs<'static> = Glyphs {
+        filled: "[]",
+        ghost: "::",
+        empty: "  ",
+        slot: "//",
+        trail: ["||", "||"],
+    };
+}
+
+impl<'a> Glyphs<'a> {
+    /// The set `ui` asks for: ASCII, or blocks with its `block_*` strings.
+    /// The high-contrast theme outlines the ghost, since a faint fill is
+    /// what washes out first.
+    pub fn of(ui: &'a UiConfig) -> Self {
+        let glyphs = if ui.ascii {
+            Glyphs::ASCII
+        } else {
+            Glyphs {
+                filled: &ui.block_filled,
+                ghost: &ui.block_ghost,
+                empty: &ui.block_empty,
+                ..Glyphs::BLOCKS
+            }
+        };
+        if ui.theme == HIGH_CONTRAST {
+            Glyphs {
+                ghost: "[]",
+                ..glyphs
+            }
+        } else {
+            glyphs
+        }
+    }
+}
+
+/// Swaps everything non-ASCII already drawn in its area (box borders, bars,
+/// arrows) for the nearest ASCII; rendered last, so nothing slips through
+pub struct AsciiOnly;
+
+impl Widget for AsciiOnly {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let cell = buf.get_mut(x, y);
+                if !cell.symbol.is_ascii() {
+                    let c = cell.symbol.chars().next().map_or(' ', to_ascii);
+                    cell.set_char(c);
+                }
+            }
+        }
+    }
+}
+
+fn to_ascii(c: char) -> char {
+    match c {
+        '─' | '━' | '═' | '—' | '–' | '−' => '-',
+        '│' | '┃' | '║' => '|',
+        // the rest of the box drawing: corners and joins
+        '\u{2500}'..='\u{2570}' => '+',
+        '╱' => '/',
+        '╲' => '\\',
+        '░' => '.',
+        '▒' => ':',
+        '▁' | '▂' => '_',
+        '▃' | '▄' => '-',
+        '▅' | '▆' => '=',
+        '▇' | '█' | '▓' | '▀' => '#',
+        '←' | '‹' => '<',
+        '→' | '›' => '>',
+        '↑' => '^',
+        '↓' | '⤓' => 'v',
+        '✔' => '*',
+        '↻' | '↺' => '@',
+        _ => '?',
+    }
+}
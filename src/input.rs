@@ -0,0 +1,231 @@
+use crossterm::event::KeyEventKind;
+use std::time::{Duration, Instant};
+
+use tetris_game::{Action, BOARD_WIDTH, RotationDir, clock::GameClock, config::GameplayConfig};
+
+/// Without release events a rotation press counts as "held" for this long
+const PRESS_HOLD_FALLBACK: Duration = Duration::from_millis(250);
+
+/// Frontend key state that outlives single presses: which rotation key is
+/// held down (for IRS) and which move key is auto-repeating (DAS/ARR)
+pub struct InputState {
+    pub irs_held: Option<RotationDir>,
+    pressed_at: Instant,
+    /// Whether the terminal reports key releases (keyboard enhancement flags)
+    release_events: bool,
+    irs_enabled: bool,
+    das: Duration,
+    arr: Duration,
+    shift: Option<Shift>,
+    /// Only known when the terminal reports releases
+    soft_drop_held: bool,
+    /// Where key timing reads the time
+    clock: GameClock,
+}
+
+/// A move key being held down
+struct Shift {
+    action: Action,
+    pressed: Instant,
+    last_repeat: Option<Instant>,
+}
+
+impl InputState {
+    pub fn new(release_events: bool, irs_enabled: bool, gameplay: &GameplayConfig) -> Self {
+        let clock = GameClock::default();
+        InputState {
+            irs_held: None,
+            pressed_at: clock.now(),
+            release_events,
+            irs_enabled,
+            das: Duration::from_millis(gameplay.das_ms),
+            arr: Duration::from_millis(gameplay.arr_ms),
+            shift: None,
+            soft_drop_held: false,
+            clock,
+        }
+    }
+
+    /// Actions to apply for a mapped key event. Horizontal repeats are timed by
+    /// DAS/ARR when the terminal reports releases, otherwise by the OS key repeat.
+    pub fn key(&mut self, action: Action, kind: KeyEventKind) -> Vec<Action> {
+        let mut out = Vec::new();
+        if let Some(held) = self.track_rotation(action, kind)
+            && self.irs_enabled
+        {
+            out.push(Action::SetIrs(held));
+        }
+        if action == Action::SoftDrop && self.release_events {
+            self.soft_drop_held = kind != KeyEventKind::Release;
+        }
+        let horizontal = matches!(action, Action::MoveLeft | Action::MoveRight);
+        match kind {
+            KeyEventKind::Release => {
+                if self.shift.as_ref().is_some_and(|s| s.action == action) {
+                    self.shift = None;
+                }
+            }
+            KeyEventKind::Repeat if horizontal && self.release_events => {}
+            KeyEventKind::Press if horizontal && self.release_events => {
+                self.shift = Some(Shift {
+                    action,
+                    pressed: self.clock.now(),
+                    last_repeat: None,
+                });
+                out.push(action);
+            }
+            _ => out.push(action),
+        }
+        out
+    }
+
+    /// Move and soft drop keys being held down, for the input display
+    pub fn held(&self) -> Vec<Action> {
+        let mut held: Vec<Action> = self.shift.iter().map(|s| s.action).collect();
+        if self.soft_drop_held {
+            held.push(Action::SoftDrop);
+        }
+        held
+    }
+
+    /// The move key being held, while DAS times it
+    pub fn shifting(&self) -> Option<Action> {
+        self.shift.as_ref().map(|s| s.action)
+    }
+
+    /// How far the held move key is through DAS, 0.0 to 1.0; 1.0 once it's
+    /// auto-repeating, and 0.0 with none held
+    pub fn das_charge_pct(&self) -> f64 {
+        match &self.shift {
+            None => 0.0,
+            Some(_) if self.das.is_zero() => 1.0,
+            Some(shift) => {
+                let held = self.clock.now().saturating_duration_since(shift.pressed);
+                (held.as_secs_f64() / self.das.as_secs_f64()).min(1.0)
+            }
+        }
+    }
+
+    /// Actions due from held keys: expired IRS holds and auto-repeated moves
+    pub fn tick(&mut self) -> Vec<Action> {
+        let mut out = Vec::new();
+        let now = self.clock.now();
+        if !self.release_events
+            && self.irs_held.is_some()
+            && now.saturating_duration_since(self.pressed_at) >= PRESS_HOLD_FALLBACK
+            && let Some(held) = self.set(None)
+            && self.irs_enabled
+        {
+            out.push(Action::SetIrs(held));
+        }
+        if let Some(shift) = &mut self.shift
+            && now.saturating_duration_since(shift.pressed) >= self.das
+        {
+            if self.arr.is_zero() {
+                // instant repeat: slide all the way to the wall, once per hold
+                if shift.last_repeat.is_none() {
+                    out.extend(std::iter::repeat_n(shift.action, BOARD_WIDTH));
+                    shift.last_repeat = Some(now);
+                }
+            } else if shift
+                .last_repeat
+                .is_none_or(|t| now.saturating_duration_since(t) >= self.arr)
+            {
+                out.push(shift.action);
+                shift.last_repeat = Some(now);
+            }
+        }
+        out
+    }
+
+    /// Update the held rotation key; returns the new one when it changed
+    fn track_rotation(
+        &mut self,
+        action: Action,
+        kind: KeyEventKind,
+    ) -> Option<Option<RotationDir>> {
+        let dir = match action {
+            Action::RotateCw => RotationDir::Cw,
+            Action::RotateCcw => RotationDir::Ccw,
+            _ => return None,
+        };
+        let held = match kind {
+            KeyEventKind::Press | KeyEventKind::Repeat => {
+                self.pressed_at = self.clock.now();
+                Some(dir)
+            }
+            KeyEventKind::Release if self.irs_held == Some(dir) => None,
+            KeyEventKind::Release => return None,
+        };
+        self.set(held)
+    }
+
+    fn set(&mut self, held: Option<RotationDir>) -> Option<Option<RotationDir>> {
+        if self.irs_held == held {
+            return None;
+        }
+        self.irs_held = held;
+        Some(held)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use std::sync::Arc;
+    use tetris_game::{clock::ManualClock, config::KeysConfig, keymap::Keymap};
+
+    /// Key state with release events, `das_ms` of DAS and time that only
+    /// moves with the returned clock
+    fn timed_input(das_ms: u64) -> (InputState, Arc<ManualClock>) {
+        let gameplay = GameplayConfig {
+            das_ms,
+            ..GameplayConfig::default()
+        };
+        let mut input = InputState::new(true, false, &gameplay);
+        let clock = Arc::new(ManualClock::default());
+        input.clock = GameClock::new(clock.clone());
+        (input, clock)
+    }
+
+    #[test]
+    fn das_charges_over_its_delay() {
+        let (mut input, clock) = timed_input(100);
+        assert_eq!(input.das_charge_pct(), 0.0);
+        input.key(Action::MoveLeft, KeyEventKind::Press);
+        assert_eq!(input.das_charge_pct(), 0.0);
+        clock.advance(Duration::from_millis(25));
+        assert!((input.das_charge_pct() - 0.25).abs() < 1e-9);
+        clock.advance(Duration::from_millis(75));
+        assert_eq!(input.das_charge_pct(), 1.0);
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(input.das_charge_pct(), 1.0);
+        input.key(Action::MoveLeft, KeyEventKind::Release);
+        assert_eq!(input.das_charge_pct(), 0.0);
+    }
+
+    #[test]
+    fn without_das_a_held_key_is_fully_charged() {
+        let (mut input, _clock) = timed_input(0);
+        input.key(Action::MoveRight, KeyEventKind::Press);
+        assert_eq!(input.das_charge_pct(), 1.0);
+    }
+
+    #[test]
+    fn a_mirrored_left_key_charges_das_to_the_right() {
+        let keymap = Keymap::from_config(&KeysConfig::default())
+            .unwrap()
+            .mirrored();
+        let left = KeyEvent::new(KeyCode::Left, KeyModifiers::NONE);
+        let action = keymap.action_for(&left).unwrap();
+        let mut input = InputState::new(true, false, &GameplayConfig::default());
+        assert_eq!(
+            input.key(action, KeyEventKind::Press),
+            vec![Action::MoveRight]
+        );
+        assert_eq!(input.shifting(), Some(Action::MoveRight));
+        input.key(action, KeyEventKind::Release);
+        assert_eq!(input.shifting(), None);
+    }
+}
@@ -0,0 +1,86 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use std::collections::VecDeque;
+
+use tetris_game::Action;
+
+/// Inputs kept for the display, oldest dropped first
+const HISTORY: usize = 10;
+
+/// Recent actions as symbols, for viewers to follow along on a stream
+#[derive(Default)]
+pub struct InputDisplay {
+    recent: VecDeque<Action>,
+    /// Actions whose keys are down right now, refreshed every frame
+    pub held: Vec<Action>,
+}
+
+/// How an action is drawn; `None` for ones that aren't a key of their own
+fn symbol(action: Action) -> Option<&'static str> {
+    Some(match action {
+        Action::MoveLeft => "←",
+        Action::MoveRight => "→",
+        Action::SoftDrop => "↓",
+        Action::HardDrop => "⤓",
+        Action::RotateCw => "↻",
+        Action::RotateCcw => "↺",
+        Action::Hold => "H",
+        Action::Zone => "Z",
+        Action::SetIrs(_) => return None,
+    })
+}
+
+impl InputDisplay {
+    /// Note a mapped key press
+    pub fn push(&mut self, action: Action) {
+        if symbol(action).is_none() {
+            return;
+        }
+        if self.recent.len() == HISTORY {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(action);
+    }
+
+    /// Held keys highlighted, then the latest presses with the newest on the
+    /// right; older presses are dropped first when `width` is short
+    pub fn line(&self, width: usize) -> Line<'static> {
+        let held: Vec<&str> = self.held.iter().filter_map(|&a| symbol(a)).collect();
+        let mut spans = Vec::new();
+        let mut used = 0;
+        if !held.is_empty() && held.len() < width {
+            spans.push(Span::styled(
+                held.concat(),
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::raw(" "));
+            used = held.len() + 1;
+        }
+        // every symbol is one column, with a space between each
+        let room = width.saturating_sub(used).div_ceil(2);
+        let skip = self.recent.len().saturating_sub(room);
+        let last = self.recent.len().saturating_sub(1);
+        for (i, &action) in self.recent.iter().enumerate().skip(skip) {
+            let style = if i == last {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::Gray)
+            };
+            let text = symbol(action).unwrap_or_default();
+            spans.push(Span::styled(
+                if i == last {
+                    text.to_string()
+                } else {
+                    format!("{} ", text)
+                },
+                style,
+            ));
+        }
+        Line::from(spans)
+    }
+}
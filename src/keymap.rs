@@ -0,0 +1,332 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{Action, config::KeysConfig};
+
+/// What a key does in single-player: a game action or a frontend command
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Play(Action),
+    Pause,
+    Restart,
+    Quit,
+    SaveQuit,
+    /// Show or hide the on-screen input display
+    ToggleInputs,
+    /// Show the first move of the puzzle's solution
+    Hint,
+    /// Save the board as a shareable position code
+    ExportPosition,
+    /// Show or hide the key bindings over the board
+    Help,
+    /// Open the leaderboard once the game is over
+    Leaderboard,
+    /// Show or hide the column heights under the board
+    ToggleHeights,
+    /// Open the settings screen
+    Settings,
+    /// While held, ghost the piece a hold would bring in
+    HoldPreview,
+    /// Put the board on the system clipboard as text rows
+    CopyBoard,
+    /// Replace the board with text rows from the system clipboard
+    PasteBoard,
+    /// Give the History box the arrow keys, to scroll it, or take them back
+    FocusHistory,
+    /// Switch to the high-contrast theme, or back to the one before it
+    ToggleContrast,
+}
+
+impl Command {
+    /// What the command does, as the help overlay lists it
+    pub fn description(self) -> &'static str {
+        match self {
+            Command::Play(Action::MoveLeft) => "Move left",
+            Command::Play(Action::MoveRight) => "Move right",
+            Command::Play(Action::SoftDrop) => "Soft drop",
+            Command::Play(Action::HardDrop) => "Hard drop",
+            Command::Play(Action::RotateCw) => "Rotate clockwise",
+            Command::Play(Action::RotateCcw) => "Rotate counterclockwise",
+            Command::Play(Action::Hold) => "Hold",
+            Command::Play(Action::Zone) => "Zone",
+            Command::Play(Action::SetIrs(_)) => "Initial rotation",
+            Command::Pause => "Pause",
+            Command::Restart => "Restart",
+            Command::Quit => "Quit",
+            Command::SaveQuit => "Save and quit",
+            Command::ToggleInputs => "Show inputs",
+            Command::Hint => "Puzzle hint",
+            Command::ExportPosition => "Export position",
+            Command::Help => "This help",
+            Command::Leaderboard => "Leaderboard (after a game)",
+            Command::ToggleHeights => "Column heights",
+            Command::Settings => "Settings",
+            Command::HoldPreview => "Preview hold (hold down)",
+            Command::CopyBoard => "Copy board",
+            Command::PasteBoard => "Paste board",
+            Command::FocusHistory => "Scroll clear history",
+            Command::ToggleContrast => "High contrast",
+        }
+    }
+}
+
+/// A key plus the modifiers that must be held with it
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KeyPress {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyPress {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // terminals report shifted letters as uppercase, sometimes without SHIFT,
+        // and shifted punctuation with or without it; compare on one form
+        let (code, modifiers) = match code {
+            KeyCode::Char(c) if c.is_ascii_uppercase() => (
+                KeyCode::Char(c.to_ascii_lowercase()),
+                modifiers | KeyModifiers::SHIFT,
+            ),
+            KeyCode::Char(c) if !c.is_alphabetic() => (code, modifiers - KeyModifiers::SHIFT),
+            _ => (code, modifiers),
+        };
+        KeyPress {
+            code,
+            modifiers: modifiers
+                & (KeyModifiers::SHIFT | KeyModifiers::CONTROL | KeyModifiers::ALT),
+        }
+    }
+
+    fn plain(code: KeyCode) -> Self {
+        KeyPress::new(code, KeyModifiers::NONE)
+    }
+
+    /// Parse a config descriptor like "Left", "a", "space" or "shift+z"
+    pub fn parse(text: &str) -> Result<KeyPress, String> {
+        let mut parts: Vec<&str> = text.split('+').collect();
+        // "+" on its own (or "shift++") is the plus key, not a separator
+        if text.ends_with("++") || text == "+" {
+            parts.pop();
+            parts.pop();
+            parts.push("+");
+        }
+        let (key, mods) = parts.split_last().ok_or("empty key")?;
+        let mut modifiers = KeyModifiers::NONE;
+        for m in mods {
+            modifiers |= match m.to_ascii_lowercase().as_str() {
+                "shift" => KeyModifiers::SHIFT,
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                _ => return Err(format!("unknown modifier '{}' in '{}'", m, text)),
+            };
+        }
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "space" => KeyCode::Char(' '),
+                "enter" | "return" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "esc" | "escape" => KeyCode::Esc,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "insert" => KeyCode::Insert,
+                "delete" => KeyCode::Delete,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key '{}'", text)),
+                },
+            },
+        };
+        Ok(KeyPress::new(code, modifiers))
+    }
+
+    /// Short label for the Controls panel
+    pub fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_uppercase().to_string(),
+            KeyCode::F(n) => format!("F{}", n),
+            other => format!("{:?}", other),
+        };
+        let mut label = String::new();
+        for (flag, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(flag) {
+                label.push_str(name);
+            }
+        }
+        label + &key
+    }
+}
+
+/// Maps keys to game actions; versus mode runs two of these side by side
+pub struct Keymap {
+    bindings: Vec<(KeyPress, Command)>,
+}
+
+impl Keymap {
+    /// Single-player bindings from the `[keys]` config section. Every command
+    /// needs a key and no key may do two things.
+    pub fn from_config(keys: &KeysConfig) -> Result<Keymap, String> {
+        let mut bindings: Vec<(KeyPress, Command)> = Vec::new();
+        let mut errors = Vec::new();
+        for (name, descriptors, command) in keys.entries() {
+            if descriptors.is_empty() {
+                errors.push(format!("keys.{} has no key bound", name));
+            }
+            for text in descriptors {
+                let press = match KeyPress::parse(text) {
+                    Ok(press) => press,
+                    Err(e) => {
+                        errors.push(format!("keys.{}: {}", name, e));
+                        continue;
+                    }
+                };
+                if let Some((_, other)) = bindings.iter().find(|(p, _)| *p == press) {
+                    errors.push(format!(
+                        "key '{}' is bound to both {} and {}",
+                        text,
+                        KeysConfig::name_of(*other),
+                        name
+                    ));
+                    continue;
+                }
+                bindings.push((press, command));
+            }
+        }
+        if errors.is_empty() {
+            Ok(Keymap { bindings })
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
+    /// Left-hand side of the keyboard
+    pub fn player_one() -> Self {
+        Keymap::plain(&[
+            (KeyCode::Char('a'), Action::MoveLeft),
+            (KeyCode::Char('d'), Action::MoveRight),
+            (KeyCode::Char('s'), Action::SoftDrop),
+            (KeyCode::Char('w'), Action::RotateCw),
+            (KeyCode::Char('e'), Action::RotateCcw),
+            (KeyCode::Char(' '), Action::HardDrop),
+            (KeyCode::Char('c'), Action::Hold),
+        ])
+    }
+
+    /// Arrow keys plus nearby punctuation
+    pub fn player_two() -> Self {
+        Keymap::plain(&[
+            (KeyCode::Left, Action::MoveLeft),
+            (KeyCode::Right, Action::MoveRight),
+            (KeyCode::Down, Action::SoftDrop),
+            (KeyCode::Up, Action::RotateCw),
+            (KeyCode::Char('.'), Action::RotateCcw),
+            (KeyCode::Enter, Action::HardDrop),
+            (KeyCode::Char('/'), Action::Hold),
+        ])
+    }
+
+    fn plain(bindings: &[(KeyCode, Action)]) -> Self {
+        Keymap {
+            bindings: bindings
+                .iter()
+                .map(|&(code, action)| (KeyPress::plain(code), Command::Play(action)))
+                .collect(),
+        }
+    }
+
+    /// The same keys with left and right swapped, and clockwise and
+    /// counterclockwise (`--mirror`)
+    pub fn mirrored(mut self) -> Self {
+        for (_, command) in &mut self.bindings {
+            *command = match *command {
+                Command::Play(Action::MoveLeft) => Command::Play(Action::MoveRight),
+                Command::Play(Action::MoveRight) => Command::Play(Action::MoveLeft),
+                Command::Play(Action::RotateCw) => Command::Play(Action::RotateCcw),
+                Command::Play(Action::RotateCcw) => Command::Play(Action::RotateCw),
+                other => other,
+            };
+        }
+        self
+    }
+
+    pub fn command_for(&self, key: &KeyEvent) -> Option<Command> {
+        let press = KeyPress::new(key.code, key.modifiers);
+        self.bindings
+            .iter()
+            .find(|(p, _)| *p == press)
+            .map(|(_, command)| *command)
+    }
+
+    pub fn action_for(&self, key: &KeyEvent) -> Option<Action> {
+        match self.command_for(key)? {
+            Command::Play(action) => Some(action),
+            _ => None,
+        }
+    }
+
+    /// Labels of every key bound to `command`, e.g. "Z/Shift+Up"
+    pub fn keys_for(&self, command: Command) -> String {
+        let labels: Vec<String> = self
+            .bindings
+            .iter()
+            .filter(|(_, c)| *c == command)
+            .map(|(p, _)| p.label())
+            .collect();
+        labels.join("/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn mirroring_swaps_directions_and_rotations() {
+        let keymap = Keymap::from_config(&KeysConfig::default())
+            .unwrap()
+            .mirrored();
+        assert_eq!(
+            keymap.action_for(&press(KeyCode::Left)),
+            Some(Action::MoveRight)
+        );
+        assert_eq!(
+            keymap.action_for(&press(KeyCode::Right)),
+            Some(Action::MoveLeft)
+        );
+        assert_eq!(
+            keymap.action_for(&press(KeyCode::Up)),
+            Some(Action::RotateCcw)
+        );
+        assert_eq!(
+            keymap.action_for(&press(KeyCode::Char('z'))),
+            Some(Action::RotateCw)
+        );
+        assert_eq!(
+            keymap.action_for(&press(KeyCode::Down)),
+            Some(Action::SoftDrop)
+        );
+        assert_eq!(
+            keymap.command_for(&press(KeyCode::Char('p'))),
+            Some(Command::Pause)
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! Hand-built boards saved as JSON, one string per row, for custom starting positions
+
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path};
+
+use crate::{BOARD_HEIGHT, BOARD_WIDTH, BlockType, Board};
+
+/// On disk: `{"rows": ["..........", "IIII..OOG."]}`, top row first. Files
+/// with fewer than `BOARD_HEIGHT` rows fill the bottom of the board.
+#[derive(Serialize, Deserialize)]
+struct LayoutFile {
+    rows: Vec<String>,
+}
+
+fn cell_char(cell: Option<BlockType>) -> char {
+    match cell {
+        None => '.',
+        Some(BlockType::I) => 'I',
+        Some(BlockType::O) => 'O',
+        Some(BlockType::T) => 'T',
+        Some(BlockType::S) => 'S',
+        Some(BlockType::Z) => 'Z',
+        Some(BlockType::J) => 'J',
+        Some(BlockType::L) => 'L',
+        Some(BlockType::Garbage) => 'G',
+    }
+}
+
+fn parse_cell(c: char) -> Option<Option<BlockType>> {
+    Some(match c.to_ascii_uppercase() {
+        '.' | ' ' => None,
+        'I' => Some(BlockType::I),
+        'O' => Some(BlockType::O),
+        'T' => Some(BlockType::T),
+        'S' => Some(BlockType::S),
+        'Z' => Some(BlockType::Z),
+        'J' => Some(BlockType::J),
+        'L' => Some(BlockType::L),
+        'G' => Some(BlockType::Garbage),
+        _ => return None,
+    })
+}
+
+/// The board as text rows, leaving out the empty rows above the stack
+pub fn to_rows(board: &Board) -> Vec<String> {
+    let top = board
+        .iter()
+        .position(|row| row.iter().any(Option::is_some))
+        .unwrap_or(BOARD_HEIGHT);
+    board[top..]
+        .iter()
+        .map(|row| row.iter().map(|&cell| cell_char(cell)).collect())
+        .collect()
+}
+
+/// The board as text, one row per line, as copied to the clipboard
+pub fn board_to_string(board: &Board) -> String {
+    to_rows(board).join("\n")
+}
+
+/// Read a board written by `board_to_string`; blank lines are skipped
+pub fn board_from_str(text: &str) -> Result<Board, String> {
+    let rows: Vec<String> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.trim_end_matches('\r').to_string())
+        .collect();
+    from_rows(&rows)
+}
+
+/// Build a board from text rows, bottom-aligned
+pub fn from_rows(rows: &[String]) -> Result<Board, String> {
+    if rows.len() > BOARD_HEIGHT {
+        return Err(format!(
+            "{} rows given, the board has {}",
+            rows.len(),
+            BOARD_HEIGHT
+        ));
+    }
+    let mut board = [[None; BOARD_WIDTH]; BOARD_HEIGHT];
+    let top = BOARD_HEIGHT - rows.len();
+    for (i, text) in rows.iter().enumerate() {
+        let cells: Vec<char> = text.chars().collect();
+        if cells.len() != BOARD_WIDTH {
+            return Err(format!(
+                "row {} has {} cells, expected {}",
+                i + 1,
+                cells.len(),
+                BOARD_WIDTH
+            ));
+        }
+        for (x, &c) in cells.iter().enumerate() {
+            board[top + i][x] = parse_cell(c).ok_or_else(|| {
+                format!(
+                    "row {}: unknown cell '{}' (use '.', I O T S Z J L or G)",
+                    i + 1,
+                    c
+                )
+            })?;
+        }
+    }
+    Ok(board)
+}
+
+pub fn load(path: &Path) -> io::Result<Board> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+    let data = fs::read_to_string(path)?;
+    let file: LayoutFile =
+        serde_json::from_str(&data).map_err(|e| invalid(format!("not a board file: {}", e)))?;
+    from_rows(&file.rows).map_err(invalid)
+}
+
+pub fn save(path: &Path, board: &Board) -> io::Result<()> {
+    if let Some(dir) = path.parent()
+        && !dir.as_os_str().is_empty()
+    {
+        fs::create_dir_all(dir)?;
+    }
+    let file = LayoutFile {
+        rows: to_rows(board),
+    };
+    let json = serde_json::to_string_pretty(&file).map_err(io::Error::other)?;
+    fs::write(path, json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_board_copies_from_the_top_of_its_stack() {
+        let mut board = [[None; BOARD_WIDTH]; BOARD_HEIGHT];
+        board[BOARD_HEIGHT - 2][0] = Some(BlockType::T);
+        board[BOARD_HEIGHT - 1][..4].fill(Some(BlockType::I));
+        board[BOARD_HEIGHT - 1][9] = Some(BlockType::Garbage);
+        let text = board_to_string(&board);
+        assert_eq!(text, "T.........\nIIII.....G");
+        assert_eq!(board_from_str(&text).unwrap(), board);
+        assert_eq!(board_to_string(&[[None; BOARD_WIDTH]; BOARD_HEIGHT]), "");
+    }
+
+    #[test]
+    fn pasted_text_is_read_leniently() {
+        let board = board_from_str("\r\nt.... ....\r\n\r\niiii.....g\r\n").unwrap();
+        assert_eq!(board_to_string(&board), "T.........\nIIII.....G");
+    }
+
+    #[test]
+    fn pasted_text_that_isnt_a_board_is_refused() {
+        let short = board_from_str("IIII").unwrap_err();
+        assert!(short.contains("4 cells"), "{}", short);
+        let unknown = board_from_str("IIII.....X").unwrap_err();
+        assert!(unknown.contains("unknown cell 'X'"), "{}", unknown);
+        let tall = board_from_str(&"..........\n".repeat(BOARD_HEIGHT + 1)).unwrap_err();
+        assert!(tall.contains("rows given"), "{}", tall);
+    }
+}
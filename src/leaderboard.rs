@@ -0,0 +1,197 @@
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Tabs},
+};
+use std::{io, sync::mpsc};
+
+use tetris_game::{
+    milestones::thousands,
+    paths,
+    scores::{LEADERBOARD_MODES, Leaderboard, Placement, ranked_by_time},
+};
+
+use crate::{InternalEvent, Term, format_duration};
+
+/// Which mode's board is shown and the row picked in it
+struct Screen {
+    board: Leaderboard,
+    mode: usize,
+    selected: usize,
+    /// The game just finished, when arriving from its game-over screen
+    placed: Option<Placement>,
+    today: String,
+}
+
+impl Screen {
+    fn new(mode: &str, placed: Option<Placement>) -> Self {
+        let board = Leaderboard::load();
+        let mode = LEADERBOARD_MODES
+            .iter()
+            .position(|&m| m == mode)
+            .unwrap_or(0);
+        Screen {
+            board,
+            mode,
+            selected: placed.map_or(0, |p| p.rank),
+            placed,
+            today: chrono::Local::now().format("%Y-%m-%d").to_string(),
+        }
+    }
+
+    fn mode(&self) -> &'static str {
+        LEADERBOARD_MODES[self.mode]
+    }
+
+    /// Act on a key; true when it closes the screen
+    fn key(&mut self, key: KeyEvent) -> bool {
+        let rows = self.board.entries(self.mode()).len();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => return true,
+            KeyCode::Tab | KeyCode::Right => self.switch(1),
+            KeyCode::BackTab | KeyCode::Left => self.switch(LEADERBOARD_MODES.len() - 1),
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if self.selected + 1 < rows => self.selected += 1,
+            _ => {}
+        }
+        false
+    }
+
+    fn switch(&mut self, by: usize) {
+        self.mode = (self.mode + by) % LEADERBOARD_MODES.len();
+        self.selected = 0;
+    }
+}
+
+/// Full-screen leaderboard from the command line, starting on marathon
+pub fn show(terminal: &mut Term) -> io::Result<()> {
+    let mut screen = Screen::new(LEADERBOARD_MODES[0], None);
+    loop {
+        terminal.draw(|f| ui(f, &screen))?;
+        if let Event::Key(key) = event::read()?
+            && key.kind != KeyEventKind::Release
+            && screen.key(key)
+        {
+            return Ok(());
+        }
+    }
+}
+
+/// The leaderboard over a finished game, on `mode`'s board with the game's
+/// entry picked out. Keys come through `rx`, since the input thread owns the
+/// terminal's event stream.
+pub fn show_after_game(
+    terminal: &mut Term,
+    rx: &mpsc::Receiver<InternalEvent>,
+    mode: &str,
+    placed: Option<Placement>,
+) -> io::Result<()> {
+    let mut screen = Screen::new(mode, placed);
+    loop {
+        terminal.draw(|f| ui(f, &screen))?;
+        match rx.recv() {
+            Ok(InternalEvent::Input(key)) if key.kind != KeyEventKind::Release => {
+                if screen.key(key) {
+                    return Ok(());
+                }
+            }
+            Ok(_) => {}
+            Err(_) => return Ok(()),
+        }
+    }
+}
+
+fn ui<B: ratatui::backend::Backend>(f: &mut ratatui::Frame<B>, screen: &Screen) {
+    let title = match paths::profile() {
+        Some(name) => format!(" Leaderboard — {} ", name),
+        None => " Leaderboard ".to_string(),
+    };
+    let outer = Block::default().borders(Borders::ALL).title(title);
+    let inner = outer.inner(f.size());
+    f.render_widget(outer, f.size());
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(2),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ]
+            .as_ref(),
+        )
+        .split(inner);
+
+    let tabs = Tabs::new(
+        LEADERBOARD_MODES
+            .iter()
+            .map(|&mode| Line::from(mode))
+            .collect(),
+    )
+    .select(screen.mode)
+    .highlight_style(
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    );
+    f.render_widget(tabs, chunks[0]);
+
+    let mode = screen.mode();
+    let entries = screen.board.entries(mode);
+    let mut lines = Vec::new();
+    if entries.is_empty() {
+        lines.push(Line::styled(
+            "No scores yet",
+            Style::default().fg(Color::DarkGray),
+        ));
+    } else {
+        let result = if ranked_by_time(mode) {
+            "Time"
+        } else {
+            "Score"
+        };
+        lines.push(Line::styled(
+            format!(
+                "{:>4}  {:<16}{:>10}{:>7}{:>7}  {}",
+                "#", "Name", result, "Lines", "Level", "Date"
+            ),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+    }
+    for (rank, entry) in entries.iter().enumerate() {
+        let result = if ranked_by_time(mode) {
+            format_duration(entry.time)
+        } else {
+            thousands(entry.score)
+        };
+        let mut style = Style::default();
+        if entry.date == screen.today {
+            style = style.fg(Color::Yellow);
+        }
+        if screen.placed == Some(Placement { mode, rank }) {
+            style = style.fg(Color::Green).add_modifier(Modifier::BOLD);
+        }
+        if rank == screen.selected {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        let name: String = entry.name.chars().take(15).collect();
+        lines.push(Line::from(Span::styled(
+            format!(
+                "{:>4}  {:<16}{:>10}{:>7}{:>7}  {}",
+                rank + 1,
+                name,
+                result,
+                entry.lines,
+                entry.level,
+                entry.date
+            ),
+            style,
+        )));
+    }
+    f.render_widget(Paragraph::new(lines), chunks[1]);
+    f.render_widget(
+        Paragraph::new("Tab : Next mode   ↑ ↓ : Select   Q : Close"),
+        chunks[2],
+    );
+}
@@ -0,0 +1,3458 @@
+//! Game core: board, pieces, rules and the deterministic simulation, with no
+//! terminal or threads involved. The `tetris_game` binary is a frontend on top.
+
+use rand::prelude::*;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    cmp::max,
+    collections::VecDeque,
+    io,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+pub mod ai;
+pub mod analysis;
+pub mod clock;
+pub mod config;
+pub mod daily;
+pub mod event_log;
+pub mod finesse;
+pub mod keymap;
+pub mod layout;
+pub mod milestones;
+pub mod paths;
+pub mod position;
+pub mod puzzle;
+pub mod replay;
+pub mod rules;
+pub mod save;
+pub mod scores;
+pub mod scoring;
+mod serde_util;
+pub mod settings;
+pub mod sprint;
+mod svg;
+pub mod tgr;
+pub mod theme;
+pub mod tutorial;
+
+use analysis::BoardSnapshot;
+use clock::GameClock;
+use event_log::{EventLog, LoggedEvent};
+use milestones::MilestoneTracker;
+use replay::Replay;
+use rules::{ClearGravity, InputBuffer, Rules, SpawnRule};
+use scoring::LineClear;
+
+/// Board dimensions (classic Tetris is 10x20)
+pub const BOARD_WIDTH: usize = 10;
+pub const BOARD_HEIGHT: usize = 20;
+
+/// Settled cells, indexed `[y][x]` from the top-left
+pub type Board = [[Option<BlockType>; BOARD_WIDTH]; BOARD_HEIGHT];
+
+/// Sides of a block that face something other than a block of its own kind
+/// (an empty cell, another kind or the walls), where an outline goes
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Edges {
+    pub top: bool,
+    pub right: bool,
+    pub bottom: bool,
+    pub left: bool,
+}
+
+impl Edges {
+    /// The exposed sides of the block at (x, y); `None` for an empty cell
+    pub fn of(board: &Board, x: usize, y: usize) -> Option<Edges> {
+        let kind = board[y][x]?;
+        let open = |dx: i32, dy: i32| {
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            if nx < 0 || ny < 0 || nx >= BOARD_WIDTH as i32 || ny >= BOARD_HEIGHT as i32 {
+                return true;
+            }
+            board[ny as usize][nx as usize] != Some(kind)
+        };
+        Some(Edges {
+            top: open(0, -1),
+            right: open(1, 0),
+            bottom: open(0, 1),
+            left: open(-1, 0),
+        })
+    }
+}
+
+/// Drop each group of touching blocks (of any kind) as far as it goes, lowest
+/// first, until every one rests on the floor or on another group
+pub fn sticky_fall(board: &mut Board) {
+    loop {
+        let mut seen = [[false; BOARD_WIDTH]; BOARD_HEIGHT];
+        let mut groups: Vec<Vec<(usize, usize)>> = Vec::new();
+        for y in 0..BOARD_HEIGHT {
+            for x in 0..BOARD_WIDTH {
+                if board[y][x].is_none() || seen[y][x] {
+                    continue;
+                }
+                seen[y][x] = true;
+                let mut group = vec![(x, y)];
+                let mut i = 0;
+                while let Some(&(cx, cy)) = group.get(i) {
+                    i += 1;
+                    let neighbours = [
+                        (cx.wrapping_sub(1), cy),
+                        (cx + 1, cy),
+                        (cx, cy.wrapping_sub(1)),
+                        (cx, cy + 1),
+                    ];
+                    for (nx, ny) in neighbours {
+                        if nx < BOARD_WIDTH
+                            && ny < BOARD_HEIGHT
+                            && board[ny][nx].is_some()
+                            && !seen[ny][nx]
+                        {
+                            seen[ny][nx] = true;
+                            group.push((nx, ny));
+                        }
+                    }
+                }
+                groups.push(group);
+            }
+        }
+        groups.sort_by_key(|group| std::cmp::Reverse(group.iter().map(|&(_, y)| y).max()));
+
+        let mut moved = false;
+        for group in groups {
+            let cells: Vec<(usize, usize, BlockType)> = group
+                .into_iter()
+                .map(|(x, y)| (x, y, board[y][x].take().expect("grouped")))
+                .collect();
+            let fits = |drop: usize| {
+                cells
+                    .iter()
+                    .all(|&(x, y, _)| y + drop < BOARD_HEIGHT && board[y + drop][x].is_none())
+            };
+            let drop = (1..).take_while(|&d| fits(d)).last().unwrap_or(0);
+            for (x, y, kind) in cells {
+                board[y + drop][x] = Some(kind);
+            }
+            moved |= drop > 0;
+        }
+        // a group that landed on another may have joined it; look again
+        if !moved {
+            return;
+        }
+    }
+}
+
+/// Fixed simulation step. Gravity counts ticks rather than reading the wall
+/// clock, so the same seed + actions always reproduce the same game.
+pub const TICK: Duration = Duration::from_millis(20);
+
+/// Garbage rows pre-filled in cheese mode unless `--cheese-rows` says otherwise
+pub const DEFAULT_CHEESE_ROWS: usize = 10;
+
+/// Lines to clear in sprint mode
+pub const SPRINT_LINES: usize = 40;
+
+/// Length of an ultra game
+pub const ULTRA_TIME: Duration = Duration::from_secs(120);
+
+/// Gravity is this many times slower while the zone is active
+pub const ZONE_SLOWDOWN: u32 = 5;
+
+/// Span of the rolling lines-per-minute figure
+pub const LPM_WINDOW: Duration = Duration::from_secs(30);
+
+/// A sprint records its time at every multiple of this many lines
+pub const SPRINT_SPLIT_LINES: usize = 10;
+
+/// Lines to clear for each level up
+pub const LINES_PER_LEVEL: usize = 10;
+
+/// Board fill samples kept for the sidebar chart, one per locked piece
+pub const FILL_HISTORY_LEN: usize = 20;
+
+/// Locked pieces the average board coverage is taken over
+pub const COVERAGE_HISTORY_LEN: usize = 20;
+
+/// Line clears kept for the sidebar's History box
+pub const CLEAR_HISTORY_LEN: usize = 10;
+
+/// Pieces placed before lines per piece says anything
+pub const EFFICIENCY_MIN_PIECES: usize = 5;
+
+/// How long a just-locked piece stays highlighted
+pub const LOCK_FLASH: Duration = Duration::from_millis(80);
+
+/// How long full rows flash before they clear, unless the rules say otherwise
+pub const CLEAR_FLASH: Duration = Duration::from_millis(200);
+
+/// Offsets a rotation tries in order: in place, left, right, up
+const KICKS: [(i32, i32); 4] = [(0, 0), (-1, 0), (1, 0), (0, -1)];
+
+/// Ruleset the game is played under
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+    Marathon,
+    /// Dig through `rows` pre-filled garbage rows as fast as possible
+    Cheese {
+        rows: usize,
+    },
+    /// Clear `SPRINT_LINES` lines as fast as possible
+    Sprint,
+    /// Score as much as possible before `ULTRA_TIME` runs out
+    Ultra,
+    /// Endless play on the day's shared seed; one attempt, no pause or hold
+    DailyChallenge {
+        number: u32,
+    },
+    /// Clear a preset board with a fixed list of pieces and no gravity
+    Puzzle {
+        id: u32,
+    },
+    /// Marathon for advanced players: no ghost, one preview, a hold box that
+    /// doesn't show what's in it, a shorter lock delay and fixed handling;
+    /// clears score `COMPETITIVE_MULTIPLIER` times as much
+    Competitive,
+    /// Guided steps through the basics, with no gravity; see `tutorial`
+    Tutorial,
+}
+
+/// Lock delay in competitive mode
+pub const COMPETITIVE_LOCK_DELAY: Duration = Duration::from_millis(300);
+
+/// DAS and ARR in competitive mode, in milliseconds
+pub const COMPETITIVE_HANDLING: (u64, u64) = (133, 0);
+
+/// Competitive clears are worth this much more, as a fraction
+pub const COMPETITIVE_MULTIPLIER: (usize, usize) = (5, 4);
+
+/// A stack has to fall this many rows below a danger threshold to drop back
+/// under it, so hovering around the line doesn't flicker
+const DANGER_HYSTERESIS: usize = 2;
+
+/// How close the stack is to the top, by its tallest column
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum DangerLevel {
+    /// Below 10 rows
+    #[default]
+    Safe,
+    /// 10 to 14 rows
+    Warning,
+    /// 15 rows or more
+    Critical,
+}
+
+impl DangerLevel {
+    /// Danger of a column `height` rows tall
+    pub fn of_height(height: usize) -> Self {
+        match height {
+            0..=9 => DangerLevel::Safe,
+            10..=14 => DangerLevel::Warning,
+            _ => DangerLevel::Critical,
+        }
+    }
+
+    /// Danger once the tallest column is `height` rows, coming from `self`:
+    /// rising takes effect at once, falling only `DANGER_HYSTERESIS` rows past
+    /// the threshold
+    pub fn settle(self, height: usize) -> Self {
+        let now = DangerLevel::of_height(height);
+        if now < self {
+            DangerLevel::of_height(height + DANGER_HYSTERESIS).min(self)
+        } else {
+            now
+        }
+    }
+}
+
+/// Board cells of the piece that just locked, highlighted briefly as placement feedback
+#[derive(Clone, Debug)]
+pub struct LockFlash {
+    /// (x, y), following the rows down as lines below them clear
+    pub cells: Vec<(usize, usize)>,
+    pub at: Instant,
+}
+
+impl LockFlash {
+    pub fn is_active_at(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.at) < LOCK_FLASH
+    }
+}
+
+/// One clear as the History box lists it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClearEvent {
+    pub lines: usize,
+    pub t_spin: bool,
+    pub back_to_back: bool,
+    /// The clear left the board empty
+    pub perfect_clear: bool,
+    /// Clearing locks in a row before this one
+    pub combo: usize,
+    pub points: usize,
+    /// Game time of the clear
+    #[serde(with = "serde_util::millis")]
+    pub elapsed: Duration,
+}
+
+impl ClearEvent {
+    /// "Tetris", or "T-Spin D" for a T-spin double
+    pub fn name(&self) -> String {
+        if self.t_spin {
+            let size = match self.lines {
+                1 => "S",
+                2 => "D",
+                _ => "T",
+            };
+            format!("T-Spin {}", size)
+        } else {
+            match self.lines {
+                1 => "Single",
+                2 => "Double",
+                3 => "Triple",
+                _ => "Tetris",
+            }
+            .to_string()
+        }
+    }
+}
+
+/// Full rows shown for a moment before they clear; gravity and spawning wait
+/// until they're gone
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClearFlash {
+    pub rows: Vec<usize>,
+    pub waited: Duration,
+    /// The lock that filled them, for finishing it once they clear
+    t_spin: bool,
+    above_board: bool,
+}
+
+impl ClearFlash {
+    /// `rules.clear_flash`, half again as long for a tetris
+    pub fn length(&self, rules: &Rules) -> Duration {
+        if self.rows.len() >= 4 {
+            rules.clear_flash * 3 / 2
+        } else {
+            rules.clear_flash
+        }
+    }
+}
+
+/// Why a game ended
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameOverReason {
+    /// The stack overflowed: garbage pushed it out of the top of the board,
+    /// or a piece spawned with no move or rotation left
+    TopOut,
+    /// A piece locked entirely above the visible board
+    LockOut,
+    /// A new piece couldn't spawn, even nudged up a row
+    BlockOut,
+    /// The ultra clock ran out
+    TimeExpired,
+    /// The mode's goal was met (sprint lines, cheese dug out); a win
+    GoalReached,
+    /// A puzzle's pieces were all used with blocks still on the board
+    OutOfPieces,
+}
+
+impl GameMode {
+    /// Look up a mode by its `--mode`/config name
+    pub fn from_name(name: &str, cheese_rows: usize) -> Option<GameMode> {
+        match name {
+            "marathon" => Some(GameMode::Marathon),
+            "cheese" => Some(GameMode::Cheese { rows: cheese_rows }),
+            "sprint" => Some(GameMode::Sprint),
+            "ultra" => Some(GameMode::Ultra),
+            "competitive" => Some(GameMode::Competitive),
+            "tutorial" => Some(GameMode::Tutorial),
+            "daily" => Some(GameMode::DailyChallenge {
+                number: daily::today(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Short lowercase name, as `from_name` takes it (daily and puzzle included)
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Marathon => "marathon",
+            GameMode::Cheese { .. } => "cheese",
+            GameMode::Sprint => "sprint",
+            GameMode::Ultra => "ultra",
+            GameMode::DailyChallenge { .. } => "daily",
+            GameMode::Puzzle { .. } => "puzzle",
+            GameMode::Competitive => "competitive",
+            GameMode::Tutorial => "tutorial",
+        }
+    }
+
+    pub fn is_daily(self) -> bool {
+        matches!(self, GameMode::DailyChallenge { .. })
+    }
+
+    pub fn is_puzzle(self) -> bool {
+        matches!(self, GameMode::Puzzle { .. })
+    }
+
+    /// Whether pieces fall on their own
+    pub fn has_gravity(self) -> bool {
+        !self.is_puzzle() && self != GameMode::Tutorial
+    }
+
+    /// Whether the ghost piece may be drawn at all
+    pub fn shows_ghost(self) -> bool {
+        self != GameMode::Competitive
+    }
+
+    /// Whether the hold box shows the held piece; hold itself still works
+    pub fn shows_hold(self) -> bool {
+        self != GameMode::Competitive
+    }
+
+    /// DAS and ARR in milliseconds when the mode fixes them, over the config's
+    pub fn fixed_handling(self) -> Option<(u64, u64)> {
+        (self == GameMode::Competitive).then_some(COMPETITIVE_HANDLING)
+    }
+
+    /// Apply the mode's own rules over `rules`; done when a game starts, so
+    /// nothing can loosen them mid-game
+    pub fn adjust_rules(self, rules: &mut Rules) {
+        if self == GameMode::Competitive {
+            rules.next_queue = 1;
+            rules.lock_delay = COMPETITIVE_LOCK_DELAY;
+        }
+    }
+}
+
+/// Everything needed to start (and restart) a game
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameSetup {
+    pub mode: GameMode,
+    pub rules: Rules,
+    /// Fixed seed for comparable runs; random per game when unset
+    pub seed: Option<u64>,
+}
+
+/// Game actions after key mapping; everything that changes the board goes through these
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    HardDrop,
+    RotateCw,
+    RotateCcw,
+    /// Swap the current piece with the held one (once per piece)
+    Hold,
+    /// Which rotation key is held, for IRS (sent when it changes)
+    SetIrs(Option<RotationDir>),
+    /// Enter the zone, or leave it early
+    Zone,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RotationDir {
+    Cw,
+    Ccw,
+}
+
+/// Represent each block cell as Option<BlockType>
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum BlockType {
+    I,
+    O,
+    T,
+    S,
+    Z,
+    J,
+    L,
+    /// Rows pushed in by an opponent; never spawned as a piece
+    Garbage,
+}
+
+impl BlockType {
+    /// The seven playable pieces
+    pub fn all() -> &'static [BlockType] {
+        &[
+            BlockType::I,
+            BlockType::O,
+            BlockType::T,
+            BlockType::S,
+            BlockType::Z,
+            BlockType::J,
+            BlockType::L,
+        ]
+    }
+
+    /// Two-column block drawn under the high-contrast theme, so pieces can be
+    /// told apart without color
+    pub fn glyph(self) -> &'static str {
+        match self {
+            BlockType::I => "II",
+            BlockType::O => "OO",
+            BlockType::T => "TT",
+            BlockType::S => "SS",
+            BlockType::Z => "ZZ",
+            BlockType::J => "JJ",
+            BlockType::L => "LL",
+            BlockType::Garbage => "##",
+        }
+    }
+}
+
+/// The 7-bag randomizer: every run of seven pieces is one of each kind,
+/// shuffled. The game itself draws each piece uniformly; this is kept for
+/// comparing the two (see `examples/analyze_distribution.rs`).
+pub struct Bag<R: Rng> {
+    rng: R,
+    pieces: Vec<BlockType>,
+}
+
+impl<R: Rng> Bag<R> {
+    pub fn new(rng: R) -> Self {
+        Bag {
+            rng,
+            pieces: Vec::with_capacity(7),
+        }
+    }
+}
+
+impl<R: Rng> Iterator for Bag<R> {
+    type Item = BlockType;
+
+    fn next(&mut self) -> Option<BlockType> {
+        if self.pieces.is_empty() {
+            self.pieces.extend_from_slice(BlockType::all());
+            self.pieces.shuffle(&mut self.rng);
+        }
+        self.pieces.pop()
+    }
+}
+
+/// A Tetromino has rotations represented as 4x4 bool grids (flattened).
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Tetromino {
+    pub kind: BlockType,
+    pub rotations: Vec<[u8; 16]>, // each rotation is 4x4 grid, row-major; 1 = block, 0 = empty
+}
+
+impl Tetromino {
+    pub fn new(kind: BlockType) -> Self {
+        let rotations = match kind {
+            BlockType::I => vec![
+                // ----  4x4
+                // ....  rotated forms
+                // ####
+                // ....
+                // ....
+                // ....
+                [0, 0, 0, 0, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0],
+                [0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0],
+            ],
+            BlockType::O => vec![[0, 1, 1, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]],
+            BlockType::T => vec![
+                [0, 1, 0, 0, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                [0, 1, 0, 0, 0, 1, 1, 0, 0, 1, 0, 0, 0, 0, 0, 0],
+                [0, 0, 0, 0, 1, 1, 1, 0, 0, 1, 0, 0, 0, 0, 0, 0],
+                [0, 1, 0, 0, 1, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0],
+            ],
+            BlockType::S => vec![
+                [0, 1, 1, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                [0, 1, 0, 0, 0, 1, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0],
+            ],
+            BlockType::Z => vec![
+                [1, 1, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                [0, 0, 1, 0, 0, 1, 1, 0, 0, 1, 0, 0, 0, 0, 0, 0],
+            ],
+            BlockType::J => vec![
+                [1, 0, 0, 0, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                [0, 1, 1, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0],
+                [0, 0, 0, 0, 1, 1, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0],
+                [0, 1, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0],
+            ],
+            BlockType::L => vec![
+                [0, 0, 1, 0, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                [0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0],
+                [0, 0, 0, 0, 1, 1, 1, 0, 1, 0, 0, 0, 0, 0, 0, 0],
+                [1, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0],
+            ],
+            BlockType::Garbage => unreachable!("garbage is never spawned as a piece"),
+        };
+
+        Tetromino { kind, rotations }
+    }
+}
+
+/// Active piece in play with position and rotation index
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ActivePiece {
+    pub tetro: Tetromino,
+    pub rotation: usize,
+    pub x: i32, // position on board (x,y refer to top-left of 4x4)
+    pub y: i32,
+}
+
+impl ActivePiece {
+    pub fn new(kind: BlockType, spawn: SpawnRule) -> Self {
+        let tetro = Tetromino::new(kind);
+        ActivePiece {
+            rotation: spawn.rotation % tetro.rotations.len(),
+            tetro,
+            x: spawn.column,
+            y: spawn.row,
+        }
+    }
+
+    pub fn cells(&self) -> Vec<(i32, i32)> {
+        let grid = &self.tetro.rotations[self.rotation % self.tetro.rotations.len()];
+        let mut out = Vec::new();
+        for by in 0..4 {
+            for bx in 0..4 {
+                if grid[(by * 4 + bx) as usize] != 0 {
+                    out.push((self.x + bx, self.y + by));
+                }
+            }
+        }
+        out
+    }
+
+    fn rotate_cw(&mut self) {
+        self.rotation = (self.rotation + 1) % self.tetro.rotations.len();
+    }
+
+    fn rotate_ccw(&mut self) {
+        if self.rotation == 0 {
+            self.rotation = self.tetro.rotations.len() - 1;
+        } else {
+            self.rotation -= 1;
+        }
+    }
+}
+
+/// Where a T could spin in, as its rotation and 4x4 grid position
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TSpinSlot {
+    pub x: i32,
+    pub y: i32,
+    pub rotation: usize,
+}
+
+impl TSpinSlot {
+    /// A T sitting in the slot
+    pub fn piece(&self) -> ActivePiece {
+        ActivePiece {
+            tetro: Tetromino::new(BlockType::T),
+            rotation: self.rotation,
+            x: self.x,
+            y: self.y,
+        }
+    }
+}
+
+/// Notable things that happened during a step, drained by the frontend each frame
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GameEvent {
+    /// A piece locked into the stack (sent before any clear it causes), with
+    /// its final rotation and 4x4 grid position
+    PieceLocked {
+        kind: BlockType,
+        rotation: usize,
+        x: i32,
+        y: i32,
+    },
+    /// The current piece was hard dropped `rows` rows, to the 4x4 grid
+    /// position it's about to lock at (sent just before its `PieceLocked`)
+    HardDropped {
+        kind: BlockType,
+        rotation: usize,
+        x: i32,
+        y: i32,
+        rows: i32,
+    },
+    LinesCleared {
+        count: usize,
+        points: usize,
+        t_spin: bool,
+    },
+    LevelUp {
+        level: usize,
+    },
+    GoalReached {
+        time: Duration,
+    },
+    /// The score passed one of the `milestones::MILESTONES` thresholds
+    Milestone {
+        threshold: usize,
+    },
+    /// The stack just rose into `DangerLevel::Critical`
+    DangerEntered,
+    GameOver,
+}
+
+/// Game state
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Game {
+    pub board: Board,
+    pub rng: ChaCha12Rng,
+    pub current: ActivePiece,
+    /// Upcoming pieces, front first
+    pub next: VecDeque<BlockType>,
+    /// What's left of `Rules::pieces` beyond the next queue
+    #[serde(default)]
+    pub sequence: VecDeque<BlockType>,
+    pub score: usize,
+    pub level: usize,
+    pub lines_cleared: usize,
+    #[serde(with = "serde_util::instant_elapsed")]
+    pub start_time: Instant,
+    /// Change through `set_paused` so pause time stays out of `elapsed`
+    pub paused: bool,
+    /// When the current pause began; saved like `start_time`, so a game
+    /// saved mid-pause doesn't count the pause as play once resumed
+    #[serde(default, with = "serde_util::option_instant_elapsed")]
+    pub paused_since: Option<Instant>,
+    /// Time spent in finished pauses
+    #[serde(default, with = "serde_util::millis")]
+    pub paused_total: Duration,
+    pub game_over: bool,
+    pub ticks: u64,
+    pub drop_timer: Duration,
+    pub gravity_interval: Duration,
+    pub setup: GameSetup,
+    /// What the piece sequence was dealt from, even when `setup` left it random
+    #[serde(default)]
+    pub seed: u64,
+    /// Sprint time at each `SPRINT_SPLIT_LINES` boundary passed so far
+    #[serde(default, with = "serde_util::millis_list")]
+    pub splits: Vec<Duration>,
+    /// Set once the mode's goal is reached, with the time it took
+    pub goal_time: Option<Duration>,
+    #[serde(default)]
+    pub game_over_reason: Option<GameOverReason>,
+    /// Time spent so far in the post-lock ARE delay; no piece is in play while set
+    pub are_waiting: Option<Duration>,
+    pub irs_held: Option<RotationDir>,
+    /// Move pressed during ARE, waiting for the next piece (`InputBuffer::ApplyLast`)
+    #[serde(default)]
+    pub buffered_action: Option<Action>,
+    /// The current piece spawned pre-rotated by IRS
+    pub irs_fired: bool,
+    /// Clearing locks in a row so far, for the combo bonus
+    #[serde(default)]
+    pub combo: usize,
+    /// The last clear was a tetris or T-spin, so the next such clear is back-to-back
+    #[serde(default)]
+    pub back_to_back: bool,
+    /// Longest combo this game
+    #[serde(default)]
+    pub max_combo: usize,
+    /// Back-to-back bonuses in a row so far
+    #[serde(default)]
+    pub b2b_chain: usize,
+    #[serde(default)]
+    pub max_b2b: usize,
+    /// Most points from a single clear this game
+    #[serde(default)]
+    pub best_clear: usize,
+    /// Four-line clears this game; a zone scores each banked four as one
+    #[serde(default)]
+    pub tetrises: usize,
+    /// The piece's last successful move was a rotation (T-spin detection)
+    #[serde(default)]
+    pub last_move_rotated: bool,
+    #[serde(default)]
+    pub milestones: MilestoneTracker,
+    #[serde(default)]
+    pub hold: Option<BlockType>,
+    /// Hold was already used since the last lock
+    #[serde(default)]
+    pub hold_used_this_drop: bool,
+    /// How long the piece has rested on something, for the lock delay
+    #[serde(default)]
+    pub grounded_for: Duration,
+    /// Rows the current piece has been soft dropped, for the scoring cap
+    #[serde(default)]
+    pub soft_drop_rows: usize,
+    /// Game key presses the game took, for keys-per-piece and APM
+    #[serde(default)]
+    pub inputs: usize,
+    /// Pieces locked onto the board so far
+    #[serde(default)]
+    pub pieces_placed: usize,
+    /// Move and rotate presses spent on the current piece
+    #[serde(default)]
+    pub piece_inputs: usize,
+    /// Pieces placed with more presses than `finesse::min_inputs` needed
+    #[serde(default)]
+    pub finesse_faults: usize,
+    /// Best lines-per-minute seen right after a clear
+    #[serde(default)]
+    pub peak_lpm: f64,
+    /// Best `efficiency` once `EFFICIENCY_MIN_PIECES` were placed
+    #[serde(default)]
+    pub peak_efficiency: f64,
+    /// (time, lines so far) after each recent clear, for the rolling LPM;
+    /// keeps one sample older than `LPM_WINDOW` as the baseline
+    #[serde(skip)]
+    pub lpm_samples: VecDeque<(Instant, usize)>,
+    #[serde(skip)]
+    pub lock_flash: Option<LockFlash>,
+    /// Rows waiting to clear; no piece is in play meanwhile
+    #[serde(default)]
+    pub clear_flash: Option<ClearFlash>,
+    /// `fill_percentage` after each of the last `FILL_HISTORY_LEN` locks, oldest first
+    #[serde(default)]
+    pub fill_history: VecDeque<f64>,
+    /// Holes and the tallest column's height as of the last lock; see `board_coverage`
+    #[serde(default)]
+    pub holes_count: usize,
+    #[serde(default)]
+    pub max_height: usize,
+    /// `board_coverage` after each of the last `COVERAGE_HISTORY_LEN` locks, oldest first
+    #[serde(default)]
+    pub coverage_history: VecDeque<f64>,
+    /// Best `average_coverage` over a full window of locks, 0.0 until there is one
+    #[serde(default)]
+    pub peak_coverage: f64,
+    /// The last `CLEAR_HISTORY_LEN` clears, newest first
+    #[serde(default)]
+    pub clear_history: VecDeque<ClearEvent>,
+    /// The stack each piece spawned into: before the first lock, then after
+    /// every one, for `analysis::BoardAnalyzer`
+    #[serde(default)]
+    pub board_history: Vec<BoardSnapshot>,
+    /// `state_hash` after each lock, when kept; see `record_state_hashes`
+    #[serde(default)]
+    pub state_hashes: Option<Vec<u64>>,
+    /// The final board of the highest scoring game since starting up, kept
+    /// across restarts; practice mode ghosts it behind the board
+    #[serde(default)]
+    pub best_board: Option<Board>,
+    #[serde(default)]
+    pub best_score: usize,
+    /// The stack's danger as of the last lock; see `DangerLevel::settle`
+    #[serde(default)]
+    pub danger: DangerLevel,
+    /// Slowed gravity, with clears banked at the bottom of the board until it ends
+    #[serde(default)]
+    pub zone_active: bool,
+    /// Game time spent in the current zone
+    #[serde(default)]
+    pub zone_elapsed: Duration,
+    /// Cleared rows banked during the zone, kept as the bottom rows of the board
+    #[serde(default)]
+    pub zone_lines: usize,
+    /// Pieces still to lock before the zone can be entered again
+    #[serde(default)]
+    pub zone_charge: usize,
+    #[serde(default)]
+    pub zone_used: bool,
+    pub replay: Option<Replay>,
+    #[serde(skip)]
+    pub events: Vec<GameEvent>,
+    /// `--log-events`; attach with `set_event_log`
+    #[serde(skip)]
+    pub event_log: EventLog,
+    /// Wall time for play time, pauses and the rolling LPM; swap with `set_clock`
+    #[serde(skip)]
+    pub clock: GameClock,
+}
+
+impl Game {
+    pub fn new(mut setup: GameSetup) -> Self {
+        setup.mode.adjust_rules(&mut setup.rules);
+        let seed = setup.seed.unwrap_or_else(random);
+        let mut rng = ChaCha12Rng::seed_from_u64(seed);
+        // pieces dealt before any random ones: a puzzle's whole list, or a
+        // shared position's current piece and queue
+        let mut sequence: VecDeque<BlockType> =
+            match (&setup.rules.pieces, &setup.rules.start_position) {
+                (Some(pieces), _) => pieces.iter().copied().collect(),
+                (None, Some(position)) => position
+                    .current
+                    .map(|piece| piece.kind)
+                    .into_iter()
+                    .chain(position.next.iter().copied())
+                    .collect(),
+                (None, None) => VecDeque::new(),
+            };
+        let first = sequence.pop_front();
+        let next = (0..setup.rules.next_queue.max(1))
+            .filter_map(|_| match &setup.rules.pieces {
+                Some(_) => sequence.pop_front(),
+                None => sequence
+                    .pop_front()
+                    .or_else(|| Some(*BlockType::all().choose(&mut rng).unwrap())),
+            })
+            .collect();
+        let current_kind = if let Some(kind) = first {
+            kind
+        } else if setup.rules.fair_start {
+            // S and Z can't be placed without an overhang on an empty board
+            let fair: Vec<BlockType> = BlockType::all()
+                .iter()
+                .copied()
+                .filter(|kind| !matches!(kind, BlockType::S | BlockType::Z))
+                .collect();
+            *fair.choose(&mut rng).unwrap()
+        } else {
+            *BlockType::all().choose(&mut rng).unwrap()
+        };
+        let level = setup.rules.start_level.max(1);
+        let gravity_interval = Game::interval_for_level(level);
+        let mut game = Game {
+            board: [[None; BOARD_WIDTH]; BOARD_HEIGHT],
+            rng,
+            current: ActivePiece::new(current_kind, setup.rules.spawn_rule(current_kind)),
+            next,
+            sequence,
+            score: 0,
+            level,
+            lines_cleared: 0,
+            start_time: Instant::now(),
+            paused: false,
+            paused_since: None,
+            paused_total: Duration::ZERO,
+            game_over: false,
+            ticks: 0,
+            drop_timer: Duration::ZERO,
+            gravity_interval,
+            replay: Some(Replay::new(seed, setup.mode, setup.rules.clone())),
+            setup,
+            seed,
+            splits: Vec::new(),
+            goal_time: None,
+            game_over_reason: None,
+            are_waiting: None,
+            irs_held: None,
+            buffered_action: None,
+            irs_fired: false,
+            combo: 0,
+            back_to_back: false,
+            max_combo: 0,
+            b2b_chain: 0,
+            max_b2b: 0,
+            best_clear: 0,
+            tetrises: 0,
+            last_move_rotated: false,
+            milestones: MilestoneTracker::default(),
+            hold: None,
+            hold_used_this_drop: false,
+            grounded_for: Duration::ZERO,
+            soft_drop_rows: 0,
+            inputs: 0,
+            pieces_placed: 0,
+            piece_inputs: 0,
+            finesse_faults: 0,
+            peak_lpm: 0.0,
+            peak_efficiency: 0.0,
+            lpm_samples: VecDeque::new(),
+            lock_flash: None,
+            clear_flash: None,
+            fill_history: VecDeque::new(),
+            holes_count: 0,
+            max_height: 0,
+            coverage_history: VecDeque::new(),
+            peak_coverage: 0.0,
+            clear_history: VecDeque::new(),
+            board_history: Vec::new(),
+            state_hashes: None,
+            best_board: None,
+            best_score: 0,
+            danger: DangerLevel::Safe,
+            zone_active: false,
+            zone_elapsed: Duration::ZERO,
+            zone_lines: 0,
+            zone_charge: 0,
+            zone_used: false,
+            events: Vec::new(),
+            event_log: EventLog::default(),
+            clock: GameClock::default(),
+        };
+        if let GameMode::Cheese { rows } = game.setup.mode {
+            game.fill_cheese(rows);
+        }
+        if let Some(board) = &game.setup.rules.start_board {
+            game.board = **board;
+        }
+        if let Some(position) = game.setup.rules.start_position.clone() {
+            game.board = position.board;
+            game.hold = position.hold;
+            if let Some(piece) = position.current {
+                game.current.rotation = piece.rotation;
+                game.current.x = piece.x;
+                game.current.y = piece.y;
+            }
+        }
+        game.measure_stack();
+        game.danger = DangerLevel::of_height(game.max_height);
+        // the first piece spawns into whatever board the game starts with, so
+        // it gets the same nudge and block-out check as later ones; a shared
+        // position's piece stays where it was
+        if game
+            .setup
+            .rules
+            .start_position
+            .as_ref()
+            .is_none_or(|position| position.current.is_none())
+        {
+            game.check_block_out();
+        }
+        game
+    }
+
+    /// Place or remove a settled block; false if (x, y) is off the board
+    pub fn set_cell(&mut self, x: usize, y: usize, cell: Option<BlockType>) -> bool {
+        match self.board.get_mut(y).and_then(|row| row.get_mut(x)) {
+            Some(slot) => {
+                *slot = cell;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Fill the bottom `rows` rows with garbage, one hole per row in a column
+    /// that differs from the row below; placement follows the game seed
+    fn fill_cheese(&mut self, rows: usize) {
+        let rows = rows.min(BOARD_HEIGHT - 4);
+        let mut last_gap = None;
+        for y in (BOARD_HEIGHT - rows..BOARD_HEIGHT).rev() {
+            let gap = loop {
+                let gap = self.rng.gen_range(0..BOARD_WIDTH);
+                if Some(gap) != last_gap {
+                    break gap;
+                }
+            };
+            last_gap = Some(gap);
+            self.board[y] = [Some(BlockType::Garbage); BOARD_WIDTH];
+            self.board[y][gap] = None;
+        }
+    }
+
+    /// Height of each column's highest block above the floor (0 when empty)
+    pub fn column_heights(&self) -> [usize; BOARD_WIDTH] {
+        let mut heights = [0; BOARD_WIDTH];
+        for (x, height) in heights.iter_mut().enumerate() {
+            if let Some(top) = (0..BOARD_HEIGHT).find(|&y| self.board[y][x].is_some()) {
+                *height = BOARD_HEIGHT - top;
+            }
+        }
+        heights
+    }
+
+    /// Sprint finish time if the pace so far holds
+    pub fn projected_sprint_time(&self) -> Option<Duration> {
+        if self.lines_cleared == 0 {
+            return None;
+        }
+        Some(
+            self.elapsed()
+                .mul_f64(SPRINT_LINES as f64 / self.lines_cleared as f64),
+        )
+    }
+
+    /// Lines cleared since the current level's threshold; 0 while a higher
+    /// starting level is still ahead of the line count
+    pub fn lines_into_level(&self) -> usize {
+        self.lines_cleared
+            .saturating_sub((self.level - 1) * LINES_PER_LEVEL)
+    }
+
+    /// Lines still to clear before the next level
+    pub fn lines_needed_for_next(&self) -> usize {
+        (self.level * LINES_PER_LEVEL).saturating_sub(self.lines_cleared)
+    }
+
+    /// Share of board cells holding a block, 0.0..=100.0
+    pub fn fill_percentage(&self) -> f64 {
+        let filled = self.board.iter().flatten().filter(|c| c.is_some()).count();
+        filled as f64 / (BOARD_WIDTH * BOARD_HEIGHT) as f64 * 100.0
+    }
+
+    pub fn danger_level(&self) -> DangerLevel {
+        self.danger
+    }
+
+    /// Empty cells with a block somewhere above them
+    pub fn holes(&self) -> usize {
+        let heights = self.column_heights();
+        (0..BOARD_WIDTH)
+            .map(|x| {
+                let top = BOARD_HEIGHT - heights[x];
+                (top..BOARD_HEIGHT)
+                    .filter(|&y| self.board[y][x].is_none())
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Share of the stack, up to its tallest column, that isn't holes: 1.0
+    /// for a clean stack (or none at all), down towards 0.0 for a holey one
+    pub fn board_coverage(&self) -> f64 {
+        if self.max_height == 0 {
+            return 1.0;
+        }
+        1.0 - self.holes_count as f64 / (self.max_height * BOARD_WIDTH) as f64
+    }
+
+    /// Mean of `coverage_history`, once a piece has locked
+    pub fn average_coverage(&self) -> Option<f64> {
+        if self.coverage_history.is_empty() {
+            return None;
+        }
+        Some(self.coverage_history.iter().sum::<f64>() / self.coverage_history.len() as f64)
+    }
+
+    /// Refresh `holes_count` and `max_height` from the board
+    fn measure_stack(&mut self) {
+        self.holes_count = self.holes();
+        self.max_height = self.column_heights().into_iter().max().unwrap_or(0);
+    }
+
+    /// Measure the board as it is now and settle `danger` against it
+    fn update_stack(&mut self) {
+        self.measure_stack();
+        let danger = self.danger.settle(self.max_height);
+        if danger == DangerLevel::Critical && self.danger != DangerLevel::Critical {
+            self.events.push(GameEvent::DangerEntered);
+        }
+        self.danger = danger;
+    }
+
+    /// Whether (x, y) belongs to the piece that just locked and is still highlighted
+    pub fn flashing(&self, x: usize, y: usize) -> bool {
+        self.lock_flash.as_ref().is_some_and(|flash| {
+            flash.is_active_at(self.clock.now()) && flash.cells.contains(&(x, y))
+        })
+    }
+
+    /// Rows that still contain garbage
+    pub fn garbage_rows_left(&self) -> usize {
+        self.board
+            .iter()
+            .filter(|row| row.contains(&Some(BlockType::Garbage)))
+            .count()
+    }
+
+    /// End the game as a win, recording how long it took
+    fn reach_goal(&mut self) {
+        let time = self.elapsed();
+        self.goal_time = Some(time);
+        self.game_over = true;
+        self.game_over_reason = Some(GameOverReason::GoalReached);
+        self.events.push(GameEvent::GoalReached { time });
+        self.log_event(LoggedEvent::GameOver {
+            reason: GameOverReason::GoalReached,
+        });
+        let _ = self.event_log.flush();
+    }
+
+    /// End the game for any reason other than reaching the goal
+    fn end_game(&mut self, reason: GameOverReason) {
+        self.game_over = true;
+        self.game_over_reason = Some(reason);
+        if self.score > self.best_score {
+            self.best_score = self.score;
+            self.best_board = Some(self.board);
+        }
+        self.events.push(GameEvent::GameOver);
+        self.log_event(LoggedEvent::GameOver { reason });
+        let _ = self.event_log.flush();
+    }
+
+    /// Time left on the ultra clock (game time, so pauses don't count)
+    pub fn ultra_remaining(&self) -> Duration {
+        ULTRA_TIME.saturating_sub(TICK * self.ticks as u32)
+    }
+
+    fn interval_for_level(level: usize) -> Duration {
+        // simple formula: base 700ms, reduce by level (cap at 50ms)
+        let base_ms = 700i32;
+        let ms = base_ms - ((level as i32 - 1) * 50);
+        let ms = max(ms, 60);
+        Duration::from_millis(ms as u64)
+    }
+
+    fn spawn_next(&mut self) {
+        // only a fixed piece list runs out
+        let Some(kind) = self.next.pop_front() else {
+            self.end_game(GameOverReason::OutOfPieces);
+            return;
+        };
+        self.current = ActivePiece::new(kind, self.setup.rules.spawn_rule(kind));
+        self.log_event(LoggedEvent::Spawn { kind });
+        let upcoming = match self.setup.rules.pieces {
+            Some(_) => self.sequence.pop_front(),
+            None => self
+                .sequence
+                .pop_front()
+                .or_else(|| Some(*BlockType::all().choose(&mut self.rng).unwrap())),
+        };
+        self.next.extend(upcoming);
+        self.grounded_for = Duration::ZERO;
+        self.soft_drop_rows = 0;
+        self.piece_inputs = 0;
+        self.irs_fired = false;
+        self.last_move_rotated = false;
+        // IRS: a held rotation key pre-rotates the piece for free, as long as
+        // the rotated spawn is still clear
+        if self.setup.rules.irs_enabled
+            && let Some(dir) = self.irs_held
+        {
+            let mut rotated = self.current.clone();
+            match dir {
+                RotationDir::Cw => rotated.rotate_cw(),
+                RotationDir::Ccw => rotated.rotate_ccw(),
+            }
+            if !self.check_collision(&rotated, 0, 0) {
+                self.current = rotated;
+                self.irs_fired = true;
+            }
+        }
+        self.check_block_out();
+    }
+
+    /// A freshly spawned piece that overlaps the stack gets one row of grace
+    /// upwards; if that's blocked too the game is over. So is a piece that
+    /// spawns with no move at all.
+    fn check_block_out(&mut self) {
+        if self.check_collision(&self.current, 0, 0) {
+            if self.check_collision(&self.current, 0, -1) {
+                self.end_game(GameOverReason::BlockOut);
+                return;
+            }
+            self.current.y -= 1;
+        }
+        if self.is_soft_locked() {
+            self.end_game(GameOverReason::TopOut);
+        }
+    }
+
+    /// Put the current piece aside, bringing back the held one (or the next
+    /// piece if nothing is held yet); allowed once until the next lock
+    fn hold(&mut self) {
+        // a puzzle's piece list is the whole challenge
+        if self.hold_used_this_drop || self.setup.mode.is_daily() || self.setup.mode.is_puzzle() {
+            return;
+        }
+        // a fixed piece list that's run out leaves nothing to swap in
+        let Some(incoming) = self.hold.or_else(|| self.next.front().copied()) else {
+            return;
+        };
+        // the swap happens whole or not at all: a piece with no room to spawn
+        // tops the game out with the hold slot and current piece as they were
+        if self.spawn_placement(incoming).is_none() {
+            self.end_game(GameOverReason::BlockOut);
+            return;
+        }
+        let kind = self.current.tetro.kind;
+        let held = self.hold.replace(kind);
+        self.log_event(LoggedEvent::Hold {
+            kind,
+            swapped_in: held,
+        });
+        match held {
+            Some(held) => {
+                self.current = ActivePiece::new(held, self.setup.rules.spawn_rule(held));
+                self.last_move_rotated = false;
+                self.grounded_for = Duration::ZERO;
+                self.soft_drop_rows = 0;
+                self.piece_inputs = 0;
+                self.drop_timer = Duration::ZERO;
+                self.check_block_out();
+            }
+            None => self.spawn_next(),
+        }
+        self.hold_used_this_drop = true;
+    }
+
+    /// Where the piece a hold would bring in (the held one, or else the next)
+    /// would land, without holding; `None` while hold can't be used
+    pub fn hold_preview(&self) -> Option<ActivePiece> {
+        if !self.piece_active()
+            || self.hold_used_this_drop
+            || !self.setup.mode.shows_hold()
+            || self.setup.mode.is_daily()
+            || self.setup.mode.is_puzzle()
+        {
+            return None;
+        }
+        let kind = self.hold.or_else(|| self.next.front().copied())?;
+        let piece = self.spawn_placement(kind)?;
+        Some(self.landing(&piece))
+    }
+
+    /// A new `kind` where it would spawn, nudged up a row as `check_block_out`
+    /// would if the stack is in the way; `None` if that's blocked too
+    fn spawn_placement(&self, kind: BlockType) -> Option<ActivePiece> {
+        let mut piece = ActivePiece::new(kind, self.setup.rules.spawn_rule(kind));
+        if !self.fits(&piece) {
+            piece.y -= 1;
+            if !self.fits(&piece) {
+                return None;
+            }
+        }
+        Some(piece)
+    }
+
+    fn check_collision(&self, piece: &ActivePiece, dx: i32, dy: i32) -> bool {
+        for (x, y) in piece.cells() {
+            let nx = x + dx;
+            let ny = y + dy;
+            if nx < 0 || nx >= BOARD_WIDTH as i32 {
+                return true;
+            }
+            if ny >= BOARD_HEIGHT as i32 {
+                return true;
+            }
+            if ny >= 0 && self.board[ny as usize][nx as usize].is_some() {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn lock_piece(&mut self) {
+        let kind = self.current.tetro.kind;
+        self.hold_used_this_drop = false;
+        self.pieces_placed += 1;
+        self.record_efficiency();
+        // soft dropping opens up tucks and spins the empty-board minimum doesn't know about
+        if self.soft_drop_rows == 0
+            && let Some(min) = finesse::min_inputs(
+                kind,
+                self.setup.rules.spawn_rule(kind),
+                self.current.rotation,
+                self.current.x,
+            )
+            && self.piece_inputs > min
+        {
+            self.finesse_faults += 1;
+        }
+        let t_spin = self.is_t_spin();
+        let cells = self.current.cells();
+        let above_board = cells.iter().all(|&(_, y)| y < 0);
+        if self.board_history.is_empty() {
+            self.board_history.push(BoardSnapshot::of(self));
+        }
+        let mut flash = Vec::new();
+        for &(x, y) in &cells {
+            if y >= 0 && y < BOARD_HEIGHT as i32 && x >= 0 && x < BOARD_WIDTH as i32 {
+                self.board[y as usize][x as usize] = Some(kind);
+                flash.push((x as usize, y as usize));
+            }
+        }
+        self.lock_flash = Some(LockFlash {
+            cells: flash,
+            at: self.clock.now(),
+        });
+        self.log_event(LoggedEvent::Lock { kind, cells });
+        self.events.push(GameEvent::PieceLocked {
+            kind,
+            rotation: self.current.rotation,
+            x: self.current.x,
+            y: self.current.y,
+        });
+        if !self.zone_active {
+            self.zone_charge = self.zone_charge.saturating_sub(1);
+        }
+        if !self.zone_active
+            && !self.setup.rules.clear_flash.is_zero()
+            && let rows = self.full_rows()
+            && !rows.is_empty()
+        {
+            self.clear_flash = Some(ClearFlash {
+                rows,
+                waited: Duration::ZERO,
+                t_spin,
+                above_board,
+            });
+            return;
+        }
+        self.finish_lock(t_spin, above_board);
+    }
+
+    /// The rest of a lock, once any flash is over: the clear, the stack's
+    /// history, then the next piece
+    fn finish_lock(&mut self, t_spin: bool, above_board: bool) {
+        self.clear_full_lines(t_spin);
+        self.board_history.push(BoardSnapshot::of(self));
+        self.update_stack();
+        if self.state_hashes.is_some() {
+            let hash = self.state_hash();
+            self.state_hashes.get_or_insert_default().push(hash);
+        }
+        self.fill_history.push_back(self.fill_percentage());
+        if self.fill_history.len() > FILL_HISTORY_LEN {
+            self.fill_history.pop_front();
+        }
+        self.coverage_history.push_back(self.board_coverage());
+        if self.coverage_history.len() > COVERAGE_HISTORY_LEN {
+            self.coverage_history.pop_front();
+        }
+        if self.coverage_history.len() == COVERAGE_HISTORY_LEN
+            && let Some(average) = self.average_coverage()
+        {
+            self.peak_coverage = self.peak_coverage.max(average);
+        }
+        if self.game_over {
+            return;
+        }
+        if above_board {
+            self.end_game(GameOverReason::LockOut);
+            return;
+        }
+        if self.setup.rules.are_delay.is_zero() {
+            self.spawn_next();
+        } else {
+            self.are_waiting = Some(Duration::ZERO);
+        }
+        self.drop_timer = Duration::ZERO;
+    }
+
+    /// Three-corner rule: a T that got into place by rotating, with at least
+    /// three of the cells diagonal to its centre blocked (walls and floor count)
+    fn is_t_spin(&self) -> bool {
+        self.last_move_rotated && self.in_t_slot(&self.current)
+    }
+
+    /// `piece` is a T with at least three of its centre's diagonal cells blocked
+    pub fn in_t_slot(&self, piece: &ActivePiece) -> bool {
+        if piece.tetro.kind != BlockType::T {
+            return false;
+        }
+        // every T rotation has its centre at (1, 1) of the 4x4 grid
+        let (cx, cy) = (piece.x + 1, piece.y + 1);
+        let blocked = [(-1, -1), (1, -1), (-1, 1), (1, 1)]
+            .iter()
+            .filter(|(dx, dy)| {
+                let (x, y) = (cx + dx, cy + dy);
+                if x < 0 || x >= BOARD_WIDTH as i32 || y >= BOARD_HEIGHT as i32 {
+                    return true;
+                }
+                y >= 0 && self.board[y as usize][x as usize].is_some()
+            })
+            .count();
+        blocked >= 3
+    }
+
+    /// Resting spots a T can only reach by rotating, with three corners
+    /// blocked and at least one line cleared by locking there: places to set
+    /// up a T-spin. The board alone decides; the current piece doesn't matter.
+    pub fn find_tspin_slots(&self) -> Vec<TSpinSlot> {
+        let mut slots = Vec::new();
+        let rotations = Tetromino::new(BlockType::T).rotations.len();
+        for rotation in 0..rotations {
+            for y in -1..BOARD_HEIGHT as i32 {
+                for x in -1..BOARD_WIDTH as i32 {
+                    let slot = TSpinSlot { x, y, rotation };
+                    let piece = slot.piece();
+                    if !self.fits(&piece)
+                        || !self.check_collision(&piece, 0, 1)
+                        || !self.in_t_slot(&piece)
+                        || self.clears_with(&piece) == 0
+                    {
+                        continue;
+                    }
+                    // a straight drop lands here, so there's nothing to spin into
+                    let mut above = piece.clone();
+                    above.y = -4;
+                    if self.landing(&above).y != y {
+                        slots.push(slot);
+                    }
+                }
+            }
+        }
+        slots
+    }
+
+    /// Rows that would be full with `piece` locked where it is
+    fn clears_with(&self, piece: &ActivePiece) -> usize {
+        let cells = piece.cells();
+        let mut rows: Vec<i32> = cells.iter().map(|&(_, y)| y).collect();
+        rows.sort_unstable();
+        rows.dedup();
+        rows.into_iter()
+            .filter(|&y| {
+                y >= 0
+                    && (0..BOARD_WIDTH as i32).all(|x| {
+                        cells.contains(&(x, y)) || self.board[y as usize][x as usize].is_some()
+                    })
+            })
+            .count()
+    }
+
+    /// Whether `current` is in play (false while rows flash and during ARE)
+    pub fn piece_active(&self) -> bool {
+        self.clear_flash.is_none() && self.are_waiting.is_none()
+    }
+
+    /// Rows about to clear, and whether they're lit (the first half of the
+    /// flash) rather than blanked
+    pub fn clearing_rows(&self) -> Option<(&[usize], bool)> {
+        self.clear_flash.as_ref().map(|flash| {
+            let lit = flash.waited * 2 < flash.length(&self.setup.rules);
+            (flash.rows.as_slice(), lit)
+        })
+    }
+
+    /// Where the current piece would land if hard dropped
+    pub fn ghost_piece(&self) -> ActivePiece {
+        self.landing(&self.current)
+    }
+
+    /// `piece` moved straight down until it rests on something
+    pub fn landing(&self, piece: &ActivePiece) -> ActivePiece {
+        let mut landed = piece.clone();
+        while !self.check_collision(&landed, 0, 1) {
+            landed.y += 1;
+        }
+        landed
+    }
+
+    /// `piece` is inside the walls and overlaps no settled block
+    pub fn fits(&self, piece: &ActivePiece) -> bool {
+        !self.check_collision(piece, 0, 0)
+    }
+
+    fn hard_drop(&mut self) {
+        let from = self.current.y;
+        while !self.check_collision(&self.current, 0, 1) {
+            self.current.y += 1;
+            self.add_score(self.setup.rules.scoring.hard_drop);
+            self.last_move_rotated = false;
+        }
+        let piece = &self.current;
+        self.events.push(GameEvent::HardDropped {
+            kind: piece.tetro.kind,
+            rotation: piece.rotation,
+            x: piece.x,
+            y: piece.y,
+            rows: piece.y - from,
+        });
+        self.lock_piece();
+    }
+
+    /// Advance the simulation by one tick
+    pub fn step(&mut self) {
+        if self.paused || self.game_over {
+            return;
+        }
+        self.ticks += 1;
+        if self.setup.mode == GameMode::Ultra && self.ultra_remaining().is_zero() {
+            self.end_game(GameOverReason::TimeExpired);
+            return;
+        }
+        if self.zone_active {
+            self.zone_elapsed += TICK;
+            if self.zone_elapsed >= self.setup.rules.zone_duration {
+                self.end_zone();
+                if self.game_over {
+                    return;
+                }
+            }
+        }
+        if let Some(flash) = &mut self.clear_flash {
+            flash.waited += TICK;
+            if flash.waited < flash.length(&self.setup.rules) {
+                return;
+            }
+            let flash = self.clear_flash.take().expect("flashing");
+            self.finish_lock(flash.t_spin, flash.above_board);
+            self.apply_buffered();
+            return;
+        }
+        if let Some(waited) = self.are_waiting {
+            let waited = waited + TICK;
+            if waited >= self.setup.rules.are_delay {
+                self.are_waiting = None;
+                self.spawn_next();
+                self.apply_buffered();
+            } else {
+                self.are_waiting = Some(waited);
+            }
+            return;
+        }
+        if !self.setup.rules.lock_delay.is_zero() {
+            if self.check_collision(&self.current, 0, 1) {
+                self.grounded_for += TICK;
+                if self.grounded_for >= self.setup.rules.lock_delay {
+                    self.lock_piece();
+                }
+                return;
+            }
+            self.grounded_for = Duration::ZERO;
+        }
+        if !self.setup.mode.has_gravity() {
+            // pieces only move when the player moves them
+            return;
+        }
+        self.drop_timer += TICK;
+        let interval = if self.zone_active {
+            self.gravity_interval * ZONE_SLOWDOWN
+        } else {
+            self.gravity_interval
+        };
+        if self.drop_timer >= interval {
+            if !self.check_collision(&self.current, 0, 1) {
+                self.current.y += 1;
+                self.last_move_rotated = false;
+            } else {
+                // unlock to board
+                self.lock_piece();
+            }
+            self.drop_timer = Duration::ZERO;
+        }
+    }
+
+    /// Apply a player (or bot) action; this is the single entry point that gets recorded.
+    ///
+    /// Returns whether the game took the action; a move blocked by a wall
+    /// still counts as taken.
+    pub fn apply(&mut self, action: Action) -> bool {
+        if self.paused || self.game_over {
+            return false;
+        }
+        // holding a rotation key matters most while waiting for the next piece
+        let accepted = self.piece_active() || matches!(action, Action::SetIrs(_));
+        let buffered = !accepted
+            && self.setup.rules.input_buffer == InputBuffer::ApplyLast
+            && matches!(
+                action,
+                Action::MoveLeft
+                    | Action::MoveRight
+                    | Action::RotateCw
+                    | Action::RotateCcw
+                    | Action::Hold
+            );
+        if !accepted && !buffered {
+            return false;
+        }
+        if let Some(replay) = &mut self.replay {
+            replay.record(self.ticks, action);
+        }
+        if buffered {
+            self.buffered_action = Some(action);
+        } else {
+            self.perform(action);
+        }
+        true
+    }
+
+    /// Apply an action from a key press, counting it towards KPP and APM
+    /// (and finesse, for moves and rotations) when the game takes it
+    pub fn press(&mut self, action: Action) {
+        let in_play = self.piece_active();
+        if !self.apply(action) || matches!(action, Action::SetIrs(_)) {
+            return;
+        }
+        self.inputs += 1;
+        if in_play
+            && matches!(
+                action,
+                Action::MoveLeft | Action::MoveRight | Action::RotateCw | Action::RotateCcw
+            )
+        {
+            self.piece_inputs += 1;
+        }
+    }
+
+    /// A move kept from while no piece was in play, given to the one just spawned
+    fn apply_buffered(&mut self) {
+        if self.piece_active()
+            && !self.game_over
+            && let Some(action) = self.buffered_action.take()
+        {
+            self.perform(action);
+        }
+    }
+
+    fn perform(&mut self, action: Action) {
+        match action {
+            Action::MoveLeft => self.move_left(),
+            Action::MoveRight => self.move_right(),
+            Action::SoftDrop => {
+                if self.move_down() {
+                    self.drop_timer = Duration::ZERO; // reset gravity timer after manual down
+                }
+            }
+            Action::HardDrop => self.hard_drop(),
+            Action::RotateCw => self.rotate_cw(),
+            Action::RotateCcw => self.rotate_ccw(),
+            Action::Hold => self.hold(),
+            Action::SetIrs(held) => self.irs_held = held,
+            Action::Zone if self.zone_active => self.end_zone(),
+            Action::Zone if self.zone_ready() => {
+                self.zone_active = true;
+                self.zone_used = true;
+                self.zone_elapsed = Duration::ZERO;
+            }
+            Action::Zone => {}
+        }
+    }
+
+    /// The zone has recharged, or without recharging has never been used
+    pub fn zone_ready(&self) -> bool {
+        !self.zone_active
+            && self.zone_charge == 0
+            && !(self.zone_used && self.setup.rules.zone_recharge == 0)
+    }
+
+    /// Leave the zone, scoring every banked line at once: a tetris for each
+    /// group of four with combo and back-to-back stacking, then the remainder
+    fn end_zone(&mut self) {
+        self.zone_active = false;
+        self.zone_charge = self.setup.rules.zone_recharge;
+        let lines = std::mem::take(&mut self.zone_lines);
+        if lines == 0 {
+            return;
+        }
+        self.board.rotate_right(lines);
+        for row in &mut self.board[..lines] {
+            *row = [None; BOARD_WIDTH];
+        }
+        self.update_stack();
+        // the stack dropped; don't leave the piece inside an overhang
+        while self.check_collision(&self.current, 0, 0) && self.current.y > -4 {
+            self.current.y -= 1;
+        }
+        let mut points = 0;
+        for _ in 0..lines / 4 {
+            points += self.clear_points(4, false);
+        }
+        let rest = lines % 4;
+        if rest > 0 {
+            points += self.clear_points(rest, false);
+        }
+        self.award_lines(lines, points, false);
+    }
+
+    /// Stop recording and hand back the replay (only once per game)
+    fn finish_replay(&mut self) -> Option<Replay> {
+        let mut replay = self.replay.take()?;
+        replay.final_score = self.score;
+        replay.end_tick = self.ticks;
+        replay.state_hashes = self.state_hashes.clone();
+        Some(replay)
+    }
+
+    fn move_left(&mut self) {
+        if !self.check_collision(&self.current, -1, 0) {
+            self.current.x -= 1;
+            self.last_move_rotated = false;
+        }
+    }
+
+    fn move_right(&mut self) {
+        if !self.check_collision(&self.current, 1, 0) {
+            self.current.x += 1;
+            self.last_move_rotated = false;
+        }
+    }
+
+    /// Soft drop one row, returning false if the piece was already resting.
+    /// A resting piece locks straight away with `rules.soft_drop_locks`, and
+    /// is otherwise left to lock delay or gravity. Points are per row actually
+    /// descended, at most `BOARD_HEIGHT` per piece so kicking back up and
+    /// dropping again can't farm them.
+    fn move_down(&mut self) -> bool {
+        if !self.check_collision(&self.current, 0, 1) {
+            self.current.y += 1;
+            self.last_move_rotated = false;
+            if self.soft_drop_rows < BOARD_HEIGHT {
+                self.soft_drop_rows += 1;
+                self.add_score(self.setup.rules.scoring.soft_drop);
+            }
+            true
+        } else {
+            // lock if can't move down, unless that's left to lock delay/gravity
+            if self.setup.rules.soft_drop_locks {
+                self.lock_piece();
+            }
+            false
+        }
+    }
+
+    /// The first wall-kick offset that fits `rotated`, if any does
+    fn kick_for(&self, rotated: &ActivePiece) -> Option<(i32, i32)> {
+        KICKS
+            .into_iter()
+            .find(|&(dx, dy)| !self.check_collision(rotated, dx, dy))
+    }
+
+    fn rotate_cw(&mut self) {
+        let mut test = self.current.clone();
+        test.rotate_cw();
+        if let Some((dx, dy)) = self.kick_for(&test) {
+            self.current = test;
+            self.current.x += dx;
+            self.current.y += dy;
+            self.last_move_rotated = true;
+        }
+    }
+
+    fn rotate_ccw(&mut self) {
+        let mut test = self.current.clone();
+        test.rotate_ccw();
+        if let Some((dx, dy)) = self.kick_for(&test) {
+            self.current = test;
+            self.current.x += dx;
+            self.current.y += dy;
+            self.last_move_rotated = true;
+        }
+    }
+
+    /// Whether the current piece can't shift, drop a row or rotate (kicks
+    /// included); all that's left is waiting for it to lock
+    pub fn is_soft_locked(&self) -> bool {
+        if !self.piece_active() || self.game_over {
+            return false;
+        }
+        let piece = &self.current;
+        if [(-1, 0), (1, 0), (0, 1)]
+            .into_iter()
+            .any(|(dx, dy)| !self.check_collision(piece, dx, dy))
+        {
+            return false;
+        }
+        let mut cw = piece.clone();
+        cw.rotate_cw();
+        let mut ccw = piece.clone();
+        ccw.rotate_ccw();
+        self.kick_for(&cw).is_none() && self.kick_for(&ccw).is_none()
+    }
+
+    /// Every score change goes through here so milestones are noticed
+    fn add_score(&mut self, points: usize) {
+        self.score += points;
+        for threshold in self.milestones.check(self.score) {
+            self.events.push(GameEvent::Milestone { threshold });
+        }
+    }
+
+    /// Full rows of the stack, top first; the bottom `zone_lines` rows are
+    /// banked zone clears, not part of it
+    fn full_rows(&self) -> Vec<usize> {
+        (0..BOARD_HEIGHT - self.zone_lines)
+            .filter(|&y| self.board[y].iter().all(Option::is_some))
+            .collect()
+    }
+
+    fn clear_full_lines(&mut self, t_spin: bool) {
+        let stack_rows = BOARD_HEIGHT - self.zone_lines;
+        let full_rows = self.full_rows();
+        // cleared cells stop flashing; the rest follow their rows, which move
+        // down past cleared rows, or up past rows sinking into the zone
+        if let Some(flash) = &mut self.lock_flash {
+            flash.cells.retain(|(_, y)| !full_rows.contains(y));
+            for (_, y) in &mut flash.cells {
+                if self.zone_active {
+                    *y -= full_rows.iter().filter(|&&full| full < *y).count();
+                } else {
+                    *y += full_rows.iter().filter(|&&full| full > *y).count();
+                }
+            }
+        }
+        let (full, kept): (Vec<&[Option<BlockType>; BOARD_WIDTH]>, Vec<_>) = self.board
+            [..stack_rows]
+            .iter()
+            .partition(|row| row.iter().all(Option::is_some));
+        let removed = full.len();
+
+        if removed == 0 {
+            if !self.zone_active {
+                self.combo = 0;
+            }
+            return;
+        }
+
+        let mut new_board = [[None; BOARD_WIDTH]; BOARD_HEIGHT];
+        if self.zone_active {
+            // full rows sink below the stack and wait there for the zone to end
+            for (y, row) in kept.into_iter().chain(full).enumerate() {
+                new_board[y] = *row;
+            }
+            new_board[stack_rows..].copy_from_slice(&self.board[stack_rows..]);
+            self.board = new_board;
+            self.zone_lines += removed;
+            // banked rows score when the zone ends
+            self.log_event(LoggedEvent::Clear {
+                rows: full_rows,
+                points: 0,
+            });
+            return;
+        }
+        let removed = match self.setup.rules.clear_gravity {
+            ClearGravity::Naive => {
+                for (y, row) in kept.into_iter().enumerate() {
+                    new_board[removed + y] = *row;
+                }
+                self.board = new_board;
+                removed
+            }
+            ClearGravity::Sticky => {
+                // blocks no longer stay with their rows, so there's no following them
+                self.lock_flash = None;
+                let mut removed = 0;
+                loop {
+                    let rows = self.full_rows();
+                    if rows.is_empty() {
+                        break removed;
+                    }
+                    for &y in &rows {
+                        self.board[y] = [None; BOARD_WIDTH];
+                    }
+                    removed += rows.len();
+                    sticky_fall(&mut self.board);
+                }
+            }
+        };
+        let points = self.clear_points(removed, t_spin);
+        self.log_event(LoggedEvent::Clear {
+            rows: full_rows,
+            points,
+        });
+        self.award_lines(removed, points, t_spin);
+    }
+
+    /// Points for a clear, advancing the combo and back-to-back state
+    fn clear_points(&mut self, lines: usize, t_spin: bool) -> usize {
+        let difficult = lines >= 4 || t_spin;
+        let clear = LineClear {
+            lines,
+            t_spin,
+            combo: self.combo,
+            back_to_back: difficult && self.back_to_back,
+        };
+        let mut points = self.setup.rules.scoring.clear_points(&clear, self.level);
+        if self.setup.mode == GameMode::Competitive {
+            let (num, den) = COMPETITIVE_MULTIPLIER;
+            points = points * num / den;
+        }
+        if lines >= 4 {
+            self.tetrises += 1;
+        }
+        self.combo += 1;
+        self.max_combo = self.max_combo.max(self.combo);
+        self.back_to_back = difficult;
+        self.b2b_chain = if clear.back_to_back {
+            self.b2b_chain + 1
+        } else {
+            0
+        };
+        self.max_b2b = self.max_b2b.max(self.b2b_chain);
+        points
+    }
+
+    /// Count cleared lines towards the score, level and the mode's goal
+    fn award_lines(&mut self, lines: usize, points: usize, t_spin: bool) {
+        self.lines_cleared += lines;
+        if self.setup.mode == GameMode::Sprint {
+            // a multi-line clear can pass a boundary; each one passed gets the same time
+            let passed = self.lines_cleared.min(SPRINT_LINES) / SPRINT_SPLIT_LINES;
+            while self.splits.len() < passed {
+                self.splits.push(self.elapsed());
+            }
+        }
+        self.record_lpm();
+        self.record_efficiency();
+        self.clear_history.push_front(ClearEvent {
+            lines,
+            t_spin,
+            back_to_back: self.b2b_chain > 0,
+            perfect_clear: self.board.iter().flatten().all(Option::is_none),
+            combo: self.combo.saturating_sub(1),
+            points,
+            elapsed: self.elapsed(),
+        });
+        self.clear_history.truncate(CLEAR_HISTORY_LEN);
+        self.events.push(GameEvent::LinesCleared {
+            count: lines,
+            points,
+            t_spin,
+        });
+        self.add_score(points);
+        self.best_clear = self.best_clear.max(points);
+        // level up every 10 lines, never dropping below the starting level
+        let new_level = max(
+            (self.lines_cleared / LINES_PER_LEVEL) + 1,
+            self.setup.rules.start_level,
+        );
+        if new_level != self.level {
+            self.level = new_level;
+            self.gravity_interval = Game::interval_for_level(self.level);
+            self.events.push(GameEvent::LevelUp { level: self.level });
+            self.log_event(LoggedEvent::LevelUp { level: self.level });
+        }
+
+        let goal = match self.setup.mode {
+            GameMode::Cheese { .. } => self.garbage_rows_left() == 0,
+            GameMode::Sprint => self.lines_cleared >= SPRINT_LINES,
+            GameMode::Puzzle { .. } => self.board.iter().flatten().all(Option::is_none),
+            GameMode::Marathon
+            | GameMode::Ultra
+            | GameMode::DailyChallenge { .. }
+            | GameMode::Competitive
+            | GameMode::Tutorial => false,
+        };
+        if goal {
+            self.reach_goal();
+        }
+    }
+
+    /// Swap in `board`, keeping score, level and the pieces. Refused while no
+    /// piece is in play, or when the current piece or the next spawn would be
+    /// stuck in the new stack, which would end the game on the spot.
+    pub fn paste_board(&mut self, board: Board) -> Result<(), String> {
+        if self.game_over || !self.piece_active() {
+            return Err("no piece in play".to_string());
+        }
+        let old = std::mem::replace(&mut self.board, board);
+        let spawn = self
+            .next
+            .front()
+            .map(|&kind| ActivePiece::new(kind, self.setup.rules.spawn_rule(kind)));
+        let blocked = self.check_collision(&self.current, 0, 0)
+            || spawn.is_some_and(|piece| {
+                self.check_collision(&piece, 0, 0) && self.check_collision(&piece, 0, -1)
+            });
+        if blocked {
+            self.board = old;
+            return Err("the pieces would have no room".to_string());
+        }
+        // a replay can't reproduce a board from outside the game
+        self.replay = None;
+        self.lock_flash = None;
+        self.update_stack();
+        Ok(())
+    }
+
+    /// Push `rows` garbage lines in from the bottom, each open at column `gap`
+    pub fn add_garbage(&mut self, rows: usize, gap: usize) {
+        if self.game_over || rows == 0 {
+            return;
+        }
+        let rows = rows.min(BOARD_HEIGHT);
+        // blocks shoved out of the top of the board mean the stack overflowed
+        let overflow = self.board[..rows]
+            .iter()
+            .any(|row| row.iter().any(Option::is_some));
+        self.board.rotate_left(rows);
+        for row in &mut self.board[BOARD_HEIGHT - rows..] {
+            *row = [Some(BlockType::Garbage); BOARD_WIDTH];
+            row[gap] = None;
+        }
+        if overflow {
+            self.end_game(GameOverReason::TopOut);
+            return;
+        }
+        self.update_stack();
+        // lift the falling piece clear of the risen stack
+        while self.check_collision(&self.current, 0, 0) && self.current.y > -4 {
+            self.current.y -= 1;
+        }
+    }
+
+    /// A copy to try moves on: it records no replay and has no pending events
+    pub fn fork(&self) -> Game {
+        let mut fork = self.clone();
+        fork.replay = None;
+        fork.events.clear();
+        fork
+    }
+
+    /// Take the events produced since the last call
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Start a new game with the same setup. The old game's replay goes with
+    /// it; call `save_replay` beforehand to keep it.
+    pub fn reset(&mut self) {
+        let hashing = self.state_hashes.is_some();
+        let mut log = std::mem::take(&mut self.event_log);
+        log.continue_after(self.ticks);
+        let clock = self.clock.clone();
+        let best = (self.best_board.take(), self.best_score);
+        *self = Game::new(self.setup.clone());
+        (self.best_board, self.best_score) = best;
+        self.set_clock(clock);
+        self.set_event_log(log);
+        if hashing {
+            self.record_state_hashes();
+        }
+    }
+
+    /// Read wall time from `clock` from now on, starting play time over from it
+    pub fn set_clock(&mut self, clock: GameClock) {
+        let now = clock.now();
+        self.start_time = now;
+        self.paused_total = Duration::ZERO;
+        self.paused_since = self.paused.then_some(now);
+        self.lpm_samples.clear();
+        self.lock_flash = None;
+        self.clock = clock;
+    }
+
+    /// Fingerprint of where the game stands: the board, the current piece,
+    /// score, level and lines. The same in every build, so replays can carry it.
+    pub fn state_hash(&self) -> u64 {
+        let piece = &self.current;
+        let state = (
+            &self.board,
+            (piece.tetro.kind, piece.x, piece.y, piece.rotation),
+            (self.score, self.level, self.lines_cleared),
+        );
+        let bytes = serde_json::to_vec(&state).expect("game state serializes");
+        let hash = Sha256::digest(bytes);
+        u64::from_le_bytes(hash[..8].try_into().expect("sha256 is 32 bytes"))
+    }
+
+    /// Keep `state_hash` after every lock, for the replay (`--debug`)
+    pub fn record_state_hashes(&mut self) {
+        self.state_hashes.get_or_insert_default();
+    }
+
+    /// Start logging to `log`, beginning with the piece already in play
+    pub fn set_event_log(&mut self, log: EventLog) {
+        self.event_log = log;
+        if self.piece_active() && !self.game_over {
+            self.log_event(LoggedEvent::Spawn {
+                kind: self.current.tetro.kind,
+            });
+        }
+    }
+
+    /// Every logged event goes through here, stamped with the current tick
+    fn log_event(&mut self, event: LoggedEvent) {
+        self.event_log.write(self.ticks, &event);
+    }
+
+    /// Play time so far, not counting pauses
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed_at(self.clock.now())
+    }
+
+    /// `elapsed` as of `now`
+    pub fn elapsed_at(&self, now: Instant) -> Duration {
+        let pausing = self
+            .paused_since
+            .map_or(Duration::ZERO, |since| now.saturating_duration_since(since));
+        now.saturating_duration_since(self.start_time)
+            .saturating_sub(self.paused_total + pausing)
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.set_paused_at(paused, self.clock.now());
+    }
+
+    /// Pause or resume as of `now`; setting the state it's already in changes nothing
+    pub fn set_paused_at(&mut self, paused: bool, now: Instant) {
+        self.paused = paused;
+        match (paused, self.paused_since) {
+            (true, None) => self.paused_since = Some(now),
+            (false, Some(since)) => {
+                self.paused_total += now.saturating_duration_since(since);
+                self.paused_since = None;
+            }
+            _ => {}
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.set_paused(!self.paused);
+    }
+
+    /// Lines per minute over the whole game
+    pub fn lpm(&self) -> f64 {
+        let secs = self.elapsed().as_secs_f64();
+        if secs == 0.0 {
+            return 0.0;
+        }
+        self.lines_cleared as f64 / secs * 60.0
+    }
+
+    /// Key presses per piece placed
+    pub fn kpp(&self) -> f64 {
+        if self.pieces_placed == 0 {
+            return 0.0;
+        }
+        self.inputs as f64 / self.pieces_placed as f64
+    }
+
+    /// Lines cleared per piece placed; all Tetrises comes to 0.4
+    pub fn efficiency(&self) -> f64 {
+        self.lines_cleared as f64 / self.pieces_placed.max(1) as f64
+    }
+
+    /// Update the peak efficiency, once enough pieces are down to go by
+    fn record_efficiency(&mut self) {
+        if self.pieces_placed >= EFFICIENCY_MIN_PIECES {
+            self.peak_efficiency = self.peak_efficiency.max(self.efficiency());
+        }
+    }
+
+    /// Key presses per minute of unpaused play
+    pub fn apm(&self) -> f64 {
+        let secs = self.elapsed().as_secs_f64();
+        if secs == 0.0 {
+            return 0.0;
+        }
+        self.inputs as f64 / secs * 60.0
+    }
+
+    /// Lines per minute over the last `LPM_WINDOW` (or the whole game while it's shorter)
+    pub fn rolling_lpm(&self) -> f64 {
+        let window = self.elapsed().min(LPM_WINDOW).as_secs_f64();
+        if window == 0.0 {
+            return 0.0;
+        }
+        let now = self.clock.now();
+        let baseline = self
+            .lpm_samples
+            .iter()
+            .rev()
+            .find(|(at, _)| now.duration_since(*at) > LPM_WINDOW)
+            .map_or(0, |&(_, lines)| lines);
+        (self.lines_cleared - baseline) as f64 / window * 60.0
+    }
+
+    /// Sample the line count after a clear and update the peak LPM
+    fn record_lpm(&mut self) {
+        let now = self.clock.now();
+        self.lpm_samples.push_back((now, self.lines_cleared));
+        while self
+            .lpm_samples
+            .get(1)
+            .is_some_and(|(at, _)| now.duration_since(*at) > LPM_WINDOW)
+        {
+            self.lpm_samples.pop_front();
+        }
+        self.peak_lpm = self.peak_lpm.max(self.lpm());
+    }
+}
+
+/// Persist the finished game's replay, if it hasn't been written yet
+pub fn save_replay(game: &mut Game) -> io::Result<()> {
+    save_replay_in(game, replay::dir()).map(|_| ())
+}
+
+/// `save_replay` into `dir`, returning the file written, if any
+fn save_replay_in(game: &mut Game, dir: Option<PathBuf>) -> io::Result<Option<PathBuf>> {
+    match game.finish_replay() {
+        Some(replay) if !replay.actions.is_empty() => {
+            let dir =
+                dir.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+            replay.save(&dir).map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Play `actions` in order on a fresh marathon game with default rules,
+/// without a terminal, threads or gravity; the same inputs always give the
+/// same result. Full rows clear at once, since the flash would wait on steps
+/// that never come. Call `Game::step` directly for timed play.
+pub fn simulate(actions: &[Action], seed: u64) -> Game {
+    let mut game = Game::new(GameSetup {
+        mode: GameMode::Marathon,
+        rules: Rules {
+            clear_flash: Duration::ZERO,
+            ..Rules::default()
+        },
+        seed: Some(seed),
+    });
+    for &action in actions {
+        game.apply(action);
+    }
+    game
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clock::ManualClock;
+    use std::sync::Arc;
+
+    /// A game dealing `pieces` in order onto `rows` (bottom-aligned, as in
+    /// `layout::from_rows`)
+    fn game_with(mode: GameMode, rules: Rules, pieces: &[BlockType], rows: &[&str]) -> Game {
+        let rows: Vec<String> = rows.iter().map(|row| row.to_string()).collect();
+        let rules = Rules {
+            pieces: Some(pieces.to_vec()),
+            start_board: Some(Box::new(layout::from_rows(&rows).unwrap())),
+            ..rules
+        };
+        Game::new(GameSetup {
+            mode,
+            rules,
+            seed: Some(1),
+        })
+    }
+
+    /// Step until no piece is waiting to spawn or clear, at most a second
+    fn settle(game: &mut Game) {
+        for _ in 0..50 {
+            if game.piece_active() || game.game_over {
+                return;
+            }
+            game.step();
+        }
+    }
+
+    /// Put `game` on a clock that only moves with `tick`
+    fn manual_clock(game: &mut Game) -> Arc<ManualClock> {
+        let clock = Arc::new(ManualClock::default());
+        game.set_clock(GameClock::new(clock.clone()));
+        clock
+    }
+
+    /// One tick of game time, with the clock moved along to match
+    fn tick(game: &mut Game, clock: &ManualClock) {
+        clock.advance(TICK);
+        game.step();
+    }
+
+    /// A T spawning at row 3 instead of above the board, so the stack can
+    /// box it in
+    fn low_t_spawn() -> Rules {
+        let mut rules = Rules::default();
+        rules.spawn.insert(
+            BlockType::T,
+            SpawnRule {
+                column: 3,
+                row: 3,
+                rotation: 0,
+            },
+        );
+        rules
+    }
+
+    #[test]
+    fn garbage_over_the_top_is_a_top_out() {
+        let mut game = game_with(
+            GameMode::Marathon,
+            Rules::default(),
+            &[BlockType::T, BlockType::T],
+            &["G........."],
+        );
+        game.board[0][0] = Some(BlockType::Garbage);
+        game.add_garbage(1, 5);
+        assert_eq!(game.game_over_reason, Some(GameOverReason::TopOut));
+    }
+
+    #[test]
+    fn spawning_with_no_moves_is_a_top_out() {
+        let mut rows = vec!["GGGGGGGGGG"; BOARD_HEIGHT];
+        rows[3] = "GGGG.GGGGG";
+        rows[4] = "GGG...GGGG";
+        let game = game_with(GameMode::Marathon, low_t_spawn(), &[BlockType::T], &rows);
+        assert_eq!(game.game_over_reason, Some(GameOverReason::TopOut));
+    }
+
+    #[test]
+    fn spawning_into_the_stack_is_a_block_out() {
+        let mut rows = vec![".........."; BOARD_HEIGHT];
+        for row in &mut rows[2..6] {
+            *row = "GGGGGGGGG.";
+        }
+        let game = game_with(GameMode::Marathon, low_t_spawn(), &[BlockType::T], &rows);
+        assert_eq!(game.game_over_reason, Some(GameOverReason::BlockOut));
+    }
+
+    #[test]
+    fn locking_above_the_board_is_a_lock_out() {
+        let rows = vec!["...GGG...."; BOARD_HEIGHT];
+        let mut game = game_with(
+            GameMode::Marathon,
+            Rules::default(),
+            &[BlockType::T, BlockType::T],
+            &rows,
+        );
+        assert!(!game.game_over, "the spawn is nudged clear of the stack");
+        game.apply(Action::HardDrop);
+        assert_eq!(game.game_over_reason, Some(GameOverReason::LockOut));
+    }
+
+    #[test]
+    fn ultra_ends_when_its_clock_runs_out() {
+        let mut game = game_with(GameMode::Ultra, Rules::default(), &[BlockType::T; 2], &[]);
+        game.ticks = (ULTRA_TIME.as_millis() / TICK.as_millis()) as u64;
+        game.step();
+        assert_eq!(game.game_over_reason, Some(GameOverReason::TimeExpired));
+    }
+
+    #[test]
+    fn sprint_ends_on_its_last_line() {
+        let mut game = game_with(
+            GameMode::Sprint,
+            Rules::default(),
+            &[BlockType::I; 3],
+            &["GGG....GGG"],
+        );
+        game.lines_cleared = SPRINT_LINES - 1;
+        game.apply(Action::HardDrop);
+        settle(&mut game);
+        assert_eq!(game.lines_cleared, SPRINT_LINES);
+        assert_eq!(game.game_over_reason, Some(GameOverReason::GoalReached));
+    }
+
+    #[test]
+    fn gravity_drops_a_row_each_interval() {
+        let mut game = game_with(
+            GameMode::Marathon,
+            Rules::default(),
+            &[BlockType::T; 2],
+            &[],
+        );
+        let clock = manual_clock(&mut game);
+        let interval = game.gravity_interval;
+        let ticks = (interval.as_millis() / TICK.as_millis()) as usize;
+        let y = game.current.y;
+        for _ in 1..ticks {
+            tick(&mut game, &clock);
+        }
+        assert_eq!(game.current.y, y, "one tick early");
+        tick(&mut game, &clock);
+        assert_eq!(game.current.y, y + 1);
+        assert_eq!(game.elapsed(), interval);
+        for _ in 0..ticks {
+            tick(&mut game, &clock);
+        }
+        assert_eq!(game.current.y, y + 2);
+    }
+
+    #[test]
+    fn lock_delay_locks_a_resting_piece_once_it_runs_out() {
+        let rules = Rules {
+            lock_delay: Duration::from_millis(500),
+            soft_drop_locks: false,
+            ..Rules::default()
+        };
+        let mut game = game_with(GameMode::Marathon, rules, &[BlockType::T; 3], &[]);
+        let clock = manual_clock(&mut game);
+        while game.move_down() {}
+        let resting = game.current.cells();
+        let ticks = (500 / TICK.as_millis()) as usize;
+        for _ in 1..ticks {
+            tick(&mut game, &clock);
+        }
+        assert_eq!(game.pieces_placed, 0, "one tick early");
+        assert_eq!(game.current.cells(), resting);
+        tick(&mut game, &clock);
+        assert_eq!(game.pieces_placed, 1);
+        assert_eq!(game.elapsed(), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn moving_off_a_ledge_restarts_lock_delay() {
+        let rules = Rules {
+            lock_delay: Duration::from_millis(500),
+            soft_drop_locks: false,
+            ..Rules::default()
+        };
+        let mut game = game_with(GameMode::Marathon, rules, &[BlockType::T; 3], &[]);
+        let clock = manual_clock(&mut game);
+        game.board[10][3..6].fill(Some(BlockType::Garbage));
+        while game.move_down() {}
+        for _ in 0..20 {
+            tick(&mut game, &clock);
+        }
+        for _ in 0..3 {
+            game.apply(Action::MoveRight);
+        }
+        // a tick in the air, then the whole delay again on the floor
+        tick(&mut game, &clock);
+        while game.move_down() {}
+        for _ in 1..25 {
+            tick(&mut game, &clock);
+        }
+        assert_eq!(game.pieces_placed, 0);
+        tick(&mut game, &clock);
+        assert_eq!(game.pieces_placed, 1);
+    }
+
+    #[test]
+    fn pauses_stay_out_of_elapsed() {
+        let mut game = game_with(
+            GameMode::Marathon,
+            Rules::default(),
+            &[BlockType::T; 2],
+            &[],
+        );
+        let clock = manual_clock(&mut game);
+        clock.advance(Duration::from_secs(10));
+        game.set_paused(true);
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(game.elapsed(), Duration::from_secs(10), "mid-pause");
+        // toggling again while paused changes nothing
+        game.set_paused(true);
+        clock.advance(Duration::from_secs(5));
+        game.toggle_pause();
+        game.toggle_pause();
+        game.toggle_pause();
+        assert!(!game.paused);
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(game.elapsed(), Duration::from_secs(13));
+        assert_eq!(game.paused_total, Duration::from_secs(65));
+    }
+
+    #[test]
+    fn a_pause_survives_saving() {
+        let mut game = game_with(
+            GameMode::Marathon,
+            Rules::default(),
+            &[BlockType::T; 2],
+            &[],
+        );
+        let now = Instant::now();
+        game.start_time = now - Duration::from_secs(100);
+        game.set_paused_at(true, now - Duration::from_secs(20));
+        game.set_paused_at(false, now - Duration::from_secs(10));
+        game.set_paused_at(true, now - Duration::from_secs(4));
+        assert_eq!(game.elapsed_at(now), Duration::from_secs(86));
+
+        let json = serde_json::to_string(&game).unwrap();
+        let mut loaded: Game = serde_json::from_str(&json).unwrap();
+        assert!(loaded.paused);
+        let played = loaded.elapsed();
+        assert!(
+            played.abs_diff(Duration::from_secs(86)) < Duration::from_secs(1),
+            "{:?}",
+            played
+        );
+        loaded.set_paused(false);
+        assert!(loaded.elapsed().abs_diff(played) < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn the_next_piece_waits_out_are() {
+        let rules = Rules {
+            are_delay: TICK * 5,
+            ..Rules::default()
+        };
+        let pieces = [BlockType::T, BlockType::O, BlockType::I];
+        let mut game = game_with(GameMode::Marathon, rules, &pieces, &[]);
+        game.apply(Action::HardDrop);
+        for _ in 0..4 {
+            assert!(!game.piece_active());
+            game.step();
+        }
+        assert!(!game.piece_active());
+        game.step();
+        assert!(game.piece_active());
+        assert_eq!(game.current.tetro.kind, BlockType::O);
+    }
+
+    #[test]
+    fn without_are_the_next_piece_spawns_on_lock() {
+        let pieces = [BlockType::T, BlockType::O, BlockType::I];
+        let mut game = game_with(GameMode::Marathon, Rules::default(), &pieces, &[]);
+        game.apply(Action::HardDrop);
+        assert!(game.piece_active());
+        assert_eq!(game.current.tetro.kind, BlockType::O);
+    }
+
+    /// The rotation of each piece dealt from `pieces` with `held` down as it spawns
+    fn irs_spawns(enabled: bool, pieces: &[BlockType], held: &[Option<RotationDir>]) -> Vec<usize> {
+        let rules = Rules {
+            irs_enabled: enabled,
+            ..Rules::default()
+        };
+        let mut game = game_with(GameMode::Marathon, rules, pieces, &[]);
+        let mut rotations = Vec::new();
+        for &dir in held {
+            game.apply(Action::SetIrs(dir));
+            game.apply(Action::HardDrop);
+            rotations.push(game.current.rotation);
+            assert_eq!(game.irs_fired, enabled && dir.is_some());
+        }
+        rotations
+    }
+
+    #[test]
+    fn irs_pre_rotates_the_spawning_piece() {
+        let pieces = [BlockType::T; 4];
+        let held = [None, Some(RotationDir::Cw), Some(RotationDir::Ccw)];
+        assert_eq!(irs_spawns(true, &pieces, &held), [0, 1, 3]);
+        assert_eq!(irs_spawns(false, &pieces, &held), [0, 0, 0]);
+    }
+
+    #[test]
+    fn irs_leaves_an_o_as_it_was() {
+        let rules = Rules {
+            irs_enabled: true,
+            ..Rules::default()
+        };
+        let mut game = game_with(GameMode::Marathon, rules, &[BlockType::O; 2], &[]);
+        let spawned = ActivePiece::new(BlockType::O, SpawnRule::default()).cells();
+        game.apply(Action::SetIrs(Some(RotationDir::Cw)));
+        game.apply(Action::HardDrop);
+        assert_eq!(game.current.cells(), spawned);
+    }
+
+    #[test]
+    fn lpm_is_lines_over_minutes_played() {
+        let mut game = game_with(GameMode::Marathon, Rules::default(), &[BlockType::T], &[]);
+        let clock = manual_clock(&mut game);
+        game.lines_cleared = 12;
+        assert_eq!(game.lpm(), 0.0);
+        assert_eq!(game.rolling_lpm(), 0.0);
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(game.lpm(), 12.0);
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(game.lpm(), 8.0);
+        game.lines_cleared = 0;
+        assert_eq!(game.lpm(), 0.0);
+    }
+
+    #[test]
+    fn a_fair_start_never_opens_with_s_or_z() {
+        let start = |seed, fair_start| {
+            Game::new(GameSetup {
+                mode: GameMode::Marathon,
+                rules: Rules {
+                    fair_start,
+                    ..Rules::default()
+                },
+                seed: Some(seed),
+            })
+        };
+        let is_s_or_z = |kind| matches!(kind, BlockType::S | BlockType::Z);
+        let mut firsts = Vec::new();
+        for seed in 0..500 {
+            let (fair, plain) = (start(seed, true), start(seed, false));
+            firsts.push(fair.current.tetro.kind);
+            // only the first piece is redrawn; the queue behind it is the same
+            assert_eq!(fair.next, plain.next);
+        }
+        assert!(!firsts.iter().copied().any(is_s_or_z));
+        for kind in [
+            BlockType::I,
+            BlockType::O,
+            BlockType::T,
+            BlockType::J,
+            BlockType::L,
+        ] {
+            assert!(firsts.contains(&kind), "{:?} never came first", kind);
+        }
+        assert!((0..500).any(|seed| is_s_or_z(start(seed, false).current.tetro.kind)));
+    }
+
+    /// Slide the O in play to the right wall and drop it
+    fn drop_o_right(game: &mut Game) {
+        for _ in 0..4 {
+            game.apply(Action::MoveRight);
+        }
+        game.apply(Action::HardDrop);
+    }
+
+    #[test]
+    fn a_zone_banks_its_lines_and_scores_them_as_tetrises() {
+        let rules = Rules {
+            zone_recharge: 2,
+            ..Rules::default()
+        };
+        let rows = ["GGGGGGGG.."; 8];
+        let mut game = game_with(GameMode::Marathon, rules, &[BlockType::O; 8], &rows);
+        game.apply(Action::Zone);
+        assert!(game.zone_active);
+        for _ in 0..4 {
+            drop_o_right(&mut game);
+        }
+        assert_eq!(game.zone_lines, 8);
+        assert_eq!(game.lines_cleared, 0);
+
+        let mut scorer = game.clone();
+        let expected = game.score + scorer.clear_points(4, false) + scorer.clear_points(4, false);
+        assert!(scorer.back_to_back);
+        game.apply(Action::Zone);
+        assert!(!game.zone_active);
+        assert_eq!(game.lines_cleared, 8);
+        assert_eq!(game.score, expected);
+        assert!(game.board.iter().flatten().all(Option::is_none));
+    }
+
+    #[test]
+    fn a_zone_recharges_after_enough_pieces() {
+        let rules = Rules {
+            zone_recharge: 2,
+            ..Rules::default()
+        };
+        let mut game = game_with(GameMode::Marathon, rules, &[BlockType::O; 4], &[]);
+        assert!(game.zone_ready());
+        game.apply(Action::Zone);
+        game.apply(Action::Zone);
+        assert!(!game.zone_ready());
+        drop_o_right(&mut game);
+        assert!(!game.zone_ready());
+        drop_o_right(&mut game);
+        assert!(game.zone_ready());
+    }
+
+    #[test]
+    fn without_recharge_the_zone_is_once_a_game() {
+        let mut game = game_with(
+            GameMode::Marathon,
+            Rules::default(),
+            &[BlockType::O; 4],
+            &[],
+        );
+        game.apply(Action::Zone);
+        game.apply(Action::Zone);
+        for _ in 0..3 {
+            drop_o_right(&mut game);
+            assert!(!game.zone_ready());
+        }
+        game.apply(Action::Zone);
+        assert!(!game.zone_active);
+    }
+
+    #[test]
+    fn a_scripted_game_plays_out_the_same_headless() {
+        use Action::*;
+        // seed 1234 deals I, I, O: two I pieces and an O fill the bottom row
+        let script = [
+            MoveLeft, MoveLeft, MoveLeft, HardDrop, MoveRight, HardDrop, MoveRight, MoveRight,
+            MoveRight, MoveRight, HardDrop,
+        ];
+        let game = simulate(&script, 1234);
+        assert_eq!(game.pieces_placed, 3);
+        assert_eq!(game.lines_cleared, 1);
+        // a single, and three hard drops of 19 rows at 2 points a row
+        assert_eq!(game.score, 100 + 3 * 19 * 2);
+        assert_eq!(
+            layout::board_to_string(&game.board).lines().last(),
+            Some("........OO")
+        );
+    }
+
+    #[test]
+    fn spamming_down_at_the_floor_scores_nothing_and_never_locks() {
+        let rules = Rules {
+            soft_drop_locks: false,
+            ..Rules::default()
+        };
+        let mut game = game_with(GameMode::Marathon, rules, &[BlockType::O; 2], &[]);
+        let mut rows = 0;
+        while game.move_down() {
+            rows += 1;
+        }
+        assert_eq!(rows, BOARD_HEIGHT - 1);
+        let score = game.score;
+        assert_eq!(score, rows * game.setup.rules.scoring.soft_drop);
+        for _ in 0..50 {
+            assert!(!game.move_down());
+        }
+        assert_eq!(game.score, score);
+        assert_eq!(game.pieces_placed, 0);
+    }
+
+    #[test]
+    fn spamming_down_at_the_floor_locks_once() {
+        let pieces = [BlockType::O; 3];
+        let mut game = game_with(GameMode::Marathon, Rules::default(), &pieces, &[]);
+        while game.move_down() {}
+        assert_eq!(game.pieces_placed, 1);
+        let score = game.score;
+        // the next piece spawned high up, so down moves it again
+        assert!(game.move_down());
+        assert_eq!(game.pieces_placed, 1);
+        assert_eq!(game.score, score + game.setup.rules.scoring.soft_drop);
+    }
+
+    #[test]
+    fn soft_drop_at_the_floor_leaves_locking_to_lock_delay() {
+        let rules = Rules {
+            soft_drop_locks: false,
+            lock_delay: TICK * 25,
+            ..Rules::default()
+        };
+        let mut game = game_with(GameMode::Marathon, rules, &[BlockType::O; 2], &[]);
+        while game.move_down() {}
+        game.apply(Action::SoftDrop);
+        assert_eq!(game.pieces_placed, 0);
+        for _ in 1..25 {
+            game.step();
+            game.apply(Action::SoftDrop);
+        }
+        assert_eq!(game.pieces_placed, 0);
+        assert_eq!(game.grounded_for, TICK * 24);
+        game.step();
+        assert_eq!(game.pieces_placed, 1);
+    }
+
+    /// Finesse faults after pressing `presses` on a T, then hard dropping it
+    fn faults_after(presses: &[Action]) -> usize {
+        let mut game = game_with(
+            GameMode::Marathon,
+            Rules::default(),
+            &[BlockType::T; 2],
+            &[],
+        );
+        for &action in presses {
+            game.press(action);
+        }
+        game.press(Action::HardDrop);
+        game.finesse_faults
+    }
+
+    #[test]
+    fn extra_presses_are_a_finesse_fault() {
+        assert_eq!(faults_after(&[Action::MoveRight]), 0);
+        assert_eq!(faults_after(&[Action::RotateCw, Action::MoveLeft]), 0);
+        assert_eq!(
+            faults_after(&[Action::MoveLeft, Action::MoveRight, Action::MoveRight]),
+            1
+        );
+        assert_eq!(
+            faults_after(&[Action::RotateCw, Action::RotateCw, Action::RotateCw]),
+            1
+        );
+    }
+
+    /// The T-spin slots `rows` leave, with the lines each would clear
+    fn slots_in(rows: &[&str]) -> Vec<(TSpinSlot, usize)> {
+        let game = game_with(GameMode::Marathon, Rules::default(), &[BlockType::I], rows);
+        game.find_tspin_slots()
+            .into_iter()
+            .map(|slot| (slot, game.clears_with(&slot.piece())))
+            .collect()
+    }
+
+    #[test]
+    fn a_tst_well_is_a_triple_slot() {
+        // a T pointing right down the wall well, its nub under the spine
+        let slot = TSpinSlot {
+            x: -1,
+            y: BOARD_HEIGHT as i32 - 3,
+            rotation: 1,
+        };
+        let well = [".G........", ".GGGGGGGGG", "..GGGGGGGG", ".GGGGGGGGG"];
+        assert!(slots_in(&well).contains(&(slot, 3)));
+        // with nothing over the nub the T just drops in
+        assert!(!slots_in(&well[2..]).iter().any(|&(found, _)| found == slot));
+    }
+
+    #[test]
+    fn an_stsd_slot_is_a_double_slot() {
+        // a T pointing left, tucked under the overhang
+        let slot = TSpinSlot {
+            x: 1,
+            y: BOARD_HEIGHT as i32 - 3,
+            rotation: 3,
+        };
+        let overhang = ["GGG.......", "GG........", "G..GGGGGGG", "GG.GGGGGGG"];
+        assert!(slots_in(&overhang).contains(&(slot, 2)));
+        // without the overhang there are only two corners
+        assert!(
+            !slots_in(&overhang[2..])
+                .iter()
+                .any(|&(found, _)| found == slot)
+        );
+    }
+
+    #[test]
+    fn a_flat_stack_has_no_slots() {
+        assert!(slots_in(&["GGGG..GGGG", "GGGGG.GGGG"]).is_empty());
+    }
+
+    #[test]
+    fn a_finished_game_writes_its_replay_once() {
+        let mut game = simulate(&[Action::HardDrop; 40], 1);
+        assert!(game.game_over);
+        let dir = std::env::temp_dir().join(format!("replay-autosave-{}", std::process::id()));
+        let path = save_replay_in(&mut game, Some(dir.clone()))
+            .unwrap()
+            .expect("a replay is written");
+        let replay = Replay::load(&path).unwrap();
+        assert!(!replay.actions.is_empty());
+        assert_eq!(replay.final_score, game.score);
+        assert!(
+            save_replay_in(&mut game, Some(dir.clone()))
+                .unwrap()
+                .is_none()
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn hidden_previews_still_advance_the_queue() {
+        let ui = config::UiConfig {
+            preview: Some(0),
+            ..Default::default()
+        };
+        let mut game = simulate(&[], 3);
+        for _ in 0..5 {
+            assert_eq!(ui.previews_shown(game.next.len()), 0);
+            let next = game.next.clone();
+            game.press(Action::HardDrop);
+            settle(&mut game);
+            assert_eq!(game.current.tetro.kind, next[0]);
+            assert!(
+                game.next
+                    .iter()
+                    .zip(next.iter().skip(1))
+                    .all(|(a, b)| a == b)
+            );
+        }
+    }
+
+    #[test]
+    fn a_spawn_is_nudged_up_a_row_but_no_further() {
+        // the low T spawns with its flat side on row 4
+        let mut rows = vec![".........."; BOARD_HEIGHT];
+        rows[4] = "...GGG....";
+        let nudged = game_with(GameMode::Marathon, low_t_spawn(), &[BlockType::T], &rows);
+        assert_eq!(nudged.game_over_reason, None);
+        assert_eq!(nudged.current.y, 2);
+        rows[3] = "...GGG....";
+        let blocked = game_with(GameMode::Marathon, low_t_spawn(), &[BlockType::T], &rows);
+        assert_eq!(blocked.game_over_reason, Some(GameOverReason::BlockOut));
+    }
+
+    #[test]
+    fn the_hold_preview_leaves_the_game_alone() {
+        let mut game = game_with(
+            GameMode::Marathon,
+            Rules::default(),
+            &[BlockType::T, BlockType::O, BlockType::I, BlockType::L],
+            &[],
+        );
+        let before = (game.state_hash(), game.hold, game.next.clone());
+        let preview = game.hold_preview().expect("hold is available");
+        assert_eq!(preview.tetro.kind, BlockType::O);
+        assert_eq!(preview.y, game.landing(&preview).y);
+        assert_eq!((game.state_hash(), game.hold, game.next.clone()), before);
+        assert_eq!(game.current.tetro.kind, BlockType::T);
+
+        game.press(Action::Hold);
+        assert!(
+            game.hold_preview().is_none(),
+            "hold is used up until the next lock"
+        );
+        game.press(Action::HardDrop);
+        settle(&mut game);
+        let before = (game.state_hash(), game.hold, game.next.clone());
+        let preview = game.hold_preview().expect("hold is available again");
+        assert_eq!(preview.tetro.kind, BlockType::T);
+        assert_eq!((game.state_hash(), game.hold, game.next.clone()), before);
+        assert_eq!(game.current.tetro.kind, BlockType::I);
+    }
+
+    #[test]
+    fn competitive_rules_are_fixed_when_the_game_starts() {
+        let loose = Rules {
+            next_queue: 6,
+            lock_delay: Duration::from_secs(1),
+            ..Rules::default()
+        };
+        let mut pieces = vec![BlockType::I, BlockType::O];
+        pieces.extend([BlockType::T; 8]);
+        let mut game = game_with(GameMode::Competitive, loose.clone(), &pieces, &[]);
+        assert_eq!(game.next.len(), 1);
+        assert_eq!(game.setup.rules.lock_delay, COMPETITIVE_LOCK_DELAY);
+        assert!(!game.setup.mode.shows_ghost());
+        assert_eq!(game.setup.mode.fixed_handling(), Some(COMPETITIVE_HANDLING));
+
+        // hold works, but its box keeps the piece to itself
+        assert!(!game.setup.mode.shows_hold());
+        assert!(game.hold_preview().is_none());
+        game.press(Action::Hold);
+        assert_eq!(game.hold, Some(BlockType::I));
+        assert_eq!(game.current.tetro.kind, BlockType::O);
+
+        // a restart keeps the restrictions
+        game.reset();
+        assert_eq!(game.next.len(), 1);
+        assert_eq!(game.setup.rules.lock_delay, COMPETITIVE_LOCK_DELAY);
+
+        let marathon = game_with(GameMode::Marathon, loose, &pieces, &[]);
+        assert_eq!(marathon.next.len(), 6);
+        assert!(marathon.setup.mode.shows_ghost() && marathon.setup.mode.shows_hold());
+        assert_eq!(marathon.setup.mode.fixed_handling(), None);
+        for lines in 1..=4 {
+            let plain = marathon.clone().clear_points(lines, false);
+            assert_eq!(game.clone().clear_points(lines, false), plain * 5 / 4);
+        }
+    }
+
+    #[test]
+    fn a_pasted_board_keeps_the_score_unless_it_blocks_the_spawn() {
+        let mut game = game_with(
+            GameMode::Marathon,
+            Rules::default(),
+            &[BlockType::T; 3],
+            &[],
+        );
+        game.score = 1200;
+        game.paste_board(layout::board_from_str("GGGG.GGGGG").unwrap())
+            .unwrap();
+        assert_eq!(game.board[BOARD_HEIGHT - 1][4], None);
+        assert_eq!(game.board[BOARD_HEIGHT - 1][0], Some(BlockType::Garbage));
+        assert_eq!(game.score, 1200);
+        assert!(game.replay.is_none());
+
+        let full = vec!["GGGGGGGGGG"; BOARD_HEIGHT].join("\n");
+        let before = game.board;
+        let err = game
+            .paste_board(layout::board_from_str(&full).unwrap())
+            .unwrap_err();
+        assert!(err.contains("no room"), "{}", err);
+        assert_eq!(game.board, before);
+    }
+
+    #[test]
+    fn an_o_is_outlined_without_its_inner_edges() {
+        let board = layout::from_rows(&[
+            "..........".to_string(),
+            "...OO.....".to_string(),
+            "...OOT...O".to_string(),
+        ])
+        .unwrap();
+        let (top, bottom) = (BOARD_HEIGHT - 2, BOARD_HEIGHT - 1);
+        let edges = |top, right, bottom, left| {
+            Some(Edges {
+                top,
+                right,
+                bottom,
+                left,
+            })
+        };
+        assert_eq!(Edges::of(&board, 3, top), edges(true, false, false, true));
+        assert_eq!(Edges::of(&board, 4, top), edges(true, true, false, false));
+        assert_eq!(
+            Edges::of(&board, 3, bottom),
+            edges(false, false, true, true)
+        );
+        // a block of another kind alongside still gets an edge
+        assert_eq!(
+            Edges::of(&board, 4, bottom),
+            edges(false, true, true, false)
+        );
+        // and so does a wall
+        assert_eq!(Edges::of(&board, 9, bottom), edges(true, true, true, true));
+        assert_eq!(Edges::of(&board, 0, bottom), None);
+    }
+
+    #[test]
+    fn the_clear_history_keeps_the_newest_ten() {
+        let mut game = game_with(
+            GameMode::Marathon,
+            Rules::default(),
+            &[BlockType::O; 2],
+            &[],
+        );
+        for points in 0..CLEAR_HISTORY_LEN + 2 {
+            game.award_lines(1, points, false);
+        }
+        let kept: Vec<usize> = game
+            .clear_history
+            .iter()
+            .map(|clear| clear.points)
+            .collect();
+        assert_eq!(kept, (2..CLEAR_HISTORY_LEN + 2).rev().collect::<Vec<_>>());
+
+        // a real clear goes on the front
+        let mut game = game_with(
+            GameMode::Marathon,
+            Rules::default(),
+            &[BlockType::O; 2],
+            &["GGGGGGGG..", "GGGGGGGG.."],
+        );
+        drop_o_right(&mut game);
+        settle(&mut game);
+        let clear = game.clear_history.front().expect("the double is recorded");
+        assert_eq!(clear.lines, 2);
+        assert!(clear.perfect_clear);
+        assert_eq!(game.clear_history.len(), 1);
+    }
+
+    #[test]
+    fn sticky_gravity_drops_what_naive_leaves_floating() {
+        let rows = ["GG........", "GGGGGGGG..", "..GGGGGG.."];
+        let cleared = |clear_gravity| {
+            let rules = Rules {
+                clear_gravity,
+                ..Rules::default()
+            };
+            let mut game = game_with(GameMode::Marathon, rules, &[BlockType::O; 2], &rows);
+            drop_o_right(&mut game);
+            settle(&mut game);
+            game
+        };
+
+        let naive = cleared(ClearGravity::Naive);
+        assert_eq!(naive.lines_cleared, 1);
+        assert_eq!(layout::to_rows(&naive.board), ["GG........", "..GGGGGGOO"]);
+
+        // the pair falls into the gap below and completes that row too
+        let sticky = cleared(ClearGravity::Sticky);
+        assert_eq!(sticky.lines_cleared, 2);
+        assert!(sticky.board.iter().flatten().all(Option::is_none));
+    }
+
+    #[test]
+    fn a_hold_with_no_room_tops_out_with_the_pieces_in_place() {
+        let mut rules = Rules::default();
+        rules.spawn.insert(
+            BlockType::I,
+            SpawnRule {
+                column: 3,
+                row: 3,
+                rotation: 0,
+            },
+        );
+        // the low I lies on row 4, and a nudge only takes it to row 3
+        let mut rows = vec![".........."; BOARD_HEIGHT];
+        rows[3] = "...GGGG...";
+        rows[4] = "...GGGG...";
+
+        let mut game = game_with(
+            GameMode::Marathon,
+            rules.clone(),
+            &[BlockType::T, BlockType::I],
+            &rows,
+        );
+        game.press(Action::Hold);
+        assert_eq!(game.game_over_reason, Some(GameOverReason::BlockOut));
+        assert_eq!(game.current.tetro.kind, BlockType::T);
+        assert_eq!(game.hold, None);
+        assert_eq!(game.next.front(), Some(&BlockType::I));
+
+        let mut game = game_with(
+            GameMode::Marathon,
+            rules,
+            &[BlockType::T, BlockType::O],
+            &rows,
+        );
+        game.hold = Some(BlockType::I);
+        game.press(Action::Hold);
+        assert_eq!(game.game_over_reason, Some(GameOverReason::BlockOut));
+        assert_eq!(game.current.tetro.kind, BlockType::T);
+        assert_eq!(game.hold, Some(BlockType::I));
+    }
+
+    #[test]
+    fn efficiency_is_lines_per_piece() {
+        let mut game = game_with(GameMode::Marathon, Rules::default(), &[BlockType::I], &[]);
+        assert_eq!(game.efficiency(), 0.0);
+
+        // a tetris every ten pieces
+        game.pieces_placed = 10;
+        game.lines_cleared = 4;
+        assert_eq!(game.efficiency(), 0.4);
+
+        // singles, two and a half pieces each
+        game.pieces_placed = 30;
+        game.lines_cleared = 12;
+        assert_eq!(game.efficiency(), 0.4);
+        game.lines_cleared = 10;
+        assert!((game.efficiency() - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn peak_efficiency_waits_for_enough_pieces() {
+        let mut game = game_with(GameMode::Marathon, Rules::default(), &[BlockType::I], &[]);
+        game.pieces_placed = EFFICIENCY_MIN_PIECES - 1;
+        game.lines_cleared = 4;
+        game.record_efficiency();
+        assert_eq!(game.peak_efficiency, 0.0);
+        game.pieces_placed = 10;
+        game.record_efficiency();
+        assert_eq!(game.peak_efficiency, 0.4);
+        game.pieces_placed = 20;
+        game.record_efficiency();
+        assert_eq!(game.peak_efficiency, 0.4);
+    }
+
+    fn cheese(rows: usize, pieces: &[BlockType]) -> Game {
+        Game::new(GameSetup {
+            mode: GameMode::Cheese { rows },
+            rules: Rules {
+                pieces: Some(pieces.to_vec()),
+                ..Rules::default()
+            },
+            seed: Some(5),
+        })
+    }
+
+    #[test]
+    fn each_cheese_row_has_one_gap() {
+        let game = cheese(10, &[BlockType::I]);
+        let gaps: Vec<Vec<usize>> = game.board[BOARD_HEIGHT - 10..]
+            .iter()
+            .map(|row| (0..BOARD_WIDTH).filter(|&x| row[x].is_none()).collect())
+            .collect();
+        for pair in gaps.windows(2) {
+            assert_eq!((pair[0].len(), pair[1].len()), (1, 1));
+            assert_ne!(pair[0], pair[1], "a gap never sits on the one below");
+        }
+        assert_eq!(game.garbage_rows_left(), 10);
+        assert!(
+            game.board[..BOARD_HEIGHT - 10]
+                .iter()
+                .flatten()
+                .all(Option::is_none)
+        );
+    }
+
+    #[test]
+    fn clearing_every_cheese_row_wins() {
+        let mut game = cheese(1, &[BlockType::I; 2]);
+        let gap = (0..BOARD_WIDTH)
+            .find(|&x| game.board[BOARD_HEIGHT - 1][x].is_none())
+            .unwrap() as i32;
+        game.press(Action::RotateCw);
+        let column = |game: &Game| game.current.cells()[0].0;
+        while column(&game) != gap {
+            let before = column(&game);
+            game.press(if before < gap {
+                Action::MoveRight
+            } else {
+                Action::MoveLeft
+            });
+            assert_ne!(column(&game), before);
+        }
+        game.press(Action::HardDrop);
+        settle(&mut game);
+        assert_eq!(game.garbage_rows_left(), 0);
+        assert_eq!(game.game_over_reason, Some(GameOverReason::GoalReached));
+    }
+
+    /// Board coverage once an O has locked in the right two columns of `rows`
+    fn coverage_after_o(rows: &[&str]) -> f64 {
+        let mut game = game_with(
+            GameMode::Marathon,
+            Rules::default(),
+            &[BlockType::O; 2],
+            rows,
+        );
+        drop_o_right(&mut game);
+        settle(&mut game);
+        assert_eq!(game.lines_cleared, 0);
+        game.board_coverage()
+    }
+
+    #[test]
+    fn coverage_is_the_share_of_the_stack_without_holes() {
+        let fresh = game_with(GameMode::Marathon, Rules::default(), &[BlockType::O], &[]);
+        assert_eq!(fresh.board_coverage(), 1.0);
+        assert_eq!(coverage_after_o(&[]), 1.0);
+        assert_eq!(coverage_after_o(&["GGGGGGG...", "GGGGGGG..."]), 1.0);
+        // 20 holes in a stack 4 high
+        let holey = ["GGGGG.....", ".....GG...", ".......G..", ".........."];
+        assert_eq!(coverage_after_o(&holey), 0.5);
+    }
+
+    /// A T lying flat in the bottom-left corner of `rows`
+    fn t_in_corner(rows: &[&str]) -> Game {
+        let mut game = game_with(
+            GameMode::Marathon,
+            Rules::default(),
+            &[BlockType::T; 2],
+            rows,
+        );
+        game.current.rotation = 0;
+        game.current.x = 0;
+        game.current.y = BOARD_HEIGHT as i32 - 2;
+        assert!(game.fits(&game.current));
+        game
+    }
+
+    #[test]
+    fn a_piece_with_no_moves_is_soft_locked() {
+        let sealed = t_in_corner(&["GGGGGGGGGG", "GGGGGGGGGG", "G.GGGGGGGG", "...GGGGGGG"]);
+        assert!(sealed.is_soft_locked());
+    }
+
+    #[test]
+    fn a_piece_with_one_move_left_is_not_soft_locked() {
+        // only the shift right fits
+        let game = t_in_corner(&["GGGGGGGGGG", "GGGGGGGGGG", "G..GGGGGGG", "....GGGGGG"]);
+        let moves = [(-1, 0), (1, 0), (0, 1)]
+            .into_iter()
+            .filter(|&(dx, dy)| !game.check_collision(&game.current, dx, dy))
+            .count();
+        let mut cw = game.current.clone();
+        cw.rotate_cw();
+        let mut ccw = game.current.clone();
+        ccw.rotate_ccw();
+        assert_eq!(moves, 1);
+        assert!(game.kick_for(&cw).is_none() && game.kick_for(&ccw).is_none());
+        assert!(!game.is_soft_locked());
+    }
+
+    /// How far right of its spawn column the O comes in when MoveLeft then
+    /// MoveRight are pressed during the ARE before it, under `buffer`
+    fn o_shift_after_are(buffer: InputBuffer) -> i32 {
+        let rules = Rules {
+            are_delay: TICK * 5,
+            input_buffer: buffer,
+            ..Rules::default()
+        };
+        let pieces = [BlockType::T, BlockType::O, BlockType::I];
+        let mut game = game_with(GameMode::Marathon, rules, &pieces, &[]);
+        let clock = manual_clock(&mut game);
+        game.press(Action::HardDrop);
+        tick(&mut game, &clock);
+        game.press(Action::MoveLeft);
+        tick(&mut game, &clock);
+        game.press(Action::MoveRight);
+        assert!(!game.piece_active(), "still waiting out ARE");
+        for _ in 0..3 {
+            tick(&mut game, &clock);
+        }
+        assert!(game.piece_active());
+        assert_eq!(game.current.tetro.kind, BlockType::O);
+        game.current.x - game.setup.rules.spawn_rule(BlockType::O).column
+    }
+
+    #[test]
+    fn the_last_move_during_are_goes_to_the_next_piece() {
+        assert_eq!(o_shift_after_are(InputBuffer::ApplyLast), 1);
+    }
+
+    #[test]
+    fn moves_during_are_are_dropped_by_default() {
+        assert_eq!(o_shift_after_are(InputBuffer::Discard), 0);
+    }
+
+    #[test]
+    fn fill_is_the_share_of_occupied_cells() {
+        let fill = |rows: &[&str]| {
+            game_with(GameMode::Marathon, Rules::default(), &[BlockType::T], rows).fill_percentage()
+        };
+        assert_eq!(fill(&[]), 0.0);
+        assert_eq!(fill(&["GGGGGGGGG.", "GGGGGGGGG."]), 9.0);
+        assert_eq!(fill(&vec!["GGGGGGGGGG"; BOARD_HEIGHT]), 100.0);
+    }
+
+    #[test]
+    fn danger_bands_follow_the_tallest_column() {
+        for (height, level) in [
+            (0, DangerLevel::Safe),
+            (9, DangerLevel::Safe),
+            (10, DangerLevel::Warning),
+            (14, DangerLevel::Warning),
+            (15, DangerLevel::Critical),
+            (BOARD_HEIGHT, DangerLevel::Critical),
+        ] {
+            assert_eq!(DangerLevel::of_height(height), level, "{height} rows");
+        }
+    }
+
+    #[test]
+    fn danger_falls_back_only_past_the_hysteresis() {
+        assert_eq!(DangerLevel::Safe.settle(15), DangerLevel::Critical);
+        assert_eq!(DangerLevel::Critical.settle(13), DangerLevel::Critical);
+        assert_eq!(DangerLevel::Critical.settle(12), DangerLevel::Warning);
+        assert_eq!(DangerLevel::Warning.settle(8), DangerLevel::Warning);
+        assert_eq!(DangerLevel::Warning.settle(7), DangerLevel::Safe);
+        assert_eq!(DangerLevel::Critical.settle(0), DangerLevel::Safe);
+    }
+
+    #[test]
+    fn a_rising_stack_raises_the_danger_level() {
+        let mut game = game_with(GameMode::Marathon, Rules::default(), &[BlockType::T], &[]);
+        game.add_garbage(9, 0);
+        assert_eq!(game.danger_level(), DangerLevel::Safe);
+        game.add_garbage(1, 0);
+        assert_eq!(game.danger_level(), DangerLevel::Warning);
+        assert!(!game.events.contains(&GameEvent::DangerEntered));
+        game.add_garbage(5, 0);
+        assert_eq!(game.danger_level(), DangerLevel::Critical);
+        assert!(game.events.contains(&GameEvent::DangerEntered));
+    }
+
+    #[test]
+    fn the_lock_flash_clears_after_its_duration() {
+        let mut game = game_with(
+            GameMode::Marathon,
+            Rules::default(),
+            &[BlockType::O; 2],
+            &[],
+        );
+        let clock = manual_clock(&mut game);
+        drop_o_right(&mut game);
+        let flash = game.lock_flash.clone().unwrap();
+        assert!(flash.is_active_at(flash.at));
+        assert!(game.flashing(9, BOARD_HEIGHT - 1));
+        assert!(!game.flashing(0, BOARD_HEIGHT - 1));
+
+        clock.advance(LOCK_FLASH - Duration::from_millis(1));
+        assert!(game.flashing(9, BOARD_HEIGHT - 1));
+        clock.advance(Duration::from_millis(1));
+        assert!(!game.flashing(9, BOARD_HEIGHT - 1));
+        assert!(!flash.is_active_at(flash.at + LOCK_FLASH));
+    }
+
+    #[test]
+    fn the_t_spawns_where_its_rule_puts_it() {
+        let cells = |spawn| {
+            let mut cells = ActivePiece::new(BlockType::T, spawn).cells();
+            cells.sort();
+            cells
+        };
+        assert_eq!(
+            cells(SpawnRule::default()),
+            [(3, 0), (4, -1), (4, 0), (5, 0)]
+        );
+        let flat_side_down = SpawnRule {
+            column: 3,
+            row: -1,
+            rotation: 2,
+        };
+        assert_eq!(cells(flat_side_down), [(3, 0), (4, 0), (4, 1), (5, 0)]);
+        let left_wall = SpawnRule {
+            column: 0,
+            row: 2,
+            rotation: 1,
+        };
+        assert_eq!(cells(left_wall), [(1, 2), (1, 3), (1, 4), (2, 3)]);
+    }
+
+    #[test]
+    fn a_game_spawns_each_piece_by_its_own_rule() {
+        let flat_side_down = SpawnRule {
+            column: 3,
+            row: -1,
+            rotation: 2,
+        };
+        let mut rules = Rules::default();
+        rules.spawn.insert(BlockType::T, flat_side_down);
+        let mut game = game_with(
+            GameMode::Marathon,
+            rules,
+            &[BlockType::T, BlockType::O, BlockType::T],
+            &[],
+        );
+        assert_eq!(
+            game.current.cells(),
+            ActivePiece::new(BlockType::T, flat_side_down).cells()
+        );
+        game.apply(Action::HardDrop);
+        settle(&mut game);
+        assert_eq!(game.current.tetro.kind, BlockType::O);
+        assert_eq!(
+            game.current.cells(),
+            ActivePiece::new(BlockType::O, SpawnRule::default()).cells()
+        );
+    }
+
+    #[test]
+    fn every_piece_has_its_own_glyph() {
+        let mut glyphs: Vec<&str> = BlockType::all().iter().map(|kind| kind.glyph()).collect();
+        glyphs.push(BlockType::Garbage.glyph());
+        assert!(glyphs.iter().all(|glyph| glyph.chars().count() == 2));
+        glyphs.sort();
+        glyphs.dedup();
+        assert_eq!(glyphs.len(), BlockType::all().len() + 1);
+    }
+}
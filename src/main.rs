@@ -1,398 +1,566 @@
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event as CEvent, KeyCode, KeyEvent},
+    event::{
+        self, DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture,
+        Event as CEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
     execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+    terminal::{
+        EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+        supports_keyboard_enhancement,
+    },
 };
-use rand::prelude::*;
 use ratatui::{
     Terminal,
     backend::CrosstermBackend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
+    symbols::Marker,
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap, canvas::Canvas},
 };
 use std::{
-    cmp::max,
-    io,
-    sync::mpsc,
+    io::{self, BufRead, Write},
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
     thread,
     time::{Duration, Instant},
 };
 
-/// Board dimensions (classic Tetris is 10x20)
-const BOARD_WIDTH: usize = 10;
-const BOARD_HEIGHT: usize = 20;
-
-/// Represent each block cell as Option<BlockType>
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-enum BlockType {
-    I,
-    O,
-    T,
-    S,
-    Z,
-    J,
-    L,
-}
-
-impl BlockType {
-    fn all() -> &'static [BlockType] {
-        &[
-            BlockType::I,
-            BlockType::O,
-            BlockType::T,
-            BlockType::S,
-            BlockType::Z,
-            BlockType::J,
-            BlockType::L,
-        ]
-    }
-
-    fn color(self) -> Color {
-        match self {
-            BlockType::I => Color::Cyan,
-            BlockType::O => Color::Yellow,
-            BlockType::T => Color::Magenta,
-            BlockType::S => Color::Green,
-            BlockType::Z => Color::Red,
-            BlockType::J => Color::Blue,
-            BlockType::L => Color::Rgb(255, 165, 0), // orange
-        }
-    }
-}
-
-/// A Tetromino has rotations represented as 4x4 bool grids (flattened).
-#[derive(Clone)]
-struct Tetromino {
-    kind: BlockType,
-    rotations: Vec<[u8; 16]>, // each rotation is 4x4 grid, row-major; 1 = block, 0 = empty
-}
-
-impl Tetromino {
-    fn new(kind: BlockType) -> Self {
-        let rotations = match kind {
-            BlockType::I => vec![
-                // ----  4x4
-                // ....  rotated forms
-                // ####
-                // ....
-                // ....
-                // ....
-                [0, 0, 0, 0, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0],
-                [0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0],
-            ],
-            BlockType::O => vec![[0, 1, 1, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]],
-            BlockType::T => vec![
-                [0, 1, 0, 0, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-                [0, 1, 0, 0, 0, 1, 1, 0, 0, 1, 0, 0, 0, 0, 0, 0],
-                [0, 0, 0, 0, 1, 1, 1, 0, 0, 1, 0, 0, 0, 0, 0, 0],
-                [0, 1, 0, 0, 1, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0],
-            ],
-            BlockType::S => vec![
-                [0, 1, 1, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-                [0, 1, 0, 0, 0, 1, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0],
-            ],
-            BlockType::Z => vec![
-                [1, 1, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-                [0, 0, 1, 0, 0, 1, 1, 0, 0, 1, 0, 0, 0, 0, 0, 0],
-            ],
-            BlockType::J => vec![
-                [1, 0, 0, 0, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-                [0, 1, 1, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0],
-                [0, 0, 0, 0, 1, 1, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0],
-                [0, 1, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0],
-            ],
-            BlockType::L => vec![
-                [0, 0, 1, 0, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-                [0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0],
-                [0, 0, 0, 0, 1, 1, 1, 0, 1, 0, 0, 0, 0, 0, 0, 0],
-                [1, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0],
-            ],
-        };
+mod accessibility;
+mod achievements;
+mod braille;
+mod broadcast;
+mod career;
+mod cli;
+mod clipboard;
+mod editor;
+mod glyphs;
+mod input;
+mod input_display;
+mod leaderboard;
+#[cfg(feature = "net")]
+mod matchmaking;
+#[cfg(feature = "net")]
+mod netplay;
+mod options;
+mod practice;
+mod profiles;
+mod records;
+mod session;
+mod sound;
+mod sparkline;
+mod versus;
 
-        Tetromino { kind, rotations }
-    }
-}
+use accessibility::Announcer;
+use braille::{BrailleBoard, BrailleCell};
+use broadcast::Broadcaster;
+use career::CareerStats;
+use glyphs::{AsciiOnly, Glyphs};
+use input::InputState;
+use input_display::InputDisplay;
+use practice::{Practice, SUGGESTION_COLOR};
+use session::Session;
+use sound::SoundEvent;
+use sparkline::Sparkline;
+use tetris_game::{
+    Action, ActivePiece, BOARD_HEIGHT, BOARD_WIDTH, BlockType, Board, DangerLevel,
+    EFFICIENCY_MIN_PIECES, Edges, Game, GameEvent, GameMode, GameOverReason, GameSetup,
+    SPRINT_LINES, SPRINT_SPLIT_LINES, TICK, Tetromino, ULTRA_TIME,
+    analysis::ReplayAnalyzer,
+    config::{Config, HIGH_CONTRAST, KeysConfig, THEMES},
+    daily::{self, DailyResult},
+    event_log::EventLog,
+    keymap::{Command, Keymap},
+    layout,
+    milestones::Notification,
+    paths,
+    position::{self, Position},
+    puzzle::{self, PuzzleDef},
+    replay::ReplayPlayer,
+    rules::Rules,
+    save, save_replay,
+    scores::{self, Leaderboard},
+    sprint::{self, SprintBest},
+    tgr,
+    theme::Theme,
+    tutorial::{TutorialState, TutorialStep},
+};
+
+/// How far Left/Right jump while watching a replay (10 seconds of game time)
+const SEEK_TICKS: i64 = 500;
+
+/// How often a running game is snapshotted for crash recovery
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Without key release reports, the hold preview lasts this long after each
+/// press; the OS key repeat keeps it up while the key is held
+const HOLD_PREVIEW_LINGER: Duration = Duration::from_millis(600);
 
-/// Active piece in play with position and rotation index
-#[derive(Clone)]
-struct ActivePiece {
-    tetro: Tetromino,
-    rotation: usize,
-    x: i32, // position on board (x,y refer to top-left of 4x4)
-    y: i32,
+/// Characters of a position code per sidebar line, narrow enough for an 80-column terminal
+const POSITION_CODE_WIDTH: usize = 20;
+
+/// Clears in the History box dim once they're this old
+const HISTORY_FADE: Duration = Duration::from_secs(30);
+
+enum InternalEvent {
+    Input(KeyEvent),
+    Tick,
+    /// The terminal gained (`true`) or lost focus
+    Focus(bool),
 }
 
-impl ActivePiece {
-    fn new(kind: BlockType) -> Self {
-        let tetro = Tetromino::new(kind);
-        // spawn near top center
-        ActivePiece {
-            tetro,
-            rotation: 0,
-            x: (BOARD_WIDTH as i32 / 2) - 2,
-            y: -1, // allow spawn partially above the visible board
-        }
-    }
+/// Pauses a game when the terminal loses focus and resumes it when focus
+/// comes back, unless something else paused it first
+#[derive(Default)]
+struct AutoPause {
+    /// The game is paused because focus went
+    paused: bool,
+}
 
-    fn cells(&self) -> Vec<(i32, i32)> {
-        let grid = &self.tetro.rotations[self.rotation % self.tetro.rotations.len()];
-        let mut out = Vec::new();
-        for by in 0..4 {
-            for bx in 0..4 {
-                if grid[(by * 4 + bx) as usize] != 0 {
-                    out.push((self.x + bx as i32, self.y + by as i32));
-                }
+impl AutoPause {
+    fn focus(&mut self, game: &mut Game, focused: bool) {
+        if focused {
+            if std::mem::take(&mut self.paused) {
+                game.set_paused(false);
             }
+        } else if !game.paused && !game.game_over && !game.setup.mode.is_daily() {
+            game.set_paused(true);
+            self.paused = true;
         }
-        out
     }
+}
+
+/// Ctrl-Z, which raw mode delivers as a key rather than a SIGTSTP
+fn is_suspend(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Char('z') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
 
-    fn rotate_cw(&mut self) {
-        self.rotation = (self.rotation + 1) % self.tetro.rotations.len();
+/// Give the terminal back to the shell and stop, as Ctrl-Z would outside raw
+/// mode, then take it over again once the shell continues the game
+#[cfg(unix)]
+fn suspend(terminal: &mut Term, release_events: bool) -> io::Result<()> {
+    if release_events {
+        execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags)?;
+    }
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableFocusChange
+    )?;
+    terminal.show_cursor()?;
+    signal_hook::low_level::raise(signal_hook::consts::SIGSTOP)?;
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableFocusChange
+    )?;
+    if release_events {
+        execute!(
+            terminal.backend_mut(),
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+        )?;
     }
+    terminal.clear()
+}
 
-    fn rotate_ccw(&mut self) {
-        if self.rotation == 0 {
-            self.rotation = self.tetro.rotations.len() - 1;
-        } else {
-            self.rotation -= 1;
-        }
+/// A `width`-character bar filled to `fraction`
+fn progress_bar(fraction: f64, width: usize) -> String {
+    let filled = ((fraction.clamp(0.0, 1.0) * width as f64).round() as usize).min(width);
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
+
+/// "DAS → ████░░░░" while a move key charges, "DAS → CHARGED" once it repeats
+fn das_line(action: Action, charge: f64) -> Line<'static> {
+    let arrow = if action == Action::MoveLeft {
+        "←"
+    } else {
+        "→"
+    };
+    if charge >= 1.0 {
+        Line::from(vec![
+            Span::raw(format!("DAS {} ", arrow)),
+            Span::styled(
+                "CHARGED",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ])
+    } else {
+        Line::from(format!("DAS {} {}", arrow, progress_bar(charge, 10)))
     }
 }
 
-/// Game state
-struct Game {
-    board: [[Option<BlockType>; BOARD_WIDTH]; BOARD_HEIGHT],
-    rng: ThreadRng,
-    current: ActivePiece,
-    next: BlockType,
-    score: usize,
-    level: usize,
-    lines_cleared: usize,
-    start_time: Instant,
-    paused: bool,
-    game_over: bool,
-    last_drop_instant: Instant,
-    gravity_interval: Duration,
-}
-
-impl Game {
-    fn new() -> Self {
-        let mut rng = thread_rng();
-        let next = *BlockType::all().choose(&mut rng).unwrap();
-        let current_kind = *BlockType::all().choose(&mut rng).unwrap();
-        let gravity_interval = Game::interval_for_level(1);
-        Game {
-            board: [[None; BOARD_WIDTH]; BOARD_HEIGHT],
-            rng,
-            current: ActivePiece::new(current_kind),
-            next,
-            score: 0,
-            level: 1,
-            lines_cleared: 0,
-            start_time: Instant::now(),
-            paused: false,
-            game_over: false,
-            last_drop_instant: Instant::now(),
-            gravity_interval,
-        }
-    }
-
-    fn interval_for_level(level: usize) -> Duration {
-        // simple formula: base 700ms, reduce by level (cap at 50ms)
-        let base_ms = 700i32;
-        let ms = base_ms - ((level as i32 - 1) * 50);
-        let ms = max(ms, 60);
-        Duration::from_millis(ms as u64)
-    }
-
-    fn spawn_next(&mut self) {
-        self.current = ActivePiece::new(self.next);
-        self.next = *BlockType::all().choose(&mut self.rng).unwrap();
-        // if spawn collides immediately -> game over
-        if self.check_collision(&self.current, 0, 0) {
-            self.game_over = true;
-        }
-    }
-
-    fn check_collision(&self, piece: &ActivePiece, dx: i32, dy: i32) -> bool {
-        for (x, y) in piece.cells() {
-            let nx = x + dx;
-            let ny = y + dy;
-            if nx < 0 || nx >= BOARD_WIDTH as i32 {
-                return true;
-            }
-            if ny >= BOARD_HEIGHT as i32 {
-                return true;
-            }
-            if ny >= 0 {
-                if let Some(_) = self.board[ny as usize][nx as usize] {
-                    return true;
-                }
+fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    let minutes = secs / 60;
+    let seconds = secs % 60;
+    format!("{:02}:{:02}", minutes, seconds)
+}
+
+type Term = Terminal<CrosstermBackend<io::Stdout>>;
+
+/// Frontend side effects of game events: sound cues and screen-reader announcements
+struct Outputs {
+    sound_tx: Option<mpsc::Sender<SoundEvent>>,
+    announcer: Option<Announcer>,
+    /// Spectators watching over `--ws-broadcast`
+    broadcaster: Option<Broadcaster>,
+}
+
+impl Outputs {
+    /// Forward the game's pending events and hand them back for mode-specific handling
+    fn dispatch(&mut self, game: &mut Game) -> Vec<GameEvent> {
+        let events = game.drain_events();
+        for ev in &events {
+            if let Some(tx) = &self.sound_tx
+                && let Some(sound) = SoundEvent::from_game_event(ev)
+            {
+                let _ = tx.send(sound);
+            }
+            if let Some(announcer) = &mut self.announcer {
+                announcer.on_event(ev, game);
             }
         }
-        false
+        if let Some(announcer) = &mut self.announcer {
+            announcer.tick(game);
+        }
+        events
     }
+}
 
-    fn lock_piece(&mut self) {
-        let kind = self.current.tetro.kind;
-        for (x, y) in self.current.cells() {
-            if y >= 0 && y < BOARD_HEIGHT as i32 && x >= 0 && x < BOARD_WIDTH as i32 {
-                self.board[y as usize][x as usize] = Some(kind);
+/// One frame at ~60fps
+const FRAME: Duration = Duration::from_millis(16);
+
+/// Everything that arrives on `rx` until the frame begun at `last_frame` is
+/// up, then start the next one. Blocks on the channel in between rather than
+/// sleeping, so an idle game doesn't spin and a key is taken as it comes.
+fn frame_events<T>(rx: &mpsc::Receiver<T>, last_frame: &mut Instant) -> Vec<T> {
+    let deadline = *last_frame + FRAME;
+    let mut events = Vec::new();
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        match rx.recv_timeout(left) {
+            Ok(ev) => events.push(ev),
+            Err(mpsc::RecvTimeoutError::Timeout) => break,
+            // nothing more will come, but the frame still runs its length
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                thread::sleep(left);
+                break;
             }
         }
-        self.clear_full_lines();
-        self.spawn_next();
-        self.last_drop_instant = Instant::now();
     }
+    *last_frame = Instant::now();
+    events
+}
 
-    fn hard_drop(&mut self) {
-        while !self.check_collision(&self.current, 0, 1) {
-            self.current.y += 1;
+fn main() -> Result<(), io::Error> {
+    let args = match cli::Args::parse() {
+        Ok(args) => args,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            std::process::exit(2);
         }
-        self.lock_piece();
+    };
+    if args.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
     }
-
-    fn step(&mut self) {
-        if self.paused || self.game_over {
-            return;
+    if let Some((from, to)) = &args.convert_replay {
+        let name = args.profile.as_deref().unwrap_or_default();
+        if let Err(e) = tgr::convert(from, to, name) {
+            eprintln!("could not convert {}: {}", from.display(), e);
+            std::process::exit(1);
         }
-        if self.last_drop_instant.elapsed() >= self.gravity_interval {
-            if !self.check_collision(&self.current, 0, 1) {
-                self.current.y += 1;
-            } else {
-                // unlock to board
-                self.lock_piece();
-            }
-            self.last_drop_instant = Instant::now();
+        println!("wrote {}", to.display());
+        return Ok(());
+    }
+    if args.write_default_config {
+        let Some(path) = args.config.clone().or_else(Config::default_path) else {
+            eprintln!("no config directory; pass --config <FILE>");
+            std::process::exit(1);
+        };
+        if let Err(e) = Config::write_default(&path) {
+            eprintln!("could not write {}: {}", path.display(), e);
+            std::process::exit(1);
         }
+        println!("wrote default settings to {}", path.display());
+        return Ok(());
     }
-
-    fn move_left(&mut self) {
-        if !self.check_collision(&self.current, -1, 0) {
-            self.current.x -= 1;
+    #[cfg(feature = "net")]
+    if let Some(port) = args.serve_matches {
+        if let Err(e) = matchmaking::serve(port) {
+            eprintln!("matchmaking server stopped: {}", e);
+            std::process::exit(1);
         }
+        return Ok(());
     }
 
-    fn move_right(&mut self) {
-        if !self.check_collision(&self.current, 1, 0) {
-            self.current.x += 1;
+    let profile = match args.profile.clone() {
+        Some(name) => Some(name),
+        // replays and versus games don't touch saved data, so they skip the picker
+        None if args.replay.is_none() && !args.two_player() && !profiles::list().is_empty() => {
+            match standalone_screen(profiles::choose)? {
+                profiles::Selection::Default => None,
+                profiles::Selection::Profile(name) => Some(name),
+                profiles::Selection::Quit => return Ok(()),
+            }
+        }
+        None => None,
+    };
+    if let Some(name) = &profile {
+        if let Err(e) = profiles::create(name) {
+            eprintln!("could not create profile '{}': {}", name, e);
+            std::process::exit(1);
         }
+        paths::set_profile(name);
     }
 
-    fn move_down(&mut self) {
-        if !self.check_collision(&self.current, 0, 1) {
-            self.current.y += 1;
-            // small score for soft drop
-            self.score += 1;
-        } else {
-            // lock if can't move down
-            self.lock_piece();
-        }
+    if args.achievements {
+        return standalone_screen(achievements::show);
+    }
+    if args.records {
+        return standalone_screen(records::show);
+    }
+    if args.leaderboard {
+        return standalone_screen(leaderboard::show);
+    }
+    if args.career {
+        let career = CareerStats::load();
+        return standalone_screen(|t| career::show(t, career.as_ref()));
+    }
+    if args.puzzles {
+        list_puzzles();
+        return Ok(());
     }
 
-    fn rotate_cw(&mut self) {
-        let mut test = self.current.clone();
-        test.rotate_cw();
-        // simple wall-kick: try no offset, left, right, up
-        let kicks = [(0, 0), (-1, 0), (1, 0), (0, -1)];
-        for (dx, dy) in &kicks {
-            if !self.check_collision(&test, *dx, *dy) {
-                self.current = test;
-                self.current.x += dx;
-                self.current.y += dy;
-                break;
+    let overrides = profile.as_deref().and_then(profiles::config_path);
+    let mut config = Config::load(args.config.as_deref(), overrides.as_deref());
+    config.ui.grid |= args.grid;
+    config.ui.outline_blocks |= args.outline_blocks;
+    config.ui.half_block |= args.halfblock;
+    config.ui.braille |= args.braille;
+    config.ui.ascii |= args.ascii;
+    if args.preview.is_some() {
+        config.ui.preview = args.preview;
+    }
+    if let Some(theme) = &args.theme {
+        config.ui.theme = theme.clone();
+    }
+    if args.high_contrast {
+        config.ui.theme = HIGH_CONTRAST.to_string();
+    }
+    // settings screen changes go to the profile's own file when there is one
+    let settings_path = overrides
+        .clone()
+        .or_else(|| args.config.clone().or_else(Config::default_path));
+    if args.settings {
+        return standalone_screen(|t| options::show(t, &mut config, settings_path.as_deref()));
+    }
+    let keymap = match Keymap::from_config(&config.keys) {
+        Ok(keymap) if args.mirror => keymap.mirrored(),
+        Ok(keymap) => keymap,
+        Err(e) => {
+            eprintln!("invalid [keys] in config:\n{}", e);
+            std::process::exit(2);
+        }
+    };
+    let mode_name = args.mode.as_deref().unwrap_or(&config.general.mode);
+    let cheese_rows = args.cheese_rows.unwrap_or(config.general.cheese_rows);
+    // both sources are validated, so the name is always known
+    let mode = GameMode::from_name(mode_name, cheese_rows).unwrap_or(GameMode::Marathon);
+    let mut rules = Rules::from_config(&config);
+    rules.fair_start = args.fair_start;
+    rules.soft_drop_locks &= !args.no_lock_on_soft_drop;
+    if let Some(gravity) = args.clear_gravity {
+        rules.clear_gravity = gravity;
+    }
+    if let Some(path) = &args.board {
+        match layout::load(path) {
+            Ok(board) => rules.start_board = Some(Box::new(board)),
+            Err(e) => {
+                eprintln!("could not load board {}: {}", path.display(), e);
+                std::process::exit(1);
             }
         }
     }
-
-    fn rotate_ccw(&mut self) {
-        let mut test = self.current.clone();
-        test.rotate_ccw();
-        let kicks = [(0, 0), (-1, 0), (1, 0), (0, -1)];
-        for (dx, dy) in &kicks {
-            if !self.check_collision(&test, *dx, *dy) {
-                self.current = test;
-                self.current.x += dx;
-                self.current.y += dy;
-                break;
+    if let Some(code) = &args.position {
+        match Position::from_code(code) {
+            Ok(position) => rules.start_position = Some(Box::new(position)),
+            Err(e) => {
+                eprintln!("could not read position code: {}", e);
+                std::process::exit(1);
             }
         }
     }
+    if let Some(path) = &args.edit {
+        match standalone_screen(|t| editor::run(t, path, &config.theme()))? {
+            Some(board) => rules.start_board = Some(Box::new(board)),
+            None => return Ok(()),
+        }
+    }
+    let puzzle_def = args.puzzle.map(|id| {
+        let (puzzles, problems) = puzzle::all();
+        for problem in problems {
+            eprintln!("skipping puzzle {}", problem);
+        }
+        puzzles
+            .into_iter()
+            .find(|def| def.id == id)
+            .unwrap_or_else(|| {
+                eprintln!("there is no puzzle {} (see --puzzles)", id);
+                std::process::exit(1);
+            })
+    });
+    let custom_board =
+        rules.start_board.is_some() || rules.start_position.is_some() || puzzle_def.is_some();
 
-    fn clear_full_lines(&mut self) {
-        let mut new_board = [[None; BOARD_WIDTH]; BOARD_HEIGHT];
-        let mut new_row = BOARD_HEIGHT as i32 - 1;
-        let mut removed = 0usize;
+    let playback = match &args.replay {
+        Some(path) => match tgr::load_any(path) {
+            Ok(replay) => Some(ReplayPlayer::new(replay)),
+            Err(e) => {
+                eprintln!("could not load replay {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
 
-        for y in (0..BOARD_HEIGHT).rev() {
-            let mut full = true;
-            for x in 0..BOARD_WIDTH {
-                if self.board[y][x].is_none() {
-                    full = false;
-                    break;
-                }
+    // Create game (a replay re-simulates the recorded one)
+    let game = if let Some(player) = &playback {
+        player.new_game()
+    } else if args.resume {
+        match save::take_saved_game() {
+            Ok(mut game) => {
+                // give the player a moment before it starts falling again
+                game.set_paused(true);
+                game
             }
-            if !full {
-                // copy this row to new_row
-                for x in 0..BOARD_WIDTH {
-                    new_board[new_row as usize][x] = self.board[y][x];
-                }
-                new_row -= 1;
-            } else {
-                removed += 1;
+            Err(e) => {
+                eprintln!("could not resume: {}", e);
+                std::process::exit(1);
             }
         }
-
-        if removed > 0 {
-            // scoring: classic-ish: 1->100, 2->300, 3->500, 4->800 times level
-            let points = match removed {
-                1 => 100,
-                2 => 300,
-                3 => 500,
-                _ => 800,
-            } * self.level;
-            self.score += points as usize;
-            self.lines_cleared += removed;
-            // level up every 10 lines
-            let new_level = (self.lines_cleared / 10) + 1;
-            if new_level != self.level {
-                self.level = new_level;
-                self.gravity_interval = Game::interval_for_level(self.level);
+    } else if let Some(game) = (!args.two_player() && !custom_board)
+        .then(recover_interrupted)
+        .flatten()
+    {
+        game
+    } else if let Some(def) = &puzzle_def {
+        match def.setup(&rules) {
+            Ok(setup) => Game::new(setup),
+            Err(e) => {
+                eprintln!("puzzle {} is broken: {}", def.id, e);
+                std::process::exit(1);
             }
-            // replace board
-            self.board = new_board;
         }
-    }
+    } else if let GameMode::DailyChallenge { number } = mode {
+        if let Some(result) = daily::result_for(number) {
+            println!("Today's challenge is done, come back tomorrow!");
+            println!("{}", result.share_text(number));
+            return Ok(());
+        }
+        // everyone plays the same pieces under the same rules
+        Game::new(GameSetup {
+            mode,
+            rules: Rules::default(),
+            seed: Some(daily::seed(&daily::date_of(number))),
+        })
+    } else {
+        Game::new(GameSetup {
+            mode,
+            rules: rules.clone(),
+            seed: args.seed,
+        })
+    };
 
-    fn reset(&mut self) {
-        *self = Game::new();
+    let mut game = game;
+    if args.debug {
+        game.record_state_hashes();
     }
-
-    fn elapsed(&self) -> Duration {
-        self.start_time.elapsed()
+    if let Some(path) = &args.log_events {
+        match EventLog::open(path) {
+            Ok(log) => game.set_event_log(log),
+            Err(e) => {
+                eprintln!("could not open event log {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
     }
-}
+    // read before the terminal is taken over, so a problem with the file shows plainly
+    let mut session = Session::new();
 
-enum InternalEvent {
-    Input(KeyEvent),
-    Tick,
-}
+    let mut outputs = Outputs {
+        // Sound runs on its own thread; muted games simply don't start it
+        sound_tx: if args.mute {
+            None
+        } else {
+            Some(sound::spawn())
+        },
+        announcer: (args.accessibility || config.ui.accessibility_mode).then(Announcer::new),
+        broadcaster: match args.ws_broadcast {
+            Some(port) => match Broadcaster::start(port) {
+                Ok(broadcaster) => Some(broadcaster),
+                Err(e) => {
+                    eprintln!("could not listen on port {}: {}", port, e);
+                    std::process::exit(1);
+                }
+            },
+            None => None,
+        },
+    };
 
-fn format_duration(d: Duration) -> String {
-    let secs = d.as_secs();
-    let minutes = secs / 60;
-    let seconds = secs % 60;
-    format!("{:02}:{:02}", minutes, seconds)
-}
+    // the other player is found before the terminal is taken over, so waiting shows plainly
+    #[cfg(feature = "net")]
+    let net_stream = match (args.host, &args.connect) {
+        (Some(port), _) => Some(netplay::host(port)),
+        (None, Some(addr)) => Some(netplay::connect(addr.as_str())),
+        (None, None) => None,
+    }
+    .transpose()
+    .unwrap_or_else(|e| {
+        eprintln!("could not start a network game: {}", e);
+        std::process::exit(1);
+    });
+    #[cfg(feature = "net")]
+    let net_match = args.match_addr.as_deref().map(|addr| {
+        let name = args.name.clone().unwrap_or_else(matchmaking::default_name);
+        println!("waiting at {} for an opponent...", addr);
+        matchmaking::MatchClient::connect(addr, &name)
+            .and_then(|mut client| Ok((client.wait_for_match()?, client)))
+            .unwrap_or_else(|e| {
+                eprintln!("could not join a match at {}: {}", addr, e);
+                std::process::exit(1);
+            })
+    });
 
-fn main() -> Result<(), io::Error> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableFocusChange
+    )?;
+    // key release events (for held-key features like IRS) need the enhanced keyboard protocol
+    let release_events = supports_keyboard_enhancement().unwrap_or(false);
+    if release_events {
+        execute!(
+            stdout,
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+        )?;
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
@@ -404,9 +572,13 @@ fn main() -> Result<(), io::Error> {
     thread::spawn(move || {
         loop {
             if event::poll(Duration::from_millis(50)).unwrap() {
-                if let CEvent::Key(k) = event::read().unwrap() {
-                    tx2.send(InternalEvent::Input(k)).unwrap();
-                }
+                let ev = match event::read().unwrap() {
+                    CEvent::Key(k) => InternalEvent::Input(k),
+                    CEvent::FocusGained => InternalEvent::Focus(true),
+                    CEvent::FocusLost => InternalEvent::Focus(false),
+                    _ => continue,
+                };
+                tx2.send(ev).unwrap();
             }
             // small sleep to avoid busy loop
             thread::sleep(Duration::from_millis(10));
@@ -418,137 +590,1588 @@ fn main() -> Result<(), io::Error> {
     thread::spawn(move || {
         loop {
             tx3.send(InternalEvent::Tick).unwrap();
-            thread::sleep(Duration::from_millis(20));
+            thread::sleep(TICK);
         }
     });
 
-    // Create game
-    let mut game = Game::new();
+    // SSH drops and window closes arrive as signals; the game loop notices and snapshots
+    let terminated = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    for signal in [signal_hook::consts::SIGHUP, signal_hook::consts::SIGTERM] {
+        signal_hook::flag::register(signal, Arc::clone(&terminated))?;
+    }
+    // a SIGTSTP from outside pauses before stopping, like Ctrl-Z
+    let suspended = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    signal_hook::flag::register(signal_hook::consts::SIGTSTP, Arc::clone(&suspended))?;
+
+    let live = playback.is_none();
+    let watched = playback.as_ref().map(|player| player.replay.clone());
+    let outcome = if args.versus {
+        Ok(versus::run(
+            &mut terminal,
+            &rx,
+            &mut outputs,
+            rules,
+            &config,
+        ))
+    } else if args.two_player() {
+        // only reachable with the net feature; the command line rejects the flags otherwise
+        #[cfg_attr(not(feature = "net"), allow(unused_mut))]
+        let mut outcome = Ok(Ok(()));
+        #[cfg(feature = "net")]
+        if let Some(stream) = net_stream {
+            outcome = Ok(netplay::run(
+                &mut terminal,
+                &rx,
+                &mut outputs,
+                stream,
+                rules,
+                &config,
+                &keymap,
+            ));
+        }
+        #[cfg(feature = "net")]
+        if let Some((matched, client)) = net_match {
+            outcome = Ok(matchmaking::run(
+                &mut terminal,
+                &rx,
+                &mut outputs,
+                client,
+                matched,
+                &config,
+                &keymap,
+            ));
+        }
+        outcome
+    } else {
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            let play = PlayState::new(&mut session, playback, args.practice, &game);
+            run_game(
+                &mut terminal,
+                &rx,
+                &mut outputs,
+                &mut game,
+                play,
+                RunOptions {
+                    config: &config,
+                    keymap: &keymap,
+                    export_svg: args.export_svg.as_deref(),
+                    debug: args.debug,
+                    settings_path: settings_path.as_deref(),
+                    release_events,
+                    terminated: &terminated,
+                    suspended: &suspended,
+                },
+            )
+        }))
+    };
+    // anything but a clean exit leaves a snapshot behind for the next start
+    if live && !args.two_player() && !matches!(outcome, Ok(Ok(()))) {
+        let _ = save::autosave(&game);
+    }
+    if matches!(outcome, Ok(Ok(()))) && session.games > 0 && !args.no_summary {
+        session::show(&mut terminal, &rx, &session)?;
+    }
+
+    // cleanup and quit
+    if release_events {
+        execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags)?;
+    }
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableFocusChange
+    )?;
+    terminal.show_cursor()?;
+
+    if live
+        && let GameMode::DailyChallenge { number } = game.setup.mode
+        && matches!(outcome, Ok(Ok(())))
+    {
+        let result = DailyResult::of(&game);
+        if let Err(e) = daily::record(number, &result) {
+            eprintln!("failed to record the daily score: {}", e);
+        }
+        println!("{}", result.share_text(number));
+    }
+    for code in &session.positions {
+        println!("position: {}", code);
+    }
+    for problem in &session.problems {
+        eprintln!("{}", problem);
+    }
+    if let Some(replay) = &watched
+        && matches!(outcome, Ok(Ok(())))
+    {
+        print!("{}", ReplayAnalyzer::analyze(replay));
+    }
+
+    match outcome {
+        Ok(result) => result,
+        Err(panic) => panic::resume_unwind(panic),
+    }
+}
+
+/// Run a full-screen view that comes before (or instead of) a game in its own terminal session
+fn standalone_screen<T>(view: impl FnOnce(&mut Term) -> io::Result<T>) -> io::Result<T> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    let result = view(&mut terminal);
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    result
+}
+
+/// Print every puzzle with whether it's been solved and how often it's been tried
+fn list_puzzles() {
+    let (puzzles, problems) = puzzle::all();
+    for problem in problems {
+        eprintln!("skipping puzzle {}", problem);
+    }
+    let progress = puzzle::Progress::load();
+    for def in &puzzles {
+        let mark = if progress.is_completed(def.id) {
+            "✔"
+        } else {
+            " "
+        };
+        let attempts = match progress.attempts(def.id) {
+            0 => String::new(),
+            n => format!(" ({} attempts)", n),
+        };
+        println!(
+            "{} {:>3}  {} — {}{}",
+            mark, def.id, def.name, def.description, attempts
+        );
+    }
+    let solved = puzzles
+        .iter()
+        .filter(|def| progress.is_completed(def.id))
+        .count();
+    println!(
+        "\n{} of {} solved; play one with --puzzle <N>",
+        solved,
+        puzzles.len()
+    );
+}
+
+/// Offer to pick up a game a previous run left behind when it didn't exit cleanly
+fn recover_interrupted() -> Option<Game> {
+    if !save::has_autosave() {
+        return None;
+    }
+    print!("Recover interrupted game? (Y/n) ");
+    let _ = io::stdout().flush();
+    let mut answer = String::new();
+    let _ = io::stdin().lock().read_line(&mut answer);
+    if answer.trim().eq_ignore_ascii_case("n") {
+        let _ = save::discard_autosave();
+        return None;
+    }
+    match save::take_autosave() {
+        Ok(mut game) => {
+            game.set_paused(true);
+            Some(game)
+        }
+        Err(e) => {
+            eprintln!("could not recover: {}", e);
+            let _ = save::discard_autosave();
+            None
+        }
+    }
+}
+
+/// Key handling for `game`: the config's, with DAS and ARR fixed where its
+/// mode fixes them
+fn input_for(game: &Game, config: &Config, release_events: bool) -> InputState {
+    let mut gameplay = config.gameplay.clone();
+    if let Some((das, arr)) = game.setup.mode.fixed_handling() {
+        gameplay.das_ms = das;
+        gameplay.arr_ms = arr;
+    }
+    InputState::new(release_events, game.setup.rules.irs_enabled, &gameplay)
+}
+
+/// How a single-player run was started: settings that hold for every game
+/// in it, and the flags main's signal handlers raise
+struct RunOptions<'a> {
+    config: &'a Config,
+    keymap: &'a Keymap,
+    export_svg: Option<&'a Path>,
+    debug: bool,
+    /// Where the settings screen saves changes
+    settings_path: Option<&'a Path>,
+    release_events: bool,
+    terminated: &'a AtomicBool,
+    suspended: &'a AtomicBool,
+}
+
+/// What a single-player run keeps alongside its game: the session's tally,
+/// messages drawn over the board, and the modes layered on top of play
+struct PlayState<'a> {
+    session: &'a mut Session,
+    /// Set when watching a replay rather than playing
+    playback: Option<ReplayPlayer>,
+    overlays: Overlays,
+    auto_pause: AutoPause,
+    tutorial: Option<TutorialState>,
+    practice: Option<Practice>,
+    /// The puzzle being played, and the player's record across all of them
+    puzzle: Option<PuzzleDef>,
+    puzzle_progress: puzzle::Progress,
+}
+
+impl<'a> PlayState<'a> {
+    /// `game` is the first game of the run; `practice` asks for `--practice` advice
+    fn new(
+        session: &'a mut Session,
+        playback: Option<ReplayPlayer>,
+        practice: bool,
+        game: &Game,
+    ) -> Self {
+        let live = playback.is_none();
+        PlayState {
+            session,
+            overlays: Overlays::default(),
+            auto_pause: AutoPause::default(),
+            tutorial: (live && game.setup.mode == GameMode::Tutorial).then(TutorialState::default),
+            practice: (practice && live).then(Practice::default),
+            puzzle: match game.setup.mode {
+                GameMode::Puzzle { id } => puzzle::find(id),
+                _ => None,
+            },
+            puzzle_progress: puzzle::Progress::load(),
+            playback,
+        }
+    }
+
+    /// Tell the player about a failure now, in the Status box, and again once
+    /// the terminal is back, where the whole message can be read
+    fn report(&mut self, problem: String) {
+        self.overlays
+            .toasts
+            .push(Notification::failure(problem.clone()));
+        self.session.problems.push(problem);
+    }
+
+    /// Count a try at the puzzle being played, if it's played live
+    fn count_attempt(&mut self) {
+        if self.playback.is_none()
+            && let Some(def) = &self.puzzle
+            && let Err(e) = self.puzzle_progress.attempt(def.id)
+        {
+            self.session
+                .problems
+                .push(format!("failed to save puzzle progress: {}", e));
+        }
+    }
+}
+
+/// Single-player loop: live play, or watching a replay when `play.playback`
+/// is set (in which case `game` must come from `ReplayPlayer::new_game`)
+fn run_game(
+    terminal: &mut Term,
+    rx: &mpsc::Receiver<InternalEvent>,
+    outputs: &mut Outputs,
+    game: &mut Game,
+    mut play: PlayState,
+    options: RunOptions,
+) -> io::Result<()> {
+    let RunOptions {
+        config,
+        keymap,
+        export_svg,
+        debug,
+        settings_path,
+        release_events,
+        terminated,
+        suspended,
+    } = options;
+    let mut input = input_for(game, config, release_events);
+    // the board image is written once, when a game ends
+    let mut svg_pending = export_svg.is_some();
+    // so is the replay, along with dropping the autosave and saving records
+    let mut finish_pending = true;
+    // a saved game keeps recording its replay when resumed, so don't write it out
+    let mut saved = false;
+    let mut last_autosave = Instant::now();
+    let live = play.playback.is_none();
+    // only live play counts towards achievements
+    let mut achievements = live.then(achievements::Tracker::load);
+    let mut records = live.then(records::Tracker::load);
+    let mut sprint_best = sprint::load();
+    // the settings screen can change it mid-game
+    let mut config = config.clone();
+    let mut show_inputs = config.ui.input_display;
+    let mut show_heights = config.ui.column_heights;
+    // the hold preview key is down, since this press
+    let mut hold_preview: Option<Instant> = None;
+    let mut input_display = InputDisplay::default();
+    let mut show_hint = false;
+    let mut exported: Option<String> = None;
+    let mut clipboard = clipboard::Clipboard::default();
+    let mut show_help = false;
+    let mut history = HistoryView::default();
+    // the theme to go back to when high contrast is toggled off
+    let mut theme_before_contrast: Option<String> = None;
+    // whether the game was already paused when help opened, to leave it that way
+    let mut paused_before_help = false;
+    let mut leaderboard = Leaderboard::load();
+    // where the last finished game landed on its mode's leaderboard
+    let mut placed = None;
+    play.count_attempt();
 
     // Game loop
     let mut last_frame = Instant::now();
     loop {
+        if terminated.load(Ordering::Relaxed) {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "terminated"));
+        }
+        if suspended.swap(false, Ordering::Relaxed) {
+            // nobody can see the board while stopped; the daily plays on regardless
+            if !game.setup.mode.is_daily() {
+                game.set_paused(true);
+            }
+            if live {
+                let _ = save::autosave(game);
+                last_autosave = Instant::now();
+            }
+            #[cfg(unix)]
+            suspend(terminal, release_events)?;
+        }
+
         // draw UI
-        terminal.draw(|f| ui(f, &game)).unwrap();
+        input_display.held = input.held();
+        let widgets = Widgets {
+            sprint_best: sprint_best.as_ref(),
+            inputs: show_inputs.then_some(&input_display),
+            heights: show_heights,
+            hold_preview: hold_preview
+                .is_some_and(|since| release_events || since.elapsed() < HOLD_PREVIEW_LINGER),
+            das: input
+                .shifting()
+                .map(|action| (action, input.das_charge_pct())),
+            practice: play.practice.as_ref(),
+            puzzle: play.puzzle.as_ref().map(|def| PuzzleStatus {
+                def,
+                attempts: play.puzzle_progress.attempts(def.id),
+                solved_before: play.puzzle_progress.is_completed(def.id),
+                hint: show_hint,
+            }),
+            exported: exported.as_deref(),
+            debug,
+            help: show_help,
+            history: &history,
+            tutorial: play.tutorial.as_ref(),
+        };
+        terminal.draw(|f| {
+            ui(
+                f,
+                game,
+                play.playback.as_ref(),
+                &play.overlays,
+                &config,
+                keymap,
+                &widgets,
+            )
+        })?;
+        if let Some(broadcaster) = &outputs.broadcaster {
+            broadcaster.send(game);
+        }
 
         // handle events (non-blocking)
         let mut did_quit = false;
         // drain events available now
-        while let Ok(ev) = rx.try_recv() {
+        for ev in frame_events(rx, &mut last_frame) {
+            // releases only feed held-key tracking
+            if let InternalEvent::Input(key) = ev
+                && key.kind == KeyEventKind::Release
+            {
+                if keymap.command_for(&key) == Some(Command::HoldPreview) {
+                    hold_preview = None;
+                }
+                if play.playback.is_none()
+                    && let Some(action) = keymap.action_for(&key)
+                {
+                    for action in input.key(action, key.kind) {
+                        game.apply(action);
+                    }
+                }
+                continue;
+            }
             match ev {
-                InternalEvent::Input(key) => {
+                InternalEvent::Input(key) if is_suspend(&key) => {
+                    suspended.store(true, Ordering::Relaxed);
+                }
+                InternalEvent::Focus(focused) => {
+                    if live && config.gameplay.auto_pause {
+                        play.auto_pause.focus(game, focused);
+                    }
+                }
+                // watching a replay only allows pause, speed and quit
+                InternalEvent::Input(key) if let Some(player) = &mut play.playback => {
                     match key.code {
                         KeyCode::Char('q') => {
                             did_quit = true;
                         }
-                        KeyCode::Char('p') => {
-                            game.paused = !game.paused;
+                        KeyCode::Char('p') | KeyCode::Char(' ') => game.toggle_pause(),
+                        KeyCode::Char('+') | KeyCode::Char('=') | KeyCode::Up => player.faster(),
+                        KeyCode::Char('-') | KeyCode::Down => player.slower(),
+                        KeyCode::Left => player.seek(game, -SEEK_TICKS),
+                        KeyCode::Right => player.seek(game, SEEK_TICKS),
+                        _ => {}
+                    }
+                }
+                InternalEvent::Tick if let Some(player) = &mut play.playback => {
+                    let synced = player.desync.is_none();
+                    player.advance(game);
+                    // stop on a desync, so the viewer can decide whether to go on
+                    if synced && player.desync.is_some() {
+                        game.set_paused(true);
+                    }
+                }
+                // help covers the board, so only closing it and quitting get through
+                InternalEvent::Input(key) if show_help => {
+                    let command = keymap.command_for(&key);
+                    if command == Some(Command::Quit) {
+                        did_quit = true;
+                    } else if command == Some(Command::Help) || key.code == KeyCode::Esc {
+                        show_help = false;
+                        if !game.setup.mode.is_daily() {
+                            game.set_paused(paused_before_help);
                         }
-                        KeyCode::Char('r') => {
-                            if game.game_over {
-                                game.reset();
-                            } else {
-                                // allow restart mid-game
-                                game.reset();
-                            }
+                    }
+                }
+                // a focused History box takes the arrow keys from the game
+                InternalEvent::Input(key)
+                    if history.focused && matches!(key.code, KeyCode::Up | KeyCode::Down) =>
+                {
+                    if key.code == KeyCode::Up {
+                        history.scroll = history.scroll.saturating_sub(1);
+                    } else if history.scroll + 1 < game.clear_history.len() {
+                        history.scroll += 1;
+                    }
+                }
+                // the end of the tutorial offers a real game
+                InternalEvent::Input(key)
+                    if key.code == KeyCode::Enter
+                        && play
+                            .tutorial
+                            .as_ref()
+                            .is_some_and(|t| t.step == TutorialStep::Complete) =>
+                {
+                    *game = Game::new(GameSetup {
+                        mode: GameMode::Marathon,
+                        rules: game.setup.rules.clone(),
+                        seed: None,
+                    });
+                    play.tutorial = None;
+                    play.overlays = Overlays::default();
+                    history = HistoryView::default();
+                    finish_pending = true;
+                    input = input_for(game, &config, release_events);
+                    if let Some(tracker) = &mut achievements {
+                        tracker.new_game();
+                    }
+                }
+                InternalEvent::Input(key) => match keymap.command_for(&key) {
+                    Some(Command::Quit) => {
+                        did_quit = true;
+                    }
+                    // the daily challenge is one straight attempt
+                    Some(Command::Pause | Command::Restart | Command::SaveQuit)
+                        if game.setup.mode.is_daily() => {}
+                    Some(Command::Pause) => {
+                        game.toggle_pause();
+                        if game.paused {
+                            let _ = save::autosave(game);
+                            last_autosave = Instant::now();
                         }
-                        KeyCode::Left => {
-                            if !game.paused && !game.game_over {
-                                game.move_left();
-                            }
+                    }
+                    Some(Command::Restart) => {
+                        // allow restart mid-game as well as after game over
+                        play.session.record(game);
+                        // cleared first so failures saving the old game still show
+                        play.overlays = Overlays::default();
+                        if let Some(records) = &mut records
+                            && let Err(e) = records.finish(game)
+                        {
+                            play.report(format!("failed to save records: {}", e));
                         }
-                        KeyCode::Right => {
-                            if !game.paused && !game.game_over {
-                                game.move_right();
-                            }
+                        if config.general.save_replays
+                            && let Err(e) = save_replay(game)
+                        {
+                            play.report(format!("failed to save replay: {}", e));
                         }
-                        KeyCode::Down => {
-                            if !game.paused && !game.game_over {
-                                game.move_down();
-                                game.last_drop_instant = Instant::now(); // reset gravity timer after manual down
-                            }
+                        game.reset();
+                        if let Some(practice) = &mut play.practice {
+                            *practice = Practice::default();
                         }
-                        KeyCode::Up => {
-                            if !game.paused && !game.game_over {
-                                game.rotate_cw();
-                            }
+                        // a best set by the game just left is the one to race now
+                        sprint_best = sprint::load();
+                        play.count_attempt();
+                        show_hint = false;
+                        if let Some(tracker) = &mut achievements {
+                            tracker.new_game();
                         }
-                        KeyCode::Char('z') => {
-                            if !game.paused && !game.game_over {
-                                game.rotate_ccw();
-                            }
+                        svg_pending = export_svg.is_some();
+                        finish_pending = true;
+                        placed = None;
+                        history = HistoryView::default();
+                        if play.tutorial.is_some() {
+                            play.tutorial = Some(TutorialState::default());
+                        }
+                    }
+                    Some(Command::Leaderboard)
+                        if game.game_over
+                            && let Some(mode) = scores::mode_key(game.setup.mode) =>
+                    {
+                        leaderboard::show_after_game(terminal, rx, mode, placed)?;
+                    }
+                    Some(Command::SaveQuit) if !game.game_over => match save::save_game(game) {
+                        Ok(_) => {
+                            saved = true;
+                            did_quit = true;
+                        }
+                        Err(e) => play.report(format!("failed to save game: {}", e)),
+                    },
+                    Some(Command::ToggleInputs) => show_inputs = !show_inputs,
+                    Some(Command::ToggleHeights) => show_heights = !show_heights,
+                    Some(Command::FocusHistory) => history.focused = !history.focused,
+                    Some(Command::ToggleContrast) => {
+                        config.ui.theme = if config.ui.theme == HIGH_CONTRAST {
+                            theme_before_contrast
+                                .take()
+                                .unwrap_or_else(|| THEMES[0].to_string())
+                        } else {
+                            theme_before_contrast = Some(config.ui.theme.clone());
+                            HIGH_CONTRAST.to_string()
+                        };
+                    }
+                    Some(Command::HoldPreview) => hold_preview = Some(Instant::now()),
+                    Some(Command::Settings) if live && !game.setup.mode.is_daily() => {
+                        let paused = game.paused;
+                        game.set_paused(true);
+                        options::show_in_game(terminal, rx, &mut config, settings_path)?;
+                        input = input_for(game, &config, release_events);
+                        game.set_paused(paused);
+                    }
+                    Some(Command::Hint) => show_hint = true,
+                    Some(Command::Help) => {
+                        show_help = true;
+                        paused_before_help = game.paused;
+                        // the daily challenge can't be paused, help or not
+                        if !game.setup.mode.is_daily() {
+                            game.set_paused(true);
+                        }
+                    }
+                    Some(Command::ExportPosition) => {
+                        let code = Position::of(game).to_code();
+                        if let Err(e) = position::save(&code) {
+                            play.report(format!("failed to save the position: {}", e));
+                        }
+                        play.session.positions.push(code.clone());
+                        exported = Some(code);
+                    }
+                    Some(Command::CopyBoard) => {
+                        play.overlays
+                            .toasts
+                            .push(clipboard.copy_board(game, &mut play.session.problems));
+                    }
+                    // the daily challenge is one straight attempt at its own board
+                    Some(Command::PasteBoard) if live && !game.setup.mode.is_daily() => {
+                        play.overlays.toasts.push(clipboard.paste_board(game));
+                    }
+                    Some(Command::Play(action)) => {
+                        let pressed = key.kind == KeyEventKind::Press;
+                        if pressed {
+                            input_display.push(action);
                         }
-                        KeyCode::Char(' ') => {
-                            if !game.paused && !game.game_over {
-                                game.hard_drop();
+                        for action in input.key(action, key.kind) {
+                            if pressed {
+                                game.press(action);
+                            } else {
+                                game.apply(action);
+                            }
+                            if let Some(tutorial) = &mut play.tutorial {
+                                tutorial.update(Some(action), game);
                             }
                         }
-                        _ => {}
                     }
-                }
+                    _ => {}
+                },
                 InternalEvent::Tick => {
+                    for action in input.tick() {
+                        game.apply(action);
+                    }
                     // update game step based on elapsed since last frame
                     game.step();
+                    if let Some(tutorial) = &mut play.tutorial {
+                        tutorial.update(None, game);
+                    }
                 }
             }
         }
 
-        if did_quit {
-            // cleanup and quit
-            disable_raw_mode()?;
-            execute!(
-                terminal.backend_mut(),
-                LeaveAlternateScreen,
-                DisableMouseCapture
-            )?;
-            terminal.show_cursor()?;
-            break;
+        let events = outputs.dispatch(game);
+        if let Some(practice) = &mut play.practice {
+            practice.update(game, &events);
+        }
+        for ev in &events {
+            match *ev {
+                GameEvent::Milestone { threshold } => play
+                    .overlays
+                    .notifications
+                    .push(Notification::milestone(threshold)),
+                GameEvent::LinesCleared { points, t_spin, .. } => play
+                    .overlays
+                    .popups
+                    .push(Notification::score(points, t_spin)),
+                GameEvent::LevelUp { level } => {
+                    play.overlays.level_up = Some(Notification::level_up(level))
+                }
+                GameEvent::HardDropped {
+                    kind,
+                    rotation,
+                    x,
+                    y,
+                    rows,
+                } if !config.ui.reduced_motion => play
+                    .overlays
+                    .trails
+                    .push(DropTrail::new(kind, rotation, x, y, rows)),
+                GameEvent::GoalReached { .. } if play.playback.is_none() => {
+                    if let Err(e) = sprint::record(game) {
+                        play.report(format!("failed to save the sprint best: {}", e));
+                    }
+                    if let Some(id) = play.puzzle.as_ref().map(|def| def.id)
+                        && let Err(e) = play.puzzle_progress.complete(id)
+                    {
+                        play.report(format!("failed to save puzzle progress: {}", e));
+                    }
+                }
+                _ => {}
+            }
+        }
+        let finished = events
+            .iter()
+            .any(|ev| matches!(ev, GameEvent::GameOver | GameEvent::GoalReached { .. }));
+        if finished && play.playback.is_none() {
+            match leaderboard.record(game) {
+                Ok(place) => placed = place,
+                Err(e) => play.report(format!("failed to save the leaderboard: {}", e)),
+            }
+        }
+        if let Some(tracker) = &mut achievements {
+            for achievement in tracker.observe(game, &events) {
+                play.overlays
+                    .toasts
+                    .push(Notification::achievement(achievement.name));
+                if let Some(announcer) = &outputs.announcer {
+                    announcer.achievement(achievement.name);
+                }
+            }
+        }
+        if let Some(records) = &mut records {
+            for record in records.observe(game) {
+                play.overlays.toasts.push(Notification::record(record.name));
+                if let Some(announcer) = &outputs.announcer {
+                    announcer.record(record.name);
+                }
+            }
+        }
+        play.overlays.expire();
+
+        if play.playback.is_none() && !game.paused && last_autosave.elapsed() >= AUTOSAVE_INTERVAL {
+            // best effort: a failed snapshot just means less to recover
+            let _ = save::autosave(game);
+            last_autosave = Instant::now();
+        }
+
+        if (game.game_over || did_quit)
+            && svg_pending
+            && let Some(path) = export_svg
+        {
+            svg_pending = false;
+            if let Err(e) = game.export_board_svg(path) {
+                play.report(format!(
+                    "failed to export board to {}: {}",
+                    path.display(),
+                    e
+                ));
+            }
+        }
+
+        if game.game_over && finish_pending {
+            finish_pending = false;
+            if config.general.save_replays
+                && let Err(e) = save_replay(game)
+            {
+                play.report(format!("failed to save replay: {}", e));
+            }
+            if play.playback.is_none() {
+                let _ = save::discard_autosave();
+            }
+            if let Some(records) = &mut records
+                && let Err(e) = records.finish(game)
+            {
+                play.report(format!("failed to save records: {}", e));
+            }
         }
 
-        // small sleep to limit CPU (already mostly blocked by draw & events)
-        let frame_time = Instant::now() - last_frame;
-        if frame_time < Duration::from_millis(16) {
-            thread::sleep(Duration::from_millis(16) - frame_time);
+        if did_quit {
+            let _ = game.event_log.flush();
+            if !saved
+                && config.general.save_replays
+                && let Err(e) = save_replay(game)
+            {
+                play.session
+                    .problems
+                    .push(format!("failed to save replay: {}", e));
+            }
+            if play.playback.is_none() {
+                let _ = save::discard_autosave();
+                play.session.record(game);
+            }
+            if let Some(records) = &mut records
+                && let Err(e) = records.finish(game)
+            {
+                play.session
+                    .problems
+                    .push(format!("failed to save records: {}", e));
+            }
+            return Ok(());
         }
-        last_frame = Instant::now();
     }
+}
 
-    Ok(())
+/// Short-lived messages drawn around a single-player game, each newest last
+#[derive(Default)]
+struct Overlays {
+    /// Milestones, over the top of the board
+    notifications: Vec<Notification>,
+    /// Achievement unlocks, at the top of the Status box
+    toasts: Vec<Notification>,
+    /// Points from recent clears, in the Stats box
+    popups: Vec<Notification>,
+    /// The latest level reached, across the board
+    level_up: Option<Notification>,
+    /// Streaks behind recent hard drops
+    trails: Vec<DropTrail>,
 }
 
-/// UI rendering function using ratatui widgets
-fn ui<B: ratatui::backend::Backend>(f: &mut ratatui::Frame<B>, game: &Game) {
-    let size = f.size();
+impl Overlays {
+    fn expire(&mut self) {
+        self.notifications.retain(Notification::is_active);
+        self.toasts.retain(Notification::is_active);
+        self.popups.retain(Notification::is_active);
+        self.level_up = self.level_up.take().filter(Notification::is_active);
+        self.trails.retain(DropTrail::is_active);
+    }
+}
 
-    // Outer layout: main game area on left, sidebar on right
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
-        .split(size);
+/// How long a hard drop's trail stays on the board
+const DROP_TRAIL: Duration = Duration::from_millis(150);
 
-    // Left side: board with border
+/// The empty cells a hard-dropped piece fell through, above where it landed
+struct DropTrail {
+    kind: BlockType,
+    cells: Vec<(usize, usize)>,
+    created: Instant,
+}
+
+impl DropTrail {
+    /// From a `GameEvent::HardDropped`: each of the piece's columns, for
+    /// `rows` rows above its top block there
+    fn new(kind: BlockType, rotation: usize, x: i32, y: i32, rows: i32) -> Self {
+        let piece = ActivePiece {
+            tetro: Tetromino::new(kind),
+            rotation,
+            x,
+            y,
+        };
+        let landed = piece.cells();
+        let mut cells = Vec::new();
+        for &(cx, _) in &landed {
+            let top = landed.iter().filter(|c| c.0 == cx).map(|c| c.1).min();
+            let Some(top) = top else { continue };
+            for cy in (top - rows).max(0)..top {
+                if !cells.contains(&(cx as usize, cy as usize)) {
+                    cells.push((cx as usize, cy as usize));
+                }
+            }
+        }
+        DropTrail {
+            kind,
+            cells,
+            created: Instant::now(),
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.created.elapsed() < DROP_TRAIL
+    }
+
+    /// In the first half of its time, before it fades
+    fn fresh(&self) -> bool {
+        self.created.elapsed() < DROP_TRAIL / 2
+    }
+}
+
+/// Text shown around a board that depends on who is driving it (player, replay, versus)
+struct Panel<'a> {
+    title: &'a str,
+    controls: Vec<Line<'a>>,
+    /// Extra lines for the Status box, shown above time/gravity
+    status: Vec<Line<'a>>,
+    /// Shown under the game over message
+    game_over_hint: Option<Line<'a>>,
+    /// Overlaid on top of the board, newest last
+    notifications: &'a [Notification],
+    /// Shown at the top of the Status box, newest last
+    toasts: &'a [Notification],
+    /// Points from recent clears, stacked in the Stats box
+    popups: &'a [Notification],
+    /// Banner for a new level, which also lights up the Stats box's level
+    level_up: Option<&'a Notification>,
+    /// Hard drop streaks, over empty cells
+    trails: &'a [DropTrail],
+    /// Recent inputs, at the top of the Status box when shown
+    inputs: Option<&'a InputDisplay>,
+    /// Column heights in a strip under the board
+    heights: bool,
+    /// Where the AI would have put the last piece, ghosted on the board
+    suggestion: Option<&'a [(i32, i32)]>,
+    /// The best run's final board, dimmed where this one's empty
+    best_board: Option<&'a Board>,
+    /// Ghost the piece a hold would bring in, instead of the current one's
+    hold_preview: bool,
+    /// Drawn over the board's side of the screen while open
+    help: Option<Vec<Line<'a>>>,
+    /// The last clears, under the Status box
+    history: Option<&'a HistoryView>,
+    /// The tutorial step's instructions, boxed across the middle of the board
+    tutorial: Option<String>,
+}
+
+/// How far down the History box is scrolled, and whether it has the arrow keys
+#[derive(Default)]
+struct HistoryView {
+    scroll: usize,
+    focused: bool,
+}
+
+/// Optional sidebar extras for single-player games
+struct Widgets<'a> {
+    sprint_best: Option<&'a SprintBest>,
+    /// Set while the input display is shown
+    inputs: Option<&'a InputDisplay>,
+    /// Set while the column heights are shown
+    heights: bool,
+    /// The hold preview key is held down
+    hold_preview: bool,
+    /// The move key DAS is timing, and how charged it is
+    das: Option<(Action, f64)>,
+    practice: Option<&'a Practice>,
+    puzzle: Option<PuzzleStatus<'a>>,
+    /// The last position code exported this game
+    exported: Option<&'a str>,
+    /// `--debug`: show the state hash
+    debug: bool,
+    /// The key bindings overlay is open
+    help: bool,
+    history: &'a HistoryView,
+    tutorial: Option<&'a TutorialState>,
+}
+
+/// The puzzle being played and how it's gone so far
+struct PuzzleStatus<'a> {
+    def: &'a PuzzleDef,
+    attempts: u32,
+    solved_before: bool,
+    /// The hint key was pressed
+    hint: bool,
+}
+
+/// UI rendering function using ratatui widgets
+fn ui<B: ratatui::backend::Backend>(
+    f: &mut ratatui::Frame<B>,
+    game: &Game,
+    playback: Option<&ReplayPlayer>,
+    overlays: &Overlays,
+    config: &Config,
+    keymap: &Keymap,
+    widgets: &Widgets,
+) {
+    let title = board_title(config, if playback.is_some() { "Replay" } else { "" });
+    let mut panel = match playback {
+        Some(player) => {
+            let mut status = vec![
+                Line::from(vec![Span::styled(
+                    format!(" REPLAY x{} ", player.speed()),
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                )]),
+                Line::from(vec![Span::raw(format!(
+                    "{} {} / {}",
+                    progress_bar(player.progress(game), 12),
+                    format_duration(TICK * game.ticks as u32),
+                    format_duration(TICK * player.replay.end_tick as u32)
+                ))]),
+                Line::from(vec![Span::raw(format!(
+                    "Target score: {}",
+                    player.replay.final_score
+                ))]),
+            ];
+            if let Some(piece) = player.desync {
+                status.push(Line::from(Span::styled(
+                    format!("REPLAY DESYNC at piece {}", piece),
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                )));
+                status.push(Line::from(" Q : Stop   P : Keep watching "));
+            }
+            if player.finished(game) {
+                status.push(Line::from(vec![Span::raw(" Replay finished — Q to quit ")]));
+            }
+            Panel {
+                title: &title,
+                controls: vec![
+                    Line::from(vec![Span::raw("+ / ↑ : Faster   - / ↓ : Slower")]),
+                    Line::from(vec![Span::raw("← → : Seek 10s")]),
+                    Line::from(vec![Span::raw("P / Space : Pause   Q : Quit")]),
+                ],
+                status,
+                game_over_hint: None,
+                notifications: &overlays.notifications,
+                toasts: &overlays.toasts,
+                popups: &overlays.popups,
+                level_up: overlays.level_up.as_ref(),
+                trails: &overlays.trails,
+                inputs: None,
+                heights: widgets.heights,
+                suggestion: None,
+                best_board: None,
+                hold_preview: false,
+                help: None,
+                history: Some(widgets.history),
+                tutorial: None,
+            }
+        }
+        None => Panel {
+            title: &title,
+            controls: {
+                let keys = |command| keymap.keys_for(command);
+                let play = |action| keys(Command::Play(action));
+                let mut lines = vec![
+                    Line::from(format!(
+                        "{} {} : Move     {} : Soft drop",
+                        play(Action::MoveLeft),
+                        play(Action::MoveRight),
+                        play(Action::SoftDrop)
+                    )),
+                    Line::from(format!(
+                        "{} : Rotate CW  {} : Rotate CCW",
+                        play(Action::RotateCw),
+                        play(Action::RotateCcw)
+                    )),
+                    Line::from(format!(
+                        "{} : Hard drop   {} : Hold",
+                        play(Action::HardDrop),
+                        play(Action::Hold)
+                    )),
+                    Line::from(format!(
+                        "{} : Pause   {} : Restart   {} : Quit",
+                        keys(Command::Pause),
+                        keys(Command::Restart),
+                        keys(Command::Quit)
+                    )),
+                    Line::from(format!(
+                        "{} : Save & quit   {} : Zone",
+                        keys(Command::SaveQuit),
+                        play(Action::Zone)
+                    )),
+                    Line::from(format!(
+                        "{} : Show inputs   {} : Export position",
+                        keys(Command::ToggleInputs),
+                        keys(Command::ExportPosition)
+                    )),
+                ];
+                lines.push(Line::from(format!("{} : All keys", keys(Command::Help))));
+                if game.setup.mode.is_puzzle() {
+                    lines.push(Line::from(format!("{} : Hint", keys(Command::Hint))));
+                }
+                lines
+            },
+            status: {
+                let mut status: Vec<Line> = paths::profile()
+                    .map(|name| Line::from(format!("Profile: {}", name)))
+                    .into_iter()
+                    .collect();
+                if game.setup.mode == GameMode::Sprint {
+                    status.extend(sprint_pace_lines(game, widgets.sprint_best));
+                }
+                if let Some(puzzle) = &widgets.puzzle {
+                    status.extend(puzzle_lines(puzzle));
+                }
+                if let Some(code) = widgets.exported {
+                    status.extend(position_lines(code));
+                }
+                if let Some(practice) = widgets.practice {
+                    status.extend(practice.status_lines());
+                }
+                if let Some((action, charge)) = widgets.das {
+                    status.push(das_line(action, charge));
+                }
+                status
+            },
+            game_over_hint: Some(Line::from(vec![Span::styled(
+                match scores::mode_key(game.setup.mode) {
+                    Some(_) => format!(
+                        " R : Restart   {} : Scores   Q : Quit ",
+                        keymap.keys_for(Command::Leaderboard)
+                    ),
+                    None => " Press 'R' to restart or 'Q' to quit ".to_string(),
+                },
+                Style::default().fg(Color::White),
+            )])),
+            notifications: &overlays.notifications,
+            toasts: &overlays.toasts,
+            popups: &overlays.popups,
+            level_up: overlays.level_up.as_ref(),
+            trails: &overlays.trails,
+            inputs: widgets.inputs,
+            heights: widgets.heights,
+            suggestion: widgets.practice.and_then(Practice::ghost),
+            best_board: widgets.practice.and(game.best_board.as_ref()),
+            hold_preview: widgets.hold_preview,
+            help: widgets.help.then(|| help_lines(game.setup.mode, keymap)),
+            history: Some(widgets.history),
+            tutorial: widgets.tutorial.map(|tutorial| {
+                let step = tutorial.step;
+                let keys = step
+                    .action()
+                    .map(|action| keymap.keys_for(Command::Play(action)))
+                    .unwrap_or_default();
+                step.instruction(&keys)
+            }),
+        },
+    };
+    if widgets.debug {
+        panel
+            .status
+            .push(Line::from(format!("State: {:016x}", game.state_hash())));
+    }
+    draw_game(f, f.size(), game, panel, config);
+}
+
+/// Puzzle number, name and attempt, then the hint once asked for
+fn puzzle_lines(puzzle: &PuzzleStatus) -> Vec<Line<'static>> {
+    let def = puzzle.def;
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("Puzzle {}: {}", def.id, def.name),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(def.description.clone()),
+    ];
+    let mut attempt = format!("Attempt {}", puzzle.attempts.max(1));
+    if puzzle.solved_before {
+        attempt.push_str(" (solved ✔)");
+    }
+    lines.push(Line::from(attempt));
+    if puzzle.hint {
+        let hint = def
+            .hint()
+            .map_or("No hint for this puzzle".to_string(), |h| {
+                format!("Hint: {}", h)
+            });
+        lines.push(Line::from(Span::styled(
+            hint,
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    lines
+}
+
+/// The help overlay: every command with the keys bound to it, then what the
+/// mode asks of the player
+fn help_lines(mode: GameMode, keymap: &Keymap) -> Vec<Line<'static>> {
+    let heading = Style::default().add_modifier(Modifier::BOLD);
+    let mut lines = vec![Line::from(Span::styled("Keys", heading))];
+    for (_, _, command) in KeysConfig::default().entries() {
+        let keys = match keymap.keys_for(command) {
+            keys if keys.is_empty() => "(unbound)".to_string(),
+            keys => keys,
+        };
+        lines.push(Line::from(format!("{} : {}", keys, command.description())));
+    }
+    let rules = match mode {
+        GameMode::Marathon => "Marathon: clear lines to level up until you top out".to_string(),
+        GameMode::Sprint => format!("Sprint: clear {} lines as fast as you can", SPRINT_LINES),
+        GameMode::Ultra => format!(
+            "Ultra: score as much as you can in {}",
+            format_duration(ULTRA_TIME)
+        ),
+        GameMode::Cheese { rows } => format!("Cheese: dig through {} garbage rows", rows),
+        GameMode::DailyChallenge { .. } => {
+            "Daily: the same pieces for everyone, one attempt, no pausing".to_string()
+        }
+        GameMode::Puzzle { .. } => {
+            "Puzzle: empty the board with the pieces given; no hold, no gravity".to_string()
+        }
+        GameMode::Competitive => {
+            "Competitive: marathon with no ghost, one preview and a hidden hold; clears score more"
+                .to_string()
+        }
+        GameMode::Tutorial => {
+            "Tutorial: the basics one step at a time, with no gravity".to_string()
+        }
+    };
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled("Mode", heading)));
+    lines.push(Line::from(rules));
+    lines.push(Line::from(""));
+    lines.push(Line::from(format!(
+        "{} / Esc : Close",
+        keymap.keys_for(Command::Help)
+    )));
+    lines
+}
+
+/// An exported position code, broken up to fit the sidebar
+fn position_lines(code: &str) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(Span::styled(
+        "Position code:",
+        Style::default().fg(Color::Cyan),
+    ))];
+    // the code is plain ASCII, so any byte offset is a character boundary
+    for start in (0..code.len()).step_by(POSITION_CODE_WIDTH) {
+        let end = (start + POSITION_CODE_WIDTH).min(code.len());
+        lines.push(Line::from(code[start..end].to_string()));
+    }
+    lines
+}
+
+/// "Eff: 0.31 (peak: 0.38)", lines per piece colored by how close it is
+/// to all Tetrises, or "N/A" for the first few pieces
+fn efficiency_span(game: &Game) -> Span<'static> {
+    if game.pieces_placed < EFFICIENCY_MIN_PIECES {
+        return Span::raw("Eff: N/A");
+    }
+    let efficiency = game.efficiency();
+    let color = if efficiency > 0.35 {
+        Color::Green
+    } else if efficiency >= 0.2 {
+        Color::Yellow
+    } else {
+        Color::Red
+    };
+    Span::styled(
+        format!("Eff: {:.2} (peak: {:.2})", efficiency, game.peak_efficiency),
+        Style::default().fg(color),
+    )
+}
+
+/// Key presses per piece and per minute, as "KPP: 2.41  APM: 152"
+fn kpp_apm(game: &Game) -> String {
+    format!("KPP: {:.2}  APM: {:.0}", game.kpp(), game.apm())
+}
+
+/// "−1.8s" ahead of the best, "+0.4s" behind
+fn format_delta(seconds: f64) -> String {
+    if seconds < 0.0 {
+        format!("−{:.1}s", -seconds)
+    } else {
+        format!("+{:.1}s", seconds)
+    }
+}
+
+/// Sprint splits so far, against the personal best when there is one, then
+/// the finish time the current pace points to
+fn sprint_pace_lines(game: &Game, best: Option<&SprintBest>) -> Vec<Line<'static>> {
+    let ahead_or_behind = |time: Duration, target: Duration| {
+        let delta = time.as_secs_f64() - target.as_secs_f64();
+        let color = if delta <= 0.0 {
+            Color::Green
+        } else {
+            Color::Red
+        };
+        (format_delta(delta), Style::default().fg(color))
+    };
+    let mut lines = Vec::new();
+    for (i, &split) in game.splits.iter().enumerate() {
+        let label = format!("{} lines: ", (i + 1) * SPRINT_SPLIT_LINES);
+        lines.push(match best.and_then(|b| b.splits.get(i)) {
+            Some(&target) => {
+                let (delta, style) = ahead_or_behind(split, target);
+                Line::from(vec![Span::raw(label), Span::styled(delta, style)])
+            }
+            None => Line::from(format!("{}{}", label, format_duration(split))),
+        });
+    }
+    if !game.game_over
+        && let Some(projected) = game.projected_sprint_time()
+    {
+        let label = format!("Pace: {}", format_duration(projected));
+        lines.push(match best {
+            Some(best) => {
+                let (delta, style) = ahead_or_behind(projected, best.time);
+                Line::from(vec![
+                    Span::raw(format!("{} ", label)),
+                    Span::styled(delta, style),
+                ])
+            }
+            None => Line::from(label),
+        });
+    }
+    lines
+}
+
+/// Border tint for a level: a pale hue sliding from blue towards red as the
+/// game speeds up
+fn level_accent(level: usize) -> Color {
+    let hue = 210.0 - (level.saturating_sub(1) as f64 * 14.0).min(210.0);
+    // HSV with low saturation, so it reads as a tint rather than a warning color
+    let (saturation, value) = (0.35, 0.95);
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let (r, g, b) = match (hue / 60.0) as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        _ => (x, 0.0, chroma),
+    };
+    let m = value - chroma;
+    let channel = |c: f64| ((c + m) * 255.0).round() as u8;
+    Color::Rgb(channel(r), channel(g), channel(b))
+}
+
+/// What a board cell shows this frame, before it's turned into characters
+enum CellLook {
+    Block(Color, BlockType),
+    /// Part of the piece that just locked
+    Flash,
+    /// Where the current piece would land, over this background
+    Ghost(Color),
+    /// Part of a T-spin slot (`ui.hints_enabled`), over this background
+    Slot(Color),
+    /// Where the AI would have put the last piece (`--practice`), over this background
+    Suggested(Color),
+    /// Filled in the best run's final board but empty here: the block's
+    /// color, then the background
+    Best(Color, Color),
+    /// Under a hard drop's trail: the piece's color, whether the trail is
+    /// still fresh, then the background
+    Trail(Color, bool, Color),
+    /// A locked block drawn as its exposed edges (`ui.outline_blocks`), over
+    /// this background
+    Outlined(Color, Edges, Color),
+    Empty(Color),
+}
+
+/// Two box-drawing characters tracing a block's exposed edges, so an outlined
+/// O reads `┌──┐` over `└──┘`
+fn outline_glyph(edges: Edges) -> String {
+    let side =
+        |outer: bool, closed: char, top: char, bottom: char| match (outer, edges.top, edges.bottom)
+        {
+            (true, true, true) => closed,
+            (true, true, false) => top,
+            (true, false, true) => bottom,
+            (true, false, false) => '│',
+            (false, true, true) => '═',
+            (false, true, false) | (false, false, true) => '─',
+            (false, false, false) => ' ',
+        };
+    [
+        side(edges.left, '[', '┌', '└'),
+        side(edges.right, ']', '┐', '┘'),
+    ]
+    .into_iter()
+    .collect()
+}
+
+/// Dim magenta hatching for T-spin slots
+const SLOT_COLOR: Color = Color::Rgb(110, 40, 110);
+
+/// Rows above the board drawn as a strip over its border, where a piece can
+/// poke out of a tall stack
+const VANISH_ROWS: usize = 2;
+
+/// One terminal line for two board rows: each `▀` takes the upper row's
+/// color as foreground and the lower row's as background
+fn half_block_line(upper: &[Color], lower: &[Color]) -> Line<'static> {
+    let spans: Vec<Span> = upper
+        .iter()
+        .zip(lower)
+        .map(|(&top, &bottom)| Span::styled("▀", Style::default().fg(top).bg(bottom)))
+        .collect();
+    Line::from(spans)
+}
+
+/// Draw `cells` as a Braille raster filling `area`, starting `top` dots down
+fn render_braille<B: ratatui::backend::Backend>(
+    f: &mut ratatui::Frame<B>,
+    area: Rect,
+    cells: Vec<Vec<Option<BrailleCell>>>,
+    scale: u16,
+    top: usize,
+    background: Color,
+) {
+    // a board cut off by a tiny terminal stays inside the frame
+    let area = area.intersection(f.size());
+    let board = BrailleBoard {
+        cells,
+        scale,
+        top,
+        dots: (usize::from(area.width) * 2, usize::from(area.height) * 4),
+    };
+    let canvas = Canvas::default()
+        .marker(Marker::Braille)
+        .background_color(background)
+        .x_bounds([0.0, f64::from(area.width)])
+        .y_bounds([0.0, f64::from(area.height)])
+        .paint(|ctx| ctx.draw(&board));
+    f.render_widget(canvas, area);
+}
+
+/// Background of an empty board cell: plain black, or a subtle checkerboard with `--grid`
+fn empty_cell_bg(x: usize, y: usize, grid: bool, theme: &Theme) -> Color {
+    if grid && (x + y) % 2 == 1 {
+        Color::Rgb(28, 28, 28)
+    } else {
+        theme.background
+    }
+}
+
+/// Top board rows shaded while the stack is critical
+const DANGER_ROWS: u16 = 4;
+
+/// Shade the top rows of the board behind whatever is drawn there, once the stack is critical
+fn render_danger_overlay<B: ratatui::backend::Backend>(
+    f: &mut ratatui::Frame<B>,
+    inner_area: Rect,
+    danger: DangerLevel,
+    theme: &Theme,
+) {
+    if danger != DangerLevel::Critical {
+        return;
+    }
+    let rows = Rect {
+        height: inner_area.height.min(DANGER_ROWS),
+        ..inner_area
+    };
+    // a bare styled block only changes the background of the cells it covers
+    f.render_widget(
+        Block::default().style(Style::default().bg(theme.danger_background)),
+        rows,
+    );
+}
+
+/// A piece in its spawn orientation, trimmed to its blocks and drawn with the
+/// board's `filled` glyph; `glyphs` letters its blocks as the high-contrast
+/// board does
+fn preview_rows(kind: BlockType, color: Color, filled: &str, glyphs: bool) -> Vec<Line<'static>> {
+    let tetro = Tetromino::new(kind);
+    let grid = &tetro.rotations[0];
+    let at = |bx: usize, by: usize| grid[by * 4 + bx] != 0;
+    let cols: Vec<usize> = (0..4).filter(|&bx| (0..4).any(|by| at(bx, by))).collect();
+    (0..4)
+        .filter(|&by| (0..4).any(|bx| at(bx, by)))
+        .map(|by| {
+            let spans: Vec<Span> = cols
+                .iter()
+                .map(|&bx| {
+                    if !at(bx, by) {
+                        Span::raw("  ")
+                    } else if glyphs {
+                        high_contrast_block(kind, color)
+                    } else {
+                        Span::styled(filled.to_string(), Style::default().fg(color))
+                    }
+                })
+                .collect();
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// A block under the high-contrast theme: its letter in black on its color
+fn high_contrast_block(kind: BlockType, color: Color) -> Span<'static> {
+    Span::styled(
+        kind.glyph(),
+        Style::default()
+            .fg(Color::Black)
+            .bg(color)
+            .add_modifier(Modifier::BOLD),
+    )
+}
+
+/// A bordered box in the configured style and the theme's colors, thick and
+/// bold under the high-contrast theme
+fn boxed(config: &Config) -> Block<'static> {
+    let high_contrast = config.ui.theme == HIGH_CONTRAST;
+    let border = if high_contrast {
+        BorderType::Thick
+    } else {
+        config.ui.border_style.border_type()
+    };
+    let theme = config.theme();
+    let bold = |style: Style| {
+        if high_contrast {
+            style.add_modifier(Modifier::BOLD)
+        } else {
+            style
+        }
+    };
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(border)
+        .style(bold(Style::default().fg(theme.text)));
+    if let Some(color) = theme.border {
+        block = block.border_style(bold(Style::default().fg(color)));
+    }
+    block
+}
+
+/// `style` set back from what's in play: dimmed, except under the
+/// high-contrast theme, where dim text can vanish altogether
+fn faded(style: Style, config: &Config) -> Style {
+    if config.ui.theme == HIGH_CONTRAST {
+        style
+    } else {
+        style.add_modifier(Modifier::DIM)
+    }
+}
+
+/// `ui.board_title` padded for the border, with `suffix` after it
+fn board_title(config: &Config, suffix: &str) -> String {
+    match (config.ui.board_title.is_empty(), suffix.is_empty()) {
+        (true, true) => String::new(),
+        (true, false) => format!(" {} ", suffix),
+        (false, true) => format!(" {} ", config.ui.board_title),
+        (false, false) => format!(" {} — {} ", config.ui.board_title, suffix),
+    }
+}
+
+/// The held piece's color, grayed out once hold has been used for this drop
+fn hold_color(kind: BlockType, used: bool, theme: &Theme) -> Color {
+    if used {
+        Color::DarkGray
+    } else {
+        theme.piece(kind)
+    }
+}
+
+/// Headline for a finished game: losses in red, time-outs in blue, wins in green
+fn game_over_message(game: &Game) -> (String, Color) {
+    match game.game_over_reason {
+        Some(GameOverReason::GoalReached) => {
+            let time = format_duration(game.goal_time.unwrap_or_default());
+            let text = match game.setup.mode {
+                GameMode::Sprint => {
+                    format!(
+                        "Congratulations! {} lines cleared in {}!",
+                        SPRINT_LINES, time
+                    )
+                }
+                GameMode::Puzzle { .. } => format!("Solved in {}!", time),
+                _ => format!("CLEARED in {}", time),
+            };
+            (text, Color::Green)
+        }
+        Some(GameOverReason::TimeExpired) => ("Time's up!".to_string(), Color::Blue),
+        Some(GameOverReason::LockOut) => ("Locked out above the board!".to_string(), Color::Red),
+        Some(GameOverReason::BlockOut) => ("Blocked out at spawn!".to_string(), Color::Red),
+        Some(GameOverReason::OutOfPieces) => ("Out of pieces!".to_string(), Color::Red),
+        Some(GameOverReason::TopOut) | None => ("You topped out!".to_string(), Color::Red),
+    }
+}
+
+/// Draw one game (board + sidebar) into `size`
+fn draw_game<B: ratatui::backend::Backend>(
+    f: &mut ratatui::Frame<B>,
+    size: Rect,
+    game: &Game,
+    panel: Panel,
+    config: &Config,
+) {
+    let theme = config.theme();
+    // Outer layout: main game area on left, sidebar on right
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
+        .split(size);
+
+    // Left side: board with border
     // let board_area = centered_rect(60, 90, chunks[0]);
-    let board_width_chars = (BOARD_WIDTH * 2) as u16;
-    let board_height_chars = BOARD_HEIGHT as u16;
     let area = chunks[0];
+    // neither Braille nor half blocks have an ASCII stand-in; a terminal too
+    // short for the whole board gets half blocks anyway, with `auto_half_block`
+    let braille = config.ui.braille && !config.ui.ascii;
+    let half_block = !config.ui.ascii
+        && !braille
+        && (config.ui.half_block
+            || config.ui.auto_half_block && area.height < BOARD_HEIGHT as u16 + 2);
+    let scale = braille::scale_for(area);
+    let (board_width_chars, board_height_chars) = if braille {
+        braille::size(BOARD_WIDTH, BOARD_HEIGHT, scale)
+    } else if half_block {
+        (BOARD_WIDTH as u16, BOARD_HEIGHT.div_ceil(2) as u16)
+    } else {
+        ((BOARD_WIDTH * 2) as u16, BOARD_HEIGHT as u16)
+    };
+    // strips above and below the board, the vanish strip first to go on a
+    // short terminal; Braille columns don't line up with characters, so
+    // there are no column heights under them
+    let height_lines = u16::from(panel.heights && !braille && area.height > board_height_chars + 2);
+    let vanish_lines = if braille {
+        braille::size(BOARD_WIDTH, VANISH_ROWS, scale).1
+    } else if half_block {
+        1
+    } else {
+        VANISH_ROWS as u16
+    };
+    let vanish_lines = if area.height >= board_height_chars + 2 + height_lines + vanish_lines {
+        vanish_lines
+    } else {
+        0
+    };
 
     let offset_x = (area.width.saturating_sub(board_width_chars + 2)) / 2; // +2 for borders
-    let offset_y = (area.height.saturating_sub(board_height_chars + 2)) / 2;
+    let offset_y = (area
+        .height
+        .saturating_sub(board_height_chars + 2 + vanish_lines + height_lines))
+        / 2;
 
     let board_area = Rect {
         x: area.x + offset_x,
-        y: area.y + offset_y,
+        y: area.y + offset_y + vanish_lines,
         width: board_width_chars + 2,
         height: board_height_chars + 2,
     };
 
-    let board_block = Block::default()
-        .borders(Borders::ALL)
-        .title(" Tetris ")
-        .border_style(Style::default().fg(Color::White));
+    let danger = game.danger_level();
+    let border_style = match danger {
+        DangerLevel::Safe => {
+            Style::default().fg(theme.border.unwrap_or_else(|| level_accent(game.level)))
+        }
+        DangerLevel::Warning => Style::default().fg(Color::Yellow),
+        DangerLevel::Critical => Style::default()
+            .fg(theme.danger)
+            .add_modifier(Modifier::RAPID_BLINK),
+    };
+    let board_block = boxed(config).title(panel.title).border_style(border_style);
     f.render_widget(board_block, board_area);
-
-    // compute inner area for drawing cells (1 char cell wide; we'll use two spaces "  " per cell)
+    // compute inner area for drawing cells (each cell is two columns wide, or
+    // one column and half a line with half blocks)
     let inner = Rect {
         x: board_area.x + 1,
         y: board_area.y + 1,
@@ -556,106 +2179,625 @@ fn ui<B: ratatui::backend::Backend>(f: &mut ratatui::Frame<B>, game: &Game) {
         height: board_area.height.saturating_sub(2),
     };
 
-    // Build rows of text for board
-    let mut rows: Vec<Line> = vec![];
-    for y in 0..BOARD_HEIGHT {
-        let mut spans: Vec<Span> = Vec::new();
-        for x in 0..BOARD_WIDTH {
-            let mut cell_color: Option<Color> = None;
-
-            // check if current piece occupies this cell
-            for (cx, cy) in game.current.cells() {
-                if cx == x as i32 && cy == y as i32 {
-                    cell_color = Some(game.current.tetro.kind.color());
-                    break;
-                }
-            }
-            // otherwise board content
-            if cell_color.is_none() {
-                if let Some(kind) = game.board[y][x] {
-                    cell_color = Some(kind.color());
-                }
+    let ui = &config.ui;
+    let high_contrast = ui.theme == HIGH_CONTRAST;
+    let glyphs = Glyphs::of(ui);
+    let Glyphs {
+        filled,
+        ghost: ghost_glyph,
+        empty,
+        ..
+    } = glyphs;
+    // while the hold preview key is down, the ghost is the piece a hold would bring in
+    let held_ghost = panel.hold_preview.then(|| game.hold_preview()).flatten();
+    // none is in play during ARE
+    let (piece_cells, ghost_cells) = if game.piece_active() {
+        let ghost = match &held_ghost {
+            Some(piece) => piece.cells(),
+            None if config.gameplay.ghost && game.setup.mode.shows_ghost() => {
+                game.ghost_piece().cells()
             }
+            None => Vec::new(),
+        };
+        (game.current.cells(), ghost)
+    } else {
+        (Vec::new(), Vec::new())
+    };
+    let piece_color = theme.piece(game.current.tetro.kind);
+    let ghost_color = theme
+        .ghost
+        .unwrap_or_else(|| held_ghost.map_or(piece_color, |piece| theme.piece(piece.tetro.kind)));
+    let slot_cells: Vec<(i32, i32)> =
+        if ui.hints_enabled && game.piece_active() && game.current.tetro.kind == BlockType::T {
+            game.find_tspin_slots()
+                .iter()
+                .flat_map(|slot| slot.piece().cells())
+                .collect()
+        } else {
+            Vec::new()
+        };
 
-            if let Some(col) = cell_color {
-                spans.push(Span::styled("██", Style::default().fg(col)));
+    // outlines need box drawing and a cell per block, and would hide the
+    // high-contrast letters
+    let outline = ui.outline_blocks && !ui.ascii && !half_block && !high_contrast;
+
+    // rows about to clear light up, then go blank before the stack drops
+    let clearing = game.clearing_rows();
+    let look = |x: usize, y: usize| {
+        let here = (x as i32, y as i32);
+        if piece_cells.contains(&here) {
+            CellLook::Block(piece_color, game.current.tetro.kind)
+        } else if let Some((_, lit)) = clearing.filter(|(rows, _)| rows.contains(&y)) {
+            if lit {
+                CellLook::Flash
+            } else {
+                CellLook::Empty(empty_cell_bg(x, y, config.ui.grid, &theme))
+            }
+        } else if let Some(kind) = game.board[y][x] {
+            if !ui.reduced_motion && game.flashing(x, y) {
+                CellLook::Flash
+            } else if y >= BOARD_HEIGHT - game.zone_lines {
+                // rows banked by the zone are set apart until it ends
+                CellLook::Block(Color::White, kind)
+            } else if outline {
+                let edges = Edges::of(&game.board, x, y).unwrap_or_default();
+                CellLook::Outlined(
+                    theme.piece(kind),
+                    edges,
+                    empty_cell_bg(x, y, config.ui.grid, &theme),
+                )
             } else {
-                spans.push(Span::styled("  ", Style::default().bg(Color::Black)));
+                CellLook::Block(theme.piece(kind), kind)
             }
+        } else if panel.suggestion.is_some_and(|cells| cells.contains(&here)) {
+            CellLook::Suggested(empty_cell_bg(x, y, config.ui.grid, &theme))
+        } else if ghost_cells.contains(&here) {
+            CellLook::Ghost(empty_cell_bg(x, y, config.ui.grid, &theme))
+        } else if slot_cells.contains(&here) {
+            CellLook::Slot(empty_cell_bg(x, y, config.ui.grid, &theme))
+        } else if let Some(trail) = panel
+            .trails
+            .iter()
+            .rev()
+            .find(|trail| trail.cells.contains(&(x, y)))
+        {
+            CellLook::Trail(
+                theme.piece(trail.kind),
+                trail.fresh(),
+                empty_cell_bg(x, y, config.ui.grid, &theme),
+            )
+        } else if let Some(kind) = panel.best_board.and_then(|best| best[y][x]) {
+            CellLook::Best(
+                theme.piece(kind),
+                empty_cell_bg(x, y, config.ui.grid, &theme),
+            )
+        } else {
+            CellLook::Empty(empty_cell_bg(x, y, config.ui.grid, &theme))
+        }
+    };
+
+    // half blocks and Braille have a color a cell and no glyphs, so a ghost
+    // is a dim block instead
+    let color_at = |x: usize, y: usize| match look(x, y) {
+        CellLook::Block(color, _) | CellLook::Outlined(color, ..) => color,
+        CellLook::Flash => Color::White,
+        CellLook::Ghost(_) if high_contrast => Color::White,
+        CellLook::Ghost(_) => Color::DarkGray,
+        CellLook::Slot(_) => SLOT_COLOR,
+        CellLook::Suggested(_) => SUGGESTION_COLOR,
+        CellLook::Best(..) => Color::DarkGray,
+        CellLook::Trail(color, true, _) => color,
+        CellLook::Trail(_, false, _) => Color::DarkGray,
+        CellLook::Empty(bg) => bg,
+    };
+
+    // Build rows of text for board
+    let mut rows: Vec<Line> = vec![];
+    if braille {
+        // big enough for an outline, a ghost takes its piece's color like a full-size one
+        let cells = (0..BOARD_HEIGHT)
+            .map(|y| {
+                (0..BOARD_WIDTH)
+                    .map(|x| match look(x, y) {
+                        CellLook::Empty(_) => None,
+                        _ if piece_cells.contains(&(x as i32, y as i32)) => {
+                            Some(BrailleCell::Piece(piece_color))
+                        }
+                        CellLook::Ghost(_) if scale >= 3 => Some(BrailleCell::Ghost(ghost_color)),
+                        _ => Some(BrailleCell::Solid(color_at(x, y))),
+                    })
+                    .collect()
+            })
+            .collect();
+        render_braille(f, inner, cells, scale, 0, theme.background);
+    } else if half_block {
+        for top in (0..BOARD_HEIGHT).step_by(2) {
+            let upper: Vec<Color> = (0..BOARD_WIDTH).map(|x| color_at(x, top)).collect();
+            let lower: Vec<Color> = if top + 1 < BOARD_HEIGHT {
+                (0..BOARD_WIDTH).map(|x| color_at(x, top + 1)).collect()
+            } else {
+                vec![theme.background; BOARD_WIDTH]
+            };
+            rows.push(half_block_line(&upper, &lower));
+        }
+    } else {
+        for y in 0..BOARD_HEIGHT {
+            let spans: Vec<Span> = (0..BOARD_WIDTH)
+                .map(|x| match look(x, y) {
+                    CellLook::Block(color, kind) if high_contrast => {
+                        high_contrast_block(kind, color)
+                    }
+                    CellLook::Block(color, _) => Span::styled(filled, Style::default().fg(color)),
+                    CellLook::Flash => Span::styled(
+                        filled,
+                        Style::default()
+                            .fg(Color::White)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    CellLook::Ghost(bg) => {
+                        let style = Style::default().fg(ghost_color).bg(bg);
+                        if high_contrast {
+                            Span::styled(ghost_glyph, style.add_modifier(Modifier::BOLD))
+                        } else {
+                            Span::styled(ghost_glyph, style)
+                        }
+                    }
+                    CellLook::Slot(bg) => {
+                        Span::styled(glyphs.slot, Style::default().fg(SLOT_COLOR).bg(bg))
+                    }
+                    CellLook::Suggested(bg) => {
+                        Span::styled(ghost_glyph, Style::default().fg(SUGGESTION_COLOR).bg(bg))
+                    }
+                    CellLook::Best(color, bg) => {
+                        Span::styled(filled, faded(Style::default().fg(color).bg(bg), config))
+                    }
+                    CellLook::Trail(color, fresh, bg) => {
+                        let streak = if fresh {
+                            glyphs.trail[0]
+                        } else {
+                            glyphs.trail[1]
+                        };
+                        Span::styled(streak, faded(Style::default().fg(color).bg(bg), config))
+                    }
+                    CellLook::Outlined(color, edges, bg) => {
+                        Span::styled(outline_glyph(edges), Style::default().fg(color).bg(bg))
+                    }
+                    CellLook::Empty(bg) => Span::styled(empty, Style::default().bg(bg)),
+                })
+                .collect();
+            rows.push(Line::from(spans));
         }
-        rows.push(Line::from(spans));
     }
 
     // render board text area
-    let board_paragraph = Paragraph::new(rows)
-        .alignment(Alignment::Left)
-        .wrap(Wrap { trim: false })
-        .block(Block::default());
-    f.render_widget(board_paragraph, inner);
+    if !braille {
+        let board_paragraph = Paragraph::new(rows)
+            .alignment(Alignment::Left)
+            .wrap(Wrap { trim: false })
+            .block(Block::default());
+        f.render_widget(board_paragraph, inner);
+    }
+    render_danger_overlay(f, inner, danger, &theme);
+
+    // parts of the piece above row 0, dimmed to mark the danger
+    if vanish_lines > 0 {
+        let vanish = Rect {
+            y: board_area.y - vanish_lines,
+            height: vanish_lines,
+            ..inner
+        };
+        let above = |x: usize, row: usize| {
+            let y = row as i32 - VANISH_ROWS as i32;
+            piece_cells.contains(&(x as i32, y))
+        };
+        let dim = faded(Style::default(), config);
+        if braille {
+            let cells = (0..VANISH_ROWS)
+                .map(|row| {
+                    (0..BOARD_WIDTH)
+                        .map(|x| above(x, row).then_some(BrailleCell::Piece(piece_color)))
+                        .collect()
+                })
+                .collect();
+            // the strip ends at the board's border however many dots it has spare
+            let spare = usize::from(vanish_lines) * 4 - VANISH_ROWS * usize::from(scale);
+            render_braille(f, vanish, cells, scale, spare, Color::Reset);
+            f.render_widget(Block::default().style(dim), vanish);
+        } else {
+            let rows: Vec<Line> = if half_block {
+                let color_at = |x: usize, row: usize| {
+                    if above(x, row) {
+                        piece_color
+                    } else {
+                        Color::Reset
+                    }
+                };
+                let upper: Vec<Color> = (0..BOARD_WIDTH).map(|x| color_at(x, 0)).collect();
+                let lower: Vec<Color> = (0..BOARD_WIDTH).map(|x| color_at(x, 1)).collect();
+                let mut line = half_block_line(&upper, &lower);
+                line.patch_style(dim);
+                vec![line]
+            } else {
+                (0..VANISH_ROWS)
+                    .map(|row| {
+                        let spans: Vec<Span> = (0..BOARD_WIDTH)
+                            .map(|x| {
+                                if above(x, row) {
+                                    Span::styled(filled, dim.fg(piece_color))
+                                } else {
+                                    Span::raw("  ")
+                                }
+                            })
+                            .collect();
+                        Line::from(spans)
+                    })
+                    .collect()
+            };
+            f.render_widget(Paragraph::new(rows), vanish);
+        }
+    }
+
+    // each column's height, or a bar per column with half blocks, colored by danger
+    if height_lines > 0 {
+        let strip = Rect {
+            y: board_area.y + board_area.height,
+            height: 1,
+            ..inner
+        };
+        let spans: Vec<Span> = game
+            .column_heights()
+            .into_iter()
+            .map(|height| {
+                let color = match DangerLevel::of_height(height) {
+                    DangerLevel::Safe => Color::DarkGray,
+                    DangerLevel::Warning => Color::Yellow,
+                    DangerLevel::Critical => Color::Red,
+                };
+                let text = if half_block {
+                    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+                    match height {
+                        0 => " ".to_string(),
+                        h => BARS[(h - 1) * BARS.len() / BOARD_HEIGHT].to_string(),
+                    }
+                } else {
+                    format!("{:>2}", height)
+                };
+                Span::styled(text, Style::default().fg(color))
+            })
+            .collect();
+        f.render_widget(Paragraph::new(Line::from(spans)), strip);
+    }
+
+    // notifications stack down from the top of the board
+    for (i, note) in panel.notifications.iter().enumerate() {
+        if i as u16 >= inner.height {
+            break;
+        }
+        let line = Rect {
+            y: inner.y + i as u16,
+            height: 1,
+            ..inner
+        };
+        let text = Paragraph::new(Line::from(Span::styled(
+            note.text.as_str(),
+            Style::default()
+                .fg(note.color)
+                .bg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        )))
+        .alignment(Alignment::Center);
+        f.render_widget(Clear, line);
+        f.render_widget(text, line);
+    }
+
+    // the level-up banner sits a third of the way down, clear of the notifications
+    if let Some(note) = panel.level_up
+        && inner.height > 0
+    {
+        let line = Rect {
+            y: inner.y + inner.height / 3,
+            height: 1,
+            ..inner
+        };
+        let text = Paragraph::new(Line::from(Span::styled(
+            format!(" {} ", note.text),
+            Style::default()
+                .fg(Color::Black)
+                .bg(note.color)
+                .add_modifier(Modifier::BOLD),
+        )))
+        .alignment(Alignment::Center);
+        f.render_widget(Clear, line);
+        f.render_widget(text, line);
+    }
+
+    // tutorial instructions go across the board's side of the screen, halfway
+    // down, where they don't hide the piece waiting at the top
+    if let Some(text) = &panel.tutorial
+        && area.width > 4
+    {
+        let lines = (text.chars().count() as u16).div_ceil(area.width - 4);
+        let height = (lines + 2).min(area.height);
+        let rect = Rect {
+            y: area.y + area.height.saturating_sub(height) / 2,
+            height,
+            ..area
+        };
+        let block = boxed(config)
+            .title(" Tutorial ")
+            .border_style(Style::default().fg(Color::Yellow));
+        let text = Paragraph::new(Span::styled(
+            text.as_str(),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ))
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+        f.render_widget(Clear, rect);
+        f.render_widget(text, rect);
+    }
+
+    // help covers the board's side of the screen, leaving the sidebar readable
+    if let Some(help) = panel.help {
+        let block = boxed(config)
+            .title(" Help ")
+            .style(Style::default().bg(Color::Rgb(16, 16, 16)));
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(help).block(block).wrap(Wrap { trim: false }),
+            area,
+        );
+    }
 
     // Right sidebar
     let side_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
             [
-                Constraint::Length(7),
-                Constraint::Length(5),
-                Constraint::Length(5),
+                Constraint::Length(4),
+                Constraint::Length(game.next.len() as u16 * 3 + 1),
+                Constraint::Length(14),
+                Constraint::Length(panel.controls.len() as u16 + 2),
                 Constraint::Min(3),
             ]
             .as_ref(),
         )
         .split(chunks[1]);
 
-    // Next piece preview
-    let next_block = Block::default().borders(Borders::ALL).title(" Next ");
+    // previews can be cut down or hidden, for less lookahead
+    let shown = config.ui.previews_shown(game.next.len());
+    let hidden = || vec![Line::from(""), Line::from("?")];
+
+    // Hold preview, dimmed while it can't be used again
+    let hold_block = boxed(config).title(" Hold ");
+    let hold_rows = match game.hold {
+        // competitive players have to remember what they held
+        Some(_) if shown == 0 || !game.setup.mode.shows_hold() => hidden(),
+        Some(kind) => preview_rows(
+            kind,
+            hold_color(kind, game.hold_used_this_drop, &theme),
+            filled,
+            high_contrast,
+        ),
+        None => Vec::new(),
+    };
+    let hold_para = Paragraph::new(hold_rows)
+        .block(hold_block)
+        .alignment(Alignment::Center);
+    f.render_widget(hold_para, side_chunks[0]);
+
+    // Next pieces, a row apart; ones that don't fit whole are left off
+    let next_block = boxed(config).title(" Next ");
+    let room = side_chunks[1].height.saturating_sub(2) as usize;
     let mut next_rows: Vec<Line> = Vec::new();
-    let next_tetro = Tetromino::new(game.next);
-    let grid = &next_tetro.rotations[0];
-    for by in 0..4 {
-        let mut spans: Vec<Span> = Vec::new();
-        for bx in 0..4 {
-            if grid[(by * 4 + bx) as usize] != 0 {
-                spans.push(Span::styled("  ", Style::default().bg(game.next.color())));
-            } else {
-                spans.push(Span::styled("  ", Style::default().bg(Color::Black)));
-            }
+    if shown == 0 {
+        next_rows = hidden();
+    }
+    for &kind in game.next.iter().take(shown) {
+        let rows = preview_rows(kind, theme.piece(kind), filled, high_contrast);
+        let gap = usize::from(!next_rows.is_empty());
+        if next_rows.len() + gap + rows.len() > room {
+            break;
         }
-        next_rows.push(Line::from(spans));
+        if gap > 0 {
+            next_rows.push(Line::from(""));
+        }
+        next_rows.extend(rows);
     }
-    let next_para = Paragraph::new(next_rows).block(next_block);
-    f.render_widget(next_para, side_chunks[0]);
+    let next_para = Paragraph::new(next_rows)
+        .block(next_block)
+        .alignment(Alignment::Center);
+    f.render_widget(next_para, side_chunks[1]);
 
     // Score box
-    let score_block = Block::default().borders(Borders::ALL).title(" Stats ");
-    let score_text = vec![
+    let score_block = boxed(config).title(" Stats ");
+    let mut score_text = vec![
         Line::from(vec![Span::raw(format!("Score: {}", game.score))]),
-        Line::from(vec![Span::raw(format!("Level: {}", game.level))]),
+        Line::from(vec![Span::styled(
+            format!("Level: {}", game.level),
+            match panel.level_up {
+                Some(note) => Style::default().fg(note.color).add_modifier(Modifier::BOLD),
+                None => Style::default(),
+            },
+        )]),
         Line::from(vec![Span::raw(format!("Lines: {}", game.lines_cleared))]),
     ];
+    let into = game.lines_into_level();
+    let goal = into + game.lines_needed_for_next();
+    score_text.push(Line::from(format!(
+        "Level {} → {}: {}/{}",
+        game.level,
+        game.level + 1,
+        into,
+        goal
+    )));
+    score_text.push(Line::from(Span::styled(
+        progress_bar(into as f64 / goal as f64, 12),
+        Style::default().fg(Color::Cyan),
+    )));
+    // sprint is a race, so its pace is what matters most
+    let lpm_style = if game.setup.mode == GameMode::Sprint {
+        Style::default().add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    };
+    score_text.push(Line::from(Span::styled(
+        format!("LPM: {:.1} (peak: {:.1})", game.lpm(), game.peak_lpm),
+        lpm_style,
+    )));
+    score_text.push(Line::from(Span::styled(
+        format!("LPM (30s): {:.1}", game.rolling_lpm()),
+        lpm_style,
+    )));
+    score_text.push(Line::from(kpp_apm(game)));
+    score_text.push(Line::from(efficiency_span(game)));
+    score_text.push(Line::from(format!(
+        "Finesse faults: {}",
+        game.finesse_faults
+    )));
+    let fill = game.fill_percentage();
+    let fill_color = if fill < 30.0 {
+        Color::Green
+    } else if fill <= 60.0 {
+        Color::Yellow
+    } else {
+        Color::Red
+    };
+    score_text.push(Line::from(Span::styled(
+        format!("Fill: {:.1}%", fill),
+        Style::default().fg(fill_color),
+    )));
+    let history: Vec<f64> = game.fill_history.iter().copied().collect();
+    score_text.push(Line::from(Span::styled(
+        Sparkline::render(&history, 0.0, 100.0),
+        Style::default().fg(fill_color),
+    )));
+    let coverage = game
+        .average_coverage()
+        .unwrap_or_else(|| game.board_coverage());
+    let coverage_style = if coverage < 0.6 {
+        Style::default().fg(Color::Red)
+    } else if coverage < 0.8 {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default()
+    };
+    score_text.push(Line::from(Span::styled(
+        if game.peak_coverage > 0.0 {
+            format!(
+                "Coverage: {:.1}% (peak: {:.1}%)",
+                coverage * 100.0,
+                game.peak_coverage * 100.0
+            )
+        } else {
+            format!("Coverage: {:.1}%", coverage * 100.0)
+        },
+        coverage_style,
+    )));
     let score_para = Paragraph::new(score_text).block(score_block);
-    f.render_widget(score_para, side_chunks[1]);
+    f.render_widget(score_para, side_chunks[2]);
+    // score popups sit on the right of the Stats box, newest on top, dimming before they go
+    let popup_lines: Vec<Line> = panel
+        .popups
+        .iter()
+        .rev()
+        .map(|popup| {
+            let mut style = Style::default()
+                .fg(popup.color)
+                .add_modifier(Modifier::BOLD);
+            if popup.is_fading() {
+                style = faded(style, config);
+            }
+            Line::from(Span::styled(format!("{} ", popup.text), style))
+        })
+        .collect();
+    let stats_inner = Block::default().borders(Borders::ALL).inner(side_chunks[2]);
+    f.render_widget(
+        Paragraph::new(popup_lines).alignment(Alignment::Right),
+        stats_inner,
+    );
 
     // Status / Controls
-    let status_block = Block::default().borders(Borders::ALL).title(" Controls ");
-    let status_text = vec![
-        Line::from(vec![Span::raw("← → : Move     ↓ : Soft drop")]),
-        Line::from(vec![Span::raw("↑ : Rotate CW  Z : Rotate CCW")]),
-        Line::from(vec![Span::raw("Space : Hard drop")]),
-        Line::from(vec![Span::raw("P : Pause   R : Restart   Q : Quit")]),
-    ];
-    let status_para = Paragraph::new(status_text).block(status_block);
-    f.render_widget(status_para, side_chunks[2]);
+    let status_block = boxed(config).title(" Controls ");
+    let status_para = Paragraph::new(panel.controls).block(status_block);
+    f.render_widget(status_para, side_chunks[3]);
 
     // Bottom area: runtime, level bar, pause/gameover message
-    let bottom = Block::default().borders(Borders::ALL).title(" Status ");
-    let mut bottom_text: Vec<Line> = vec![];
+    let bottom = boxed(config).title(" Status ");
+    let mut bottom_text: Vec<Line> = panel
+        .toasts
+        .iter()
+        .map(|toast| {
+            Line::from(Span::styled(
+                toast.text.clone(),
+                Style::default()
+                    .fg(toast.color)
+                    .add_modifier(Modifier::BOLD),
+            ))
+        })
+        .collect();
+    // the History box takes up to half of what's left, once there's a clear
+    let (status_area, history_area) = match panel.history {
+        Some(_) if !game.clear_history.is_empty() => {
+            let rows = (game.clear_history.len() as u16 + 2).min(side_chunks[4].height / 2);
+            let split = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(3), Constraint::Length(rows)].as_ref())
+                .split(side_chunks[4]);
+            (split[0], Some(split[1]))
+        }
+        _ => (side_chunks[4], None),
+    };
+    if let Some(inputs) = panel.inputs {
+        let inner = Block::default().borders(Borders::ALL).inner(status_area);
+        bottom_text.push(inputs.line(inner.width as usize));
+    }
+    bottom_text.extend(panel.status);
     let elapsed = format_duration(game.elapsed());
     bottom_text.push(Line::from(vec![Span::raw(format!("Time: {}", elapsed))]));
     bottom_text.push(Line::from(vec![Span::raw(format!(
         "Gravity: {:?}ms",
         game.gravity_interval.as_millis()
     ))]));
+    if game.zone_active {
+        // flash while the zone runs, with the time left as a bar
+        let color = if (game.ticks / 10).is_multiple_of(2) {
+            Color::LightCyan
+        } else {
+            Color::Magenta
+        };
+        let left =
+            1.0 - game.zone_elapsed.as_secs_f64() / game.setup.rules.zone_duration.as_secs_f64();
+        bottom_text.push(Line::from(vec![Span::styled(
+            " ZONE ACTIVE ",
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        )]));
+        bottom_text.push(Line::from(vec![Span::styled(
+            format!("{} {} lines", progress_bar(left, 12), game.zone_lines),
+            Style::default().fg(color),
+        )]));
+    } else if game.zone_ready() {
+        bottom_text.push(Line::from(vec![Span::raw("Zone: ready")]));
+    } else if game.zone_charge > 0 {
+        bottom_text.push(Line::from(vec![Span::raw(format!(
+            "Zone: {} pieces",
+            game.zone_charge
+        ))]));
+    }
+    if game.is_soft_locked() {
+        bottom_text.push(Line::from(vec![Span::styled(
+            " LOCKING... ",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )]));
+    }
+    if game.irs_fired && !game.game_over {
+        bottom_text.push(Line::from(vec![Span::styled(
+            " IRS ",
+            Style::default().fg(Color::Magenta),
+        )]));
+    }
     if game.paused {
         bottom_text.push(Line::from(vec![Span::styled(
             " PAUSED ",
@@ -664,17 +2806,321 @@ fn ui<B: ratatui::backend::Backend>(f: &mut ratatui::Frame<B>, game: &Game) {
                 .add_modifier(Modifier::BOLD),
         )]));
     }
-    if game.game_over {
-        bottom_text.push(Line::from(vec![Span::styled(
-            format!(" GAME OVER — Final score: {} ", game.score),
+    if let GameMode::Cheese { .. } = game.setup.mode {
+        bottom_text.push(Line::from(vec![Span::raw(format!(
+            "Garbage left: {}",
+            game.garbage_rows_left()
+        ))]));
+    }
+    match game.setup.mode {
+        GameMode::Sprint => bottom_text.push(Line::from(vec![Span::raw(format!(
+            "Lines left: {}",
+            SPRINT_LINES.saturating_sub(game.lines_cleared)
+        ))])),
+        GameMode::Ultra => bottom_text.push(Line::from(vec![Span::raw(format!(
+            "Time left: {}",
+            format_duration(game.ultra_remaining())
+        ))])),
+        GameMode::DailyChallenge { number } => {
+            bottom_text.push(Line::from(vec![Span::raw(format!("Daily #{}", number))]))
+        }
+        GameMode::Competitive => bottom_text.push(Line::from(Span::styled(
+            "COMPETITIVE",
             Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-        )]));
+        ))),
+        GameMode::Puzzle { .. } if !game.game_over => {
+            // the current piece plus everything still to come
+            let left = 1 + game.next.len() + game.sequence.len();
+            bottom_text.push(Line::from(format!("Pieces left: {}", left)))
+        }
+        GameMode::Marathon
+        | GameMode::Cheese { .. }
+        | GameMode::Puzzle { .. }
+        | GameMode::Tutorial => {}
+    }
+    if game.game_over {
+        let (text, color) = game_over_message(game);
         bottom_text.push(Line::from(vec![Span::styled(
-            " Press 'R' to restart or 'Q' to quit ",
-            Style::default().fg(Color::White),
+            format!(" {} ", text),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
         )]));
+        bottom_text.push(Line::from(vec![Span::raw(format!(
+            " Final score: {} ",
+            game.score
+        ))]));
+        bottom_text.push(Line::from(format!(" {} ", kpp_apm(game))));
+        if let GameMode::DailyChallenge { number } = game.setup.mode {
+            bottom_text.push(Line::from(vec![Span::raw(format!(
+                " {} ",
+                DailyResult::of(game).share_text(number)
+            ))]));
+        }
+        if let Some(hint) = panel.game_over_hint {
+            bottom_text.push(hint);
+        }
     }
 
     let bottom_para = Paragraph::new(bottom_text).block(bottom);
-    f.render_widget(bottom_para, side_chunks[3]);
+    f.render_widget(bottom_para, status_area);
+
+    if let (Some(view), Some(area)) = (panel.history, history_area) {
+        render_history(f, area, game, view, config);
+    }
+
+    if config.ui.ascii {
+        f.render_widget(AsciiOnly, size);
+    }
+}
+
+/// The last clears, newest first; ones more than `HISTORY_FADE` old are dimmed
+fn render_history<B: ratatui::backend::Backend>(
+    f: &mut ratatui::Frame<B>,
+    area: Rect,
+    game: &Game,
+    view: &HistoryView,
+    config: &Config,
+) {
+    let mut block = boxed(config).title(" History ");
+    if view.focused {
+        block = block
+            .title(" History ↑↓ ")
+            .border_style(Style::default().fg(Color::Yellow));
+    }
+    let rows = area.height.saturating_sub(2) as usize;
+    let scroll = view
+        .scroll
+        .min(game.clear_history.len().saturating_sub(rows));
+    let now = game.elapsed();
+    let lines: Vec<Line> = game
+        .clear_history
+        .iter()
+        .skip(scroll)
+        .map(|clear| {
+            let mut text = format!("{} +{}", clear.name(), clear.points);
+            if clear.back_to_back {
+                text.push_str(" B2B");
+            }
+            if clear.combo > 0 {
+                text.push_str(&format!(" x{}", clear.combo));
+            }
+            if clear.perfect_clear {
+                text.push_str(" PC");
+            }
+            text.push_str(&format!(" [{}]", format_duration(clear.elapsed)));
+            let style = if now.saturating_sub(clear.elapsed) > HISTORY_FADE {
+                faded(Style::default(), config)
+            } else {
+                Style::default()
+            };
+            Line::styled(text, style)
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+
+    fn game(mode: GameMode) -> Game {
+        Game::new(GameSetup {
+            mode,
+            rules: Rules::default(),
+            seed: Some(1),
+        })
+    }
+
+    #[test]
+    fn losing_focus_pauses_until_it_comes_back() {
+        let mut game = game(GameMode::Marathon);
+        let mut auto_pause = AutoPause::default();
+        auto_pause.focus(&mut game, false);
+        assert!(game.paused);
+        auto_pause.focus(&mut game, true);
+        assert!(!game.paused);
+
+        // the player's own pause outlasts a trip away
+        game.set_paused(true);
+        auto_pause.focus(&mut game, false);
+        auto_pause.focus(&mut game, true);
+        assert!(game.paused);
+    }
+
+    #[test]
+    fn a_daily_run_keeps_going_without_focus() {
+        let mut game = game(GameMode::DailyChallenge { number: 1 });
+        let mut auto_pause = AutoPause::default();
+        auto_pause.focus(&mut game, false);
+        assert!(!game.paused);
+    }
+
+    #[test]
+    fn frames_are_no_shorter_than_the_frame_interval() {
+        let (tx, rx) = mpsc::channel();
+        let sender = thread::spawn(move || {
+            for i in 0..40 {
+                tx.send(i).unwrap();
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+        let mut last_frame = Instant::now();
+        let mut received = Vec::new();
+        for _ in 0..5 {
+            let start = last_frame;
+            received.extend(frame_events(&rx, &mut last_frame));
+            assert!(last_frame - start >= FRAME);
+        }
+        sender.join().unwrap();
+        // the sender is gone; a frame still waits out its interval
+        let start = last_frame;
+        received.extend(frame_events(&rx, &mut last_frame));
+        assert!(last_frame - start >= FRAME);
+        assert_eq!(received, (0..40).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn the_grid_shades_every_other_cell() {
+        let theme = Theme::default();
+        let shade = Color::Rgb(28, 28, 28);
+        for (x, y, with_grid) in [
+            (0, 0, theme.background),
+            (1, 0, shade),
+            (0, 1, shade),
+            (1, 1, theme.background),
+            (9, 19, theme.background),
+            (9, 18, shade),
+        ] {
+            assert_eq!(empty_cell_bg(x, y, true, &theme), with_grid, "({x}, {y})");
+            assert_eq!(empty_cell_bg(x, y, false, &theme), theme.background);
+        }
+    }
+
+    #[test]
+    fn two_rows_share_a_half_block_line() {
+        let line = half_block_line(&[Color::Red, Color::Blue], &[Color::Green, Color::Black]);
+        let cells: Vec<_> = line
+            .spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style.fg, span.style.bg))
+            .collect();
+        assert_eq!(
+            cells,
+            [
+                ("▀", Some(Color::Red), Some(Color::Green)),
+                ("▀", Some(Color::Blue), Some(Color::Black)),
+            ]
+        );
+    }
+
+    #[test]
+    fn the_level_accent_warms_from_blue_to_red() {
+        let rgb = |level| match level_accent(level) {
+            Color::Rgb(r, g, b) => (r, g, b),
+            other => panic!("level {level} gave {other:?}"),
+        };
+        let (r, g, b) = rgb(1);
+        assert!(b > r && b > g, "level 1 is {:?}", (r, g, b));
+        let (r, g, b) = rgb(16);
+        assert!(r > g && g == b, "level 16 is {:?}", (r, g, b));
+        for level in 1..16 {
+            assert!(rgb(level + 1).0 >= rgb(level).0, "level {}", level + 1);
+        }
+        // past the point where the hue runs out it stays red
+        assert_eq!(level_accent(30), level_accent(16));
+        assert_eq!(level_accent(0), level_accent(1));
+    }
+
+    #[test]
+    fn the_hold_is_dimmed_until_the_next_drop() {
+        let theme = Theme::default();
+        let mut game = game(GameMode::Marathon);
+        game.apply(Action::Hold);
+        let held = game.hold.unwrap();
+        assert!(game.hold_used_this_drop);
+        assert_eq!(
+            hold_color(held, game.hold_used_this_drop, &theme),
+            Color::DarkGray
+        );
+
+        game.apply(Action::HardDrop);
+        while !game.piece_active() {
+            game.step();
+        }
+        assert!(!game.hold_used_this_drop);
+        assert_eq!(
+            hold_color(held, game.hold_used_this_drop, &theme),
+            theme.piece(held)
+        );
+    }
+
+    #[test]
+    fn help_shows_the_keys_as_rebound() {
+        let keys = KeysConfig {
+            move_left: vec!["a".to_string()],
+            move_right: vec!["d".to_string()],
+            hard_drop: vec!["w".to_string(), "space".to_string()],
+            help: vec!["F10".to_string()],
+            ..KeysConfig::default()
+        };
+        let keymap = Keymap::from_config(&keys).unwrap();
+        let lines: Vec<String> = help_lines(GameMode::Marathon, &keymap)
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect();
+        assert!(lines.contains(&"A : Move left".to_string()));
+        assert!(lines.contains(&"D : Move right".to_string()));
+        assert!(lines.contains(&"W/Space : Hard drop".to_string()));
+        assert!(lines.contains(&"↑ : Rotate clockwise".to_string()));
+        assert!(!lines.iter().any(|line| line.starts_with('←')));
+        assert_eq!(lines.last().unwrap(), "F10 / Esc : Close");
+    }
+
+    /// The color each piece's preview is drawn in under `theme`, read back
+    /// from a test terminal
+    fn rendered_piece_colors(theme: &Theme) -> Vec<Color> {
+        BlockType::all()
+            .iter()
+            .map(|&kind| {
+                let mut terminal = Terminal::new(TestBackend::new(8, 2)).unwrap();
+                terminal
+                    .draw(|f| {
+                        let rows = preview_rows(kind, theme.piece(kind), "██", false);
+                        f.render_widget(Paragraph::new(rows), f.size());
+                    })
+                    .unwrap();
+                let buffer = terminal.backend().buffer();
+                let colors: Vec<Color> = buffer
+                    .content
+                    .iter()
+                    .filter(|cell| cell.symbol == "█")
+                    .map(|cell| cell.fg)
+                    .collect();
+                assert_eq!(colors.len(), 8, "{kind:?} draws four blocks");
+                assert!(colors.iter().all(|&c| c == colors[0]));
+                colors[0]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn colorblind_palettes_keep_every_piece_apart() {
+        for name in ["deuteranopia", "tritanopia"] {
+            let theme = Theme::builtin(name).unwrap();
+            let mut colors = rendered_piece_colors(&theme);
+            colors.push(theme.piece(BlockType::Garbage));
+            colors.extend(theme.ghost);
+            colors.push(theme.background);
+            for (i, a) in colors.iter().enumerate() {
+                for b in &colors[i + 1..] {
+                    assert_ne!(a, b, "{name} repeats a color");
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,986 @@
+//! Matchmaking through a server, over a plain-text line protocol that can be
+//! watched (or typed) with `nc`:
+//!
+//! | from | line | meaning |
+//! |------|------|---------|
+//! | client | `HELLO <name>` | join the queue, or rejoin a match after a dropped connection |
+//! | server | `WAITING` | no opponent yet |
+//! | server | `MATCH <opponent> <seed> <timestamp>` | both play `seed`, starting at unix time `timestamp` (ms) |
+//! | client | `INPUT <action> <ms>` | action number pressed at game time `ms` |
+//! | client | `OVER <ms>` | the sender topped out at game time `ms` |
+//! | server | `BOARD <cells> <score> <lines>` | the opponent's stack, every 200 ms |
+//! | server | `RESULT <your_score> <opponent_score>` | both games are over |
+//!
+//! Game time counts ticks, so it stands still while a game is paused, and the
+//! server replays each player's inputs on its own copy of their game to know
+//! their board. Names are one word; `cells` is the board row by row from the
+//! top, `.` for empty, a piece letter or `X` for garbage.
+
+use crossterm::event::KeyEventKind;
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use std::{
+    collections::HashMap,
+    env, fmt,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    str::FromStr,
+    sync::{Arc, Mutex, mpsc},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use tetris_game::{
+    Action, BOARD_WIDTH, BlockType, Board, Game, GameMode, GameSetup, RotationDir, TICK,
+    config::Config,
+    keymap::{Command, Keymap},
+    milestones::thousands,
+    paths,
+    rules::Rules,
+};
+
+use crate::{InternalEvent, Outputs, Panel, Term, draw_game, frame_events};
+
+/// How often each player is sent the other's board
+pub const BOARD_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Time between the match being made and its first tick, to get ready
+const START_DELAY: Duration = Duration::from_secs(3);
+
+/// How long a dropped player has to reconnect before their game counts as over
+const RECONNECT_GRACE: Duration = Duration::from_secs(30);
+
+/// Pause between reconnection attempts
+const RECONNECT_RETRY: Duration = Duration::from_secs(1);
+
+/// `INPUT` action numbers, in order
+const ACTIONS: [Action; 11] = [
+    Action::MoveLeft,
+    Action::MoveRight,
+    Action::SoftDrop,
+    Action::HardDrop,
+    Action::RotateCw,
+    Action::RotateCcw,
+    Action::Hold,
+    Action::Zone,
+    Action::SetIrs(None),
+    Action::SetIrs(Some(RotationDir::Cw)),
+    Action::SetIrs(Some(RotationDir::Ccw)),
+];
+
+/// A line from a client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientLine {
+    Hello { name: String },
+    Input { action: Action, at_ms: u64 },
+    Over { at_ms: u64 },
+}
+
+/// A line from the server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerLine {
+    Waiting,
+    Match {
+        opponent: String,
+        seed: u64,
+        timestamp: u64,
+    },
+    Board {
+        cells: String,
+        score: usize,
+        lines: usize,
+    },
+    Result {
+        yours: usize,
+        theirs: usize,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LineError {
+    UnknownCommand(String),
+    /// The command is known but what follows it isn't what it takes
+    BadArguments(&'static str),
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LineError::UnknownCommand(word) => write!(f, "unknown command '{}'", word),
+            LineError::BadArguments(command) => write!(f, "bad arguments to {}", command),
+        }
+    }
+}
+
+impl std::error::Error for LineError {}
+
+fn action_number(action: Action) -> usize {
+    ACTIONS.iter().position(|&a| a == action).unwrap_or(0)
+}
+
+/// The words of a line after its command, parsed as `N` values
+fn args<const N: usize>(command: &'static str, rest: &[&str]) -> Result<[u64; N], LineError> {
+    let bad = || LineError::BadArguments(command);
+    let values: Vec<u64> = rest
+        .iter()
+        .map(|word| word.parse().map_err(|_| bad()))
+        .collect::<Result<_, _>>()?;
+    values.try_into().map_err(|_| bad())
+}
+
+impl fmt::Display for ClientLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClientLine::Hello { name } => write!(f, "HELLO {}", name),
+            ClientLine::Input { action, at_ms } => {
+                write!(f, "INPUT {} {}", action_number(*action), at_ms)
+            }
+            ClientLine::Over { at_ms } => write!(f, "OVER {}", at_ms),
+        }
+    }
+}
+
+impl FromStr for ClientLine {
+    type Err = LineError;
+
+    fn from_str(line: &str) -> Result<Self, LineError> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["HELLO", name] => Ok(ClientLine::Hello {
+                name: name.to_string(),
+            }),
+            ["HELLO", ..] => Err(LineError::BadArguments("HELLO")),
+            ["INPUT", rest @ ..] => {
+                let [number, at_ms] = args("INPUT", rest)?;
+                let action = *ACTIONS
+                    .get(number as usize)
+                    .ok_or(LineError::BadArguments("INPUT"))?;
+                Ok(ClientLine::Input { action, at_ms })
+            }
+            ["OVER", rest @ ..] => {
+                let [at_ms] = args("OVER", rest)?;
+                Ok(ClientLine::Over { at_ms })
+            }
+            [word, ..] => Err(LineError::UnknownCommand(word.to_string())),
+            [] => Err(LineError::UnknownCommand(String::new())),
+        }
+    }
+}
+
+impl fmt::Display for ServerLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ServerLine::Waiting => write!(f, "WAITING"),
+            ServerLine::Match {
+                opponent,
+                seed,
+                timestamp,
+            } => write!(f, "MATCH {} {} {}", opponent, seed, timestamp),
+            ServerLine::Board {
+                cells,
+                score,
+                lines,
+            } => write!(f, "BOARD {} {} {}", cells, score, lines),
+            ServerLine::Result { yours, theirs } => write!(f, "RESULT {} {}", yours, theirs),
+        }
+    }
+}
+
+impl FromStr for ServerLine {
+    type Err = LineError;
+
+    fn from_str(line: &str) -> Result<Self, LineError> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["WAITING"] => Ok(ServerLine::Waiting),
+            ["MATCH", opponent, rest @ ..] => {
+                let [seed, timestamp] = args("MATCH", rest)?;
+                Ok(ServerLine::Match {
+                    opponent: opponent.to_string(),
+                    seed,
+                    timestamp,
+                })
+            }
+            ["BOARD", cells, rest @ ..] => {
+                let [score, lines] = args("BOARD", rest)?;
+                Ok(ServerLine::Board {
+                    cells: cells.to_string(),
+                    score: score as usize,
+                    lines: lines as usize,
+                })
+            }
+            ["RESULT", rest @ ..] => {
+                let [yours, theirs] = args("RESULT", rest)?;
+                Ok(ServerLine::Result {
+                    yours: yours as usize,
+                    theirs: theirs as usize,
+                })
+            }
+            ["WAITING", ..] => Err(LineError::BadArguments("WAITING")),
+            ["MATCH", ..] => Err(LineError::BadArguments("MATCH")),
+            ["BOARD", ..] => Err(LineError::BadArguments("BOARD")),
+            [word, ..] => Err(LineError::UnknownCommand(word.to_string())),
+            [] => Err(LineError::UnknownCommand(String::new())),
+        }
+    }
+}
+
+/// `board` as a `BOARD` line's cells
+pub fn board_cells(board: &Board) -> String {
+    board
+        .iter()
+        .flatten()
+        .map(|cell| match cell {
+            None => '.',
+            Some(BlockType::Garbage) => 'X',
+            Some(kind) => kind.glyph().chars().next().unwrap_or('X'),
+        })
+        .collect()
+}
+
+/// Rows from the top of the stack in `cells` to the floor
+fn stack_height(cells: &str) -> usize {
+    let rows: Vec<&[u8]> = cells.as_bytes().chunks(BOARD_WIDTH).collect();
+    rows.iter()
+        .position(|row| row.iter().any(|&c| c != b'.'))
+        .map_or(0, |top| rows.len() - top)
+}
+
+/// One-word name to play under: the profile, else the login name
+pub fn default_name() -> String {
+    paths::profile()
+        .map(str::to_string)
+        .or_else(|| env::var("USER").ok())
+        .or_else(|| env::var("USERNAME").ok())
+        .map(|name| name.split_whitespace().collect::<Vec<_>>().join("_"))
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "Player".to_string())
+}
+
+fn unix_ms(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// Game time of `game`, as `INPUT` and `OVER` carry it
+fn game_ms(game: &Game) -> u64 {
+    game.ticks * TICK.as_millis() as u64
+}
+
+fn send(stream: &mut TcpStream, line: impl fmt::Display) -> io::Result<()> {
+    stream.write_all(format!("{}\n", line).as_bytes())
+}
+
+/// Read one line a byte at a time, so nothing after it is taken from the
+/// stream; `None` at end of stream
+fn read_first_line(stream: &mut TcpStream) -> io::Result<Option<String>> {
+    let mut line = Vec::new();
+    let mut byte = [0u8];
+    loop {
+        if stream.read(&mut byte)? == 0 {
+            return Ok(None);
+        }
+        match byte[0] {
+            b'\n' => return Ok(Some(String::from_utf8_lossy(&line).trim().to_string())),
+            b => line.push(b),
+        }
+    }
+}
+
+/// Forward each parsed line from `stream` to `tx`, tagged with `tag`, then
+/// `None` once it closes or sends something unreadable
+fn spawn_reader<L, T>(
+    stream: &TcpStream,
+    tag: T,
+    tx: mpsc::Sender<(T, Option<L>)>,
+) -> io::Result<()>
+where
+    L: FromStr + Send + 'static,
+    T: Copy + Send + 'static,
+{
+    let reader = BufReader::new(stream.try_clone()?);
+    thread::spawn(move || {
+        for line in reader.lines() {
+            let Some(parsed) = line.ok().and_then(|l| l.parse().ok()) else {
+                break;
+            };
+            if tx.send((tag, Some(parsed))).is_err() {
+                return;
+            }
+        }
+        let _ = tx.send((tag, None));
+    });
+    Ok(())
+}
+
+/// Players in a match whose connection dropped, by name, and where to hand
+/// their next one
+type Dropped = Arc<Mutex<HashMap<String, mpsc::Sender<TcpStream>>>>;
+
+/// Pairs players up in the order they arrive and referees their matches
+pub struct MatchServer {
+    listener: TcpListener,
+    dropped: Dropped,
+}
+
+impl MatchServer {
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(MatchServer {
+            listener: TcpListener::bind(addr)?,
+            dropped: Dropped::default(),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serve until the listener fails, each match on its own thread
+    pub fn run(&self) -> io::Result<()> {
+        let (hello_tx, hello_rx) = mpsc::channel::<(String, TcpStream)>();
+        let dropped = Arc::clone(&self.dropped);
+        thread::spawn(move || {
+            let mut waiting: Option<(String, TcpStream)> = None;
+            for (name, mut stream) in hello_rx {
+                let rejoin = dropped.lock().unwrap().remove(&name);
+                if let Some(seat) = rejoin {
+                    match seat.send(stream) {
+                        Ok(()) => continue,
+                        // the match ended while they were away; queue them afresh
+                        Err(mpsc::SendError(returned)) => stream = returned,
+                    }
+                }
+                match waiting.take() {
+                    Some(first) => {
+                        let dropped = Arc::clone(&dropped);
+                        thread::spawn(move || referee([first, (name, stream)], dropped));
+                    }
+                    None => {
+                        if send(&mut stream, ServerLine::Waiting).is_ok() {
+                            waiting = Some((name, stream));
+                        }
+                    }
+                }
+            }
+        });
+        for stream in self.listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            let hello_tx = hello_tx.clone();
+            // greeted on its own thread so a silent client can't hold up the rest
+            thread::spawn(move || {
+                if let Ok(Some(line)) = read_first_line(&mut stream)
+                    && let Ok(ClientLine::Hello { name }) = line.parse()
+                {
+                    let _ = hello_tx.send((name, stream));
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Run `matchmaking::MatchServer` on `port` until it fails
+pub fn serve(port: u16) -> io::Result<()> {
+    let server = MatchServer::bind(("0.0.0.0", port))?;
+    println!("matchmaking on port {}", server.local_addr()?.port());
+    server.run()
+}
+
+/// One player as the server sees them
+struct Seat {
+    name: String,
+    /// `None` while disconnected
+    stream: Option<TcpStream>,
+    /// Bumped with each new connection, so a stale reader's close is ignored
+    connection: usize,
+    game: Game,
+    over: bool,
+    dropped_at: Option<Instant>,
+    rejoin: Option<mpsc::Receiver<TcpStream>>,
+}
+
+impl Seat {
+    /// Step the server's copy of the game up to game time `ms`
+    fn advance_to(&mut self, ms: u64) {
+        let tick = ms / TICK.as_millis() as u64;
+        while self.game.ticks < tick && !self.game.game_over {
+            self.game.step();
+        }
+        self.over |= self.game.game_over;
+    }
+
+    fn send(&mut self, line: &ServerLine) {
+        if let Some(stream) = &mut self.stream
+            && send(stream, line).is_err()
+        {
+            self.stream = None;
+        }
+    }
+}
+
+/// Play one match between `players` to the end
+fn referee(players: [(String, TcpStream); 2], dropped: Dropped) {
+    let seed = rand::random();
+    let timestamp = unix_ms(SystemTime::now() + START_DELAY);
+    let (tx, rx) = mpsc::channel::<((usize, usize), Option<ClientLine>)>();
+    let mut seats: Vec<Seat> = players
+        .into_iter()
+        .map(|(name, stream)| {
+            let mut game = Game::new(GameSetup {
+                mode: GameMode::Marathon,
+                rules: Rules::default(),
+                seed: Some(seed),
+            });
+            game.replay = None;
+            Seat {
+                name,
+                stream: Some(stream),
+                connection: 0,
+                game,
+                over: false,
+                dropped_at: None,
+                rejoin: None,
+            }
+        })
+        .collect();
+    let match_line = |seats: &[Seat], i: usize| ServerLine::Match {
+        opponent: seats[1 - i].name.clone(),
+        seed,
+        timestamp,
+    };
+    for i in 0..2 {
+        let line = match_line(&seats, i);
+        seats[i].send(&line);
+        if let Some(stream) = &seats[i].stream
+            && spawn_reader(stream, (i, 0), tx.clone()).is_err()
+        {
+            seats[i].stream = None;
+        }
+    }
+
+    let mut next_board = Instant::now() + BOARD_INTERVAL;
+    loop {
+        let wait = next_board.saturating_duration_since(Instant::now());
+        match rx.recv_timeout(wait) {
+            Ok(((i, connection), line)) if connection == seats[i].connection => match line {
+                Some(ClientLine::Input { action, at_ms }) if !seats[i].over => {
+                    seats[i].advance_to(at_ms);
+                    if !seats[i].over {
+                        seats[i].game.apply(action);
+                        seats[i].over = seats[i].game.game_over;
+                    }
+                }
+                Some(ClientLine::Over { at_ms }) => {
+                    seats[i].advance_to(at_ms);
+                    seats[i].over = true;
+                }
+                Some(_) => {}
+                None => seats[i].stream = None,
+            },
+            Ok(_) | Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => return,
+        }
+        if Instant::now() < next_board {
+            continue;
+        }
+        next_board += BOARD_INTERVAL;
+
+        for i in 0..2 {
+            let seat = &mut seats[i];
+            if seat.stream.is_none() && seat.dropped_at.is_none() && !seat.over {
+                let (rejoin_tx, rejoin_rx) = mpsc::channel();
+                dropped.lock().unwrap().insert(seat.name.clone(), rejoin_tx);
+                seat.dropped_at = Some(Instant::now());
+                seat.rejoin = Some(rejoin_rx);
+            }
+            if let Some(stream) = seat.rejoin.as_ref().and_then(|r| r.try_recv().ok()) {
+                seat.connection += 1;
+                seat.dropped_at = None;
+                seat.rejoin = None;
+                if spawn_reader(&stream, (i, seat.connection), tx.clone()).is_ok() {
+                    seat.stream = Some(stream);
+                    let line = match_line(&seats, i);
+                    seats[i].send(&line);
+                }
+            } else if seat
+                .dropped_at
+                .is_some_and(|at| at.elapsed() >= RECONNECT_GRACE)
+            {
+                seat.over = true;
+            }
+        }
+        for i in 0..2 {
+            let theirs = &seats[1 - i].game;
+            let line = ServerLine::Board {
+                cells: board_cells(&theirs.board),
+                score: theirs.score,
+                lines: theirs.lines_cleared,
+            };
+            seats[i].send(&line);
+        }
+        if seats.iter().all(|seat| seat.over) {
+            for i in 0..2 {
+                let line = ServerLine::Result {
+                    yours: seats[i].game.score,
+                    theirs: seats[1 - i].game.score,
+                };
+                seats[i].send(&line);
+            }
+            let mut dropped = dropped.lock().unwrap();
+            for seat in &seats {
+                if seat.rejoin.is_some() {
+                    dropped.remove(&seat.name);
+                }
+            }
+            return;
+        }
+    }
+}
+
+/// What `MATCH` said
+#[derive(Debug, Clone)]
+pub struct Matched {
+    pub opponent: String,
+    pub seed: u64,
+    /// When both games start
+    pub start: SystemTime,
+}
+
+/// A player's connection to a `MatchServer`
+pub struct MatchClient {
+    addr: SocketAddr,
+    name: String,
+    stream: TcpStream,
+    incoming: mpsc::Receiver<((), Option<ServerLine>)>,
+}
+
+impl MatchClient {
+    /// Connect to the server at `addr` and say hello as `name`
+    pub fn connect(addr: impl ToSocketAddrs, name: &str) -> io::Result<Self> {
+        let addr = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no address to connect to"))?;
+        let mut stream = TcpStream::connect_timeout(&addr, RECONNECT_RETRY)?;
+        stream.set_nodelay(true)?;
+        send(
+            &mut stream,
+            ClientLine::Hello {
+                name: name.to_string(),
+            },
+        )?;
+        let (tx, incoming) = mpsc::channel();
+        spawn_reader(&stream, (), tx)?;
+        Ok(MatchClient {
+            addr,
+            name: name.to_string(),
+            stream,
+            incoming,
+        })
+    }
+
+    /// Block until the server makes a match, skipping `WAITING`
+    pub fn wait_for_match(&mut self) -> io::Result<Matched> {
+        loop {
+            match self.incoming.recv() {
+                Ok((
+                    _,
+                    Some(ServerLine::Match {
+                        opponent,
+                        seed,
+                        timestamp,
+                    }),
+                )) => {
+                    return Ok(Matched {
+                        opponent,
+                        seed,
+                        start: UNIX_EPOCH + Duration::from_millis(timestamp),
+                    });
+                }
+                Ok((_, Some(_))) => {}
+                Ok((_, None)) | Err(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::ConnectionAborted,
+                        "the server closed the connection",
+                    ));
+                }
+            }
+        }
+    }
+
+    pub fn send(&mut self, line: &ClientLine) -> io::Result<()> {
+        send(&mut self.stream, line)
+    }
+
+    /// Lines received since the last call; an error once the connection is gone
+    pub fn poll(&mut self) -> io::Result<Vec<ServerLine>> {
+        let mut lines = Vec::new();
+        loop {
+            match self.incoming.try_recv() {
+                Ok((_, Some(line))) => lines.push(line),
+                Err(mpsc::TryRecvError::Empty) => return Ok(lines),
+                Ok((_, None)) | Err(mpsc::TryRecvError::Disconnected) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::ConnectionAborted,
+                        "lost the connection to the server",
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Connect again under the same name; the server puts it back in the match
+    pub fn reconnect(&mut self) -> io::Result<()> {
+        let _ = self.stream.shutdown(Shutdown::Both);
+        *self = MatchClient::connect(self.addr, &self.name)?;
+        Ok(())
+    }
+}
+
+/// Where the local side of the match is
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Link {
+    /// Matched, counting down to the start
+    Starting,
+    Playing,
+    /// The connection dropped; the game waits, paused, until it's back
+    Reconnecting {
+        next_try: Instant,
+    },
+    Finished {
+        yours: usize,
+        theirs: usize,
+    },
+    /// The server no longer knows the match
+    Lost,
+}
+
+/// The opponent's last `BOARD`
+#[derive(Default)]
+struct OpponentView {
+    height: usize,
+    score: usize,
+    lines: usize,
+}
+
+/// Play a match made by `client`'s server
+pub fn run(
+    terminal: &mut Term,
+    rx: &mpsc::Receiver<InternalEvent>,
+    outputs: &mut Outputs,
+    mut client: MatchClient,
+    matched: Matched,
+    config: &Config,
+    keymap: &Keymap,
+) -> io::Result<()> {
+    // the server replays inputs under default rules, so play by those
+    let mut game = Game::new(GameSetup {
+        mode: GameMode::Marathon,
+        rules: Rules::default(),
+        seed: Some(matched.seed),
+    });
+    game.replay = None;
+    game.set_paused(true);
+    let title = format!(" You vs {} ", matched.opponent);
+    let mut link = Link::Starting;
+    let mut opponent = OpponentView::default();
+    let mut sent_over = false;
+    let mut last_frame = Instant::now();
+    loop {
+        if link == Link::Starting && SystemTime::now() >= matched.start {
+            link = Link::Playing;
+            game.set_paused(false);
+        }
+
+        terminal.draw(|f| {
+            let mut status = vec![link_line(link, &matched, game.game_over)];
+            status.push(Line::from(format!(
+                "{}: {} pts, {} lines",
+                matched.opponent,
+                thousands(opponent.score),
+                opponent.lines
+            )));
+            status.push(Line::from(format!("Their stack: {} rows", opponent.height)));
+            let play = |action| keymap.keys_for(Command::Play(action));
+            let controls = vec![
+                Line::from(format!(
+                    "{} {} : Move     {} : Soft drop",
+                    play(Action::MoveLeft),
+                    play(Action::MoveRight),
+                    play(Action::SoftDrop)
+                )),
+                Line::from(format!(
+                    "{} : Rotate CW  {} : Rotate CCW",
+                    play(Action::RotateCw),
+                    play(Action::RotateCcw)
+                )),
+                Line::from(format!(
+                    "{} : Hard drop   {} : Hold",
+                    play(Action::HardDrop),
+                    play(Action::Hold)
+                )),
+                Line::from(format!("{} : Quit", keymap.keys_for(Command::Quit))),
+            ];
+            let panel = Panel {
+                title: &title,
+                controls,
+                status,
+                game_over_hint: Some(Line::from(vec![Span::raw(" Press 'Q' to quit ")])),
+                notifications: &[],
+                toasts: &[],
+                popups: &[],
+                level_up: None,
+                trails: &[],
+                inputs: None,
+                heights: config.ui.column_heights,
+                suggestion: None,
+                best_board: None,
+                hold_preview: false,
+                help: None,
+                history: None,
+                tutorial: None,
+            };
+            draw_game(f, f.size(), &game, panel, config);
+        })?;
+
+        let mut did_quit = false;
+        let mut pressed = Vec::new();
+        for ev in frame_events(rx, &mut last_frame) {
+            match ev {
+                InternalEvent::Input(key) if key.kind == KeyEventKind::Release => {}
+                InternalEvent::Input(key) => match keymap.command_for(&key) {
+                    Some(Command::Quit) => did_quit = true,
+                    Some(Command::Play(action)) if link == Link::Playing && !game.game_over => {
+                        pressed.push(ClientLine::Input {
+                            action,
+                            at_ms: game_ms(&game),
+                        });
+                        game.press(action);
+                    }
+                    _ => {}
+                },
+                InternalEvent::Tick if link == Link::Playing => game.step(),
+                InternalEvent::Tick => {}
+                // the other side plays on, so there's no pausing for focus
+                InternalEvent::Focus(_) => {}
+            }
+        }
+        outputs.dispatch(&mut game);
+        if game.game_over && !sent_over && link == Link::Playing {
+            sent_over = true;
+            pressed.push(ClientLine::Over {
+                at_ms: game_ms(&game),
+            });
+        }
+
+        match link {
+            Link::Reconnecting { next_try } if Instant::now() >= next_try => {
+                link = match client.reconnect() {
+                    // `MATCH` comes back once the server has the new connection
+                    Ok(()) => Link::Reconnecting {
+                        next_try: Instant::now() + RECONNECT_GRACE,
+                    },
+                    Err(_) => Link::Reconnecting {
+                        next_try: Instant::now() + RECONNECT_RETRY,
+                    },
+                };
+            }
+            Link::Reconnecting { .. } | Link::Finished { .. } | Link::Lost => {}
+            Link::Starting | Link::Playing => {
+                let sent = pressed.iter().try_for_each(|line| client.send(line));
+                if sent.is_err() {
+                    game.set_paused(true);
+                    link = Link::Reconnecting {
+                        next_try: Instant::now(),
+                    };
+                }
+            }
+        }
+        if !matches!(link, Link::Finished { .. } | Link::Lost) {
+            match client.poll() {
+                Ok(lines) => {
+                    for line in lines {
+                        match line {
+                            ServerLine::Board {
+                                cells,
+                                score,
+                                lines,
+                            } => {
+                                opponent = OpponentView {
+                                    height: stack_height(&cells),
+                                    score,
+                                    lines,
+                                }
+                            }
+                            ServerLine::Result { yours, theirs } => {
+                                link = Link::Finished { yours, theirs }
+                            }
+                            ServerLine::Match { .. }
+                                if matches!(link, Link::Reconnecting { .. }) =>
+                            {
+                                // in case the top-out was lost with the connection
+                                sent_over = false;
+                                link = if SystemTime::now() >= matched.start {
+                                    game.set_paused(false);
+                                    Link::Playing
+                                } else {
+                                    Link::Starting
+                                };
+                            }
+                            // back in the queue: the match was given up while away
+                            ServerLine::Waiting => link = Link::Lost,
+                            ServerLine::Match { .. } => {}
+                        }
+                    }
+                }
+                Err(_) if !matches!(link, Link::Reconnecting { .. }) => {
+                    game.set_paused(true);
+                    link = Link::Reconnecting {
+                        next_try: Instant::now(),
+                    };
+                }
+                Err(_) => {}
+            }
+        }
+
+        if did_quit {
+            return Ok(());
+        }
+    }
+}
+
+/// The match's state, for the top of the Status box
+fn link_line(link: Link, matched: &Matched, topped_out: bool) -> Line<'static> {
+    let (text, color) = match link {
+        Link::Starting => {
+            let left = matched
+                .start
+                .duration_since(SystemTime::now())
+                .unwrap_or_default();
+            (
+                format!(" Starting in {}... ", left.as_secs() + 1),
+                Color::Cyan,
+            )
+        }
+        Link::Playing if topped_out => (
+            format!(" Waiting for {}... ", matched.opponent),
+            Color::Cyan,
+        ),
+        Link::Playing => return Line::from(""),
+        Link::Reconnecting { .. } => (" Reconnecting... ".to_string(), Color::Yellow),
+        Link::Finished { yours, theirs } if yours > theirs => {
+            (" WINNER! ".to_string(), Color::Green)
+        }
+        Link::Finished { yours, theirs } if yours < theirs => {
+            (format!(" {} wins ", matched.opponent), Color::Red)
+        }
+        Link::Finished { .. } => (" Draw ".to_string(), Color::Yellow),
+        Link::Lost => (" Connection lost ".to_string(), Color::Red),
+    };
+    Line::from(Span::styled(
+        text,
+        Style::default().fg(color).add_modifier(Modifier::BOLD),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What the server's copy of a player's game comes to after `inputs`
+    fn expected_score(seed: u64, inputs: &[(Action, u64)], over_ms: u64) -> usize {
+        let mut seat = Seat {
+            name: String::new(),
+            stream: None,
+            connection: 0,
+            game: Game::new(GameSetup {
+                mode: GameMode::Marathon,
+                rules: Rules::default(),
+                seed: Some(seed),
+            }),
+            over: false,
+            dropped_at: None,
+            rejoin: None,
+        };
+        for &(action, at_ms) in inputs {
+            seat.advance_to(at_ms);
+            seat.game.apply(action);
+        }
+        seat.advance_to(over_ms);
+        seat.game.score
+    }
+
+    fn play(client: &mut MatchClient, inputs: &[(Action, u64)], over_ms: u64) {
+        for &(action, at_ms) in inputs {
+            client.send(&ClientLine::Input { action, at_ms }).unwrap();
+        }
+        client.send(&ClientLine::Over { at_ms: over_ms }).unwrap();
+    }
+
+    /// Lines up to and including `RESULT`
+    fn until_result(client: &mut MatchClient) -> Vec<ServerLine> {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut lines = Vec::new();
+        while Instant::now() < deadline {
+            lines.extend(client.poll().unwrap());
+            if matches!(lines.last(), Some(ServerLine::Result { .. })) {
+                return lines;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        panic!("no RESULT within 10s, got {:?}", lines);
+    }
+
+    #[test]
+    fn a_match_is_played_to_its_result() {
+        let server = MatchServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+
+        let mut alice = MatchClient::connect(addr, "alice").unwrap();
+        let mut bob = MatchClient::connect(addr, "bob").unwrap();
+        let alice_match = alice.wait_for_match().unwrap();
+        let bob_match = bob.wait_for_match().unwrap();
+        assert_eq!(alice_match.opponent, "bob");
+        assert_eq!(bob_match.opponent, "alice");
+        assert_eq!(alice_match.seed, bob_match.seed);
+        assert_eq!(alice_match.start, bob_match.start);
+
+        let seed = alice_match.seed;
+        let alice_inputs = [
+            (Action::MoveLeft, 100),
+            (Action::HardDrop, 200),
+            (Action::HardDrop, 400),
+        ];
+        let bob_inputs = [(Action::RotateCw, 60), (Action::HardDrop, 500)];
+        play(&mut alice, &alice_inputs, 1_000);
+        play(&mut bob, &bob_inputs, 2_000);
+
+        let alice_score = expected_score(seed, &alice_inputs, 1_000);
+        let bob_score = expected_score(seed, &bob_inputs, 2_000);
+        assert!(alice_score > 0 && bob_score > 0);
+        assert_eq!(
+            until_result(&mut alice).last(),
+            Some(&ServerLine::Result {
+                yours: alice_score,
+                theirs: bob_score,
+            })
+        );
+        let bob_lines = until_result(&mut bob);
+        assert_eq!(
+            bob_lines.last(),
+            Some(&ServerLine::Result {
+                yours: bob_score,
+                theirs: alice_score,
+            })
+        );
+        assert!(bob_lines.iter().any(|line| matches!(
+            line,
+            ServerLine::Board { score, .. } if *score == alice_score
+        )));
+    }
+}
@@ -0,0 +1,184 @@
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Score thresholds that get a notification, in increasing order
+pub const MILESTONES: &[usize] = &[
+    1000, 5000, 10000, 25000, 50000, 100000, 250000, 500000, 1_000_000,
+];
+
+/// How long a milestone notification stays on the board
+const NOTIFICATION_TTL: Duration = Duration::from_secs(2);
+
+/// Achievement and record toasts are rarer and wordier, so they stay longer
+const TOAST_TTL: Duration = Duration::from_secs(5);
+
+/// Score popups are frequent, so they clear out quickly
+const POPUP_TTL: Duration = Duration::from_secs(1);
+
+/// The level-up banner only needs to catch the eye
+const LEVEL_UP_TTL: Duration = Duration::from_secs(1);
+
+/// Remembers which score milestones have been passed so each fires once per game
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MilestoneTracker {
+    #[serde(skip, default = "all_milestones")]
+    milestones: &'static [usize],
+    next_index: usize,
+}
+
+fn all_milestones() -> &'static [usize] {
+    MILESTONES
+}
+
+impl Default for MilestoneTracker {
+    fn default() -> Self {
+        MilestoneTracker {
+            milestones: MILESTONES,
+            next_index: 0,
+        }
+    }
+}
+
+impl MilestoneTracker {
+    /// Thresholds newly reached at `score` (a big clear can pass several)
+    pub fn check(&mut self, score: usize) -> Vec<usize> {
+        let mut reached = Vec::new();
+        while let Some(&threshold) = self.milestones.get(self.next_index) {
+            if score < threshold {
+                break;
+            }
+            reached.push(threshold);
+            self.next_index += 1;
+        }
+        reached
+    }
+}
+
+/// A short message overlaid on top of the board
+pub struct Notification {
+    pub text: String,
+    pub color: Color,
+    pub created: Instant,
+    ttl: Duration,
+}
+
+impl Notification {
+    pub fn milestone(threshold: usize) -> Self {
+        Notification {
+            text: format!("{} points!", thousands(threshold)),
+            color: Color::Rgb(255, 215, 0), // gold
+            created: Instant::now(),
+            ttl: NOTIFICATION_TTL,
+        }
+    }
+
+    pub fn achievement(name: &str) -> Self {
+        Notification {
+            text: format!("Achievement unlocked: {}", name),
+            color: Color::Green,
+            created: Instant::now(),
+            ttl: TOAST_TTL,
+        }
+    }
+
+    pub fn record(name: &str) -> Self {
+        Notification {
+            text: format!("New record: {}", name),
+            color: Color::Cyan,
+            created: Instant::now(),
+            ttl: TOAST_TTL,
+        }
+    }
+
+    /// The outcome of copying or pasting the board
+    pub fn clipboard(text: impl Into<String>, failed: bool) -> Self {
+        Notification {
+            text: text.into(),
+            color: if failed { Color::Red } else { Color::Green },
+            created: Instant::now(),
+            ttl: TOAST_TTL,
+        }
+    }
+
+    /// Something that couldn't be saved or written
+    pub fn failure(text: impl Into<String>) -> Self {
+        Notification {
+            text: text.into(),
+            color: Color::Red,
+            created: Instant::now(),
+            ttl: TOAST_TTL,
+        }
+    }
+
+    /// "+1200" for a clear worth that much
+    pub fn score(points: usize, t_spin: bool) -> Self {
+        Notification {
+            text: if t_spin {
+                format!("T-spin +{}", points)
+            } else {
+                format!("+{}", points)
+            },
+            color: Color::Yellow,
+            created: Instant::now(),
+            ttl: POPUP_TTL,
+        }
+    }
+
+    /// "LEVEL 7", across the board
+    pub fn level_up(level: usize) -> Self {
+        Notification {
+            text: format!("LEVEL {}", level),
+            color: Color::Magenta,
+            created: Instant::now(),
+            ttl: LEVEL_UP_TTL,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.created.elapsed() < self.ttl
+    }
+
+    /// In the last part of its life, when it's drawn dimmed
+    pub fn is_fading(&self) -> bool {
+        self.created.elapsed() > self.ttl.mul_f32(0.6)
+    }
+}
+
+/// `50000` -> `"50,000"`
+pub fn thousands(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_milestone_fires_once_at_its_score() {
+        let mut tracker = MilestoneTracker::default();
+        assert!(tracker.check(999).is_empty());
+        assert_eq!(tracker.check(1000), [1000]);
+        assert!(tracker.check(1000).is_empty());
+        assert!(tracker.check(4999).is_empty());
+        assert_eq!(tracker.check(5200), [5000]);
+        assert!(tracker.check(9000).is_empty());
+    }
+
+    #[test]
+    fn a_big_jump_fires_every_milestone_it_passes() {
+        let mut tracker = MilestoneTracker::default();
+        assert_eq!(tracker.check(30_000), [1000, 5000, 10000, 25000]);
+        assert!(tracker.check(30_000).is_empty());
+        assert_eq!(tracker.check(2_000_000), &MILESTONES[4..]);
+        assert!(tracker.check(usize::MAX).is_empty());
+    }
+}
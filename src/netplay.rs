@@ -0,0 +1,354 @@
+use crossterm::event::KeyEventKind;
+use rand::prelude::*;
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use std::{
+    fmt,
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc,
+    thread,
+    time::Instant,
+};
+
+use tetris_game::{
+    Action, BOARD_WIDTH, Game, GameEvent, GameMode, GameSetup,
+    config::Config,
+    keymap::{Command, Keymap},
+    rules::Rules,
+};
+
+use crate::{InternalEvent, Outputs, Panel, Term, draw_game, frame_events, versus};
+
+const TAG_ATTACK: u8 = 1;
+const TAG_GAME_OVER: u8 = 2;
+
+/// What the two instances tell each other. On the wire each message is one
+/// frame: a length byte counting the bytes after it, a tag, then the payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    /// Garbage rows for the receiver, all with the hole at `gap`
+    Attack { lines: u8, gap: u8 },
+    /// The sender topped out
+    GameOver,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FrameError {
+    Empty,
+    UnknownTag(u8),
+    /// The frame's length doesn't match what its tag carries
+    BadLength {
+        tag: u8,
+        len: usize,
+    },
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FrameError::Empty => write!(f, "empty frame"),
+            FrameError::UnknownTag(tag) => write!(f, "unknown message tag {}", tag),
+            FrameError::BadLength { tag, len } => {
+                write!(f, "message tag {} can't be {} bytes long", tag, len)
+            }
+        }
+    }
+}
+
+impl Message {
+    pub fn encode(&self) -> Vec<u8> {
+        let body = match *self {
+            Message::Attack { lines, gap } => vec![TAG_ATTACK, lines, gap],
+            Message::GameOver => vec![TAG_GAME_OVER],
+        };
+        let mut frame = vec![body.len() as u8];
+        frame.extend(body);
+        frame
+    }
+
+    /// Decode one frame body (everything after the length byte)
+    pub fn decode(body: &[u8]) -> Result<Message, FrameError> {
+        let (&tag, payload) = body.split_first().ok_or(FrameError::Empty)?;
+        match (tag, payload) {
+            (TAG_ATTACK, &[lines, gap]) => Ok(Message::Attack { lines, gap }),
+            (TAG_GAME_OVER, []) => Ok(Message::GameOver),
+            (TAG_ATTACK | TAG_GAME_OVER, _) => Err(FrameError::BadLength {
+                tag,
+                len: body.len(),
+            }),
+            _ => Err(FrameError::UnknownTag(tag)),
+        }
+    }
+
+    /// Read the next whole frame; `None` once the peer has closed the connection
+    pub fn read_from(reader: &mut impl Read) -> io::Result<Option<Message>> {
+        let mut len = [0u8];
+        if let Err(e) = reader.read_exact(&mut len) {
+            return match e.kind() {
+                io::ErrorKind::UnexpectedEof => Ok(None),
+                _ => Err(e),
+            };
+        }
+        let mut body = vec![0u8; len[0] as usize];
+        reader.read_exact(&mut body)?;
+        Message::decode(&body)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    }
+}
+
+/// Wait for one player to join on `port`
+pub fn host(port: u16) -> io::Result<TcpStream> {
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    println!("waiting for a player on port {}...", port);
+    let (stream, addr) = listener.accept()?;
+    println!("{} joined", addr);
+    Ok(stream)
+}
+
+/// Join a game hosted at `addr` ("host:port")
+pub fn connect(addr: impl ToSocketAddrs) -> io::Result<TcpStream> {
+    TcpStream::connect(addr)
+}
+
+/// What's known about the other player
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Opponent {
+    Playing,
+    ToppedOut,
+    /// The connection dropped or sent something unreadable
+    Gone,
+}
+
+/// One local game against a remote one; each side runs its own bag and only
+/// attacks and top-outs cross the connection
+struct NetGame {
+    game: Game,
+    opponent: Opponent,
+    stream: TcpStream,
+    incoming: mpsc::Receiver<Option<Message>>,
+    rng: ThreadRng,
+}
+
+impl NetGame {
+    fn new(stream: TcpStream, rules: &Rules) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        let mut reader = stream.try_clone()?;
+        let (tx, incoming) = mpsc::channel();
+        // a dead connection arrives as `None`, same as a clean close
+        thread::spawn(move || {
+            while let Ok(Some(msg)) = Message::read_from(&mut reader) {
+                if tx.send(Some(msg)).is_err() {
+                    return;
+                }
+            }
+            let _ = tx.send(None);
+        });
+        let mut game = Game::new(GameSetup {
+            mode: GameMode::Marathon,
+            rules: rules.clone(),
+            seed: None,
+        });
+        // incoming garbage isn't one of the game's inputs, so it can't be replayed
+        game.replay = None;
+        Ok(NetGame {
+            game,
+            opponent: Opponent::Playing,
+            stream,
+            incoming,
+            rng: thread_rng(),
+        })
+    }
+
+    fn over(&self) -> bool {
+        self.game.game_over || self.opponent != Opponent::Playing
+    }
+
+    fn send(&mut self, msg: Message) {
+        if self.stream.write_all(&msg.encode()).is_err() {
+            self.opponent = Opponent::Gone;
+        }
+    }
+
+    fn receive(&mut self) {
+        while let Ok(msg) = self.incoming.try_recv() {
+            match msg {
+                _ if self.over() => {}
+                Some(Message::Attack { lines, gap }) => {
+                    let gap = (gap as usize).min(BOARD_WIDTH - 1);
+                    self.game.add_garbage(lines as usize, gap);
+                }
+                Some(Message::GameOver) => self.opponent = Opponent::ToppedOut,
+                None => self.opponent = Opponent::Gone,
+            }
+        }
+    }
+
+    /// Forward events, turning clears into attacks and a top-out into the result
+    fn exchange(&mut self, outputs: &mut Outputs) {
+        let topped_out = self.game.game_over;
+        for ev in outputs.dispatch(&mut self.game) {
+            if let GameEvent::LinesCleared { count, .. } = ev {
+                let lines = versus::attack_for_clear(count);
+                if lines > 0 && self.opponent == Opponent::Playing {
+                    let gap = self.rng.gen_range(0..BOARD_WIDTH) as u8;
+                    self.send(Message::Attack {
+                        lines: lines as u8,
+                        gap,
+                    });
+                }
+            }
+        }
+        if !topped_out && self.game.game_over && self.opponent == Opponent::Playing {
+            self.send(Message::GameOver);
+        }
+    }
+
+    /// The win message, once the other side is out; a loss shows as game over
+    fn result(&self) -> Option<Line<'static>> {
+        let text = match self.opponent {
+            _ if self.game.game_over => return None,
+            Opponent::Playing => return None,
+            Opponent::ToppedOut => " WINNER! ",
+            Opponent::Gone => " Opponent left — WINNER! ",
+        };
+        Some(Line::from(vec![Span::styled(
+            text,
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        )]))
+    }
+}
+
+pub fn run(
+    terminal: &mut Term,
+    rx: &mpsc::Receiver<InternalEvent>,
+    outputs: &mut Outputs,
+    stream: TcpStream,
+    rules: Rules,
+    config: &Config,
+    keymap: &Keymap,
+) -> io::Result<()> {
+    let peer = stream
+        .peer_addr()
+        .map_or("opponent".to_string(), |addr| addr.to_string());
+    let title = format!(" You vs {} ", peer);
+    let mut net = NetGame::new(stream, &rules)?;
+    let mut last_frame = Instant::now();
+    loop {
+        terminal.draw(|f| {
+            let play = |action| keymap.keys_for(Command::Play(action));
+            let controls = vec![
+                Line::from(format!(
+                    "{} {} : Move     {} : Soft drop",
+                    play(Action::MoveLeft),
+                    play(Action::MoveRight),
+                    play(Action::SoftDrop)
+                )),
+                Line::from(format!(
+                    "{} : Rotate CW  {} : Rotate CCW",
+                    play(Action::RotateCw),
+                    play(Action::RotateCcw)
+                )),
+                Line::from(format!(
+                    "{} : Hard drop   {} : Hold",
+                    play(Action::HardDrop),
+                    play(Action::Hold)
+                )),
+                Line::from(format!("{} : Quit", keymap.keys_for(Command::Quit))),
+            ];
+            let status = net.result().into_iter().collect();
+            let panel = Panel {
+                title: &title,
+                controls,
+                status,
+                game_over_hint: Some(Line::from(vec![Span::raw(" Press 'Q' to quit ")])),
+                notifications: &[],
+                toasts: &[],
+                popups: &[],
+                level_up: None,
+                trails: &[],
+                inputs: None,
+                heights: config.ui.column_heights,
+                suggestion: None,
+                best_board: None,
+                hold_preview: false,
+                help: None,
+                history: None,
+                tutorial: None,
+            };
+            draw_game(f, f.size(), &net.game, panel, config);
+        })?;
+
+        let mut did_quit = false;
+        for ev in frame_events(rx, &mut last_frame) {
+            match ev {
+                InternalEvent::Input(key) if key.kind == KeyEventKind::Release => {}
+                // there's no pausing a game the other side keeps playing
+                InternalEvent::Input(key) => match keymap.command_for(&key) {
+                    Some(Command::Quit) => did_quit = true,
+                    Some(Command::Play(action)) if !net.over() => net.game.press(action),
+                    _ => {}
+                },
+                InternalEvent::Tick if !net.over() => net.game.step(),
+                InternalEvent::Tick => {}
+                // the other side plays on, so there's no pausing for focus
+                InternalEvent::Focus(_) => {}
+            }
+        }
+        net.receive();
+        net.exchange(outputs);
+
+        if did_quit {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn every_message_survives_the_wire() {
+        for msg in [
+            Message::Attack { lines: 4, gap: 9 },
+            Message::Attack { lines: 0, gap: 0 },
+            Message::GameOver,
+        ] {
+            let frame = msg.encode();
+            assert_eq!(frame[0] as usize, frame.len() - 1);
+            assert_eq!(Message::decode(&frame[1..]), Ok(msg));
+            let mut wire = Cursor::new(frame);
+            assert_eq!(Message::read_from(&mut wire).unwrap(), Some(msg));
+            assert_eq!(Message::read_from(&mut wire).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn a_truncated_frame_is_refused() {
+        let frame = Message::Attack { lines: 2, gap: 3 }.encode();
+        let mut cut = Cursor::new(&frame[..frame.len() - 1]);
+        let err = Message::read_from(&mut cut).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        // a length byte that agrees with a short body still isn't an attack
+        assert_eq!(
+            Message::decode(&[TAG_ATTACK, 2]),
+            Err(FrameError::BadLength {
+                tag: TAG_ATTACK,
+                len: 2
+            })
+        );
+        assert_eq!(Message::decode(&[]), Err(FrameError::Empty));
+        assert_eq!(Message::decode(&[9]), Err(FrameError::UnknownTag(9)));
+        let mut bad = Cursor::new(vec![2, TAG_GAME_OVER, 0]);
+        let err = Message::read_from(&mut bad).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,161 @@
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::mpsc,
+};
+
+use tetris_game::{
+    config::Config,
+    settings::{self, Setting},
+};
+
+use crate::{InternalEvent, Term};
+
+/// The row picked, and what came of the last save
+struct Screen<'a> {
+    config: &'a mut Config,
+    selected: usize,
+    /// Where changes are saved; `None` when there's no config directory
+    path: Option<PathBuf>,
+    saved: Option<io::Result<()>>,
+}
+
+impl<'a> Screen<'a> {
+    fn new(config: &'a mut Config, path: Option<&Path>) -> Self {
+        Screen {
+            config,
+            selected: 0,
+            path: path.map(Path::to_path_buf),
+            saved: None,
+        }
+    }
+
+    /// Act on a key; true when it closes the screen. Each change is saved as
+    /// it's made.
+    fn key(&mut self, key: KeyEvent) -> bool {
+        let setting = Setting::ALL[self.selected];
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if self.selected + 1 < Setting::ALL.len() => self.selected += 1,
+            KeyCode::Left => self.change(setting, false),
+            KeyCode::Right | KeyCode::Enter => self.change(setting, true),
+            _ => {}
+        }
+        false
+    }
+
+    fn change(&mut self, setting: Setting, up: bool) {
+        setting.adjust(self.config, up);
+        if let Some(path) = &self.path {
+            self.saved = Some(settings::save(self.config, path));
+        }
+    }
+}
+
+/// Full-screen settings from the command line
+pub fn show(terminal: &mut Term, config: &mut Config, path: Option<&Path>) -> io::Result<()> {
+    let mut screen = Screen::new(config, path);
+    loop {
+        terminal.draw(|f| ui(f, &screen))?;
+        if let Event::Key(key) = event::read()?
+            && key.kind != KeyEventKind::Release
+            && screen.key(key)
+        {
+            return Ok(());
+        }
+    }
+}
+
+/// The settings over a game, changing `config` as it's played with. Keys come
+/// through `rx`, since the input thread owns the terminal's event stream.
+pub fn show_in_game(
+    terminal: &mut Term,
+    rx: &mpsc::Receiver<InternalEvent>,
+    config: &mut Config,
+    path: Option<&Path>,
+) -> io::Result<()> {
+    let mut screen = Screen::new(config, path);
+    loop {
+        terminal.draw(|f| ui(f, &screen))?;
+        match rx.recv() {
+            Ok(InternalEvent::Input(key)) if key.kind != KeyEventKind::Release => {
+                if screen.key(key) {
+                    return Ok(());
+                }
+            }
+            Ok(_) => {}
+            Err(_) => return Ok(()),
+        }
+    }
+}
+
+fn ui<B: ratatui::backend::Backend>(f: &mut ratatui::Frame<B>, screen: &Screen) {
+    let mut lines = vec![Line::from("")];
+    for (i, setting) in Setting::ALL.into_iter().enumerate() {
+        let row = format!(
+            "  {:<14}‹ {} ›",
+            setting.label(),
+            setting.value(screen.config)
+        );
+        let style = if i == screen.selected {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(Span::styled(row, style)));
+    }
+    lines.push(Line::from(""));
+    let dim = Style::default().fg(Color::DarkGray);
+    lines.push(match (&screen.path, &screen.saved) {
+        (None, _) => Line::styled("No config directory; changes last until you quit", dim),
+        (Some(path), Some(Ok(()))) => Line::styled(format!("Saved to {}", path.display()), dim),
+        (Some(_), Some(Err(e))) => Line::styled(
+            format!("Could not save: {}", e),
+            Style::default().fg(Color::Red),
+        ),
+        (Some(path), None) => Line::styled(format!("Changes are saved to {}", path.display()), dim),
+    });
+    lines.push(Line::from(""));
+    lines.push(Line::from(
+        "↑ ↓ : Select   ← → / Enter : Change   Q : Close",
+    ));
+
+    let paragraph =
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" Settings "));
+    f.render_widget(paragraph, f.size());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+    use std::fs;
+
+    fn press(screen: &mut Screen, code: KeyCode) -> bool {
+        screen.key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn each_change_on_the_screen_is_saved() {
+        let path = std::env::temp_dir().join(format!("options-test-{}.toml", std::process::id()));
+        let mut config = Config::default();
+        let mut screen = Screen::new(&mut config, Some(&path));
+        press(&mut screen, KeyCode::Down);
+        assert!(!press(&mut screen, KeyCode::Enter));
+        assert!(matches!(screen.saved, Some(Ok(()))));
+        assert!(press(&mut screen, KeyCode::Esc));
+        let saved = Config::load(Some(&path), None);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(Setting::ALL[1], Setting::Grid);
+        assert_eq!(config.ui.grid, !Config::default().ui.grid);
+        assert_eq!(saved.ui.grid, config.ui.grid);
+    }
+}
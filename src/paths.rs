@@ -0,0 +1,40 @@
+use std::{path::PathBuf, sync::OnceLock};
+
+/// Application name used for the config and data directories
+const APP_DIR: &str = "tetris-game";
+
+/// Profile chosen at startup; unset means the shared default data directory
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Keep this run's persistent data under the named profile. Only the first call counts.
+pub fn set_profile(name: &str) {
+    let _ = PROFILE.set(name.to_string());
+}
+
+pub fn profile() -> Option<&'static str> {
+    PROFILE.get().map(String::as_str)
+}
+
+/// Where persistent game data lives (e.g. `~/.local/share/tetris-game`),
+/// or the active profile's subdirectory of it
+pub fn data_dir() -> Option<PathBuf> {
+    let base = base_data_dir()?;
+    Some(match profile() {
+        Some(name) => base.join("profiles").join(name),
+        None => base,
+    })
+}
+
+/// Parent of every profile's data directory
+pub fn profiles_dir() -> Option<PathBuf> {
+    base_data_dir().map(|d| d.join("profiles"))
+}
+
+fn base_data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join(APP_DIR))
+}
+
+/// Where user settings live (e.g. `~/.config/tetris-game`)
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join(APP_DIR))
+}
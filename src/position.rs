@@ -0,0 +1,487 @@
+//! Board positions as short text codes, for sharing a spot worth seeing
+//!
+//! A code is URL-safe base64, without padding, of a format version byte and
+//! then these fields packed high bit first, zero-padded to a whole byte
+//! (format version 2):
+//!
+//! | bits | contents |
+//! |------|----------|
+//! | 3 | current piece kind, 0 while none is in play |
+//! | 2 | current piece rotation |
+//! | 8 | current piece x, then 8 for y, as `i8` |
+//! | 3 | held piece kind, 0 for none |
+//! | 8 | length of the next queue, then 3 for each kind in it |
+//! | 5 | rows from the top of the stack to the floor, then 1 for each of their cells |
+//!
+//! Kinds take 3 bits: 0 for none, 1–7 for I O T S Z J L. Cells only say
+//! whether they're filled, since colors would take a full board past 100
+//! characters and it's the shape that's worth sharing; filled cells read back
+//! as garbage. Empty rows above the stack aren't stored, so a stack up to the
+//! top with five pieces queued comes to 44 characters.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use crate::{
+    ActivePiece, BOARD_HEIGHT, BOARD_WIDTH, BlockType, Board, Game, Tetromino, paths,
+    rules::SpawnRule,
+};
+
+pub const POSITION_VERSION: u8 = 2;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Why a position code couldn't be read
+#[derive(Debug, PartialEq, Eq)]
+pub enum PositionError {
+    /// A character outside the base64 alphabet, or a dangling one at the end
+    NotBase64,
+    UnsupportedVersion(u8),
+    Truncated,
+    TooManyRows(usize),
+    /// Bytes left over after the board
+    TrailingData,
+    /// Decodes fine but can't happen in a game, e.g. a piece inside the stack
+    Impossible(String),
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PositionError::NotBase64 => write!(f, "not a position code"),
+            PositionError::UnsupportedVersion(v) => {
+                write!(f, "position code version {} isn't supported", v)
+            }
+            PositionError::Truncated => write!(f, "position code is cut short"),
+            PositionError::TooManyRows(rows) => {
+                write!(f, "{} rows given, the board has {}", rows, BOARD_HEIGHT)
+            }
+            PositionError::TrailingData => write!(f, "position code has extra data at the end"),
+            PositionError::Impossible(why) => write!(f, "impossible position: {}", why),
+        }
+    }
+}
+
+impl std::error::Error for PositionError {}
+
+/// The piece in play, where it is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PieceAt {
+    pub kind: BlockType,
+    pub rotation: usize,
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Everything a code carries: what's on the board and what's coming
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub board: Board,
+    /// `None` between a lock and the next spawn
+    pub current: Option<PieceAt>,
+    pub hold: Option<BlockType>,
+    pub next: Vec<BlockType>,
+}
+
+fn kind_code(kind: Option<BlockType>) -> u32 {
+    match kind {
+        None => 0,
+        Some(BlockType::I) => 1,
+        Some(BlockType::O) => 2,
+        Some(BlockType::T) => 3,
+        Some(BlockType::S) => 4,
+        Some(BlockType::Z) => 5,
+        Some(BlockType::J) => 6,
+        Some(BlockType::L) => 7,
+        Some(BlockType::Garbage) => unreachable!("garbage is never a piece"),
+    }
+}
+
+fn kind_of(code: u32) -> Option<BlockType> {
+    Some(match code {
+        1 => BlockType::I,
+        2 => BlockType::O,
+        3 => BlockType::T,
+        4 => BlockType::S,
+        5 => BlockType::Z,
+        6 => BlockType::J,
+        7 => BlockType::L,
+        _ => return None,
+    })
+}
+
+/// Packs fields high bit first
+#[derive(Default)]
+struct Writer {
+    bytes: Vec<u8>,
+    bits: usize,
+}
+
+impl Writer {
+    fn put(&mut self, value: u32, width: usize) {
+        for i in (0..width).rev() {
+            if self.bits.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if value >> i & 1 == 1 {
+                *self.bytes.last_mut().unwrap() |= 0x80 >> (self.bits % 8);
+            }
+            self.bits += 1;
+        }
+    }
+}
+
+/// Reads through a decoded code, failing once it runs out
+struct Reader<'a> {
+    bytes: &'a [u8],
+    bits: usize,
+}
+
+impl Reader<'_> {
+    fn get(&mut self, width: usize) -> Result<u32, PositionError> {
+        let mut value = 0;
+        for _ in 0..width {
+            let byte = self
+                .bytes
+                .get(self.bits / 8)
+                .ok_or(PositionError::Truncated)?;
+            value = value << 1 | u32::from(byte >> (7 - self.bits % 8) & 1);
+            self.bits += 1;
+        }
+        Ok(value)
+    }
+
+    /// Only the zero padding of the last byte may be left
+    fn finish(&self) -> Result<(), PositionError> {
+        let padding = self.bytes.len() * 8 - self.bits;
+        if padding >= 8
+            || self
+                .bytes
+                .last()
+                .is_some_and(|&b| b & ((1 << padding) - 1) != 0)
+        {
+            return Err(PositionError::TrailingData);
+        }
+        Ok(())
+    }
+}
+
+impl Position {
+    pub fn of(game: &Game) -> Self {
+        Position {
+            board: game.board,
+            current: game.piece_active().then_some(PieceAt {
+                kind: game.current.tetro.kind,
+                rotation: game.current.rotation,
+                x: game.current.x,
+                y: game.current.y,
+            }),
+            hold: game.hold,
+            next: game.next.iter().copied().collect(),
+        }
+    }
+
+    pub fn to_code(&self) -> String {
+        let mut out = Writer::default();
+        out.put(u32::from(POSITION_VERSION), 8);
+        match self.current {
+            Some(piece) => {
+                out.put(kind_code(Some(piece.kind)), 3);
+                out.put(piece.rotation as u32 & 3, 2);
+                out.put(u32::from(piece.x as i8 as u8), 8);
+                out.put(u32::from(piece.y as i8 as u8), 8);
+            }
+            None => out.put(0, 21),
+        }
+        out.put(kind_code(self.hold), 3);
+        out.put(self.next.len() as u32, 8);
+        for &kind in &self.next {
+            out.put(kind_code(Some(kind)), 3);
+        }
+        let top = self
+            .board
+            .iter()
+            .position(|row| row.iter().any(Option::is_some))
+            .unwrap_or(BOARD_HEIGHT);
+        out.put((BOARD_HEIGHT - top) as u32, 5);
+        for &cell in self.board[top..].iter().flatten() {
+            out.put(u32::from(cell.is_some()), 1);
+        }
+        encode_base64(&out.bytes)
+    }
+
+    pub fn from_code(code: &str) -> Result<Self, PositionError> {
+        let bytes = decode_base64(code.trim())?;
+        let mut reader = Reader {
+            bytes: &bytes,
+            bits: 0,
+        };
+        let version = reader.get(8)? as u8;
+        if version != POSITION_VERSION {
+            return Err(PositionError::UnsupportedVersion(version));
+        }
+        let kind = kind_of(reader.get(3)?);
+        let rotation = reader.get(2)? as usize;
+        let x = reader.get(8)? as u8 as i8 as i32;
+        let y = reader.get(8)? as u8 as i8 as i32;
+        let current = kind.map(|kind| PieceAt {
+            kind,
+            rotation,
+            x,
+            y,
+        });
+        let hold = kind_of(reader.get(3)?);
+        let count = reader.get(8)?;
+        let mut next = Vec::new();
+        for _ in 0..count {
+            let kind = kind_of(reader.get(3)?)
+                .ok_or_else(|| PositionError::Impossible("a gap in the next queue".to_string()))?;
+            next.push(kind);
+        }
+        let rows = reader.get(5)? as usize;
+        if rows > BOARD_HEIGHT {
+            return Err(PositionError::TooManyRows(rows));
+        }
+        let mut board = [[None; BOARD_WIDTH]; BOARD_HEIGHT];
+        for row in &mut board[BOARD_HEIGHT - rows..] {
+            for cell in row {
+                *cell = (reader.get(1)? == 1).then_some(BlockType::Garbage);
+            }
+        }
+        reader.finish()?;
+        let position = Position {
+            board,
+            current,
+            hold,
+            next,
+        };
+        position.check()?;
+        Ok(position)
+    }
+
+    /// Reject what no game could have produced
+    fn check(&self) -> Result<(), PositionError> {
+        let impossible = |why: String| Err(PositionError::Impossible(why));
+        let Some(piece) = self.current else {
+            return Ok(());
+        };
+        let rotations = Tetromino::new(piece.kind).rotations.len();
+        if piece.rotation >= rotations {
+            return impossible(format!(
+                "{:?} has {} rotations, not {}",
+                piece.kind,
+                rotations,
+                piece.rotation + 1
+            ));
+        }
+        let mut active = ActivePiece::new(piece.kind, SpawnRule::default());
+        active.rotation = piece.rotation;
+        active.x = piece.x;
+        active.y = piece.y;
+        for (x, y) in active.cells() {
+            let off_board = x < 0 || x >= BOARD_WIDTH as i32 || y >= BOARD_HEIGHT as i32;
+            if off_board || (y >= 0 && self.board[y as usize][x as usize].is_some()) {
+                return impossible(
+                    "the current piece is off the board or overlaps blocks".to_string(),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Exported codes, one per line, newest last
+pub fn file_path() -> Option<PathBuf> {
+    paths::data_dir().map(|d| d.join("positions.txt"))
+}
+
+/// Add `code` to the end of the positions file
+pub fn save(code: &str) -> io::Result<PathBuf> {
+    let path =
+        file_path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    writeln!(file, "{}", code)?;
+    Ok(path)
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        // 3 bytes make 4 characters; a short last chunk makes one more than its bytes
+        for i in 0..=chunk.len() {
+            out.push(BASE64[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+        }
+    }
+    out
+}
+
+fn decode_base64(text: &str) -> Result<Vec<u8>, PositionError> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    for chunk in text.as_bytes().chunks(4) {
+        if chunk.len() == 1 {
+            return Err(PositionError::NotBase64);
+        }
+        let mut n = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let value = BASE64
+                .iter()
+                .position(|&b| b == c)
+                .ok_or(PositionError::NotBase64)?;
+            n |= (value as u32) << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            out.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameMode, GameSetup, layout, rules::Rules};
+
+    fn board(rows: &[&str]) -> Board {
+        let rows: Vec<String> = rows.iter().map(|r| r.to_string()).collect();
+        layout::from_rows(&rows).unwrap()
+    }
+
+    fn position() -> Position {
+        Position {
+            board: board(&["GG........", "GGG.GGGGGG", "GGGGGGGG.G"]),
+            current: Some(PieceAt {
+                kind: BlockType::T,
+                rotation: 1,
+                x: 3,
+                y: 2,
+            }),
+            hold: Some(BlockType::I),
+            next: vec![BlockType::S, BlockType::Z, BlockType::L],
+        }
+    }
+
+    #[test]
+    fn a_code_reads_back_as_the_same_position() {
+        let position = position();
+        assert_eq!(Position::from_code(&position.to_code()), Ok(position));
+    }
+
+    #[test]
+    fn a_game_reads_back_with_its_shape_and_pieces() {
+        let mut game = Game::new(GameSetup {
+            mode: GameMode::Marathon,
+            rules: Rules::default(),
+            seed: Some(7),
+        });
+        game.board = board(&["IIII.TTT..", "OOJJJ.TSSZ"]);
+        game.hold = Some(BlockType::O);
+        let position = Position::of(&game);
+        let read = Position::from_code(&position.to_code()).unwrap();
+        assert_eq!(read.current, position.current);
+        assert_eq!(read.hold, Some(BlockType::O));
+        assert_eq!(read.next, position.next);
+        let filled = |b: &Board| b.map(|row| row.map(|cell| cell.is_some()));
+        assert_eq!(filled(&read.board), filled(&game.board));
+    }
+
+    #[test]
+    fn a_full_board_stays_well_under_a_hundred_characters() {
+        let mut position = position();
+        position.board = [[Some(BlockType::Garbage); BOARD_WIDTH]; BOARD_HEIGHT];
+        for (y, row) in position.board.iter_mut().enumerate() {
+            row[y % BOARD_WIDTH] = None;
+        }
+        position.current = None;
+        position.next = vec![BlockType::T; 5];
+        let code = position.to_code();
+        assert_eq!(code.len(), 44);
+        assert_eq!(Position::from_code(&code), Ok(position));
+    }
+
+    #[test]
+    fn malformed_codes_are_rejected() {
+        let code = position().to_code();
+        assert_eq!(
+            Position::from_code("not a code!"),
+            Err(PositionError::NotBase64)
+        );
+        assert_eq!(
+            Position::from_code(&code[..code.len() - 4]),
+            Err(PositionError::Truncated)
+        );
+        assert_eq!(
+            Position::from_code(&format!("{}AAAA", code)),
+            Err(PositionError::TrailingData)
+        );
+        let mut bytes = decode_base64(&code).unwrap();
+        bytes[0] = 1;
+        assert_eq!(
+            Position::from_code(&encode_base64(&bytes)),
+            Err(PositionError::UnsupportedVersion(1))
+        );
+
+        let mut out = Writer::default();
+        out.put(u32::from(POSITION_VERSION), 8);
+        out.put(0, 21 + 3 + 8);
+        out.put(31, 5);
+        assert_eq!(
+            Position::from_code(&encode_base64(&out.bytes)),
+            Err(PositionError::TooManyRows(31))
+        );
+    }
+
+    #[test]
+    fn impossible_positions_are_rejected() {
+        let mut position = position();
+        position.current.as_mut().unwrap().y = BOARD_HEIGHT as i32 - 2;
+        assert!(matches!(
+            Position::from_code(&position.to_code()),
+            Err(PositionError::Impossible(_))
+        ));
+        let mut position = self::position();
+        position.current = Some(PieceAt {
+            kind: BlockType::O,
+            rotation: 3,
+            x: 0,
+            y: 0,
+        });
+        assert!(matches!(
+            Position::from_code(&position.to_code()),
+            Err(PositionError::Impossible(_))
+        ));
+    }
+
+    #[test]
+    fn a_game_starts_where_a_code_left_off() {
+        let shared = position();
+        let position = Position::from_code(&shared.to_code()).unwrap();
+        let game = Game::new(GameSetup {
+            mode: GameMode::Marathon,
+            rules: Rules {
+                start_position: Some(Box::new(position)),
+                ..Rules::default()
+            },
+            seed: Some(3),
+        });
+        assert_eq!(Position::of(&game).current, shared.current);
+        assert_eq!(game.board, shared.board);
+        assert_eq!(game.hold, shared.hold);
+        let queue: Vec<BlockType> = game.next.iter().chain(&game.sequence).copied().collect();
+        assert!(queue.starts_with(&shared.next));
+        assert!(!game.game_over);
+    }
+}
@@ -0,0 +1,158 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::Line,
+};
+use std::time::{Duration, Instant};
+
+use tetris_game::{
+    ActivePiece, Game, GameEvent, Tetromino,
+    ai::{PracticeAiAdvisor, Suggestion},
+};
+
+/// How long the AI's placement stays on the board after a piece went elsewhere
+const SHOW_SUGGESTION: Duration = Duration::from_secs(2);
+
+/// Green ghost for the AI's placement
+pub const SUGGESTION_COLOR: Color = Color::Green;
+
+/// `--practice`: the AI's advice, and the last placement it disagreed with
+#[derive(Default)]
+pub struct Practice {
+    advisor: PracticeAiAdvisor,
+    missed: Option<(Suggestion, Instant)>,
+}
+
+impl Practice {
+    /// Judge the pieces locked in `events`, then think ahead about the one in play
+    pub fn update(&mut self, game: &Game, events: &[GameEvent]) {
+        for ev in events {
+            if let GameEvent::PieceLocked {
+                kind,
+                rotation,
+                x,
+                y,
+            } = *ev
+            {
+                let piece = ActivePiece {
+                    tetro: Tetromino::new(kind),
+                    rotation,
+                    x,
+                    y,
+                };
+                if let Some(suggestion) = self.advisor.judge(&piece) {
+                    self.missed = Some((suggestion, Instant::now()));
+                }
+            }
+        }
+        if self
+            .missed
+            .as_ref()
+            .is_some_and(|(_, at)| at.elapsed() >= SHOW_SUGGESTION)
+        {
+            self.missed = None;
+        }
+        self.advisor.consider(game);
+    }
+
+    /// Cells of the AI's placement for the last piece, while it's shown
+    pub fn ghost(&self) -> Option<&[(i32, i32)]> {
+        self.missed.as_ref().map(|(s, _)| s.cells.as_slice())
+    }
+
+    /// Agreement so far, then the suggestion while it's shown
+    pub fn status_lines(&self) -> Vec<Line<'static>> {
+        let agreement = match self.advisor.agreement_rate() {
+            Some(rate) => format!(
+                "AI agreement: {:.0}% ({}/{})",
+                rate, self.advisor.agreed, self.advisor.compared
+            ),
+            None => "AI agreement: -".to_string(),
+        };
+        let mut lines = vec![Line::from(agreement)];
+        if let Some((suggestion, _)) = &self.missed {
+            lines.push(Line::styled(
+                format!("AI suggested: {}", suggestion.describe()),
+                Style::default()
+                    .fg(SUGGESTION_COLOR)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tetris_game::{Action, BlockType, GameMode, GameSetup, rules::Rules};
+
+    /// Practice on a game of nothing but O pieces, with advice for the first
+    fn practice() -> (Practice, Game) {
+        let game = Game::new(GameSetup {
+            mode: GameMode::Marathon,
+            rules: Rules {
+                pieces: Some(vec![BlockType::O; 3]),
+                ..Rules::default()
+            },
+            seed: Some(1),
+        });
+        let mut practice = Practice::default();
+        practice.update(&game, &[]);
+        (practice, game)
+    }
+
+    /// Lock the piece in play where the AI suggested, or a column off when
+    /// `stray`, and show the lock to `practice`
+    fn place(practice: &mut Practice, game: &mut Game, stray: bool) {
+        let mv = practice.advisor.last_suggestion.unwrap();
+        game.current.rotation = mv.rotation;
+        game.current.x = mv.column;
+        if stray {
+            game.apply(Action::MoveLeft);
+            if game.current.x == mv.column {
+                game.apply(Action::MoveRight);
+            }
+            assert_ne!(game.current.x, mv.column);
+        }
+        game.apply(Action::HardDrop);
+        let events = game.drain_events();
+        practice.update(game, &events);
+    }
+
+    /// The status panel as plain text
+    fn status(practice: &Practice) -> Vec<String> {
+        practice
+            .status_lines()
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn following_the_ai_counts_as_agreement() {
+        let (mut practice, mut game) = practice();
+        place(&mut practice, &mut game, false);
+        assert_eq!((practice.advisor.agreed, practice.advisor.compared), (1, 1));
+        assert!(practice.ghost().is_none());
+        assert_eq!(status(&practice), ["AI agreement: 100% (1/1)"]);
+    }
+
+    #[test]
+    fn going_elsewhere_shows_the_suggestion() {
+        let (mut practice, mut game) = practice();
+        place(&mut practice, &mut game, true);
+        assert_eq!((practice.advisor.agreed, practice.advisor.compared), (0, 1));
+        assert_eq!(practice.ghost().unwrap().len(), 4);
+        let lines = status(&practice);
+        assert_eq!(lines[0], "AI agreement: 0% (0/1)");
+        assert!(lines[1].starts_with("AI suggested: O piece at column"));
+
+        place(&mut practice, &mut game, false);
+        assert_eq!((practice.advisor.agreed, practice.advisor.compared), (1, 2));
+    }
+}
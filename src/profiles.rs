@@ -0,0 +1,250 @@
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+use std::{fs, io, path::PathBuf};
+
+use tetris_game::paths;
+
+use crate::Term;
+
+/// Longest profile name; names become directory names, so they're kept short and plain
+const MAX_NAME_LEN: usize = 20;
+
+/// Check a profile name is usable as a directory name
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("profile name is empty".to_string());
+    }
+    if name.chars().count() > MAX_NAME_LEN {
+        return Err(format!(
+            "profile name is longer than {} characters",
+            MAX_NAME_LEN
+        ));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "profile name '{}' may only use letters, digits, '-' and '_'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Existing profiles, sorted by name
+pub fn list() -> Vec<String> {
+    let Some(dir) = paths::profiles_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| e.file_name().into_string().ok())
+        .filter(|name| validate_name(name).is_ok())
+        .collect();
+    names.sort();
+    names
+}
+
+fn dir(name: &str) -> io::Result<PathBuf> {
+    paths::profiles_dir()
+        .map(|d| d.join(name))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))
+}
+
+/// Make sure the profile's data directory exists
+pub fn create(name: &str) -> io::Result<()> {
+    validate_name(name).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    fs::create_dir_all(dir(name)?)
+}
+
+/// Remove a profile along with its saves, replays and settings
+pub fn delete(name: &str) -> io::Result<()> {
+    fs::remove_dir_all(dir(name)?)
+}
+
+/// Settings layered over the shared config while this profile is active
+pub fn config_path(name: &str) -> Option<PathBuf> {
+    paths::profiles_dir().map(|d| d.join(name).join("config.toml"))
+}
+
+/// Outcome of the startup selector
+pub enum Selection {
+    /// Play without a profile, using the shared data directory
+    Default,
+    Profile(String),
+    Quit,
+}
+
+/// What the selector is waiting for
+enum Prompt {
+    Browse,
+    /// Typing a new profile's name
+    Name(String),
+    /// Asking before deleting the highlighted profile
+    ConfirmDelete,
+}
+
+struct Selector {
+    profiles: Vec<String>,
+    /// Row 0 is the default (no profile), then one row per profile
+    cursor: usize,
+    prompt: Prompt,
+    error: Option<String>,
+}
+
+impl Selector {
+    fn highlighted(&self) -> Option<&str> {
+        self.cursor
+            .checked_sub(1)
+            .map(|i| self.profiles[i].as_str())
+    }
+
+    /// Handle one key press, returning the selection once one is made
+    fn key(&mut self, code: KeyCode) -> Option<Selection> {
+        match &mut self.prompt {
+            Prompt::Browse => match code {
+                KeyCode::Up => self.cursor = self.cursor.saturating_sub(1),
+                KeyCode::Down => self.cursor = (self.cursor + 1).min(self.profiles.len()),
+                KeyCode::Char('n') => {
+                    self.error = None;
+                    self.prompt = Prompt::Name(String::new());
+                }
+                KeyCode::Char('d') | KeyCode::Delete if self.cursor > 0 => {
+                    self.prompt = Prompt::ConfirmDelete
+                }
+                KeyCode::Enter => {
+                    return Some(match self.highlighted() {
+                        Some(name) => Selection::Profile(name.to_string()),
+                        None => Selection::Default,
+                    });
+                }
+                KeyCode::Char('q') | KeyCode::Esc => return Some(Selection::Quit),
+                _ => {}
+            },
+            Prompt::Name(name) => match code {
+                KeyCode::Char(c) if name.chars().count() < MAX_NAME_LEN => name.push(c),
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Enter => {
+                    let name = name.clone();
+                    if self.profiles.contains(&name) {
+                        self.error = Some(format!("profile '{}' already exists", name));
+                    } else {
+                        match create(&name) {
+                            Ok(()) => return Some(Selection::Profile(name)),
+                            Err(e) => self.error = Some(e.to_string()),
+                        }
+                    }
+                }
+                KeyCode::Esc => {
+                    self.error = None;
+                    self.prompt = Prompt::Browse;
+                }
+                _ => {}
+            },
+            Prompt::ConfirmDelete => {
+                if code == KeyCode::Char('y')
+                    && let Some(name) = self.highlighted()
+                {
+                    match delete(name) {
+                        Ok(()) => {
+                            self.profiles.remove(self.cursor - 1);
+                            self.cursor -= 1;
+                            self.error = None;
+                        }
+                        Err(e) => self.error = Some(format!("could not delete: {}", e)),
+                    }
+                }
+                self.prompt = Prompt::Browse;
+            }
+        }
+        None
+    }
+}
+
+/// Full-screen profile picker shown at startup; also creates and deletes profiles
+pub fn choose(terminal: &mut Term) -> io::Result<Selection> {
+    let mut selector = Selector {
+        profiles: list(),
+        cursor: 0,
+        prompt: Prompt::Browse,
+        error: None,
+    };
+    loop {
+        terminal.draw(|f| ui(f, &selector))?;
+        if let Event::Key(key) = event::read()?
+            && key.kind != KeyEventKind::Release
+            && let Some(selection) = selector.key(key.code)
+        {
+            return Ok(selection);
+        }
+    }
+}
+
+fn ui<B: ratatui::backend::Backend>(f: &mut ratatui::Frame<B>, selector: &Selector) {
+    let area = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(44), Constraint::Min(0)].as_ref())
+        .split(f.size())[0];
+
+    let mut lines = vec![Line::from("")];
+    let names = std::iter::once("(no profile)").chain(selector.profiles.iter().map(String::as_str));
+    for (i, name) in names.enumerate() {
+        let style = if i == selector.cursor {
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        lines.push(Line::from(Span::styled(format!(" {} ", name), style)));
+    }
+    lines.push(Line::from(""));
+    match &selector.prompt {
+        Prompt::Browse => {
+            lines.push(Line::from("↑ ↓ : Choose   Enter : Play"));
+            lines.push(Line::from("N : New   D : Delete   Q : Quit"));
+        }
+        Prompt::Name(name) => {
+            lines.push(Line::from(vec![
+                Span::raw("New profile: "),
+                Span::styled(format!("{}_", name), Style::default().fg(Color::Yellow)),
+            ]));
+            lines.push(Line::from("Enter : Create   Esc : Cancel"));
+        }
+        Prompt::ConfirmDelete => {
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "Delete '{}' and all its data? (y/N)",
+                    selector.highlighted().unwrap_or_default()
+                ),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            )));
+        }
+    }
+    if let Some(error) = &selector.error {
+        lines.push(Line::from(Span::styled(
+            error.clone(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Tetris — Profiles "),
+    );
+    f.render_widget(paragraph, area);
+}
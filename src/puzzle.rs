@@ -0,0 +1,965 @@
+//! Puzzle mode: a preset board to clear with a fixed list of pieces and no
+//! gravity. Puzzles are TOML; the built-in ones are below, and more can be
+//! dropped into the config directory's `puzzles/` folder.
+
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io, path::PathBuf};
+
+use crate::{
+    Action, ActivePiece, BlockType, Board, Game, GameMode, GameOverReason, GameSetup, ai::AiMove,
+    layout, paths, rules::Rules,
+};
+
+/// A puzzle as written in TOML. `board` is text rows as in board files
+/// (`.` empty, `G` garbage, piece letters), top first and bottom-aligned.
+/// `solution`, if given, is one `rotation:column` placement per piece, e.g.
+/// `"1:4 0:-1"`: the piece is turned clockwise `rotation` times from spawn,
+/// its 4x4 grid moved to `column` and hard dropped.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PuzzleDef {
+    pub id: u32,
+    pub name: String,
+    pub description: String,
+    pub board: String,
+    pub pieces: Vec<BlockType>,
+    pub solution: Option<String>,
+}
+
+impl PuzzleDef {
+    /// Read and check one puzzle
+    pub fn parse(text: &str) -> Result<PuzzleDef, String> {
+        let def: PuzzleDef = toml::from_str(text).map_err(|e| e.to_string())?;
+        if def.pieces.is_empty() {
+            return Err("a puzzle needs at least one piece".to_string());
+        }
+        if def.pieces.contains(&BlockType::Garbage) {
+            return Err("G is not a piece".to_string());
+        }
+        let board = def.start_board()?;
+        if board.iter().flatten().all(Option::is_none) {
+            return Err("the board is empty".to_string());
+        }
+        if let Some(moves) = def.solution_moves()?
+            && moves.len() > def.pieces.len()
+        {
+            return Err(format!(
+                "the solution places {} pieces but there are only {}",
+                moves.len(),
+                def.pieces.len()
+            ));
+        }
+        Ok(def)
+    }
+
+    pub fn start_board(&self) -> Result<Board, String> {
+        let rows: Vec<String> = self
+            .board
+            .lines()
+            .map(str::trim)
+            .filter(|row| !row.is_empty())
+            .map(String::from)
+            .collect();
+        layout::from_rows(&rows)
+    }
+
+    /// `base` with this puzzle's board and pieces; anything else in `base`
+    /// (lock delay, spawn, ...) still applies
+    pub fn rules(&self, base: &Rules) -> Result<Rules, String> {
+        Ok(Rules {
+            start_board: Some(Box::new(self.start_board()?)),
+            pieces: Some(self.pieces.clone()),
+            ..base.clone()
+        })
+    }
+
+    pub fn setup(&self, base: &Rules) -> Result<GameSetup, String> {
+        Ok(GameSetup {
+            mode: GameMode::Puzzle { id: self.id },
+            rules: self.rules(base)?,
+            seed: Some(u64::from(self.id)),
+        })
+    }
+
+    /// The solution's placements, if it has one
+    pub fn solution_moves(&self) -> Result<Option<Vec<AiMove>>, String> {
+        let Some(solution) = &self.solution else {
+            return Ok(None);
+        };
+        solution
+            .split_whitespace()
+            .map(|step| {
+                let bad = || format!("solution step '{}' should be rotation:column", step);
+                let (rotation, column) = step.split_once(':').ok_or_else(bad)?;
+                Ok(AiMove {
+                    rotation: rotation.parse().map_err(|_| bad())?,
+                    column: column.parse().map_err(|_| bad())?,
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+
+    /// The solution's first placement in words, for the hint key
+    pub fn hint(&self) -> Option<String> {
+        let first = self.solution_moves().ok()??.into_iter().next()?;
+        let kind = self.pieces[0];
+        let mut piece = ActivePiece::new(kind, Rules::default().spawn_rule(kind));
+        piece.rotation = first.rotation % piece.tetro.rotations.len();
+        piece.x = first.column;
+        let left = piece.cells().iter().map(|&(x, _)| x).min()? + 1;
+        let turns = match piece.rotation {
+            0 => "unrotated".to_string(),
+            1 => "turned once clockwise".to_string(),
+            n => format!("turned {} times clockwise", n),
+        };
+        Some(format!(
+            "{:?} {}, left edge in column {}",
+            kind, turns, left
+        ))
+    }
+
+    /// Play the solution with the same keys a player would press, and report
+    /// whether it clears the board
+    pub fn check_solution(&self) -> Result<bool, String> {
+        let moves = self
+            .solution_moves()?
+            .ok_or_else(|| "no solution given".to_string())?;
+        let mut game = Game::new(self.setup(&Rules::default())?);
+        game.replay = None;
+        for mv in moves {
+            if game.game_over {
+                break;
+            }
+            play_move(&mut game, mv);
+            // let any clear flash out and the next piece spawn
+            while !game.game_over && !game.piece_active() {
+                game.step();
+            }
+        }
+        Ok(game.game_over_reason == Some(GameOverReason::GoalReached))
+    }
+}
+
+/// Rotate, shift and hard drop the current piece as `mv` says
+pub fn play_move(game: &mut Game, mv: AiMove) {
+    for _ in 0..mv.rotation {
+        game.apply(Action::RotateCw);
+    }
+    // a blocked shift stops moving the piece, so give up rather than loop
+    while game.current.x != mv.column {
+        let before = game.current.x;
+        game.apply(if game.current.x < mv.column {
+            Action::MoveRight
+        } else {
+            Action::MoveLeft
+        });
+        if game.current.x == before {
+            break;
+        }
+    }
+    game.apply(Action::HardDrop);
+}
+
+/// The puzzles that ship with the game, in order
+pub const BUILT_IN_PUZZLES: &[&str] = &[
+    r#"
+id = 1
+name = "First Drop"
+description = "Clear both rows with a single O."
+board = """
+G..GGGGGGG
+G..GGGGGGG
+"""
+pieces = ["O"]
+solution = "0:0"
+"#,
+    r#"
+id = 2
+name = "Deep Well"
+description = "Clear all four rows with a single I."
+board = """
+GGGGG.GGGG
+GGGGG.GGGG
+GGGGG.GGGG
+GGGGG.GGGG
+"""
+pieces = ["I"]
+solution = "1:3"
+"#,
+    r#"
+id = 3
+name = "Second Shaft"
+description = "Clear all three rows with a single J."
+board = """
+GGGG..GGGG
+GGGG.GGGGG
+GGGG.GGGGG
+"""
+pieces = ["J"]
+solution = "1:3"
+"#,
+    r#"
+id = 4
+name = "Hook"
+description = "Clear the row with a single I."
+board = """
+GG....GGGG
+"""
+pieces = ["I"]
+solution = "0:2"
+"#,
+    r#"
+id = 5
+name = "Square Peg"
+description = "Clear both rows with a single L."
+board = """
+GG...GGGGG
+GG.GGGGGGG
+"""
+pieces = ["L"]
+solution = "2:2"
+"#,
+    r#"
+id = 6
+name = "Corner Post"
+description = "Clear all three rows with a single L."
+board = """
+GGG..GGGGG
+GGGG.GGGGG
+GGGG.GGGGG
+"""
+pieces = ["L"]
+solution = "3:3"
+"#,
+    r#"
+id = 7
+name = "Side Step"
+description = "Clear both rows with a single J."
+board = """
+GG...GGGGG
+GGGG.GGGGG
+"""
+pieces = ["J"]
+solution = "2:2"
+"#,
+    r#"
+id = 8
+name = "Zigzag"
+description = "Clear both rows with a single T."
+board = """
+GGG...GGGG
+GGGG.GGGGG
+"""
+pieces = ["T"]
+solution = "2:3"
+"#,
+    r#"
+id = 9
+name = "Double Up"
+description = "Clear all four rows with two pieces."
+board = """
+GGG.GGGGGG
+GGG.GG..GG
+GGG.GG.GGG
+GGG.GG.GGG
+"""
+pieces = ["I", "J"]
+solution = "1:1 1:5"
+"#,
+    r#"
+id = 10
+name = "Twin Peaks"
+description = "Clear both rows with two pieces."
+board = """
+G...GG..GG
+G.GGGG..GG
+"""
+pieces = ["L", "O"]
+solution = "2:1 0:5"
+"#,
+    r#"
+id = 11
+name = "Tea for Two"
+description = "Clear both rows with two pieces."
+board = """
+GG......GG
+GGG.GG.GGG
+"""
+pieces = ["T", "T"]
+solution = "2:2 2:5"
+"#,
+    r#"
+id = 12
+name = "Pair Bond"
+description = "Clear all four rows with two pieces."
+board = """
+GGG.GGGGGG
+GGG.GGGGGG
+GGG.GGGGGG
+GGG.....GG
+"""
+pieces = ["I", "I"]
+solution = "1:1 0:4"
+"#,
+    r#"
+id = 13
+name = "Staircase"
+description = "Clear both rows with two pieces."
+board = """
+GGG..GGG..
+GGG..GGG..
+"""
+pieces = ["O", "O"]
+solution = "0:7 0:2"
+"#,
+    r#"
+id = 14
+name = "Lock and Key"
+description = "Clear all four rows with two pieces."
+board = """
+GGGGG.GGGG
+GGGGG.GGGG
+GGGGG....G
+GGGGG.G.GG
+"""
+pieces = ["I", "T"]
+solution = "1:3 2:6"
+"#,
+    r#"
+id = 15
+name = "Crossroads"
+description = "Clear both rows with two pieces."
+board = """
+...GG...GG
+GG.GGG.GGG
+"""
+pieces = ["T", "J"]
+solution = "2:5 2:0"
+"#,
+    r#"
+id = 16
+name = "Overhang"
+description = "Clear both rows with two pieces."
+board = """
+G.....GGGG
+G..GG.GGGG
+"""
+pieces = ["J", "O"]
+solution = "2:3 0:0"
+"#,
+    r#"
+id = 17
+name = "Backbone"
+description = "Clear all four rows with two pieces."
+board = """
+GG.GGGGGGG
+GG.GGGGGGG
+GG....GGGG
+GG.GG.GGGG
+"""
+pieces = ["I", "J"]
+solution = "1:0 2:3"
+"#,
+    r#"
+id = 18
+name = "Split Level"
+description = "Clear both rows with two pieces."
+board = """
+GGG...G...
+GGG.GGG.GG
+"""
+pieces = ["L", "L"]
+solution = "2:3 2:7"
+"#,
+    r#"
+id = 19
+name = "Gap Year"
+description = "Clear all four rows with two pieces."
+board = """
+GGGGGG.G.G
+GGGGGG.G.G
+GGGGGG.G.G
+GGGGGG.G.G
+"""
+pieces = ["I", "I"]
+solution = "1:6 1:4"
+"#,
+    r#"
+id = 20
+name = "Two Step"
+description = "Clear both rows with two pieces."
+board = """
+GG...G...G
+GGGG.GGG.G
+"""
+pieces = ["J", "J"]
+solution = "2:2 2:6"
+"#,
+    r#"
+id = 21
+name = "Fault Line"
+description = "Clear all three rows with two pieces."
+board = """
+GGGGGGG..G
+GG...GG.GG
+GG.GGGG.GG
+"""
+pieces = ["J", "L"]
+solution = "1:6 2:2"
+"#,
+    r#"
+id = 22
+name = "Crevice"
+description = "Clear all three rows with two pieces."
+board = """
+GGGGG..GGG
+G..GG.GGGG
+G..GG.GGGG
+"""
+pieces = ["J", "O"]
+solution = "1:4 0:0"
+"#,
+    r#"
+id = 23
+name = "Shelf Life"
+description = "Clear all four rows with two pieces."
+board = """
+GGGGGGGGG.
+GGGGGGGGG.
+G..GGGGGG.
+G..GGGGGG.
+"""
+pieces = ["I", "O"]
+solution = "1:7 0:0"
+"#,
+    r#"
+id = 24
+name = "Bookends"
+description = "Clear both rows with two pieces."
+board = """
+GGG...GG..
+GGGG.GGG..
+"""
+pieces = ["T", "O"]
+solution = "2:3 0:7"
+"#,
+    r#"
+id = 25
+name = "Triple Play"
+description = "Clear all four rows with three pieces."
+board = """
+......GGGG
+G...G.GGGG
+GG.GGGGGGG
+GG.GGGGGGG
+"""
+pieces = ["J", "J", "T"]
+solution = "1:1 2:3 2:0"
+"#,
+    r#"
+id = 26
+name = "Cornerstone"
+description = "Clear all three rows with three pieces."
+board = """
+..GGG..GGG
+G.GGG.G...
+G.GGG.GGG.
+"""
+pieces = ["J", "L", "J"]
+solution = "1:4 3:0 2:7"
+"#,
+    r#"
+id = 27
+name = "Three's Company"
+description = "Clear all four rows with three pieces."
+board = """
+GGGG.GGGGG
+GG...G..GG
+GGG..G.GGG
+GGG..G.GGG
+"""
+pieces = ["I", "J", "L"]
+solution = "1:2 1:5 3:2"
+"#,
+    r#"
+id = 28
+name = "Keyhole"
+description = "Clear both rows with three pieces."
+board = """
+..GG..G...
+..GG..GGG.
+"""
+pieces = ["O", "J", "O"]
+solution = "0:-1 2:7 0:3"
+"#,
+    r#"
+id = 29
+name = "Sawtooth"
+description = "Clear all three rows with three pieces."
+board = """
+GG.....GGG
+....GGGGGG
+...GGGGGGG
+"""
+pieces = ["T", "I", "O"]
+solution = "1:1 0:3 0:-1"
+"#,
+    r#"
+id = 30
+name = "Canyon"
+description = "Clear all four rows with three pieces."
+board = """
+GG.GGGGGGG
+GG.GG....G
+GG.GGG.G.G
+GG.GGG.G.G
+"""
+pieces = ["I", "L", "L"]
+solution = "1:0 3:5 3:7"
+"#,
+    r#"
+id = 31
+name = "Tower Base"
+description = "Clear all four rows with three pieces."
+board = """
+GGGG.GGGGG
+GGGG.GGGGG
+GG...GG..G
+GG...GG..G
+"""
+pieces = ["I", "O", "O"]
+solution = "1:2 0:6 0:1"
+"#,
+    r#"
+id = 32
+name = "Terraces"
+description = "Clear all three rows with three pieces."
+board = """
+GG...GGGGG
+GG...GG...
+GGG..GG.GG
+"""
+pieces = ["Z", "J", "L"]
+solution = "0:2 2:2 2:7"
+"#,
+    r#"
+id = 33
+name = "Trapdoor"
+description = "Clear all four rows with three pieces."
+board = """
+GG......GG
+GGG...G.GG
+GGGG.GGGGG
+GGGG.GGGGG
+"""
+pieces = ["J", "J", "T"]
+solution = "1:3 2:5 2:2"
+"#,
+    r#"
+id = 34
+name = "Chimney"
+description = "Clear both rows with three pieces."
+board = """
+GG..G..G..
+GG..G..G..
+"""
+pieces = ["O", "O", "O"]
+solution = "0:4 0:7 0:1"
+"#,
+    r#"
+id = 35
+name = "Mosaic"
+description = "Clear all three rows with three pieces."
+board = """
+G....GGG..
+G..GGGGGG.
+G..GGGGGG.
+"""
+pieces = ["O", "L", "I"]
+solution = "0:0 3:8 0:1"
+"#,
+    r#"
+id = 36
+name = "Fjord"
+description = "Clear all four rows with three pieces."
+board = """
+...GGGGG.G
+G..GGGGG.G
+G..GGGGG.G
+GG.GGGGG.G
+"""
+pieces = ["I", "S", "J"]
+solution = "1:6 1:0 2:0"
+"#,
+    r#"
+id = 37
+name = "Catacomb"
+description = "Clear all four rows with three pieces."
+board = """
+...GGGGGGG
+.GGGGGGGGG
+...GGGG..G
+.GGGGGG..G
+"""
+pieces = ["L", "O", "L"]
+solution = "2:0 0:6 2:0"
+"#,
+    r#"
+id = 38
+name = "Rampart"
+description = "Clear all four rows with three pieces."
+board = """
+GGGGGGG.GG
+GGGGGGG.GG
+GGGGG...GG
+G.......GG
+"""
+pieces = ["I", "O", "I"]
+solution = "1:5 0:4 0:1"
+"#,
+    r#"
+id = 39
+name = "Labyrinth"
+description = "Clear all four rows with three pieces."
+board = """
+GGGG.GGGGG
+GGGG.GGGGG
+G....G...G
+G.GG.GG.GG
+"""
+pieces = ["I", "L", "T"]
+solution = "1:2 2:1 2:6"
+"#,
+    r#"
+id = 40
+name = "Tripwire"
+description = "Clear all four rows with three pieces."
+board = """
+GGGG.GGGGG
+GGGG.GGGGG
+..GG.G..GG
+..GG.G..GG
+"""
+pieces = ["I", "O", "O"]
+solution = "1:2 0:5 0:-1"
+"#,
+    r#"
+id = 41
+name = "Foursome"
+description = "Clear all four rows with four pieces."
+board = """
+GGG..GGGG.
+GGG..GGGG.
+GGG..GG...
+GGG..GG...
+"""
+pieces = ["I", "I", "I", "O"]
+solution = "1:1 1:2 1:7 0:6"
+"#,
+    r#"
+id = 42
+name = "Quarry"
+description = "Clear all four rows with four pieces."
+board = """
+.....GGGGG
+...GG..GGG
+.G.GGG.GGG
+.G.GGG.GGG
+"""
+pieces = ["L", "I", "I", "L"]
+solution = "3:1 0:1 1:-2 3:5"
+"#,
+    r#"
+id = 43
+name = "Mineshaft"
+description = "Clear all three rows with four pieces."
+board = """
+G......GGG
+G....G.GGG
+G....G.GGG
+"""
+pieces = ["I", "O", "O", "L"]
+solution = "0:1 0:2 0:0 3:5"
+"#,
+    r#"
+id = 44
+name = "Patchwork"
+description = "Clear all four rows with four pieces."
+board = """
+GGGGG.GGGG
+G.....GGGG
+GGG...GGGG
+GGG.......
+"""
+pieces = ["I", "O", "I", "I"]
+solution = "1:3 0:2 0:1 0:6"
+"#,
+    r#"
+id = 45
+name = "Skyline"
+description = "Clear all three rows with four pieces."
+board = """
+GGG.....GG
+G..G....GG
+G..G...GGG
+"""
+pieces = ["L", "O", "J", "O"]
+solution = "3:3 0:4 2:5 0:0"
+"#,
+    r#"
+id = 46
+name = "Bedrock"
+description = "Clear all four rows with four pieces."
+board = """
+.GGGGGG..G
+.GGGGGG..G
+.GGG.....G
+.GGGG.G..G
+"""
+pieces = ["I", "O", "O", "T"]
+solution = "1:-2 0:6 0:6 2:4"
+"#,
+    r#"
+id = 47
+name = "Grand Finale"
+description = "Clear all four rows with five pieces."
+board = """
+G.......GG
+GG..G...GG
+GG..G..GGG
+GG..G..GGG
+"""
+pieces = ["O", "L", "O", "Z", "I"]
+solution = "0:4 0:5 0:1 0:1 0:3"
+"#,
+    r#"
+id = 48
+name = "Masonry"
+description = "Clear all four rows with five pieces."
+board = """
+G.....G..G
+G.G..GG..G
+G.GG.GG..G
+G.GG.GG..G
+"""
+pieces = ["L", "L", "I", "L", "I"]
+solution = "1:6 3:3 0:2 3:7 1:-1"
+"#,
+    r#"
+id = 49
+name = "Full House"
+description = "Clear all four rows with five pieces."
+board = """
+GG.GGGGGGG
+...G....GG
+.G.G....GG
+.G.G....GG
+"""
+pieces = ["I", "J", "O", "J", "L"]
+solution = "1:0 1:-1 0:4 1:3 3:6"
+"#,
+    r#"
+id = 50
+name = "Last Word"
+description = "Clear all four rows with five pieces."
+board = """
+GG......GG
+GGGGG...GG
+..GGGG....
+..GGGG.G..
+"""
+pieces = ["L", "I", "L", "O", "O"]
+solution = "3:5 0:2 3:6 0:7 0:-1"
+"#,
+];
+
+/// Every built-in puzzle; a bad one is a bug
+pub fn built_in() -> Vec<PuzzleDef> {
+    BUILT_IN_PUZZLES
+        .iter()
+        .map(|text| PuzzleDef::parse(text).expect("built-in puzzles are valid"))
+        .collect()
+}
+
+/// Where extra puzzles are read from
+pub fn external_dir() -> Option<PathBuf> {
+    paths::config_dir().map(|d| d.join("puzzles"))
+}
+
+/// Puzzles from `external_dir`, sorted by id, plus a message for each file
+/// that couldn't be used
+pub fn load_external() -> (Vec<PuzzleDef>, Vec<String>) {
+    let mut puzzles = Vec::new();
+    let mut problems = Vec::new();
+    let Some(entries) = external_dir().and_then(|dir| fs::read_dir(dir).ok()) else {
+        return (puzzles, problems);
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    files.sort();
+    for path in files {
+        match fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| PuzzleDef::parse(&text))
+        {
+            Ok(def) => puzzles.push(def),
+            Err(e) => problems.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+    puzzles.sort_by_key(|def| def.id);
+    (puzzles, problems)
+}
+
+/// Built-in puzzles then external ones; an external puzzle can't take a
+/// built-in puzzle's id
+pub fn all() -> (Vec<PuzzleDef>, Vec<String>) {
+    let mut puzzles = built_in();
+    let (external, mut problems) = load_external();
+    for def in external {
+        if puzzles.iter().any(|p| p.id == def.id) {
+            problems.push(format!(
+                "{} ({}): the id is already taken",
+                def.id, def.name
+            ));
+        } else {
+            puzzles.push(def);
+        }
+    }
+    (puzzles, problems)
+}
+
+pub fn find(id: u32) -> Option<PuzzleDef> {
+    all().0.into_iter().find(|def| def.id == id)
+}
+
+/// Attempts and completions, per puzzle id
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Progress {
+    /// Puzzle id -> local date it was first solved
+    pub completed: BTreeMap<u32, String>,
+    pub attempts: BTreeMap<u32, u32>,
+}
+
+fn progress_path() -> Option<PathBuf> {
+    paths::data_dir().map(|d| d.join("puzzles_completed.json"))
+}
+
+impl Progress {
+    /// The saved progress; a missing or unreadable file starts from scratch
+    pub fn load() -> Self {
+        progress_path()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn attempts(&self, id: u32) -> u32 {
+        self.attempts.get(&id).copied().unwrap_or(0)
+    }
+
+    pub fn is_completed(&self, id: u32) -> bool {
+        self.completed.contains_key(&id)
+    }
+
+    /// Count a new try at puzzle `id`
+    pub fn attempt(&mut self, id: u32) -> io::Result<()> {
+        *self.attempts.entry(id).or_default() += 1;
+        self.save()
+    }
+
+    /// Mark puzzle `id` solved; only the first time is kept
+    pub fn complete(&mut self, id: u32) -> io::Result<()> {
+        if self.is_completed(id) {
+            return Ok(());
+        }
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        self.completed.insert(id, today);
+        self.save()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let path = progress_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_drop() -> PuzzleDef {
+        PuzzleDef::parse(BUILT_IN_PUZZLES[0]).unwrap()
+    }
+
+    #[test]
+    fn every_built_in_puzzle_is_solved_by_its_solution() {
+        for def in built_in() {
+            assert_eq!(def.check_solution(), Ok(true), "puzzle {}", def.id);
+        }
+    }
+
+    #[test]
+    fn a_wrong_solution_is_caught() {
+        let mut def = first_drop();
+        def.solution = Some("0:3".to_string());
+        assert_eq!(def.check_solution(), Ok(false));
+        def.solution = None;
+        assert!(def.check_solution().is_err());
+    }
+
+    #[test]
+    fn malformed_puzzles_are_refused() {
+        let puzzle = |board: &str, pieces: &str, solution: &str| {
+            PuzzleDef::parse(&format!(
+                "id = 99\nname = \"x\"\ndescription = \"x\"\nboard = \"{}\"\npieces = {}\nsolution = \"{}\"",
+                board, pieces, solution
+            ))
+        };
+        assert!(puzzle("G..GGGGGGG", "[\"O\"]", "0:0").is_ok());
+        let errors = [
+            (puzzle("G..GGGGGGG", "[]", "0:0"), "at least one piece"),
+            (
+                puzzle("G..GGGGGGG", "[\"Garbage\"]", "0:0"),
+                "G is not a piece",
+            ),
+            (puzzle("..........", "[\"O\"]", "0:0"), "the board is empty"),
+            (puzzle("G..GGGG", "[\"O\"]", "0:0"), "7 cells"),
+            (puzzle("G..GGGGGGG", "[\"O\"]", "0:0 0:4"), "only 1"),
+            (puzzle("G..GGGGGGG", "[\"O\"]", "left"), "rotation:column"),
+            (PuzzleDef::parse("id = \"one\""), "invalid type"),
+        ];
+        for (result, message) in errors {
+            let err = result.unwrap_err();
+            assert!(
+                err.contains(message),
+                "{:?} should mention {:?}",
+                err,
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn clearing_the_board_reaches_the_goal() {
+        let def = first_drop();
+        let mut game = Game::new(def.setup(&Rules::default()).unwrap());
+        for mv in def.solution_moves().unwrap().unwrap() {
+            play_move(&mut game, mv);
+        }
+        for _ in 0..50 {
+            game.step();
+        }
+        assert!(game.game_over);
+        assert_eq!(game.game_over_reason, Some(GameOverReason::GoalReached));
+        assert!(game.board.iter().flatten().all(Option::is_none));
+    }
+}
@@ -0,0 +1,205 @@
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io, path::PathBuf, time::Duration};
+
+use tetris_game::{Game, TICK, milestones::thousands, paths};
+
+use crate::{Term, format_duration};
+
+/// An all-time best that a game can beat
+pub struct RecordKind {
+    /// Stable key in the saved file; never rename
+    pub id: &'static str,
+    pub name: &'static str,
+    value: fn(&Game) -> u64,
+    format: fn(u64) -> String,
+}
+
+fn count(value: u64) -> String {
+    thousands(value as usize)
+}
+
+/// Every tracked record, in the order the records screen lists them
+pub const RECORDS: &[RecordKind] = &[
+    RecordKind {
+        id: "max_combo",
+        name: "Highest combo",
+        value: |game| game.max_combo as u64,
+        format: count,
+    },
+    RecordKind {
+        id: "max_b2b",
+        name: "Longest back-to-back chain",
+        value: |game| game.max_b2b as u64,
+        format: count,
+    },
+    RecordKind {
+        id: "most_lines",
+        name: "Most lines in one game",
+        value: |game| game.lines_cleared as u64,
+        format: count,
+    },
+    RecordKind {
+        id: "longest_game",
+        name: "Longest survival",
+        // game time, so pauses don't count
+        value: |game| (TICK * game.ticks as u32).as_millis() as u64,
+        format: |ms| format_duration(Duration::from_millis(ms)),
+    },
+    RecordKind {
+        id: "highest_level",
+        name: "Highest level",
+        value: |game| game.level as u64,
+        format: count,
+    },
+    RecordKind {
+        id: "best_clear",
+        name: "Biggest single clear",
+        value: |game| game.best_clear as u64,
+        format: count,
+    },
+];
+
+/// The game that set a record
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub value: u64,
+    /// Local date it was set
+    pub date: String,
+    pub seed: u64,
+}
+
+/// Checks the running game against the saved bests, per profile
+pub struct Tracker {
+    /// Record id -> current best
+    records: BTreeMap<String, Record>,
+    /// Records beaten by the current game and already announced
+    announced: Vec<&'static str>,
+    changed: bool,
+}
+
+fn path() -> Option<PathBuf> {
+    paths::data_dir().map(|d| d.join("records.json"))
+}
+
+impl Tracker {
+    /// Read the saved records; a missing or unreadable file starts from scratch
+    pub fn load() -> Self {
+        let records = path()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Tracker {
+            records,
+            announced: Vec::new(),
+            changed: false,
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Record> {
+        self.records.get(id)
+    }
+
+    /// Take any records `game` has beaten so far, returning the ones newly
+    /// broken this game. A first-ever record is kept but not announced.
+    pub fn observe(&mut self, game: &Game) -> Vec<&'static RecordKind> {
+        let mut broken = Vec::new();
+        for kind in RECORDS {
+            let value = (kind.value)(game);
+            let previous = self.records.get(kind.id).map(|r| r.value);
+            if value == 0 || previous.is_some_and(|best| value <= best) {
+                continue;
+            }
+            self.records.insert(
+                kind.id.to_string(),
+                Record {
+                    value,
+                    date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+                    seed: game.seed,
+                },
+            );
+            self.changed = true;
+            if previous.is_some() && !self.announced.contains(&kind.id) {
+                self.announced.push(kind.id);
+                broken.push(kind);
+            }
+        }
+        broken
+    }
+
+    /// Final check for a game that ended or is being left; writes the file if anything changed
+    pub fn finish(&mut self, game: &Game) -> io::Result<()> {
+        self.observe(game);
+        self.announced.clear();
+        if self.changed {
+            self.save()?;
+            self.changed = false;
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let path =
+            path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(&self.records).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+}
+
+/// Full-screen list of every record; any of Q, Esc or Enter closes it
+pub fn show(terminal: &mut Term) -> io::Result<()> {
+    let tracker = Tracker::load();
+    loop {
+        terminal.draw(|f| ui(f, &tracker))?;
+        if let Event::Key(key) = event::read()?
+            && key.kind != KeyEventKind::Release
+            && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter)
+        {
+            return Ok(());
+        }
+    }
+}
+
+fn ui<B: ratatui::backend::Backend>(f: &mut ratatui::Frame<B>, tracker: &Tracker) {
+    let mut lines = vec![Line::from("")];
+    for kind in RECORDS {
+        let line = match tracker.get(kind.id) {
+            Some(record) => Line::from(vec![
+                Span::raw(format!("{:<28}", kind.name)),
+                Span::styled(
+                    format!("{:>10}", (kind.format)(record.value)),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("  {} (seed {})", record.date, record.seed),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]),
+            None => Line::from(Span::styled(
+                format!("{:<28}{:>10}", kind.name, "—"),
+                Style::default().fg(Color::DarkGray),
+            )),
+        };
+        lines.push(line);
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("Q : Close"));
+
+    let title = match paths::profile() {
+        Some(name) => format!(" Records — {} ", name),
+        None => " Records ".to_string(),
+    };
+    let paragraph =
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, f.size());
+}
@@ -0,0 +1,377 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{Action, Game, GameMode, GameSetup, paths, rules::Rules};
+
+/// Bumped whenever the file layout or the simulation rules change in a way
+/// that would make old replays play back differently. Version 2 flashes
+/// cleared rows before they drop; version 3 nudges a blocked spawn up a row;
+/// version 4 scores soft drops only for rows actually descended; version 5
+/// leaves a hold undone when the piece it brings out can't spawn.
+pub const REPLAY_VERSION: u32 = 5;
+
+/// Where finished games' replays are kept
+pub fn dir() -> Option<PathBuf> {
+    paths::data_dir().map(|d| d.join("replays"))
+}
+
+/// One recorded input: the simulation tick it was applied on and the action
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayEntry(pub u64, pub Action);
+
+/// A recorded game: seed + mode + every action, enough to re-simulate it exactly
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    pub mode: GameMode,
+    #[serde(default)]
+    pub rules: Rules,
+    pub recorded_at: String,
+    pub final_score: usize,
+    /// Tick the recording stopped on (game over, restart or quit)
+    #[serde(default)]
+    pub end_tick: u64,
+    pub actions: Vec<ReplayEntry>,
+    /// `Game::state_hash` after each piece locked, when recorded with `--debug`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_hashes: Option<Vec<u64>>,
+}
+
+/// Just enough of the file to check compatibility before parsing the rest
+#[derive(Deserialize)]
+struct ReplayHeader {
+    version: u32,
+}
+
+impl Replay {
+    pub fn new(seed: u64, mode: GameMode, rules: Rules) -> Self {
+        Replay {
+            version: REPLAY_VERSION,
+            seed,
+            mode,
+            rules,
+            recorded_at: chrono::Local::now().to_rfc3339(),
+            final_score: 0,
+            end_tick: 0,
+            actions: Vec::new(),
+            state_hashes: None,
+        }
+    }
+
+    pub fn record(&mut self, tick: u64, action: Action) {
+        self.actions.push(ReplayEntry(tick, action));
+    }
+
+    /// Write the replay into `dir` (usually `replay::dir()`), named by date,
+    /// mode and score (e.g. `2026-10-16_21-04-55_sprint_4200.json`),
+    /// returning the path. A name already taken within the same second gets
+    /// a counter (`..._4200_2.json`) rather than being overwritten.
+    pub fn save(&self, dir: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let date = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+        let stem = format!("{}_{}_{}", date, self.mode.name(), self.final_score);
+        let json = serde_json::to_string(self).map_err(io::Error::other)?;
+        let mut n = 1;
+        loop {
+            let path = match n {
+                1 => dir.join(format!("{}.json", stem)),
+                n => dir.join(format!("{}_{}.json", stem, n)),
+            };
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(mut file) => {
+                    file.write_all(json.as_bytes())?;
+                    return Ok(path);
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => n += 1,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Read a replay, refusing files recorded under different rules rather
+    /// than letting them silently desync
+    pub fn load(path: &Path) -> io::Result<Replay> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let data = fs::read_to_string(path)?;
+        let header: ReplayHeader = serde_json::from_str(&data)
+            .map_err(|e| invalid(format!("not a replay file: {}", e)))?;
+        if header.version < REPLAY_VERSION {
+            return Err(invalid(format!(
+                "replay version {} was recorded under older rules and would desync \
+                 (this build plays version {})",
+                header.version, REPLAY_VERSION
+            )));
+        }
+        if header.version > REPLAY_VERSION {
+            return Err(invalid(format!(
+                "replay version {} is not supported (this build plays version {})",
+                header.version, REPLAY_VERSION
+            )));
+        }
+        serde_json::from_str(&data).map_err(|e| invalid(format!("corrupt replay: {}", e)))
+    }
+}
+
+/// Playback speeds selectable while watching
+const SPEEDS: [f64; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+
+/// Game ticks between stored snapshots; seeking back re-simulates at most this many
+const KEYFRAME_TICKS: u64 = 500;
+
+/// Drives a `Game` from a recorded action list instead of the keyboard
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    next: usize,
+    speed_index: usize,
+    // fractional ticks owed at slow speeds
+    budget: f64,
+    /// Snapshots taken while playing (game, index of the next action), oldest first
+    keyframes: Vec<(Game, usize)>,
+    /// Locks whose state hash has been checked
+    verified: usize,
+    /// The first piece whose state hash didn't match the recording (1-based)
+    pub desync: Option<usize>,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayer {
+            replay,
+            next: 0,
+            speed_index: 2,
+            budget: 0.0,
+            keyframes: Vec::new(),
+            verified: 0,
+            desync: None,
+        }
+    }
+
+    /// A fresh game with the recorded seed and mode; it doesn't record itself
+    pub fn new_game(&self) -> Game {
+        let mut game = Game::new(GameSetup {
+            mode: self.replay.mode,
+            rules: self.replay.rules.clone(),
+            seed: Some(self.replay.seed),
+        });
+        game.replay = None;
+        if self.replay.state_hashes.is_some() {
+            game.record_state_hashes();
+        }
+        game
+    }
+
+    pub fn speed(&self) -> f64 {
+        SPEEDS[self.speed_index]
+    }
+
+    pub fn faster(&mut self) {
+        self.speed_index = (self.speed_index + 1).min(SPEEDS.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed_index = self.speed_index.saturating_sub(1);
+    }
+
+    pub fn finished(&self, game: &Game) -> bool {
+        game.game_over
+            || (self.next >= self.replay.actions.len() && game.ticks >= self.replay.end_tick)
+    }
+
+    /// Fraction of the recording already played, 0.0..=1.0
+    pub fn progress(&self, game: &Game) -> f64 {
+        if self.replay.end_tick == 0 {
+            return 1.0;
+        }
+        (game.ticks as f64 / self.replay.end_tick as f64).min(1.0)
+    }
+
+    /// Called once per real tick; simulates as many game ticks as the speed allows
+    pub fn advance(&mut self, game: &mut Game) {
+        if game.paused {
+            return;
+        }
+        self.budget += self.speed();
+        while self.budget >= 1.0 {
+            self.budget -= 1.0;
+            if !self.step_once(game) {
+                self.budget = 0.0;
+                return;
+            }
+        }
+    }
+
+    /// Jump by `delta_ticks` (negative rewinds), restoring the nearest earlier
+    /// keyframe and re-simulating from there
+    pub fn seek(&mut self, game: &mut Game, delta_ticks: i64) {
+        let target = (game.ticks as i64 + delta_ticks).clamp(0, self.replay.end_tick as i64) as u64;
+        let paused = game.paused;
+        if target < game.ticks
+            && let Some((snapshot, next)) =
+                self.keyframes.iter().rev().find(|(g, _)| g.ticks <= target)
+        {
+            *game = snapshot.clone();
+            self.next = *next;
+        }
+        game.paused = false;
+        while game.ticks < target && self.step_once(game) {}
+        game.paused = paused;
+        // don't replay sounds/announcements for the skipped stretch
+        game.drain_events();
+    }
+
+    /// Simulate one game tick; false once the recording is exhausted
+    pub(crate) fn step_once(&mut self, game: &mut Game) -> bool {
+        let due = match self.keyframes.last() {
+            Some((last, _)) => game.ticks >= last.ticks + KEYFRAME_TICKS,
+            None => true,
+        };
+        if due {
+            self.keyframes.push((game.clone(), self.next));
+        }
+        // actions recorded on this tick were applied before the tick's step
+        while let Some(&ReplayEntry(tick, action)) = self.replay.actions.get(self.next) {
+            if tick > game.ticks {
+                break;
+            }
+            game.apply(action);
+            self.next += 1;
+        }
+        self.verify(game);
+        if self.finished(game) {
+            return false;
+        }
+        game.step();
+        self.verify(game);
+        true
+    }
+
+    /// Compare the state hashes of locks since the last check with the
+    /// recorded ones, noting the first piece where they differ
+    fn verify(&mut self, game: &Game) {
+        let (Some(recorded), Some(played)) = (&self.replay.state_hashes, &game.state_hashes) else {
+            return;
+        };
+        let checked = played.len().min(recorded.len());
+        if self.desync.is_none() && self.verified < checked {
+            self.desync = (self.verified..checked)
+                .find(|&i| played[i] != recorded[i])
+                .map(|i| i + 1);
+        }
+        self.verified = checked;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_json(json: &str) -> io::Result<Replay> {
+        let path = std::env::temp_dir().join(format!(
+            "replay-test-{}-{}.json",
+            std::process::id(),
+            json.len()
+        ));
+        fs::write(&path, json)?;
+        let replay = Replay::load(&path);
+        fs::remove_file(&path)?;
+        replay
+    }
+
+    fn with_version(version: u32) -> String {
+        let mut replay = Replay::new(1, GameMode::Marathon, Rules::default());
+        replay.version = version;
+        serde_json::to_string(&replay).unwrap()
+    }
+
+    #[test]
+    fn the_current_version_loads() {
+        let replay = load_json(&with_version(REPLAY_VERSION)).unwrap();
+        assert_eq!(replay.rules, Rules::default());
+    }
+
+    #[test]
+    fn saves_in_the_same_second_keep_both_files() {
+        let dir = std::env::temp_dir().join(format!("replay-save-test-{}", std::process::id()));
+        let replay = Replay::new(1, GameMode::Sprint, Rules::default());
+        let paths: Vec<PathBuf> = (0..3).map(|_| replay.save(&dir).unwrap()).collect();
+        let names: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        let loaded: Vec<bool> = paths.iter().map(|p| Replay::load(p).is_ok()).collect();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(names.len(), 3, "{:?}", names);
+        assert_eq!(loaded, [true; 3]);
+        // unless the clock ticked over between saves, the later two are numbered
+        let stem = paths[0].file_stem().unwrap().to_string_lossy().into_owned();
+        if names.iter().all(|name| name.starts_with(&stem)) {
+            assert!(paths[1].ends_with(format!("{}_2.json", stem)));
+            assert!(paths[2].ends_with(format!("{}_3.json", stem)));
+        }
+    }
+
+    #[test]
+    fn older_and_newer_versions_are_refused() {
+        let old = load_json(&with_version(1)).unwrap_err().to_string();
+        assert!(old.contains("older rules"), "{}", old);
+        let previous = load_json(&with_version(REPLAY_VERSION - 1))
+            .unwrap_err()
+            .to_string();
+        assert!(previous.contains("older rules"), "{}", previous);
+        let new = load_json(&with_version(REPLAY_VERSION + 1))
+            .unwrap_err()
+            .to_string();
+        assert!(new.contains("is not supported"), "{}", new);
+    }
+
+    /// Six hard drops, recorded with a state hash after each lock
+    fn recorded_drops() -> Replay {
+        let mut game = Game::new(GameSetup {
+            mode: GameMode::Marathon,
+            rules: Rules::default(),
+            seed: Some(7),
+        });
+        game.record_state_hashes();
+        for _ in 0..6 {
+            game.press(Action::HardDrop);
+            for _ in 0..50 {
+                game.step();
+            }
+        }
+        game.finish_replay().unwrap()
+    }
+
+    #[test]
+    fn a_faithful_playback_matches_every_hash() {
+        let replay = recorded_drops();
+        assert_eq!(replay.state_hashes.as_ref().map(Vec::len), Some(6));
+        let mut player = ReplayPlayer::new(replay);
+        let mut game = player.new_game();
+        while player.step_once(&mut game) {}
+        assert_eq!(player.verified, 6);
+        assert_eq!(player.desync, None);
+    }
+
+    #[test]
+    fn a_game_changed_mid_replay_is_a_desync() {
+        let mut player = ReplayPlayer::new(recorded_drops());
+        let mut game = player.new_game();
+        while game.state_hashes.as_ref().map_or(0, Vec::len) < 3 {
+            assert!(player.step_once(&mut game));
+        }
+        assert_eq!(player.desync, None);
+        game.score += 1;
+        while player.step_once(&mut game) {}
+        assert_eq!(player.desync, Some(4));
+    }
+}
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, time::Duration};
+
+use crate::{
+    BOARD_WIDTH, BlockType, Board, CLEAR_FLASH, config::Config, position::Position,
+    scoring::ScoreConfig,
+};
+
+/// Gameplay tunables that change how a game plays out. They're recorded in
+/// replays so playback simulates under the same rules.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    /// Pause between a piece locking and the next one appearing
+    #[serde(with = "crate::serde_util::millis")]
+    pub are_delay: Duration,
+    /// Initial Rotation System: a held rotation key pre-rotates new pieces
+    pub irs_enabled: bool,
+    pub scoring: ScoreConfig,
+    /// How long a landed piece waits before locking; zero locks on the next gravity step
+    #[serde(with = "crate::serde_util::millis")]
+    pub lock_delay: Duration,
+    pub start_level: usize,
+    /// Pieces drawn ahead of the current one
+    pub next_queue: usize,
+    /// Where pieces appear; pieces not listed use `SpawnRule::default()`
+    pub spawn: BTreeMap<BlockType, SpawnRule>,
+    /// The first piece is never S or Z; later pieces are unaffected
+    pub fair_start: bool,
+    /// How long the zone lasts unless ended early
+    #[serde(with = "crate::serde_util::millis")]
+    pub zone_duration: Duration,
+    /// Pieces to lock after a zone before another one (0 = once per game)
+    pub zone_recharge: usize,
+    /// Blocks already on the board when the game starts, e.g. from the editor
+    pub start_board: Option<Box<Board>>,
+    /// What happens to moves pressed while no piece is in play
+    pub input_buffer: InputBuffer,
+    /// Soft dropping into the floor locks the piece at once; when off it just
+    /// rests there until lock delay or gravity locks it
+    pub soft_drop_locks: bool,
+    /// A fixed list of pieces to play, in order, instead of random ones; the
+    /// game ends when it runs out
+    pub pieces: Option<Vec<BlockType>>,
+    /// A shared position to start from: its board, hold and pieces, with the
+    /// current piece where it was; random pieces follow its queue
+    pub start_position: Option<Box<Position>>,
+    /// How long full rows flash before clearing (zero clears at once)
+    #[serde(with = "crate::serde_util::millis")]
+    pub clear_flash: Duration,
+    /// How what's left of the stack comes down once rows clear
+    pub clear_gravity: ClearGravity,
+}
+
+/// Handling of moves and rotations pressed during ARE, when no piece is in play
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InputBuffer {
+    /// Drop them
+    #[default]
+    Discard,
+    /// Keep the latest and apply it to the next piece as it spawns
+    ApplyLast,
+}
+
+impl InputBuffer {
+    pub fn name(self) -> &'static str {
+        match self {
+            InputBuffer::Discard => "discard",
+            InputBuffer::ApplyLast => "apply-last",
+        }
+    }
+}
+
+/// What happens to the blocks above cleared rows
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClearGravity {
+    /// Every row above moves down one per row cleared, overhangs and all
+    #[default]
+    Naive,
+    /// Each group of touching blocks falls on its own until it lands, and
+    /// rows that fills clear along with the rest
+    Sticky,
+}
+
+impl ClearGravity {
+    pub fn name(self) -> &'static str {
+        match self {
+            ClearGravity::Naive => "naive",
+            ClearGravity::Sticky => "sticky",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "naive" => Some(ClearGravity::Naive),
+            "sticky" => Some(ClearGravity::Sticky),
+            _ => None,
+        }
+    }
+}
+
+/// Spawn placement of a piece: its 4x4 grid's top-left corner and starting rotation
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpawnRule {
+    pub column: i32,
+    /// Negative rows start partly above the visible board
+    pub row: i32,
+    pub rotation: usize,
+}
+
+impl Default for SpawnRule {
+    fn default() -> Self {
+        SpawnRule {
+            column: (BOARD_WIDTH as i32 / 2) - 2,
+            row: -1,
+            rotation: 0,
+        }
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            are_delay: Duration::ZERO,
+            irs_enabled: false,
+            scoring: ScoreConfig::default(),
+            lock_delay: Duration::ZERO,
+            start_level: 1,
+            next_queue: 1,
+            spawn: BTreeMap::new(),
+            fair_start: false,
+            zone_duration: Duration::from_secs(10),
+            zone_recharge: 0,
+            start_board: None,
+            input_buffer: InputBuffer::Discard,
+            soft_drop_locks: true,
+            pieces: None,
+            start_position: None,
+            clear_flash: CLEAR_FLASH,
+            clear_gravity: ClearGravity::Naive,
+        }
+    }
+}
+
+impl Rules {
+    pub fn spawn_rule(&self, kind: BlockType) -> SpawnRule {
+        self.spawn.get(&kind).copied().unwrap_or_default()
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Rules {
+            are_delay: Duration::from_millis(config.gameplay.are_delay_ms),
+            irs_enabled: config.gameplay.irs_enabled,
+            scoring: config.scoring.clone(),
+            lock_delay: Duration::from_millis(config.gameplay.lock_delay_ms),
+            start_level: config.general.start_level,
+            next_queue: config.gameplay.next_queue,
+            spawn: config.spawn.clone(),
+            fair_start: false,
+            zone_duration: Duration::from_millis(config.gameplay.zone_duration_ms),
+            zone_recharge: config.gameplay.zone_recharge_pieces,
+            start_board: None,
+            input_buffer: config.gameplay.input_buffer,
+            soft_drop_locks: config.gameplay.soft_drop_locks,
+            pieces: None,
+            start_position: None,
+            clear_flash: if config.ui.reduced_motion {
+                Duration::ZERO
+            } else {
+                CLEAR_FLASH
+            },
+            clear_gravity: config.gameplay.clear_gravity,
+        }
+    }
+}
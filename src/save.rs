@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{Game, paths};
+
+/// Bumped whenever `Game`'s serialized layout changes
+pub const SAVE_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct SaveFileRef<'a> {
+    version: u32,
+    game: &'a Game,
+}
+
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct SaveFile {
+    game: Game,
+}
+
+pub fn save_path() -> Option<PathBuf> {
+    paths::data_dir().map(|d| d.join("save.json"))
+}
+
+/// Periodic snapshot of the running game; only left behind if the game didn't exit cleanly
+pub fn autosave_path() -> Option<PathBuf> {
+    paths::data_dir().map(|d| d.join("autosave.json"))
+}
+
+/// Write the in-progress game so it can be resumed later
+pub fn save_game(game: &Game) -> io::Result<PathBuf> {
+    let path =
+        save_path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+    write_game(&path, game)?;
+    Ok(path)
+}
+
+/// Snapshot a running game for crash recovery; finished games aren't worth recovering
+pub fn autosave(game: &Game) -> io::Result<()> {
+    if game.game_over {
+        return discard_autosave();
+    }
+    let path = autosave_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+    write_game(&path, game)
+}
+
+/// Remove the crash-recovery snapshot, e.g. on a clean exit
+pub fn discard_autosave() -> io::Result<()> {
+    let Some(path) = autosave_path() else {
+        return Ok(());
+    };
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Whether a previous run left a crash-recovery snapshot behind
+pub fn has_autosave() -> bool {
+    autosave_path().is_some_and(|p| p.exists())
+}
+
+/// Write to a temp file and rename it over `path`, so an interrupted write
+/// never replaces a good save with a partial one
+fn write_game(path: &Path, game: &Game) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string(&SaveFileRef {
+        version: SAVE_VERSION,
+        game,
+    })
+    .map_err(io::Error::other)?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json)?;
+    fs::rename(&tmp, path)
+}
+
+/// Load the saved game and delete the file, so a save can only be resumed once
+pub fn take_saved_game() -> Result<Game, String> {
+    let path = save_path().ok_or("no data directory to look for a saved game in")?;
+    take_game(&path, "there is no saved game to resume")
+}
+
+/// Load the crash-recovery snapshot and delete it
+pub fn take_autosave() -> Result<Game, String> {
+    let path = autosave_path().ok_or("no data directory to look for a snapshot in")?;
+    let game = take_game(&path, "there is no interrupted game to recover")?;
+    if game.game_over {
+        return Err("the interrupted game had already ended".to_string());
+    }
+    Ok(game)
+}
+
+fn take_game(path: &Path, missing: &str) -> Result<Game, String> {
+    let data = match fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Err(missing.to_string()),
+        Err(e) => return Err(format!("could not read {}: {}", path.display(), e)),
+    };
+    let header: SaveHeader = serde_json::from_str(&data)
+        .map_err(|e| format!("saved game {} is corrupt: {}", path.display(), e))?;
+    if header.version != SAVE_VERSION {
+        return Err(format!(
+            "saved game was written by an incompatible version (save format {}, expected {})",
+            header.version, SAVE_VERSION
+        ));
+    }
+    let save: SaveFile = serde_json::from_str(&data)
+        .map_err(|e| format!("saved game {} is corrupt: {}", path.display(), e))?;
+    fs::remove_file(path).map_err(|e| format!("could not remove {}: {}", path.display(), e))?;
+    Ok(save.game)
+}
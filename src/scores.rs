@@ -0,0 +1,138 @@
+//! Top results per mode, kept on disk for the leaderboard
+
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, env, fs, io, path::PathBuf, time::Duration};
+
+use crate::{Game, GameMode, GameOverReason, TICK, paths};
+
+/// Entries kept per mode
+pub const TOP_ENTRIES: usize = 10;
+
+/// Modes with a leaderboard, in the order the screen cycles through them
+pub const LEADERBOARD_MODES: [&str; 5] = ["marathon", "sprint", "ultra", "cheese", "competitive"];
+
+/// One finished game on the leaderboard
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScoreEntry {
+    pub name: String,
+    pub score: usize,
+    /// Game time taken, which ranks sprint and cheese
+    #[serde(with = "crate::serde_util::millis")]
+    pub time: Duration,
+    pub lines: usize,
+    pub level: usize,
+    /// Local date it was played
+    pub date: String,
+    pub seed: u64,
+}
+
+/// Where a game just landed on its mode's board
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Placement {
+    pub mode: &'static str,
+    /// 0 for first place
+    pub rank: usize,
+}
+
+/// Leaderboard name of `mode`, if it has one (daily, puzzles and the tutorial don't)
+pub fn mode_key(mode: GameMode) -> Option<&'static str> {
+    match mode {
+        GameMode::Marathon => Some("marathon"),
+        GameMode::Sprint => Some("sprint"),
+        GameMode::Ultra => Some("ultra"),
+        GameMode::Cheese { .. } => Some("cheese"),
+        GameMode::Competitive => Some("competitive"),
+        GameMode::DailyChallenge { .. } | GameMode::Puzzle { .. } | GameMode::Tutorial => None,
+    }
+}
+
+/// Sprint and cheese race the clock; the rest go by score
+pub fn ranked_by_time(mode: &str) -> bool {
+    matches!(mode, "sprint" | "cheese")
+}
+
+/// Mode name -> its entries, best first
+#[derive(Default, Serialize, Deserialize)]
+pub struct Leaderboard {
+    modes: BTreeMap<String, Vec<ScoreEntry>>,
+}
+
+fn path() -> Option<PathBuf> {
+    paths::data_dir().map(|d| d.join("scores.json"))
+}
+
+/// Who to credit: the profile, else the login name
+fn player_name() -> String {
+    paths::profile()
+        .map(str::to_string)
+        .or_else(|| env::var("USER").ok())
+        .or_else(|| env::var("USERNAME").ok())
+        .unwrap_or_else(|| "Player".to_string())
+}
+
+impl Leaderboard {
+    /// Read the saved scores; a missing or unreadable file starts empty
+    pub fn load() -> Self {
+        path()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn entries(&self, mode: &str) -> &[ScoreEntry] {
+        self.modes.get(mode).map_or(&[], Vec::as_slice)
+    }
+
+    /// Add a finished `game` if it makes its mode's top entries, saving the
+    /// file; `None` if it didn't place. A race lost to the clock or the stack
+    /// has no time to rank, so it doesn't count.
+    pub fn record(&mut self, game: &Game) -> io::Result<Option<Placement>> {
+        let Some(mode) = mode_key(game.setup.mode) else {
+            return Ok(None);
+        };
+        let by_time = ranked_by_time(mode);
+        if !game.game_over
+            || (by_time && game.game_over_reason != Some(GameOverReason::GoalReached))
+        {
+            return Ok(None);
+        }
+        let entry = ScoreEntry {
+            name: player_name(),
+            score: game.score,
+            time: game.goal_time.unwrap_or(TICK * game.ticks as u32),
+            lines: game.lines_cleared,
+            level: game.level,
+            date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+            seed: game.seed,
+        };
+        let entries = self.modes.entry(mode.to_string()).or_default();
+        // ties go to the earlier entry
+        let rank = entries
+            .iter()
+            .position(|e| {
+                if by_time {
+                    entry.time < e.time
+                } else {
+                    entry.score > e.score
+                }
+            })
+            .unwrap_or(entries.len());
+        if rank >= TOP_ENTRIES {
+            return Ok(None);
+        }
+        entries.insert(rank, entry);
+        entries.truncate(TOP_ENTRIES);
+        self.save()?;
+        Ok(Some(Placement { mode, rank }))
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let path =
+            path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+}
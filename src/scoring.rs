@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+
+/// Points awarded for clears and drops. Defaults follow the modern guideline;
+/// the `[scoring]` config section can swap in NES-style or custom values.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoreConfig {
+    pub single: usize,
+    pub double: usize,
+    pub triple: usize,
+    pub tetris: usize,
+    /// Per cell moved with soft drop
+    pub soft_drop: usize,
+    /// Per cell moved with hard drop
+    pub hard_drop: usize,
+    /// Applied to the clear value when the lock was a T-spin
+    pub t_spin_multiplier: f64,
+    /// Per consecutive clear after the first, times level
+    pub combo_bonus: usize,
+    /// Applied to a tetris or T-spin clear that follows another one
+    pub back_to_back_multiplier: f64,
+}
+
+impl Default for ScoreConfig {
+    fn default() -> Self {
+        ScoreConfig {
+            single: 100,
+            double: 300,
+            triple: 500,
+            tetris: 800,
+            soft_drop: 1,
+            hard_drop: 2,
+            t_spin_multiplier: 4.0,
+            combo_bonus: 50,
+            back_to_back_multiplier: 1.5,
+        }
+    }
+}
+
+/// What a single lock cleared, as far as scoring cares
+pub struct LineClear {
+    pub lines: usize,
+    pub t_spin: bool,
+    /// Consecutive clearing locks before this one
+    pub combo: usize,
+    pub back_to_back: bool,
+}
+
+impl ScoreConfig {
+    /// Points for a clear at `level`
+    pub fn clear_points(&self, clear: &LineClear, level: usize) -> usize {
+        let base = match clear.lines {
+            0 => 0,
+            1 => self.single,
+            2 => self.double,
+            3 => self.triple,
+            _ => self.tetris,
+        };
+        let mut points = base as f64;
+        if clear.t_spin {
+            points *= self.t_spin_multiplier;
+        }
+        if clear.back_to_back {
+            points *= self.back_to_back_multiplier;
+        }
+        (points.round() as usize + self.combo_bonus * clear.combo) * level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Action, BlockType, Game, GameMode, GameSetup, layout, rules::Rules};
+
+    /// Score after an I stood up in the well clears four rows
+    fn tetris_score(scoring: ScoreConfig) -> usize {
+        let well: Vec<String> = vec!["GGGGGGGGG.".to_string(); 4];
+        let mut game = Game::new(GameSetup {
+            mode: GameMode::Marathon,
+            rules: Rules {
+                scoring,
+                pieces: Some(vec![BlockType::I; 2]),
+                start_board: Some(Box::new(layout::from_rows(&well).unwrap())),
+                ..Rules::default()
+            },
+            seed: Some(1),
+        });
+        game.press(Action::RotateCw);
+        for _ in 0..4 {
+            game.press(Action::MoveRight);
+        }
+        game.press(Action::HardDrop);
+        for _ in 0..50 {
+            game.step();
+        }
+        assert_eq!(game.lines_cleared, 4);
+        game.score
+    }
+
+    #[test]
+    fn the_tetris_value_sets_what_a_tetris_scores() {
+        let tetris = LineClear {
+            lines: 4,
+            t_spin: false,
+            combo: 0,
+            back_to_back: false,
+        };
+        let custom = ScoreConfig {
+            tetris: 1200,
+            ..ScoreConfig::default()
+        };
+        assert_eq!(ScoreConfig::default().clear_points(&tetris, 2), 1600);
+        assert_eq!(custom.clear_points(&tetris, 2), 2400);
+
+        let plain = tetris_score(ScoreConfig::default());
+        assert_eq!(tetris_score(custom) - plain, 400);
+    }
+}
@@ -0,0 +1,194 @@
+use crossterm::event::KeyEventKind;
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+};
+use std::{io, sync::mpsc, time::Duration};
+
+use tetris_game::{
+    Game, TICK,
+    analysis::{BoardAnalysis, BoardAnalyzer},
+    milestones::thousands,
+};
+
+use crate::{InternalEvent, Term, career::CareerStats, format_duration};
+
+/// Totals over every game played since the program started, restarts included
+#[derive(Default)]
+pub struct Session {
+    pub games: usize,
+    pub lines: usize,
+    pub pieces: usize,
+    pub best_score: usize,
+    pub best_level: usize,
+    total_score: usize,
+    /// Game time, so pauses and idling on the game-over screen don't count
+    pub play_time: Duration,
+    /// Position codes exported along the way, printed once the terminal is back
+    pub positions: Vec<String>,
+    /// What went wrong along the way, e.g. a file that couldn't be written;
+    /// stderr can't be seen while the game has the screen, so it waits too
+    pub problems: Vec<String>,
+    /// The stack over the last game recorded
+    pub last_game: Option<BoardAnalysis>,
+    /// The all-time totals each game is also added to; `None` if they couldn't be read
+    career: Option<CareerStats>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Session {
+            career: CareerStats::load(),
+            ..Session::default()
+        }
+    }
+
+    /// Count `game` towards the session; call once per game, as it's left
+    pub fn record(&mut self, game: &Game) {
+        // a game restarted before its first tick was never really played
+        if game.ticks == 0 {
+            return;
+        }
+        self.games += 1;
+        self.lines += game.lines_cleared;
+        self.pieces += game.pieces_placed;
+        self.best_score = self.best_score.max(game.score);
+        self.best_level = self.best_level.max(game.level);
+        self.total_score += game.score;
+        self.play_time += TICK * game.ticks as u32;
+        self.last_game = Some(BoardAnalyzer::compute(&game.board_history));
+        if let Some(career) = &mut self.career {
+            career.record(game);
+            if let Err(e) = career.save() {
+                self.problems
+                    .push(format!("failed to save career stats: {}", e));
+            }
+        }
+    }
+
+    pub fn average_score(&self) -> usize {
+        self.total_score.checked_div(self.games).unwrap_or(0)
+    }
+}
+
+/// Show the summary until any key is pressed. Keys come through `rx`, since
+/// the input thread owns the terminal's event stream.
+pub fn show(
+    terminal: &mut Term,
+    rx: &mpsc::Receiver<InternalEvent>,
+    session: &Session,
+) -> io::Result<()> {
+    terminal.draw(|f| ui(f, session))?;
+    loop {
+        match rx.recv() {
+            Ok(InternalEvent::Input(key)) if key.kind != KeyEventKind::Release => return Ok(()),
+            Ok(_) => {}
+            Err(_) => return Ok(()),
+        }
+    }
+}
+
+fn ui<B: ratatui::backend::Backend>(f: &mut ratatui::Frame<B>, session: &Session) {
+    let area = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(56), Constraint::Min(0)].as_ref())
+        .split(f.size())[0];
+    let area = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(18), Constraint::Min(0)].as_ref())
+        .split(area)[0];
+
+    let games = |n: usize| if n == 1 { "game" } else { "games" };
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(format!(
+            "Session: {} {}, {} lines, {} played",
+            session.games,
+            games(session.games),
+            thousands(session.lines),
+            format_duration(session.play_time)
+        )),
+        Line::from(format!("Pieces placed: {}", thousands(session.pieces))),
+        Line::from(format!("Best score:    {}", session.best_score)),
+        Line::from(format!("Average score: {}", session.average_score())),
+        Line::from(format!("Best level:    {}", session.best_level)),
+    ];
+    if let Some(career) = &session.career {
+        lines.push(Line::from(format!(
+            "Career: {} {}, {} lines",
+            thousands(career.games as usize),
+            games(career.games as usize),
+            thousands(career.lines as usize)
+        )));
+    }
+    if let Some(analysis) = &session.last_game {
+        lines.extend([
+            Line::from(""),
+            Line::styled("Analysis", Style::default().add_modifier(Modifier::BOLD)),
+            Line::from(analysis.to_string()),
+            Line::from(format!(
+                "Avg stack height: {:.1}, avg fill: {:.0}%",
+                analysis.avg_height, analysis.avg_fill
+            )),
+        ]);
+        if let Some(worst) = analysis.worst {
+            lines.push(Line::from(format!(
+                "Worst placement: piece {} (+{} holes)",
+                worst.piece, worst.holes_added
+            )));
+        }
+        if let Some(best) = analysis.best {
+            lines.push(Line::from(format!(
+                "Best placement: piece {} ({} lines, {} keys)",
+                best.piece, best.lines, best.inputs
+            )));
+        }
+    }
+    lines.extend([
+        Line::from(""),
+        Line::styled(
+            "Press any key to exit",
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+    ]);
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Session Summary "),
+    );
+    f.render_widget(paragraph, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tetris_game::{GameMode, GameSetup, rules::Rules};
+
+    fn played(score: usize, lines: usize, ticks: u64) -> Game {
+        let mut game = Game::new(GameSetup {
+            mode: GameMode::Marathon,
+            rules: Rules::default(),
+            seed: Some(1),
+        });
+        game.score = score;
+        game.lines_cleared = lines;
+        game.ticks = ticks;
+        game
+    }
+
+    #[test]
+    fn each_game_left_adds_to_the_session() {
+        let mut session = Session::default();
+        session.record(&played(3000, 12, 500));
+        session.record(&played(1000, 4, 250));
+        // restarted before it began
+        session.record(&played(0, 0, 0));
+        assert_eq!(session.games, 2);
+        assert_eq!(session.lines, 16);
+        assert_eq!(session.best_score, 3000);
+        assert_eq!(session.average_score(), 2000);
+        assert_eq!(session.play_time, TICK * 750);
+    }
+}
@@ -0,0 +1,244 @@
+//! The settings screen's list: config values that can be stepped through
+//! with the arrow keys, and saved back to a config file
+
+use std::{fs, io, path::Path};
+
+use crate::config::{Config, THEMES};
+
+/// Ghost glyphs the settings screen cycles through
+pub const GHOST_STYLES: [&str; 5] = ["░░", "▒▒", "▓▓", "[]", "::"];
+
+/// DAS and ARR move by this much per key press
+const TIMING_STEP: u64 = 10;
+const MAX_DAS_MS: u64 = 500;
+const MAX_ARR_MS: u64 = 200;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Setting {
+    Ghost,
+    Grid,
+    Theme,
+    Preview,
+    GhostStyle,
+    Das,
+    Arr,
+    ReducedMotion,
+}
+
+impl Setting {
+    /// Every setting, in screen order
+    pub const ALL: [Setting; 8] = [
+        Setting::Ghost,
+        Setting::Grid,
+        Setting::Theme,
+        Setting::Preview,
+        Setting::GhostStyle,
+        Setting::Das,
+        Setting::Arr,
+        Setting::ReducedMotion,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Setting::Ghost => "Ghost piece",
+            Setting::Grid => "Grid",
+            Setting::Theme => "Theme",
+            Setting::Preview => "Preview",
+            Setting::GhostStyle => "Ghost style",
+            Setting::Das => "DAS",
+            Setting::Arr => "ARR",
+            Setting::ReducedMotion => "Reduced motion",
+        }
+    }
+
+    /// Section and key in the config file
+    fn key(self) -> (&'static str, &'static str) {
+        match self {
+            Setting::Ghost => ("gameplay", "ghost"),
+            Setting::Grid => ("ui", "grid"),
+            Setting::Theme => ("ui", "theme"),
+            Setting::Preview => ("ui", "preview"),
+            Setting::GhostStyle => ("ui", "block_ghost"),
+            Setting::Das => ("gameplay", "das_ms"),
+            Setting::Arr => ("gameplay", "arr_ms"),
+            Setting::ReducedMotion => ("ui", "reduced_motion"),
+        }
+    }
+
+    /// The current value as the screen shows it
+    pub fn value(self, config: &Config) -> String {
+        let on_off = |on| if on { "on" } else { "off" }.to_string();
+        match self {
+            Setting::Ghost => on_off(config.gameplay.ghost),
+            Setting::Grid => on_off(config.ui.grid),
+            Setting::Theme => config.ui.theme.clone(),
+            Setting::Preview => match config.ui.preview {
+                None => "all".to_string(),
+                Some(0) => "hidden".to_string(),
+                Some(n) => n.to_string(),
+            },
+            Setting::GhostStyle => config.ui.block_ghost.clone(),
+            Setting::Das => format!("{} ms", config.gameplay.das_ms),
+            Setting::Arr => format!("{} ms", config.gameplay.arr_ms),
+            Setting::ReducedMotion => on_off(config.ui.reduced_motion),
+        }
+    }
+
+    /// Step the value forward (`up`) or back: toggles flip, lists wrap around
+    /// and timings stop at their limits
+    pub fn adjust(self, config: &mut Config, up: bool) {
+        let step = |i: usize, len: usize| {
+            if up {
+                (i + 1) % len
+            } else {
+                (i + len - 1) % len
+            }
+        };
+        let ui = &mut config.ui;
+        let gameplay = &mut config.gameplay;
+        match self {
+            Setting::Ghost => gameplay.ghost = !gameplay.ghost,
+            Setting::Grid => ui.grid = !ui.grid,
+            Setting::Theme => {
+                let i = THEMES.iter().position(|&t| t == ui.theme).unwrap_or(0);
+                ui.theme = THEMES[step(i, THEMES.len())].to_string();
+            }
+            Setting::Preview => {
+                // all, then hidden, 1, 2, ... up to one short of the whole queue
+                let len = gameplay.next_queue + 1;
+                let i = ui.preview.map_or(0, |n| n.min(gameplay.next_queue - 1) + 1);
+                ui.preview = match step(i, len) {
+                    0 => None,
+                    i => Some(i - 1),
+                };
+            }
+            Setting::GhostStyle => {
+                let i = GHOST_STYLES.iter().position(|&g| g == ui.block_ghost);
+                let i = match i {
+                    Some(i) => step(i, GHOST_STYLES.len()),
+                    None => 0,
+                };
+                ui.block_ghost = GHOST_STYLES[i].to_string();
+            }
+            Setting::Das => gameplay.das_ms = stepped(gameplay.das_ms, up, MAX_DAS_MS),
+            Setting::Arr => gameplay.arr_ms = stepped(gameplay.arr_ms, up, MAX_ARR_MS),
+            Setting::ReducedMotion => ui.reduced_motion = !ui.reduced_motion,
+        }
+    }
+
+    /// The value as it goes in the file; `None` to leave the key out
+    fn toml_value(self, config: &Config) -> Option<toml::Value> {
+        Some(match self {
+            Setting::Ghost => config.gameplay.ghost.into(),
+            Setting::Grid => config.ui.grid.into(),
+            Setting::Theme => config.ui.theme.clone().into(),
+            Setting::Preview => (config.ui.preview? as i64).into(),
+            Setting::GhostStyle => config.ui.block_ghost.clone().into(),
+            Setting::Das => (config.gameplay.das_ms as i64).into(),
+            Setting::Arr => (config.gameplay.arr_ms as i64).into(),
+            Setting::ReducedMotion => config.ui.reduced_motion.into(),
+        })
+    }
+}
+
+fn stepped(ms: u64, up: bool, max: u64) -> u64 {
+    if up {
+        (ms + TIMING_STEP).min(max)
+    } else {
+        ms.saturating_sub(TIMING_STEP)
+    }
+}
+
+/// Write every `Setting` of `config` into the file at `path`, keeping the
+/// file's other keys (though not its comments); the file is created if needed
+pub fn save(config: &Config, path: &Path) -> io::Result<()> {
+    let mut table: toml::Table = match fs::read_to_string(path) {
+        Ok(text) => text
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => toml::Table::new(),
+        Err(e) => return Err(e),
+    };
+    for setting in Setting::ALL {
+        let (name, key) = setting.key();
+        let section = table
+            .entry(name)
+            .or_insert_with(|| toml::Table::new().into());
+        let Some(section) = section.as_table_mut() else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("'{}' isn't a section", name),
+            ));
+        };
+        match setting.toml_value(config) {
+            Some(value) => section.insert(key.to_string(), value),
+            None => section.remove(key),
+        };
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let text = toml::to_string(&table).map_err(io::Error::other)?;
+    fs::write(path, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adjusting_steps_each_kind_of_value() {
+        let mut config = Config::default();
+        Setting::Ghost.adjust(&mut config, true);
+        assert!(!config.gameplay.ghost);
+        Setting::Theme.adjust(&mut config, false);
+        assert_eq!(config.ui.theme, THEMES[THEMES.len() - 1]);
+        Setting::Theme.adjust(&mut config, true);
+        assert_eq!(config.ui.theme, "default");
+        Setting::GhostStyle.adjust(&mut config, true);
+        assert_eq!(config.ui.block_ghost, GHOST_STYLES[1]);
+        Setting::Das.adjust(&mut config, true);
+        assert_eq!(Setting::Das.value(&config), "180 ms");
+        config.gameplay.arr_ms = 5;
+        Setting::Arr.adjust(&mut config, false);
+        assert_eq!(config.gameplay.arr_ms, 0);
+        config.gameplay.das_ms = MAX_DAS_MS;
+        Setting::Das.adjust(&mut config, true);
+        assert_eq!(config.gameplay.das_ms, MAX_DAS_MS);
+    }
+
+    #[test]
+    fn saved_settings_load_back_beside_the_other_keys() {
+        let dir = std::env::temp_dir().join(format!("settings-test-{}", std::process::id()));
+        let path = dir.join("config.toml");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            &path,
+            "[general]\nstart_level = 5\n\n[gameplay]\ndas_ms = 100\n",
+        )
+        .unwrap();
+
+        let mut config = Config::load(Some(&path), None);
+        assert_eq!(config.gameplay.das_ms, 100);
+        Setting::Das.adjust(&mut config, true);
+        Setting::Grid.adjust(&mut config, true);
+        Setting::Theme.adjust(&mut config, true);
+        let saved = save(&config, &path);
+        let loaded = Config::load(Some(&path), None);
+        fs::remove_dir_all(&dir).unwrap();
+
+        saved.unwrap();
+        assert_eq!(loaded.general.start_level, 5);
+        assert_eq!(loaded.gameplay.das_ms, 110);
+        assert_eq!(loaded.ui.grid, config.ui.grid);
+        assert_eq!(loaded.ui.theme, THEMES[1]);
+        for setting in Setting::ALL {
+            assert_eq!(
+                setting.value(&loaded),
+                setting.value(&config),
+                "{}",
+                setting.label()
+            );
+        }
+    }
+}
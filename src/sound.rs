@@ -0,0 +1,171 @@
+use std::{sync::mpsc, thread};
+
+use tetris_game::GameEvent;
+
+/// Audio cues, sent to a background thread so rendering never waits on playback
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SoundEvent {
+    LineClear,
+    LevelUp,
+    /// The stack rose into the top rows
+    Danger,
+    GameOver,
+}
+
+impl SoundEvent {
+    pub fn from_game_event(event: &GameEvent) -> Option<SoundEvent> {
+        match event {
+            GameEvent::LinesCleared { .. } => Some(SoundEvent::LineClear),
+            GameEvent::LevelUp { .. } | GameEvent::GoalReached { .. } => Some(SoundEvent::LevelUp),
+            GameEvent::DangerEntered => Some(SoundEvent::Danger),
+            GameEvent::GameOver => Some(SoundEvent::GameOver),
+            GameEvent::PieceLocked { .. }
+            | GameEvent::HardDropped { .. }
+            | GameEvent::Milestone { .. } => None,
+        }
+    }
+}
+
+/// Start the playback thread and return the channel feeding it
+pub fn spawn() -> mpsc::Sender<SoundEvent> {
+    let (tx, rx) = mpsc::channel::<SoundEvent>();
+    thread::spawn(move || {
+        let player = Player::new();
+        for ev in rx {
+            player.play(ev);
+        }
+    });
+    tx
+}
+
+#[cfg(not(feature = "audio"))]
+struct Player;
+
+#[cfg(not(feature = "audio"))]
+impl Player {
+    fn new() -> Self {
+        Player
+    }
+
+    /// Terminal bell fallback: the cue is told apart by the number of rings
+    fn play(&self, ev: SoundEvent) {
+        use crossterm::{execute, style::Print};
+        use std::{io, time::Duration};
+
+        let rings = match ev {
+            SoundEvent::LineClear => 1,
+            SoundEvent::LevelUp => 2,
+            SoundEvent::Danger => 4,
+            SoundEvent::GameOver => 3,
+        };
+        for i in 0..rings {
+            if i > 0 {
+                thread::sleep(Duration::from_millis(150));
+            }
+            let _ = execute!(io::stdout(), Print("\x07"));
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+struct Player {
+    // the stream must stay alive for the handle to keep working
+    _stream: Option<rodio::OutputStream>,
+    handle: Option<rodio::OutputStreamHandle>,
+}
+
+#[cfg(feature = "audio")]
+impl Player {
+    fn new() -> Self {
+        match rodio::OutputStream::try_default() {
+            Ok((stream, handle)) => Player {
+                _stream: Some(stream),
+                handle: Some(handle),
+            },
+            Err(_) => Player {
+                _stream: None,
+                handle: None,
+            },
+        }
+    }
+
+    /// Short sine tones: a beep, a rising pair, a high warble and a low buzz
+    fn play(&self, ev: SoundEvent) {
+        use rodio::{Sink, Source, source::SineWave};
+        use std::time::Duration;
+
+        let Some(handle) = &self.handle else {
+            return;
+        };
+        let Ok(sink) = Sink::try_new(handle) else {
+            return;
+        };
+        let tones: &[(f32, u64)] = match ev {
+            SoundEvent::LineClear => &[(880.0, 80)],
+            SoundEvent::LevelUp => &[(660.0, 90), (990.0, 140)],
+            SoundEvent::Danger => &[(1320.0, 60), (1100.0, 60), (1320.0, 60)],
+            SoundEvent::GameOver => &[(110.0, 400)],
+        };
+        for &(freq, ms) in tones {
+            sink.append(
+                SineWave::new(freq)
+                    .take_duration(Duration::from_millis(ms))
+                    .amplify(0.2),
+            );
+        }
+        sink.sleep_until_end();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tetris_game::BlockType;
+
+    #[test]
+    fn game_events_map_to_their_cues() {
+        let cases = [
+            (
+                GameEvent::LinesCleared {
+                    count: 2,
+                    points: 300,
+                    t_spin: false,
+                },
+                Some(SoundEvent::LineClear),
+            ),
+            (GameEvent::LevelUp { level: 2 }, Some(SoundEvent::LevelUp)),
+            (
+                GameEvent::GoalReached {
+                    time: Duration::from_secs(60),
+                },
+                Some(SoundEvent::LevelUp),
+            ),
+            (GameEvent::DangerEntered, Some(SoundEvent::Danger)),
+            (GameEvent::GameOver, Some(SoundEvent::GameOver)),
+            (
+                GameEvent::PieceLocked {
+                    kind: BlockType::T,
+                    rotation: 0,
+                    x: 3,
+                    y: 18,
+                },
+                None,
+            ),
+            (
+                GameEvent::HardDropped {
+                    kind: BlockType::T,
+                    rotation: 0,
+                    x: 3,
+                    y: 18,
+                    rows: 17,
+                },
+                None,
+            ),
+            (GameEvent::Milestone { threshold: 10_000 }, None),
+        ];
+        for (event, cue) in cases {
+            assert_eq!(SoundEvent::from_game_event(&event), cue, "{:?}", event);
+        }
+    }
+}
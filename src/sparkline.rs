@@ -0,0 +1,35 @@
+/// Bar heights from lowest to highest
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// A one-row chart drawn with block characters, one per value
+pub struct Sparkline;
+
+impl Sparkline {
+    /// `▁` at or below `min` up to `█` at or above `max`
+    pub fn render(data: &[f64], min: f64, max: f64) -> String {
+        let span = max - min;
+        data.iter()
+            .map(|&value| {
+                let fraction = if span > 0.0 {
+                    ((value - min) / span).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                BARS[(fraction * (BARS.len() - 1) as f64).round() as usize]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_map_onto_bar_heights() {
+        assert_eq!(Sparkline::render(&[0.0, 1.0, 7.0], 0.0, 7.0), "▁▂█");
+        assert_eq!(Sparkline::render(&[-5.0, 50.0], 0.0, 7.0), "▁█");
+        assert_eq!(Sparkline::render(&[3.0, 3.0], 3.0, 3.0), "▁▁");
+        assert_eq!(Sparkline::render(&[], 0.0, 1.0), "");
+    }
+}
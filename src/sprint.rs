@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::PathBuf, time::Duration};
+
+use crate::{Game, GameMode, GameOverReason, paths};
+
+/// The fastest finished sprint, with its splits to race against
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SprintBest {
+    #[serde(with = "crate::serde_util::millis")]
+    pub time: Duration,
+    /// Time at each `SPRINT_SPLIT_LINES` boundary
+    #[serde(with = "crate::serde_util::millis_list")]
+    pub splits: Vec<Duration>,
+    pub date: String,
+}
+
+fn path() -> Option<PathBuf> {
+    paths::data_dir().map(|d| d.join("sprint_best.json"))
+}
+
+/// The personal best, if a sprint has ever been finished
+pub fn load() -> Option<SprintBest> {
+    let text = fs::read_to_string(path()?).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Keep `game`'s result if it's a finished sprint faster than the saved best;
+/// true when it set a new one
+pub fn record(game: &Game) -> io::Result<bool> {
+    let (GameMode::Sprint, Some(GameOverReason::GoalReached), Some(time)) =
+        (game.setup.mode, game.game_over_reason, game.goal_time)
+    else {
+        return Ok(false);
+    };
+    if load().is_some_and(|best| best.time <= time) {
+        return Ok(false);
+    }
+    let best = SprintBest {
+        time,
+        splits: game.splits.clone(),
+        date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+    };
+    let path =
+        path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string_pretty(&best).map_err(io::Error::other)?;
+    fs::write(path, json)?;
+    Ok(true)
+}
@@ -0,0 +1,142 @@
+use ratatui::style::Color;
+use std::{fmt::Write, fs, io, path::Path};
+
+use crate::{BOARD_HEIGHT, BOARD_WIDTH, Game, theme::Theme};
+
+/// Size of one board cell in the exported image
+const CELL_PX: usize = 20;
+/// Frame drawn around the cells
+const BORDER_PX: usize = 2;
+const EMPTY_FILL: &str = "#111111";
+
+/// Approximate RGB for the terminal colors the pieces use
+fn rgb(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Red => (255, 0, 0),
+        Color::Green => (0, 255, 0),
+        Color::Yellow => (255, 255, 0),
+        Color::Blue => (0, 0, 255),
+        Color::Magenta => (255, 0, 255),
+        Color::Cyan => (0, 255, 255),
+        Color::Gray => (192, 192, 192),
+        Color::DarkGray => (128, 128, 128),
+        Color::Black => (0, 0, 0),
+        _ => (255, 255, 255),
+    }
+}
+
+fn hex(color: Color) -> String {
+    let (r, g, b) = rgb(color);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Same hue at 60% brightness, for cell outlines
+fn darker_hex(color: Color) -> String {
+    let (r, g, b) = rgb(color);
+    let dim = |c: u8| (c as u16 * 3 / 5) as u8;
+    format!("#{:02x}{:02x}{:02x}", dim(r), dim(g), dim(b))
+}
+
+impl Game {
+    /// Render the board (stack, active piece and ghost) as an SVG document,
+    /// in the default theme's colors
+    pub fn board_svg(&self) -> String {
+        let theme = Theme::default();
+        let width = BOARD_WIDTH * CELL_PX + 2 * BORDER_PX;
+        let height = BOARD_HEIGHT * CELL_PX + 2 * BORDER_PX;
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = width,
+            h = height
+        );
+        let _ = writeln!(
+            out,
+            r##"<rect x="0" y="0" width="{}" height="{}" fill="{}" stroke="#ffffff" stroke-width="{}"/>"##,
+            width, height, EMPTY_FILL, BORDER_PX
+        );
+
+        let cell = |out: &mut String, x: i32, y: i32, color: Color, opacity: f32| {
+            if x < 0 || y < 0 || x >= BOARD_WIDTH as i32 || y >= BOARD_HEIGHT as i32 {
+                return;
+            }
+            let _ = writeln!(
+                out,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="{}" stroke-width="1" fill-opacity="{}"/>"#,
+                BORDER_PX + x as usize * CELL_PX,
+                BORDER_PX + y as usize * CELL_PX,
+                CELL_PX,
+                CELL_PX,
+                hex(color),
+                darker_hex(color),
+                opacity
+            );
+        };
+
+        for (y, row) in self.board.iter().enumerate() {
+            for (x, kind) in row.iter().enumerate() {
+                if let Some(kind) = kind {
+                    cell(&mut out, x as i32, y as i32, theme.piece(*kind), 1.0);
+                }
+            }
+        }
+        if self.piece_active() {
+            let color = theme.piece(self.current.tetro.kind);
+            if !self.game_over {
+                for (x, y) in self.ghost_piece().cells() {
+                    cell(&mut out, x, y, color, 0.3);
+                }
+            }
+            for (x, y) in self.current.cells() {
+                cell(&mut out, x, y, color, 1.0);
+            }
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+
+    pub fn export_board_svg(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.board_svg())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlockType, GameMode, GameSetup, rules::Rules};
+
+    fn on_board(cells: Vec<(i32, i32)>) -> usize {
+        cells
+            .into_iter()
+            .filter(|&(x, y)| x >= 0 && y >= 0 && x < BOARD_WIDTH as i32 && y < BOARD_HEIGHT as i32)
+            .count()
+    }
+
+    #[test]
+    fn the_export_is_xml_with_a_rect_per_cell() {
+        let mut game = Game::new(GameSetup {
+            mode: GameMode::Marathon,
+            rules: Rules::default(),
+            seed: Some(1),
+        });
+        for x in 0..BOARD_WIDTH - 1 {
+            game.set_cell(x, BOARD_HEIGHT - 1, Some(BlockType::Garbage));
+        }
+        game.set_cell(4, BOARD_HEIGHT - 2, Some(BlockType::T));
+        let filled = game.board.iter().flatten().flatten().count();
+        let pieces = on_board(game.current.cells()) + on_board(game.ghost_piece().cells());
+
+        let svg = game.board_svg();
+        let doc = roxmltree::Document::parse(&svg).expect("well-formed XML");
+        assert_eq!(doc.root_element().tag_name().name(), "svg");
+        let rects = doc
+            .descendants()
+            .filter(|node| node.has_tag_name("rect"))
+            .count();
+        // the background, then one per settled block and piece block
+        assert_eq!(rects, 1 + filled + pieces);
+        assert_eq!(filled, BOARD_WIDTH);
+    }
+}
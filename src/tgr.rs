@@ -0,0 +1,508 @@
+use lz4_flex::block::DecompressError;
+use std::{fmt, fs, io, path::Path, time::Duration};
+
+use crate::{
+    Action, DEFAULT_CHEESE_ROWS, GameMode, RotationDir, TICK, daily,
+    replay::{Replay, ReplayEntry, ReplayPlayer},
+    rules::Rules,
+};
+
+/// File signature; the last byte is the format version
+const MAGIC: &[u8; 3] = b"TGR";
+/// Version 2 games flash cleared rows (`Rules::clear_flash`); version 1
+/// files are from before that and still play back without it
+pub const TGR_VERSION: u8 = 2;
+
+/// Bytes of the player name field, null-padded
+const NAME_LEN: usize = 16;
+
+/// Magic + version, seed, mode, name, action count
+const HEADER_LEN: usize = 4 + 8 + 1 + NAME_LEN + 4;
+
+/// Bytes per record: `delta_ms: u16` then `action: u8`
+const RECORD_LEN: usize = 3;
+
+/// Most bytes one byte of LZ4 block data can expand to, so a header can't
+/// claim more records than its data could hold
+const LZ4_MAX_RATIO: usize = 255;
+
+/// Action code of a record that only lets time pass: fills gaps too long for
+/// one `u16` delta and carries the time from the last action to the end
+const WAIT: u8 = 0xFF;
+
+/// Why a .tgr file couldn't be read or a replay couldn't be written as one
+#[derive(Debug)]
+pub enum TgrError {
+    Io(io::Error),
+    InvalidMagic,
+    UnsupportedVersion(u8),
+    Decompress(String),
+    Truncated,
+    UnknownMode(u8),
+    UnknownAction(u8),
+    /// The replay uses something the format has no room for
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for TgrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TgrError::Io(e) => write!(f, "{}", e),
+            TgrError::InvalidMagic => write!(f, "not a .tgr replay"),
+            TgrError::UnsupportedVersion(v) => write!(
+                f,
+                ".tgr version {} is not supported (this build reads versions 1-{})",
+                v, TGR_VERSION
+            ),
+            TgrError::Decompress(e) => write!(f, "corrupt action data: {}", e),
+            TgrError::Truncated => write!(f, "file is truncated"),
+            TgrError::UnknownMode(m) => write!(f, "unknown mode {}", m),
+            TgrError::UnknownAction(a) => write!(f, "unknown action {}", a),
+            TgrError::Unsupported(what) => write!(f, ".tgr can't store {}", what),
+        }
+    }
+}
+
+impl std::error::Error for TgrError {}
+
+impl From<io::Error> for TgrError {
+    fn from(e: io::Error) -> Self {
+        TgrError::Io(e)
+    }
+}
+
+/// One record: milliseconds since the previous one, then what happened
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TgrRecord {
+    pub delta_ms: u16,
+    /// `None` for a wait record
+    pub action: Option<Action>,
+}
+
+/// A replay in the compact binary .tgr format, for sharing: header fields
+/// plus LZ4-compressed action records
+#[derive(Clone, Debug, PartialEq)]
+pub struct TgrReplay {
+    /// Format version the replay was recorded under
+    pub version: u8,
+    pub seed: u64,
+    pub mode: GameMode,
+    /// At most 16 bytes; longer names are cut on a character boundary
+    pub player_name: String,
+    pub records: Vec<TgrRecord>,
+}
+
+fn mode_code(mode: GameMode) -> Result<u8, TgrError> {
+    Ok(match mode {
+        GameMode::Marathon => 0,
+        GameMode::Sprint => 1,
+        GameMode::Ultra => 2,
+        GameMode::Cheese { rows } if rows == DEFAULT_CHEESE_ROWS => 3,
+        GameMode::Cheese { .. } => return Err(TgrError::Unsupported("a custom cheese height")),
+        GameMode::DailyChallenge { .. } => 4,
+        GameMode::Puzzle { .. } => return Err(TgrError::Unsupported("puzzles")),
+        GameMode::Competitive => return Err(TgrError::Unsupported("competitive games")),
+        GameMode::Tutorial => return Err(TgrError::Unsupported("the tutorial")),
+    })
+}
+
+/// The daily challenge whose seed is `seed`; the number isn't stored, so
+/// it's found again by searching the days up to tomorrow
+fn daily_number(seed: u64) -> u32 {
+    (1..=daily::today() + 1)
+        .rev()
+        .find(|&n| daily::seed(&daily::date_of(n)) == seed)
+        .unwrap_or(0)
+}
+
+fn mode_from_code(code: u8, seed: u64) -> Result<GameMode, TgrError> {
+    Ok(match code {
+        0 => GameMode::Marathon,
+        1 => GameMode::Sprint,
+        2 => GameMode::Ultra,
+        3 => GameMode::Cheese {
+            rows: DEFAULT_CHEESE_ROWS,
+        },
+        4 => GameMode::DailyChallenge {
+            number: daily_number(seed),
+        },
+        other => return Err(TgrError::UnknownMode(other)),
+    })
+}
+
+fn action_code(action: Action) -> u8 {
+    match action {
+        Action::MoveLeft => 0,
+        Action::MoveRight => 1,
+        Action::SoftDrop => 2,
+        Action::HardDrop => 3,
+        Action::RotateCw => 4,
+        Action::RotateCcw => 5,
+        Action::Hold => 6,
+        Action::SetIrs(None) => 7,
+        Action::SetIrs(Some(RotationDir::Cw)) => 8,
+        Action::SetIrs(Some(RotationDir::Ccw)) => 9,
+        Action::Zone => 10,
+    }
+}
+
+fn action_from_code(code: u8) -> Result<Option<Action>, TgrError> {
+    Ok(Some(match code {
+        0 => Action::MoveLeft,
+        1 => Action::MoveRight,
+        2 => Action::SoftDrop,
+        3 => Action::HardDrop,
+        4 => Action::RotateCw,
+        5 => Action::RotateCcw,
+        6 => Action::Hold,
+        7 => Action::SetIrs(None),
+        8 => Action::SetIrs(Some(RotationDir::Cw)),
+        9 => Action::SetIrs(Some(RotationDir::Ccw)),
+        10 => Action::Zone,
+        WAIT => return Ok(None),
+        other => return Err(TgrError::UnknownAction(other)),
+    }))
+}
+
+/// Records advancing `ms` milliseconds and then, if given, doing `action`
+fn push_records(records: &mut Vec<TgrRecord>, mut ms: u64, action: Option<Action>) {
+    while ms > u64::from(u16::MAX) {
+        records.push(TgrRecord {
+            delta_ms: u16::MAX,
+            action: None,
+        });
+        ms -= u64::from(u16::MAX);
+    }
+    if ms > 0 || action.is_some() {
+        records.push(TgrRecord {
+            delta_ms: ms as u16,
+            action,
+        });
+    }
+}
+
+impl TgrReplay {
+    /// Pack a JSON replay. Only default rules fit, since the format has no
+    /// room for them and anything else would play back differently; a game
+    /// from before cleared rows flashed goes in as version 1.
+    pub fn from_replay(replay: &Replay, player_name: &str) -> Result<TgrReplay, TgrError> {
+        let unflashed = Rules {
+            clear_flash: Duration::ZERO,
+            ..Rules::default()
+        };
+        let version = if replay.rules == Rules::default() {
+            TGR_VERSION
+        } else if replay.rules == unflashed {
+            1
+        } else {
+            return Err(TgrError::Unsupported("non-default rules"));
+        };
+        let mode = replay.mode;
+        mode_code(mode)?;
+        let tick_ms = TICK.as_millis() as u64;
+        let mut records = Vec::with_capacity(replay.actions.len());
+        let mut last_tick = 0;
+        for &ReplayEntry(tick, action) in &replay.actions {
+            push_records(&mut records, (tick - last_tick) * tick_ms, Some(action));
+            last_tick = tick;
+        }
+        push_records(
+            &mut records,
+            replay.end_tick.saturating_sub(last_tick) * tick_ms,
+            None,
+        );
+        Ok(TgrReplay {
+            version,
+            seed: replay.seed,
+            mode,
+            player_name: truncate_name(player_name),
+            records,
+        })
+    }
+
+    /// Unpack into a JSON replay, re-simulating the game for its final score
+    pub fn to_replay(&self) -> Replay {
+        let tick_ms = TICK.as_millis() as u64;
+        let mut rules = Rules::default();
+        if self.version < 2 {
+            rules.clear_flash = Duration::ZERO;
+        }
+        let mut replay = Replay::new(self.seed, self.mode, rules);
+        let mut ms = 0;
+        for record in &self.records {
+            ms += u64::from(record.delta_ms);
+            if let Some(action) = record.action {
+                replay.record(ms / tick_ms, action);
+            }
+        }
+        replay.end_tick = ms / tick_ms;
+        let mut player = ReplayPlayer::new(replay);
+        let mut game = player.new_game();
+        while player.step_once(&mut game) {}
+        player.replay.final_score = game.score;
+        player.replay
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, TgrError> {
+        let mode = mode_code(self.mode)?;
+        let mut raw = Vec::with_capacity(self.records.len() * RECORD_LEN);
+        for record in &self.records {
+            raw.extend_from_slice(&record.delta_ms.to_le_bytes());
+            raw.push(record.action.map_or(WAIT, action_code));
+        }
+        let mut name = [0u8; NAME_LEN];
+        let name_bytes = truncate_name(&self.player_name);
+        name[..name_bytes.len()].copy_from_slice(name_bytes.as_bytes());
+
+        let mut out = Vec::with_capacity(HEADER_LEN + raw.len() / 2);
+        out.extend_from_slice(MAGIC);
+        out.push(self.version);
+        out.extend_from_slice(&self.seed.to_le_bytes());
+        out.push(mode);
+        out.extend_from_slice(&name);
+        out.extend_from_slice(&(self.records.len() as u32).to_le_bytes());
+        out.extend_from_slice(&lz4_flex::block::compress(&raw));
+        Ok(out)
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<TgrReplay, TgrError> {
+        if data.len() < MAGIC.len() || &data[..MAGIC.len()] != MAGIC {
+            return Err(TgrError::InvalidMagic);
+        }
+        let version = *data.get(MAGIC.len()).ok_or(TgrError::Truncated)?;
+        if !(1..=TGR_VERSION).contains(&version) {
+            return Err(TgrError::UnsupportedVersion(version));
+        }
+        if data.len() < HEADER_LEN {
+            return Err(TgrError::Truncated);
+        }
+        let (header, compressed) = data.split_at(HEADER_LEN);
+        let seed = u64::from_le_bytes(header[4..12].try_into().expect("8 bytes"));
+        let mode = mode_from_code(header[12], seed)?;
+        let name = &header[13..13 + NAME_LEN];
+        let name_len = name.iter().position(|&b| b == 0).unwrap_or(NAME_LEN);
+        let player_name = String::from_utf8_lossy(&name[..name_len]).into_owned();
+        let count = u32::from_le_bytes(header[29..33].try_into().expect("4 bytes")) as usize;
+
+        if count > 0 && compressed.is_empty() {
+            return Err(TgrError::Truncated);
+        }
+        // the output buffer is allocated up front, so check the size first
+        let raw_len = count
+            .checked_mul(RECORD_LEN)
+            .filter(|&len| len <= compressed.len().saturating_mul(LZ4_MAX_RATIO))
+            .ok_or_else(|| {
+                TgrError::Decompress(format!(
+                    "{} records can't come from {} bytes",
+                    count,
+                    compressed.len()
+                ))
+            })?;
+        let raw = lz4_flex::block::decompress(compressed, raw_len).map_err(|e| match e {
+            DecompressError::ExpectedAnotherByte | DecompressError::LiteralOutOfBounds => {
+                TgrError::Truncated
+            }
+            other => TgrError::Decompress(other.to_string()),
+        })?;
+        if raw.len() != raw_len {
+            return Err(TgrError::Truncated);
+        }
+        let records = raw
+            .chunks_exact(RECORD_LEN)
+            .map(|r| {
+                Ok(TgrRecord {
+                    delta_ms: u16::from_le_bytes([r[0], r[1]]),
+                    action: action_from_code(r[2])?,
+                })
+            })
+            .collect::<Result<_, TgrError>>()?;
+        Ok(TgrReplay {
+            version,
+            seed,
+            mode,
+            player_name,
+            records,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let bytes = self
+            .to_bytes()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        fs::write(path, bytes)
+    }
+
+    pub fn load(path: &Path) -> Result<TgrReplay, TgrError> {
+        TgrReplay::from_bytes(&fs::read(path)?)
+    }
+}
+
+/// Whether `path` names a .tgr file rather than a JSON replay
+pub fn is_tgr(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("tgr"))
+}
+
+/// Read a replay in either format
+pub fn load_any(path: &Path) -> Result<Replay, String> {
+    if is_tgr(path) {
+        TgrReplay::load(path)
+            .map(|tgr| tgr.to_replay())
+            .map_err(|e| e.to_string())
+    } else {
+        Replay::load(path).map_err(|e| e.to_string())
+    }
+}
+
+/// Convert a replay between JSON and .tgr, by the extension of `to`;
+/// `player_name` is stored when writing .tgr
+pub fn convert(from: &Path, to: &Path, player_name: &str) -> Result<(), String> {
+    let replay = load_any(from)?;
+    if is_tgr(to) {
+        let tgr = TgrReplay::from_replay(&replay, player_name).map_err(|e| e.to_string())?;
+        tgr.save(to).map_err(|e| e.to_string())
+    } else {
+        let json = serde_json::to_string(&replay).map_err(|e| e.to_string())?;
+        fs::write(to, json).map_err(|e| e.to_string())
+    }
+}
+
+/// `name` cut to fit the name field without splitting a character
+fn truncate_name(name: &str) -> String {
+    let mut end = name.len().min(NAME_LEN);
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+    name[..end].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A marathon game's worth of moves and drops, with a long idle gap
+    fn known_replay() -> Replay {
+        let mut replay = Replay::new(42, GameMode::Marathon, Rules::default());
+        let cycle = [
+            Action::MoveLeft,
+            Action::RotateCw,
+            Action::SetIrs(Some(RotationDir::Ccw)),
+            Action::HardDrop,
+            Action::Hold,
+        ];
+        let mut tick = 0;
+        for i in 0..400 {
+            tick += 3 + i % 7;
+            replay.record(tick, cycle[i as usize % cycle.len()]);
+        }
+        // longer than one record's delta can hold
+        replay.end_tick = tick + 5_000;
+        replay
+    }
+
+    fn header(count: u32) -> Vec<u8> {
+        let mut data = MAGIC.to_vec();
+        data.push(TGR_VERSION);
+        data.extend_from_slice(&7u64.to_le_bytes());
+        data.push(0);
+        data.extend_from_slice(&[0; NAME_LEN]);
+        data.extend_from_slice(&count.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn round_trips_through_a_file() {
+        let replay = known_replay();
+        let tgr = TgrReplay::from_replay(&replay, "player one").unwrap();
+        let path = std::env::temp_dir().join(format!("tgr-test-{}.tgr", std::process::id()));
+        tgr.save(&path).unwrap();
+        let loaded = TgrReplay::load(&path);
+        let _ = fs::remove_file(&path);
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded, tgr);
+        let back = loaded.to_replay();
+        assert_eq!(back.seed, replay.seed);
+        assert_eq!(back.mode, replay.mode);
+        assert_eq!(back.actions, replay.actions);
+        assert_eq!(back.end_tick, replay.end_tick);
+    }
+
+    #[test]
+    fn is_much_smaller_than_json() {
+        let replay = known_replay();
+        let json = serde_json::to_vec(&replay).unwrap();
+        let bytes = TgrReplay::from_replay(&replay, "")
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+        assert!(
+            bytes.len() * 5 <= json.len(),
+            "{} vs {}",
+            bytes.len(),
+            json.len()
+        );
+    }
+
+    #[test]
+    fn long_names_are_cut_on_a_character_boundary() {
+        let tgr = TgrReplay::from_replay(&known_replay(), "ééééééééé").unwrap();
+        assert_eq!(tgr.player_name, "éééééééé");
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        let good = TgrReplay::from_replay(&known_replay(), "")
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+        let err = |data: &[u8]| TgrReplay::from_bytes(data).unwrap_err();
+
+        assert!(matches!(err(b"JSON{}"), TgrError::InvalidMagic));
+        assert!(matches!(err(b"TG"), TgrError::InvalidMagic));
+        assert!(matches!(err(b"TGR"), TgrError::Truncated));
+        let mut future = good.clone();
+        future[3] = TGR_VERSION + 1;
+        assert!(matches!(err(&future), TgrError::UnsupportedVersion(_)));
+        assert!(matches!(err(&good[..HEADER_LEN - 1]), TgrError::Truncated));
+        assert!(matches!(err(&good[..HEADER_LEN]), TgrError::Truncated));
+        assert!(matches!(
+            err(&good[..good.len() - 10]),
+            TgrError::Truncated | TgrError::Decompress(_)
+        ));
+        let mut mode = good.clone();
+        mode[12] = 99;
+        assert!(matches!(err(&mode), TgrError::UnknownMode(99)));
+    }
+
+    #[test]
+    fn rejects_unknown_actions() {
+        let raw = [0, 0, 0x42];
+        let mut data = header(1);
+        data.extend_from_slice(&lz4_flex::block::compress(&raw));
+        assert!(matches!(
+            TgrReplay::from_bytes(&data),
+            Err(TgrError::UnknownAction(0x42))
+        ));
+    }
+
+    #[test]
+    fn rejects_counts_the_data_cant_hold_before_allocating() {
+        let mut data = header(u32::MAX);
+        data.push(0);
+        assert!(matches!(
+            TgrReplay::from_bytes(&data),
+            Err(TgrError::Decompress(_))
+        ));
+    }
+
+    #[test]
+    fn highly_compressible_data_is_within_the_ratio() {
+        let raw = vec![0u8; 300_000];
+        let compressed = lz4_flex::block::compress(&raw);
+        assert!(raw.len() <= compressed.len() * LZ4_MAX_RATIO);
+        let mut data = header((raw.len() / RECORD_LEN) as u32);
+        data.extend_from_slice(&compressed);
+        let tgr = TgrReplay::from_bytes(&data).unwrap();
+        assert_eq!(tgr.records.len(), raw.len() / RECORD_LEN);
+    }
+}
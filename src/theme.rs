@@ -0,0 +1,259 @@
+//! Colors for the pieces and the board's chrome: a built-in preset picked by
+//! `ui.theme`, with any of its colors overridden in the `[colors]` section
+
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::{BlockType, config::HIGH_CONTRAST};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    /// I, O, T, S, Z, J, L, then garbage
+    pub pieces: [Color; 8],
+    /// Behind empty cells
+    pub background: Color,
+    /// Box outlines; `None` tints the board's by level as the game speeds up
+    pub border: Option<Color>,
+    pub text: Color,
+    /// The ghost piece; `None` draws it in the piece's own color
+    pub ghost: Option<Color>,
+    /// The board's border once the stack is critical
+    pub danger: Color,
+    /// Behind the board's top rows once the stack is critical
+    pub danger_background: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            pieces: [
+                Color::Cyan,
+                Color::Yellow,
+                Color::Magenta,
+                Color::Green,
+                Color::Red,
+                Color::Blue,
+                Color::Rgb(255, 165, 0), // orange
+                Color::Gray,
+            ],
+            background: Color::Black,
+            border: None,
+            text: Color::Reset,
+            ghost: None,
+            danger: Color::Red,
+            danger_background: Color::Rgb(64, 0, 0),
+        }
+    }
+}
+
+impl Theme {
+    /// The preset named `name`, one of `config::THEMES`
+    pub fn builtin(name: &str) -> Option<Theme> {
+        let default = Theme::default();
+        Some(match name {
+            "default" => default,
+            "pastel" => Theme {
+                pieces: [
+                    Color::Rgb(150, 220, 230),
+                    Color::Rgb(250, 235, 150),
+                    Color::Rgb(210, 170, 230),
+                    Color::Rgb(170, 225, 170),
+                    Color::Rgb(245, 165, 165),
+                    Color::Rgb(160, 180, 240),
+                    Color::Rgb(250, 200, 150),
+                    Color::Rgb(190, 190, 190),
+                ],
+                border: Some(Color::Rgb(200, 190, 220)),
+                danger: Color::Rgb(245, 130, 130),
+                ..default
+            },
+            "dark" => Theme {
+                pieces: [
+                    Color::Rgb(0, 140, 160),
+                    Color::Rgb(170, 150, 0),
+                    Color::Rgb(130, 50, 150),
+                    Color::Rgb(40, 140, 50),
+                    Color::Rgb(160, 40, 40),
+                    Color::Rgb(40, 70, 170),
+                    Color::Rgb(180, 100, 0),
+                    Color::Rgb(80, 80, 80),
+                ],
+                background: Color::Rgb(12, 12, 16),
+                border: Some(Color::Rgb(70, 70, 90)),
+                text: Color::Gray,
+                ghost: Some(Color::Rgb(70, 70, 70)),
+                danger: Color::Rgb(160, 40, 40),
+                ..default
+            },
+            // Okabe-Ito: S and Z, and I and J, differ in lightness as well as hue
+            "deuteranopia" => Theme {
+                pieces: [
+                    Color::Rgb(86, 180, 233),
+                    Color::Rgb(240, 228, 66),
+                    Color::Rgb(204, 121, 167),
+                    Color::Rgb(0, 158, 115),
+                    Color::Rgb(213, 94, 0),
+                    Color::Rgb(0, 114, 178),
+                    Color::Rgb(230, 159, 0),
+                    Color::Rgb(110, 110, 110),
+                ],
+                ..default
+            },
+            // reds and teals, kept apart by lightness, with no blue/yellow pair
+            "tritanopia" => Theme {
+                pieces: [
+                    Color::Rgb(0, 190, 200),
+                    Color::Rgb(255, 190, 200),
+                    Color::Rgb(140, 40, 160),
+                    Color::Rgb(0, 110, 100),
+                    Color::Rgb(220, 30, 40),
+                    Color::Rgb(110, 20, 40),
+                    Color::Rgb(255, 130, 60),
+                    Color::Rgb(110, 110, 110),
+                ],
+                ..default
+            },
+            "mono" => Theme {
+                pieces: [
+                    Color::White,
+                    Color::White,
+                    Color::White,
+                    Color::White,
+                    Color::White,
+                    Color::White,
+                    Color::White,
+                    Color::Gray,
+                ],
+                border: Some(Color::White),
+                ..default
+            },
+            HIGH_CONTRAST => Theme {
+                pieces: [
+                    Color::LightCyan,
+                    Color::LightYellow,
+                    Color::LightMagenta,
+                    Color::LightGreen,
+                    Color::LightRed,
+                    Color::LightBlue,
+                    Color::Rgb(255, 140, 0),
+                    Color::White,
+                ],
+                border: Some(Color::White),
+                text: Color::White,
+                ghost: Some(Color::White),
+                ..default
+            },
+            _ => return None,
+        })
+    }
+
+    pub fn piece(&self, kind: BlockType) -> Color {
+        let i = match kind {
+            BlockType::I => 0,
+            BlockType::O => 1,
+            BlockType::T => 2,
+            BlockType::S => 3,
+            BlockType::Z => 4,
+            BlockType::J => 5,
+            BlockType::L => 6,
+            BlockType::Garbage => 7,
+        };
+        self.pieces[i]
+    }
+
+    /// This theme with every color `colors` sets; unreadable ones are skipped
+    /// (`ColorsConfig::validate` warns about them)
+    pub fn with(mut self, colors: &ColorsConfig) -> Theme {
+        let parse = |value: &Option<String>| value.as_deref().and_then(|s| s.parse().ok());
+        let pieces = [
+            &colors.i,
+            &colors.o,
+            &colors.t,
+            &colors.s,
+            &colors.z,
+            &colors.j,
+            &colors.l,
+            &colors.garbage,
+        ];
+        for (color, value) in self.pieces.iter_mut().zip(pieces) {
+            if let Some(c) = parse(value) {
+                *color = c;
+            }
+        }
+        if let Some(c) = parse(&colors.background) {
+            self.background = c;
+        }
+        self.border = parse(&colors.border).or(self.border);
+        if let Some(c) = parse(&colors.text) {
+            self.text = c;
+        }
+        self.ghost = parse(&colors.ghost).or(self.ghost);
+        if let Some(c) = parse(&colors.danger) {
+            self.danger = c;
+        }
+        if let Some(c) = parse(&colors.danger_background) {
+            self.danger_background = c;
+        }
+        self
+    }
+}
+
+/// The `[colors]` config section: names like "red" or "lightblue", "#rrggbb",
+/// or a palette index; anything left out comes from the theme
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorsConfig {
+    pub i: Option<String>,
+    pub o: Option<String>,
+    pub t: Option<String>,
+    pub s: Option<String>,
+    pub z: Option<String>,
+    pub j: Option<String>,
+    pub l: Option<String>,
+    pub garbage: Option<String>,
+    pub background: Option<String>,
+    pub border: Option<String>,
+    pub text: Option<String>,
+    pub ghost: Option<String>,
+    pub danger: Option<String>,
+    pub danger_background: Option<String>,
+}
+
+impl ColorsConfig {
+    /// Every key with its value
+    pub fn entries_mut(&mut self) -> [(&'static str, &mut Option<String>); 14] {
+        [
+            ("i", &mut self.i),
+            ("o", &mut self.o),
+            ("t", &mut self.t),
+            ("s", &mut self.s),
+            ("z", &mut self.z),
+            ("j", &mut self.j),
+            ("l", &mut self.l),
+            ("garbage", &mut self.garbage),
+            ("background", &mut self.background),
+            ("border", &mut self.border),
+            ("text", &mut self.text),
+            ("ghost", &mut self.ghost),
+            ("danger", &mut self.danger),
+            ("danger_background", &mut self.danger_background),
+        ]
+    }
+
+    /// Drop colors that can't be read, each with a warning, so the theme's
+    /// own color shows instead
+    pub fn validate(&mut self, warnings: &mut Vec<String>) {
+        for (name, value) in self.entries_mut() {
+            if let Some(text) = value
+                && Color::from_str(text).is_err()
+            {
+                warnings.push(format!(
+                    "colors.{}: '{}' isn't a color, using the theme's",
+                    name, text
+                ));
+                *value = None;
+            }
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! Tutorial mode: a guided first game, one step at a time. Gravity is off,
+//! and each step waits for the move it teaches; the later ones set up a
+//! board of their own.
+
+use crate::{Action, ActivePiece, BlockType, Game, layout};
+
+/// Where the tutorial is, in order
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TutorialStep {
+    Step1MoveLeft,
+    Step1MoveRight,
+    Step2RotateCw,
+    Step2RotateCcw,
+    Step3HardDrop,
+    /// A bottom row one block short, and an I to finish it
+    Step4ClearLine,
+    Step5Hold,
+    /// A slot only a rotating T gets into
+    Step6TSpinIntro,
+    Complete,
+}
+
+/// Step 4's board: a bottom row waiting for the I, in the well on the right
+const CLEAR_LINE_BOARD: [&str; 1] = ["GGGGGGGGG."];
+
+/// Step 6's board: the T drops in on the right and slides left under the
+/// ledge, then a turn clockwise fills the hole and clears the bottom row
+const T_SPIN_BOARD: [&str; 3] = ["GGGG...GGG", "GGG....GGG", "GGGG.GGGGG"];
+
+impl TutorialStep {
+    /// The key to show in the instructions
+    pub fn action(self) -> Option<Action> {
+        match self {
+            TutorialStep::Step1MoveLeft => Some(Action::MoveLeft),
+            TutorialStep::Step1MoveRight => Some(Action::MoveRight),
+            TutorialStep::Step2RotateCw => Some(Action::RotateCw),
+            TutorialStep::Step2RotateCcw => Some(Action::RotateCcw),
+            TutorialStep::Step3HardDrop | TutorialStep::Step4ClearLine => Some(Action::HardDrop),
+            TutorialStep::Step5Hold => Some(Action::Hold),
+            TutorialStep::Step6TSpinIntro => Some(Action::SoftDrop),
+            TutorialStep::Complete => None,
+        }
+    }
+
+    /// What to do, with `keys` the bindings of `action`
+    pub fn instruction(self, keys: &str) -> String {
+        match self {
+            TutorialStep::Step1MoveLeft => format!("Press {} to move left", keys),
+            TutorialStep::Step1MoveRight => format!("Press {} to move right", keys),
+            TutorialStep::Step2RotateCw => format!("Press {} to rotate clockwise", keys),
+            TutorialStep::Step2RotateCcw => {
+                format!("Press {} to rotate counterclockwise", keys)
+            }
+            TutorialStep::Step3HardDrop => format!("Press {} to drop the piece", keys),
+            TutorialStep::Step4ClearLine => format!(
+                "A full row clears. Stand the I up over the gap and press {}",
+                keys
+            ),
+            TutorialStep::Step5Hold => format!("Press {} to keep this piece for later", keys),
+            TutorialStep::Step6TSpinIntro => format!(
+                "T-spin: move right, {} down into the gap, slide left under the \
+                 ledge and rotate clockwise",
+                keys
+            ),
+            TutorialStep::Complete => "Tutorial complete! Start a real game? [Enter]".to_string(),
+        }
+    }
+
+    fn next(self) -> TutorialStep {
+        match self {
+            TutorialStep::Step1MoveLeft => TutorialStep::Step1MoveRight,
+            TutorialStep::Step1MoveRight => TutorialStep::Step2RotateCw,
+            TutorialStep::Step2RotateCw => TutorialStep::Step2RotateCcw,
+            TutorialStep::Step2RotateCcw => TutorialStep::Step3HardDrop,
+            TutorialStep::Step3HardDrop => TutorialStep::Step4ClearLine,
+            TutorialStep::Step4ClearLine => TutorialStep::Step5Hold,
+            TutorialStep::Step5Hold => TutorialStep::Step6TSpinIntro,
+            TutorialStep::Step6TSpinIntro | TutorialStep::Complete => TutorialStep::Complete,
+        }
+    }
+}
+
+/// The step a tutorial game is on, and the game as that step began
+#[derive(Clone, Debug)]
+pub struct TutorialState {
+    pub step: TutorialStep,
+    /// The step's board and piece are in place
+    ready: bool,
+    pieces_at_start: usize,
+    lines_at_start: usize,
+}
+
+impl Default for TutorialState {
+    fn default() -> Self {
+        TutorialState {
+            step: TutorialStep::Step1MoveLeft,
+            ready: false,
+            pieces_at_start: 0,
+            lines_at_start: 0,
+        }
+    }
+}
+
+impl TutorialState {
+    /// The step to go on to now that `game` has taken `action` (`None` for
+    /// a tick, where only a lock can have happened), if this one is done
+    pub fn check_advance(&self, action: Option<Action>, game: &Game) -> Option<TutorialStep> {
+        if !self.ready {
+            return None;
+        }
+        let locked = game.pieces_placed > self.pieces_at_start;
+        let done = match self.step {
+            TutorialStep::Step1MoveLeft => action == Some(Action::MoveLeft),
+            TutorialStep::Step1MoveRight => action == Some(Action::MoveRight),
+            TutorialStep::Step2RotateCw => action == Some(Action::RotateCw),
+            TutorialStep::Step2RotateCcw => action == Some(Action::RotateCcw),
+            TutorialStep::Step3HardDrop => action == Some(Action::HardDrop),
+            TutorialStep::Step4ClearLine => game.lines_cleared > self.lines_at_start,
+            TutorialStep::Step5Hold => action == Some(Action::Hold) && game.hold.is_some(),
+            // spun in or not, the T has shown what it can do
+            TutorialStep::Step6TSpinIntro => locked,
+            TutorialStep::Complete => false,
+        };
+        done.then(|| self.step.next())
+    }
+
+    /// Move on if `action` (or a tick, for `None`) finished the step, then
+    /// set up the next one once there's a piece in play. A piece that locks
+    /// without clearing step 4's row gets the row back for another try, once
+    /// any clear it did make has played out.
+    pub fn update(&mut self, action: Option<Action>, game: &mut Game) {
+        // a paused game didn't take the action
+        if game.paused {
+            return;
+        }
+        if let Some(step) = self.check_advance(action, game) {
+            self.step = step;
+            self.ready = false;
+        } else if self.ready
+            && self.step == TutorialStep::Step4ClearLine
+            && game.pieces_placed > self.pieces_at_start
+            && game.piece_active()
+        {
+            self.ready = false;
+        }
+        if !self.ready && game.piece_active() {
+            self.set_up(game);
+        }
+    }
+
+    fn set_up(&mut self, game: &mut Game) {
+        let (rows, kind): (&[&str], _) = match self.step {
+            TutorialStep::Step4ClearLine => (&CLEAR_LINE_BOARD, BlockType::I),
+            TutorialStep::Step6TSpinIntro => (&T_SPIN_BOARD, BlockType::T),
+            _ => (&[], game.current.tetro.kind),
+        };
+        if !rows.is_empty() {
+            let rows: Vec<String> = rows.iter().map(|row| row.to_string()).collect();
+            game.board = layout::from_rows(&rows).expect("tutorial boards are valid");
+            game.current = ActivePiece::new(kind, game.setup.rules.spawn_rule(kind));
+            game.hold_used_this_drop = false;
+            game.update_stack();
+        }
+        self.ready = true;
+        self.pieces_at_start = game.pieces_placed;
+        self.lines_at_start = game.lines_cleared;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameMode, GameSetup, rules::Rules};
+
+    /// Press `action`, then play out a second of ticks, with the tutorial
+    /// watching throughout as the frontend has it
+    fn press(tutorial: &mut TutorialState, game: &mut Game, action: Action) {
+        game.press(action);
+        tutorial.update(Some(action), game);
+        for _ in 0..50 {
+            game.step();
+            tutorial.update(None, game);
+        }
+    }
+
+    #[test]
+    fn each_step_waits_for_what_it_teaches() {
+        let mut game = Game::new(GameSetup {
+            mode: GameMode::Tutorial,
+            rules: Rules::default(),
+            seed: Some(1),
+        });
+        let mut tutorial = TutorialState::default();
+        assert_eq!(tutorial.check_advance(Some(Action::MoveLeft), &game), None);
+        tutorial.update(None, &mut game);
+
+        for (action, next) in [
+            (Action::MoveLeft, TutorialStep::Step1MoveRight),
+            (Action::MoveRight, TutorialStep::Step2RotateCw),
+            (Action::RotateCw, TutorialStep::Step2RotateCcw),
+            (Action::RotateCcw, TutorialStep::Step3HardDrop),
+            (Action::HardDrop, TutorialStep::Step4ClearLine),
+        ] {
+            assert_eq!(tutorial.check_advance(None, &game), None);
+            assert_eq!(tutorial.check_advance(Some(Action::Hold), &game), None);
+            press(&mut tutorial, &mut game, action);
+            assert_eq!(tutorial.step, next);
+        }
+
+        // a drop that leaves the row unfinished gets the row back to try again
+        assert_eq!(game.current.tetro.kind, BlockType::I);
+        press(&mut tutorial, &mut game, Action::HardDrop);
+        assert_eq!(tutorial.step, TutorialStep::Step4ClearLine);
+        assert_eq!(layout::to_rows(&game.board), CLEAR_LINE_BOARD);
+        press(&mut tutorial, &mut game, Action::RotateCw);
+        for _ in 0..4 {
+            press(&mut tutorial, &mut game, Action::MoveRight);
+        }
+        press(&mut tutorial, &mut game, Action::HardDrop);
+        assert_eq!(tutorial.step, TutorialStep::Step5Hold);
+
+        press(&mut tutorial, &mut game, Action::Hold);
+        assert_eq!(tutorial.step, TutorialStep::Step6TSpinIntro);
+        assert_eq!(game.current.tetro.kind, BlockType::T);
+        press(&mut tutorial, &mut game, Action::HardDrop);
+        assert_eq!(tutorial.step, TutorialStep::Complete);
+        assert_eq!(tutorial.check_advance(Some(Action::HardDrop), &game), None);
+    }
+}
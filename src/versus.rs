@@ -0,0 +1,198 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use rand::prelude::*;
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use std::{io, sync::mpsc, time::Instant};
+
+use tetris_game::{
+    BOARD_WIDTH, Game, GameEvent, GameMode, GameSetup, config::Config, keymap::Keymap, rules::Rules,
+};
+
+use crate::{InternalEvent, Outputs, Panel, Term, draw_game, frame_events};
+
+/// Garbage rows sent to the opponent for clearing `lines` at once
+pub fn attack_for_clear(lines: usize) -> usize {
+    lines.saturating_sub(1)
+}
+
+/// Two local games side by side; the first to top out loses
+struct Versus {
+    games: [Game; 2],
+    keymaps: [Keymap; 2],
+    rng: ThreadRng,
+}
+
+impl Versus {
+    fn new(rules: &Rules) -> Self {
+        Versus {
+            games: [new_game(rules), new_game(rules)],
+            keymaps: [Keymap::player_one(), Keymap::player_two()],
+            rng: thread_rng(),
+        }
+    }
+
+    fn over(&self) -> bool {
+        self.games.iter().any(|g| g.game_over)
+    }
+
+    fn handle_key(&mut self, key: &KeyEvent) {
+        if self.over() {
+            return;
+        }
+        for (game, keymap) in self.games.iter_mut().zip(&self.keymaps) {
+            if let Some(action) = keymap.action_for(key) {
+                game.press(action);
+            }
+        }
+    }
+
+    fn step(&mut self) {
+        if self.over() {
+            return;
+        }
+        for game in &mut self.games {
+            game.step();
+        }
+    }
+
+    /// Forward events and turn each player's clears into garbage for the other
+    fn exchange(&mut self, outputs: &mut Outputs) {
+        for i in 0..2 {
+            for ev in outputs.dispatch(&mut self.games[i]) {
+                if let GameEvent::LinesCleared { count, .. } = ev {
+                    let gap = self.rng.gen_range(0..BOARD_WIDTH);
+                    self.games[1 - i].add_garbage(attack_for_clear(count), gap);
+                }
+            }
+        }
+    }
+
+    fn toggle_pause(&mut self) {
+        let paused = !self.games[0].paused;
+        for game in &mut self.games {
+            game.set_paused(paused);
+        }
+    }
+}
+
+/// Garbage comes from outside the game's own inputs, so single-game replays
+/// can't reproduce a versus match; these games don't record.
+fn new_game(rules: &Rules) -> Game {
+    let mut game = Game::new(GameSetup {
+        mode: GameMode::Marathon,
+        rules: rules.clone(),
+        seed: None,
+    });
+    game.replay = None;
+    game
+}
+
+pub fn run(
+    terminal: &mut Term,
+    rx: &mpsc::Receiver<InternalEvent>,
+    outputs: &mut Outputs,
+    rules: Rules,
+    config: &Config,
+) -> io::Result<()> {
+    let mut versus = Versus::new(&rules);
+    let mut last_frame = Instant::now();
+    loop {
+        terminal.draw(|f| ui(f, &versus, config))?;
+
+        let mut did_quit = false;
+        for ev in frame_events(rx, &mut last_frame) {
+            match ev {
+                InternalEvent::Input(key) if key.kind == KeyEventKind::Release => {}
+                InternalEvent::Input(key) => match key.code {
+                    KeyCode::Char('q') => did_quit = true,
+                    KeyCode::Char('p') => versus.toggle_pause(),
+                    KeyCode::Char('r') => versus = Versus::new(&rules),
+                    _ => versus.handle_key(&key),
+                },
+                InternalEvent::Tick => versus.step(),
+                // one player leaving the window doesn't stop the other
+                InternalEvent::Focus(_) => {}
+            }
+        }
+        versus.exchange(outputs);
+
+        if did_quit {
+            return Ok(());
+        }
+    }
+}
+
+fn ui<B: ratatui::backend::Backend>(f: &mut ratatui::Frame<B>, versus: &Versus, config: &Config) {
+    let halves = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(f.size());
+
+    let controls = [
+        [
+            "A D : Move     S : Soft drop",
+            "W : Rotate CW  E : Rotate CCW",
+            "Space : Hard drop   C : Hold",
+        ],
+        [
+            "← → : Move     ↓ : Soft drop",
+            "↑ : Rotate CW  . : Rotate CCW",
+            "Enter : Hard drop   / : Hold",
+        ],
+    ];
+    for (i, game) in versus.games.iter().enumerate() {
+        let mut lines: Vec<Line> = controls[i]
+            .iter()
+            .map(|text| Line::from(vec![Span::raw(*text)]))
+            .collect();
+        lines.push(Line::from(vec![Span::raw(
+            "P : Pause  R : Rematch  Q : Quit",
+        )]));
+
+        let mut status = vec![];
+        if versus.over() && !game.game_over {
+            status.push(Line::from(vec![Span::styled(
+                " WINNER! ",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            )]));
+        }
+        let panel = Panel {
+            title: if i == 0 { " Player 1 " } else { " Player 2 " },
+            controls: lines,
+            status,
+            game_over_hint: Some(Line::from(vec![Span::raw(
+                " Press 'R' for a rematch or 'Q' to quit ",
+            )])),
+            notifications: &[],
+            toasts: &[],
+            popups: &[],
+            level_up: None,
+            trails: &[],
+            inputs: None,
+            heights: config.ui.column_heights,
+            suggestion: None,
+            best_board: None,
+            hold_preview: false,
+            help: None,
+            history: None,
+            tutorial: None,
+        };
+        draw_game(f, halves[i], game, panel, config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clearing_n_lines_sends_n_minus_one_rows() {
+        let sent: Vec<usize> = (0..=4).map(attack_for_clear).collect();
+        assert_eq!(sent, [0, 0, 1, 2, 3]);
+    }
+}